@@ -0,0 +1,75 @@
+//! How long commands take on a large tracker: 100 projects with 10,000
+//! tasks between them, made up by `generate`. They're meant to stay under
+//! about 50ms each.
+//!
+//! Run with `cargo bench`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+const BINARY: &str = env!("CARGO_BIN_EXE_project-tracker");
+
+/// A home directory of its own, holding the generated data.
+fn home() -> PathBuf {
+    let home = env::temp_dir().join(format!("project-tracker-bench-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&home);
+    fs::create_dir_all(&home).expect("Unable to create the bench directory.");
+    run(
+        &home,
+        &["generate", "--projects", "100", "--tasks", "10000"],
+    );
+    home
+}
+
+fn run(home: &Path, args: &[&str]) {
+    let status = Command::new(BINARY)
+        .args(args)
+        .env("HOME", home)
+        .env_remove("XDG_CONFIG_HOME")
+        .env_remove("PT_WORKSPACE")
+        .env_remove("PT_REMOTE")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .status()
+        .expect("Unable to run project-tracker.");
+    assert!(
+        status.success(),
+        "project-tracker {} failed",
+        args.join(" ")
+    );
+}
+
+fn commands(c: &mut Criterion) {
+    let home = home();
+    let mut group = c.benchmark_group("10000 tasks");
+    group.sample_size(20);
+
+    for args in [
+        &["list-projects"][..],
+        &["list-tasks", "project-42"],
+        &["list-tasks", "project-42", "--table", "--sort", "due"],
+        &["search", "login page"],
+        &["agenda"],
+        &["today"],
+    ] {
+        group.bench_function(args.join(" "), |b| b.iter(|| run(&home, args)));
+    }
+
+    // Completing and reopening the same task, so the data stays the same.
+    group.bench_function("complete-task and reopen-task", |b| {
+        b.iter(|| {
+            run(&home, &["complete-task", "project-42", "2"]);
+            run(&home, &["reopen-task", "project-42", "2"]);
+        })
+    });
+
+    group.finish();
+    let _ = fs::remove_dir_all(&home);
+}
+
+criterion_group!(benches, commands);
+criterion_main!(benches);
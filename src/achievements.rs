@@ -0,0 +1,184 @@
+//! Completion streaks and badges, worked out from when tasks were completed,
+//! archived ones included.
+
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use colored::Colorize;
+use std::collections::BTreeSet;
+
+use crate::{archive, bars, config::load_config, i18n::tr, load_data, Task};
+
+/// Longest streaks, in days, earning a badge.
+const STREAK_BADGES: &[u32] = &[3, 5, 7, 14, 30, 100, 365];
+
+/// Numbers of completed tasks earning a badge.
+const TOTAL_BADGES: &[usize] = &[10, 50, 100, 250, 500, 1000];
+
+struct Stats {
+    total: usize,
+    today: usize,
+    this_week: usize,
+    current_streak: u32,
+    longest_streak: u32,
+}
+
+/// Days with at least one task completed.
+fn completion_days<'a>(tasks: impl IntoIterator<Item = &'a Task>) -> BTreeSet<NaiveDate> {
+    tasks
+        .into_iter()
+        .filter_map(|t| t.completed_at)
+        .map(|at| at.date_naive())
+        .collect()
+}
+
+/// The streak going on at `today` and the longest one ever. A streak isn't
+/// broken until a whole day goes by without completing anything, so one
+/// ending yesterday is still current.
+fn streaks(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> (u32, u32) {
+    let mut current = 0;
+    let mut day = if days.contains(&today) {
+        today
+    } else {
+        today - Duration::days(1)
+    };
+    while days.contains(&day) {
+        current += 1;
+        day -= Duration::days(1);
+    }
+
+    let (mut longest, mut run, mut previous) = (0, 0, None);
+    for &day in days {
+        run = if previous == Some(day - Duration::days(1)) {
+            run + 1
+        } else {
+            1
+        };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+
+    (current, longest)
+}
+
+fn compute() -> Stats {
+    let data = load_data();
+    let archive = archive::load_archive();
+    let tasks: Vec<&Task> = data
+        .iter()
+        .chain(&archive)
+        .flat_map(|p| &p.tasks)
+        .filter(|t| t.completed)
+        .collect();
+
+    let today = Local::now().date_naive();
+    let week_start = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let completed_on = |from: NaiveDate| {
+        tasks
+            .iter()
+            .filter(|t| t.completed_at.is_some_and(|at| at.date_naive() >= from))
+            .count()
+    };
+
+    let (current_streak, longest_streak) = streaks(&completion_days(tasks.iter().copied()), today);
+    Stats {
+        total: tasks.len(),
+        today: completed_on(today),
+        this_week: completed_on(week_start),
+        current_streak,
+        longest_streak,
+    }
+}
+
+fn days(count: u32) -> String {
+    if count == 1 {
+        tr!("1 day")
+    } else {
+        tr!("{} days", count)
+    }
+}
+
+fn streak_badge(days: u32) -> String {
+    tr!("{}-day streak", days)
+}
+
+fn total_badge(count: usize) -> String {
+    tr!("{} tasks done", count)
+}
+
+pub fn stats() {
+    let stats = compute();
+
+    println!(
+        "{}",
+        tr!(
+            "Completed tasks: {} ({} today, {} this week)",
+            stats.total.to_string().bold(),
+            stats.today,
+            stats.this_week
+        )
+    );
+    println!("{}", tr!("Current streak: {}", days(stats.current_streak)));
+    println!("{}", tr!("Longest streak: {}", days(stats.longest_streak)));
+
+    let earned: Vec<String> = STREAK_BADGES
+        .iter()
+        .filter(|&&n| stats.longest_streak >= n)
+        .map(|&n| streak_badge(n))
+        .chain(
+            TOTAL_BADGES
+                .iter()
+                .filter(|&&n| stats.total >= n)
+                .map(|&n| total_badge(n)),
+        )
+        .collect();
+    if earned.is_empty() {
+        println!("{}", tr!("Badges: none yet."));
+    } else {
+        println!("{}", tr!("Badges:"));
+        for badge in earned {
+            let trophy = if bars::is_ascii() { "*" } else { "🏆" };
+            println!("  {} {}", trophy, badge.yellow());
+        }
+    }
+
+    if let Some(&next) = STREAK_BADGES.iter().find(|&&n| stats.longest_streak < n) {
+        println!(
+            "{}",
+            tr!(
+                "Next: {}, {} to go.",
+                streak_badge(next),
+                days(next - stats.current_streak)
+            )
+            .dimmed()
+        );
+    }
+    if let Some(&next) = TOTAL_BADGES.iter().find(|&&n| stats.total < n) {
+        println!(
+            "{}",
+            tr!(
+                "Next: {}, {} tasks to go.",
+                total_badge(next),
+                next - stats.total
+            )
+            .dimmed()
+        );
+    }
+}
+
+/// The line shown above the overview when the `streak-banner` setting is on
+/// and there's a streak going on.
+pub fn banner() -> Option<String> {
+    if load_config().effective().streak_banner != Some(true) {
+        return None;
+    }
+    let stats = compute();
+    if stats.current_streak == 0 {
+        return None;
+    }
+    let fire = if bars::is_ascii() { "*" } else { "🔥" };
+    Some(format!(
+        "{} {}, {}",
+        fire,
+        streak_badge(stats.current_streak),
+        total_badge(stats.total)
+    ))
+}
@@ -0,0 +1,83 @@
+//! The agenda: every pending task of every project in one flat list, for
+//! deciding what to do next.
+
+use clap::ValueEnum;
+use colored::Colorize;
+use std::cmp::Ordering;
+
+use crate::{
+    contexts, display, filter::Filter, i18n::tr, load_data, snooze, table::Column, task_details,
+    template::TaskTemplate, theme, Project, Task,
+};
+
+/// What the agenda can be sorted on.
+#[derive(Clone, Copy, PartialEq, Debug, ValueEnum)]
+pub enum SortKey {
+    Due,
+    Priority,
+    Project,
+    Scheduled,
+    Status,
+}
+
+/// Keys the agenda is sorted on unless `--sort` says otherwise.
+pub const DEFAULT_SORT: &[SortKey] = &[SortKey::Due, SortKey::Priority, SortKey::Project];
+
+impl SortKey {
+    fn compare(self, (a_project, a): (&str, &Task), (b_project, b): (&str, &Task)) -> Ordering {
+        match self {
+            SortKey::Due => Column::Due.compare(a, b),
+            SortKey::Priority => Column::Priority.compare(a, b),
+            SortKey::Project => a_project.cmp(b_project),
+            SortKey::Scheduled => Column::Scheduled.compare(a, b),
+            SortKey::Status => Column::Status.compare(a, b),
+        }
+    }
+}
+
+pub fn show_agenda(sort: &[SortKey], filter: Option<&Filter>, template: Option<&TaskTemplate>) {
+    let data = load_data();
+    let mut tasks: Vec<(&Project, &Task)> = data
+        .iter()
+        .flat_map(|project| project.tasks.iter().map(move |task| (project, task)))
+        .filter(|(_, task)| !task.completed && contexts::shown(task) && snooze::shown(task))
+        .filter(|(project, task)| filter.is_none_or(|f| f.matches(&project.name, task)))
+        .collect();
+
+    // Escalated tasks come first, whatever the order asked for.
+    tasks.sort_by(|(a_project, a), (b_project, b)| {
+        display::is_escalated(b)
+            .cmp(&display::is_escalated(a))
+            .then_with(|| {
+                sort.iter()
+                    .map(|key| key.compare((&a_project.name, a), (&b_project.name, b)))
+                    .find(|order| order.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
+    });
+
+    if let Some(template) = template {
+        for (project, task) in tasks {
+            println!("{}", template.render(project, task));
+        }
+        return;
+    }
+    if tasks.is_empty() {
+        println!("{}", tr!("Nothing left to do."));
+        return;
+    }
+
+    println!("{}", tr!("Agenda ({} pending tasks):", tasks.len()));
+    if let Some(context) = contexts::active() {
+        println!("{}", tr!("Only showing tasks in {}.", context).dimmed());
+    }
+    for (project, task) in tasks {
+        println!(
+            "    {} {}: {}{}",
+            theme::checkbox(false),
+            project.task_ref(task.id),
+            display::description(task),
+            task_details(task)
+        );
+    }
+}
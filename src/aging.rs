@@ -0,0 +1,59 @@
+//! How long pending tasks have been around, shown next to them as in
+//! `(3w)`, tinted yellow and then red as they get older than the
+//! `age-warn-after` and `age-alert-after` settings.
+
+use chrono::Local;
+use colored::{ColoredString, Colorize};
+use std::sync::OnceLock;
+
+use crate::{config::load_config, theme::Themed, Task};
+
+/// Default age, in days, from which tasks are tinted yellow.
+const WARN_AFTER: u32 = 14;
+
+/// Default age, in days, from which tasks are tinted red.
+const ALERT_AFTER: u32 = 30;
+
+/// The warn and alert thresholds, read once since every listed task needs
+/// them.
+fn thresholds() -> (u32, u32) {
+    static THRESHOLDS: OnceLock<(u32, u32)> = OnceLock::new();
+    *THRESHOLDS.get_or_init(|| {
+        let settings = load_config().effective();
+        (
+            settings.age_warn_after.unwrap_or(WARN_AFTER),
+            settings.age_alert_after.unwrap_or(ALERT_AFTER),
+        )
+    })
+}
+
+/// An age in days, in the largest unit that fits: `5d`, `3w`, `4mo`, `2y`.
+fn format_age(days: i64) -> String {
+    match days {
+        ..=6 => format!("{}d", days),
+        7..=59 => format!("{}w", days / 7),
+        60..=364 => format!("{}mo", days / 30),
+        _ => format!("{}y", days / 365),
+    }
+}
+
+/// The age marker of `task`, if it's pending and at least a day old.
+pub fn marker(task: &Task) -> Option<ColoredString> {
+    if task.completed {
+        return None;
+    }
+    let days = (Local::now() - task.created_at()?).num_days();
+    if days < 1 {
+        return None;
+    }
+
+    let (warn, alert) = thresholds();
+    let label = format!("({})", format_age(days));
+    Some(if days >= i64::from(alert) {
+        label.bad()
+    } else if days >= i64::from(warn) {
+        label.warn()
+    } else {
+        label.dimmed()
+    })
+}
@@ -0,0 +1,222 @@
+//! User-defined aliases, expanded before the command line is parsed:
+//! command aliases (`a` for `add-task`, or `lo` for
+//! `list-tasks --context @office`) and project aliases (`w` for
+//! `work-website-redesign`), wherever a project name is expected.
+
+use clap::{Arg, Command, CommandFactory, Subcommand};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{
+    config::{load_config, save_config},
+    i18n::tr,
+    Cli,
+};
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Aliases {
+    /// Commands, possibly with arguments, by alias.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub commands: BTreeMap<String, String>,
+    /// Project names, by alias.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub projects: BTreeMap<String, String>,
+}
+
+impl Aliases {
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty() && self.projects.is_empty()
+    }
+}
+
+#[derive(Subcommand)]
+pub enum AliasCommand {
+    /// Make `name` stand for a command (with arguments), or with --project
+    /// for a project.
+    Set {
+        name: String,
+        expansion: String,
+        #[arg(long)]
+        project: bool,
+    },
+    /// List the aliases.
+    List,
+    /// Remove an alias.
+    Remove {
+        name: String,
+        #[arg(long)]
+        project: bool,
+    },
+}
+
+pub fn run(command: &AliasCommand) {
+    match command {
+        AliasCommand::Set {
+            name,
+            expansion,
+            project,
+        } => set_alias(name, expansion, *project),
+        AliasCommand::List => list_aliases(),
+        AliasCommand::Remove { name, project } => remove_alias(name, *project),
+    }
+}
+
+fn set_alias(name: &str, expansion: &str, project: bool) {
+    let mut config = load_config();
+    if project {
+        config
+            .aliases
+            .projects
+            .insert(name.to_string(), expansion.to_string());
+    } else {
+        let cli = Cli::command();
+        if cli.find_subcommand(name).is_some() {
+            println!(
+                "{}",
+                tr!("'{}' is already a command and can't be an alias.", name)
+            );
+            return;
+        }
+        let first = shlex::split(expansion).and_then(|words| words.into_iter().next());
+        if first.is_none_or(|word| cli.find_subcommand(&word).is_none()) {
+            println!("{}", tr!("'{}' doesn't start with a command.", expansion));
+            return;
+        }
+        config
+            .aliases
+            .commands
+            .insert(name.to_string(), expansion.to_string());
+    }
+    save_config(&config);
+    println!(
+        "{}",
+        tr!("Alias '{}' now stands for '{}'.", name, expansion)
+    );
+}
+
+fn list_aliases() {
+    let aliases = load_config().aliases;
+    if aliases.is_empty() {
+        println!("{}", tr!("No aliases defined."));
+        return;
+    }
+
+    if !aliases.commands.is_empty() {
+        println!("{}", tr!("Command aliases:"));
+        for (name, expansion) in &aliases.commands {
+            println!(" - {}: {}", name.bold(), expansion);
+        }
+    }
+    if !aliases.projects.is_empty() {
+        println!("{}", tr!("Project aliases:"));
+        for (name, project) in &aliases.projects {
+            println!(" - {}: {}", name.bold(), project);
+        }
+    }
+}
+
+fn remove_alias(name: &str, project: bool) {
+    let mut config = load_config();
+    let aliases = if project {
+        &mut config.aliases.projects
+    } else {
+        &mut config.aliases.commands
+    };
+    if aliases.remove(name).is_some() {
+        save_config(&config);
+        println!("{}", tr!("Alias '{}' removed.", name));
+    } else {
+        println!("{}", tr!("Alias '{}' not found.", name));
+    }
+}
+
+/// The option `arg` (`--name`, `-n` or `--name=value`) names, if any.
+fn find_option<'a>(command: &'a Command, arg: &str) -> Option<&'a Arg> {
+    if let Some(long) = arg.strip_prefix("--") {
+        let long = long.split('=').next().unwrap_or_default();
+        command.get_arguments().find(|a| a.get_long() == Some(long))
+    } else {
+        let short = arg.strip_prefix('-')?.chars().next()?;
+        command
+            .get_arguments()
+            .find(|a| a.get_short() == Some(short))
+    }
+}
+
+fn takes_value(arg: &Arg) -> bool {
+    arg.get_action().takes_values()
+}
+
+/// Expand the command alias and project aliases in `args`, the full command
+/// line including the binary name.
+pub fn expand(mut args: Vec<String>) -> Vec<String> {
+    let aliases = load_config().aliases;
+    if aliases.is_empty() {
+        return args;
+    }
+
+    let cli = Cli::command();
+    let mut command = &cli;
+    let mut positional = 0;
+    let mut i = 1;
+    while i < args.len() {
+        let arg = args[i].clone();
+
+        if arg == "--" {
+            break;
+        }
+        if arg.starts_with('-') && arg.len() > 1 {
+            let option = find_option(command, &arg).or_else(|| find_option(&cli, &arg));
+            if let Some(option) = option {
+                if option.get_id() == "project" {
+                    if let Some(value) = arg.strip_prefix("--project=") {
+                        if let Some(project) = aliases.projects.get(value) {
+                            args[i] = format!("--project={}", project);
+                        }
+                    } else if let Some(project) =
+                        args.get(i + 1).and_then(|v| aliases.projects.get(v))
+                    {
+                        args[i + 1] = project.clone();
+                    }
+                }
+                if takes_value(option) && !arg.contains('=') {
+                    i += 1;
+                }
+            }
+            i += 1;
+            continue;
+        }
+
+        // A subcommand, possibly an alias for one, as long as no positional
+        // argument came before.
+        if positional == 0 && command.has_subcommands() {
+            if std::ptr::eq(command, &cli) {
+                if let Some(expansion) = aliases.commands.get(&arg) {
+                    let words = shlex::split(expansion).unwrap_or_default();
+                    args.splice(i..=i, words);
+                    continue;
+                }
+            }
+            if let Some(subcommand) = command.find_subcommand(&arg) {
+                command = subcommand;
+                i += 1;
+                continue;
+            }
+        }
+
+        let id = command
+            .get_positionals()
+            .nth(positional)
+            .map(|a| a.get_id().as_str());
+        if id == Some("project") {
+            if let Some(project) = aliases.projects.get(&arg) {
+                args[i] = project.clone();
+            }
+        }
+        positional += 1;
+        i += 1;
+    }
+    args
+}
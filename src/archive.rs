@@ -0,0 +1,166 @@
+//! Archival of old completed tasks, following the `archive-completed-after`
+//! setting, so the data file stays small and listings stay focused.
+//!
+//! Archived tasks are moved to `<data file>.archive.json`, next to the data
+//! file, whatever format the data itself is stored in.
+
+use chrono::Local;
+use std::{fs, io::ErrorKind, path::PathBuf};
+use tracing::info;
+
+use crate::{
+    config::load_config, i18n::tr, load_data, privacy, remote, save_data, storage,
+    storage::DataFormat, workspace, Project,
+};
+
+fn get_archive_path(workspace_name: &str) -> PathBuf {
+    workspace::data_file_path(workspace_name, DataFormat::Json).with_extension("archive.json")
+}
+
+pub fn load_archive() -> Vec<Project> {
+    match fs::read(get_archive_path(workspace::active())) {
+        Ok(content) => serde_json::from_slice(&content).expect("Unable to parse archive file."),
+        Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+        Err(e) => panic!("Unable to read archive file: {}", e),
+    }
+}
+
+/// The archive, or why it can't be read, for checking it rather than
+/// relying on it.
+pub fn try_load_archive() -> Result<Vec<Project>, String> {
+    match fs::read(get_archive_path(workspace::active())) {
+        Ok(content) => serde_json::from_slice(&content).map_err(|e| e.to_string()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn save_archive(archive: &[Project]) {
+    let content = serde_json::to_vec_pretty(archive).expect("Unable to serialize archive.");
+    fs::write(get_archive_path(workspace::active()), content)
+        .expect("Unable to write archive file.");
+}
+
+/// Delete the archive of a workspace being deleted.
+pub fn remove(workspace_name: &str) {
+    if let Err(e) = fs::remove_file(get_archive_path(workspace_name)) {
+        if e.kind() != ErrorKind::NotFound {
+            panic!("Unable to remove archive file: {}", e);
+        }
+    }
+}
+
+/// Completed tasks to archive, by project: those completed at least
+/// `after` days ago, or as long ago as their project's setting says.
+fn old_tasks(data: &[Project], after: Option<u32>) -> Vec<(String, u32)> {
+    let config = load_config();
+    let now = Local::now();
+
+    let mut old = Vec::new();
+    for project in data {
+        let Some(days) = after.or(config
+            .effective_for(Some(&project.name))
+            .archive_completed_after)
+        else {
+            continue;
+        };
+        for task in &project.tasks {
+            let is_old = task
+                .completed_at
+                .is_some_and(|at| (now - at).num_days() >= i64::from(days));
+            if task.completed && is_old {
+                old.push((project.name.clone(), task.id));
+            }
+        }
+    }
+    old
+}
+
+/// Move the given tasks from `data` to the archive.
+fn archive_tasks(data: &mut [Project], tasks: &[(String, u32)]) {
+    let mut archive = load_archive();
+
+    for project in data.iter_mut() {
+        let (moved, kept) = std::mem::take(&mut project.tasks)
+            .into_iter()
+            .partition(|task| tasks.contains(&(project.name.clone(), task.id)));
+        project.tasks = kept;
+        if moved.is_empty() {
+            continue;
+        }
+        for task in &moved {
+            info!(
+                "archiving {}#{} to {}",
+                project.name,
+                task.id,
+                get_archive_path(workspace::active()).display()
+            );
+        }
+
+        let archived = match archive.iter_mut().position(|p| p.name == project.name) {
+            Some(index) => &mut archive[index],
+            None => {
+                archive.push(Project {
+                    name: project.name.clone(),
+                    prefix: project.prefix.clone(),
+                    ..Default::default()
+                });
+                archive.last_mut().unwrap()
+            }
+        };
+        archived.tasks.extend(moved);
+    }
+
+    save_archive(&archive);
+}
+
+/// Archive old completed tasks right away, with `after` overriding the
+/// `archive-completed-after` setting.
+pub fn autoclean(after: Option<u32>, dry_run: bool) {
+    let mut data = load_data();
+    let old = old_tasks(&data, after);
+
+    if old.is_empty() {
+        println!("{}", tr!("Nothing to archive."));
+        return;
+    }
+
+    for (project_name, task_id) in &old {
+        let project = data.iter().find(|p| &p.name == project_name).unwrap();
+        let task = project.tasks.iter().find(|t| t.id == *task_id).unwrap();
+        println!(
+            "    {}: {}",
+            project.task_ref(task.id),
+            privacy::description(task)
+        );
+    }
+
+    if dry_run {
+        println!("{}", tr!("Dry run, {} tasks would be archived.", old.len()));
+        return;
+    }
+
+    archive_tasks(&mut data, &old);
+    save_data(&data);
+    println!("{}", tr!("Archived {} completed tasks.", old.len()));
+}
+
+/// Archive old completed tasks following the `archive-completed-after`
+/// setting, as done on every run.
+pub fn archive_per_policy() {
+    // Remote data is archived by its server.
+    if remote::is_active() {
+        return;
+    }
+    let mut data = load_data();
+    let old = old_tasks(&data, None);
+    // Archiving can wait for a data file that's been read entirely.
+    if old.is_empty() || storage::is_corrupt() {
+        return;
+    }
+
+    archive_tasks(&mut data, &old);
+    save_data(&data);
+    // On stderr, not to get in the way of output read by other programs.
+    eprintln!("{}", tr!("Archived {} old completed tasks.", old.len()));
+}
@@ -0,0 +1,452 @@
+//! Shorthands for the project and task ID arguments, expanded before the
+//! command line is parsed, after the aliases:
+//!
+//! - `PREFIX-ID` in place of both, as in `complete-task WEB-12`.
+//! - `--match <text>` in place of the task ID, as in
+//!   `complete-task myproject --match "fix login"`, picking the task by its
+//!   description. Without a project, all projects are searched.
+//! - `last` (the task added last), `first-pending` (the first pending task
+//!   in the project's order) or a range like `3..7` in place of the task ID.
+//!   A range runs the command once for each task in it; its ends can be
+//!   `last` or `first-pending` too.
+//! - The task ID alone while focused on a project, as in `complete-task 3`,
+//!   see the `focus` module.
+
+use clap::{Args, Command, CommandFactory, FromArgMatches, Parser};
+
+use crate::{focus, i18n::tr, load_data, prefixes, privacy, Cli, GlobalArgs, Project, Task};
+
+/// Names of the subcommands, at any depth, taking a project and a task ID
+/// as their first two arguments.
+fn task_commands(command: &Command) -> Vec<String> {
+    let mut names = Vec::new();
+    for subcommand in command.get_subcommands() {
+        let mut positionals = subcommand
+            .get_positionals()
+            .map(|arg| arg.get_id().as_str());
+        if positionals.next() == Some("project") && positionals.next() == Some("task_id") {
+            names.push(subcommand.get_name().to_string());
+        }
+        names.extend(task_commands(subcommand));
+    }
+    names
+}
+
+/// Split `args`, after the binary name, into the global options with their
+/// values, and everything else.
+fn split_global_options(args: &[String]) -> (Vec<String>, Vec<String>) {
+    let command = GlobalArgs::augment_args(Command::new("pt"));
+    let takes_value = |arg: &str| {
+        let name = arg.split_once('=').map_or(arg, |(name, _)| name);
+        command.get_arguments().find_map(|global| {
+            let matches = match name.strip_prefix("--") {
+                Some(long) => global.get_long() == Some(long),
+                // Short flags can be bundled, as in -vv.
+                None => {
+                    name.len() > 1
+                        && name.starts_with('-')
+                        && name[1..].chars().all(|c| global.get_short() == Some(c))
+                }
+            };
+            matches.then(|| global.get_action().takes_values() && !arg.contains('='))
+        })
+    };
+
+    let (mut globals, mut rest) = (Vec::new(), Vec::new());
+    let mut args = args.iter().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            rest.push(arg.clone());
+            rest.extend(args.by_ref().cloned());
+            break;
+        }
+        match takes_value(arg) {
+            Some(takes_value) => {
+                globals.push(arg.clone());
+                if takes_value {
+                    globals.extend(args.next().cloned());
+                }
+            }
+            None => rest.push(arg.clone()),
+        }
+    }
+    (globals, rest)
+}
+
+/// The global options in `args`, which don't depend on the shorthands.
+/// Found without failing on arguments that only make sense once expanded,
+/// wherever they are.
+pub fn global_options(args: &[String]) -> GlobalArgs {
+    if let Ok(cli) = Cli::try_parse_from(args) {
+        return cli.globals;
+    }
+    let (globals, _) = split_global_options(args);
+    let matches = GlobalArgs::augment_args(Command::new("pt"))
+        .ignore_errors(true)
+        .get_matches_from(args.iter().take(1).chain(&globals));
+    GlobalArgs::from_arg_matches(&matches).unwrap_or_default()
+}
+
+/// The name of the command in `args`, if any.
+pub fn command_name(args: &[String]) -> Option<String> {
+    let (_, rest) = split_global_options(args);
+    rest.into_iter().next()
+}
+
+const LAST: &str = "last";
+const FIRST_PENDING: &str = "first-pending";
+
+/// Whether `arg` may be a task ID shorthand, to be resolved against the
+/// data.
+fn is_smart_id(arg: &str) -> bool {
+    arg == LAST || arg == FIRST_PENDING || parse_range(arg).is_some()
+}
+
+/// The ends of a range like `3..7` or `first-pending..last`.
+fn parse_range(arg: &str) -> Option<(&str, &str)> {
+    let (start, end) = arg.split_once("..")?;
+    let is_end = |end: &str| end == LAST || end == FIRST_PENDING || end.parse::<u32>().is_ok();
+    (is_end(start) && is_end(end)).then_some((start, end))
+}
+
+/// Expand the shorthands in `args`, once the aliases are, into the command
+/// lines to run: one, unless a range of task IDs was given. They're resolved
+/// against the active workspace or remote tracker. Prints why and returns
+/// `None` when a `--match` or a task ID shorthand can't be resolved.
+pub fn expand(args: Vec<String>) -> Option<Vec<Vec<String>>> {
+    let has_match = args
+        .iter()
+        .any(|arg| arg == "--match" || arg.starts_with("--match="));
+    let has_smart_id = args.iter().any(|arg| is_smart_id(arg));
+    let has_id = has_smart_id || args.iter().any(|arg| arg.parse::<u32>().is_ok());
+    let focus = focus::active().filter(|_| has_id);
+    if !has_match
+        && !has_smart_id
+        && focus.is_none()
+        && !args.iter().any(|arg| prefixes::parse_ref(arg).is_some())
+    {
+        return Some(vec![args]);
+    }
+    let commands = task_commands(&Cli::command());
+    let args = match &focus {
+        Some(focus) => insert_focus(args, focus, &commands),
+        None => args,
+    };
+
+    let data = load_data();
+
+    let mut args = expand_task_refs(args, &data, &commands);
+    if has_match {
+        args = expand_match(args, &data, &commands)?;
+    }
+    if has_smart_id {
+        expand_smart_ids(args, &data, &commands)
+    } else {
+        Some(vec![args])
+    }
+}
+
+/// Resolve `last`, `first-pending` or a task ID in `project`.
+fn resolve_id(project: &Project, id: &str) -> Option<u32> {
+    match id {
+        LAST => project.tasks.iter().map(|t| t.id).max(),
+        FIRST_PENDING => project.tasks.iter().find(|t| !t.completed).map(|t| t.id),
+        id => id.parse().ok(),
+    }
+}
+
+/// Replace a task ID shorthand following a command taking a project and a
+/// task ID, and the project, with the ID, or with each ID of a range in a
+/// command line of its own.
+fn expand_smart_ids(
+    args: Vec<String>,
+    data: &[Project],
+    commands: &[String],
+) -> Option<Vec<Vec<String>>> {
+    let Some(position) = (2..args.len()).find(|&i| {
+        commands.contains(&args[i - 2]) && !args[i - 1].starts_with('-') && is_smart_id(&args[i])
+    }) else {
+        return Some(vec![args]);
+    };
+
+    let name = &args[position - 1];
+    let Some(project) = data.iter().find(|p| &p.name == name) else {
+        println!("{}", tr!("Project '{}' not found.", name));
+        return None;
+    };
+    let arg = &args[position];
+    let mut ids: Vec<u32> = match parse_range(arg) {
+        Some((start, end)) => match (resolve_id(project, start), resolve_id(project, end)) {
+            (Some(start), Some(end)) => project
+                .tasks
+                .iter()
+                .map(|t| t.id)
+                .filter(|id| (start..=end).contains(id))
+                .collect(),
+            _ => Vec::new(),
+        },
+        None => resolve_id(project, arg).into_iter().collect(),
+    };
+    ids.sort_unstable();
+    if ids.is_empty() {
+        match parse_range(arg) {
+            Some(_) => println!("{}", tr!("No task in {} of project '{}'.", arg, name)),
+            None if arg == LAST => println!("{}", tr!("Project '{}' has no tasks.", name)),
+            None => println!("{}", tr!("No pending task in project '{}'.", name)),
+        }
+        return None;
+    }
+
+    Some(
+        ids.into_iter()
+            .map(|id| {
+                let mut args = args.clone();
+                args[position] = id.to_string();
+                args
+            })
+            .collect(),
+    )
+}
+
+/// Insert `focus` as the project right after a command taking a project and
+/// a task ID, when it's directly followed by the task ID.
+fn insert_focus(args: Vec<String>, focus: &str, commands: &[String]) -> Vec<String> {
+    let mut expanded: Vec<String> = Vec::with_capacity(args.len() + 1);
+    for arg in args {
+        let follows_command = expanded
+            .last()
+            .is_some_and(|previous| commands.contains(previous));
+        if follows_command && (arg.parse::<u32>().is_ok() || is_smart_id(&arg)) {
+            expanded.push(focus.to_string());
+        }
+        expanded.push(arg);
+    }
+    expanded
+}
+
+/// Replace a `PREFIX-ID` argument right after a command taking a project
+/// and a task ID with the project's name and the ID.
+fn expand_task_refs(args: Vec<String>, data: &[Project], commands: &[String]) -> Vec<String> {
+    let mut expanded: Vec<String> = Vec::with_capacity(args.len() + 1);
+    for arg in args {
+        let follows_command = expanded
+            .last()
+            .is_some_and(|previous| commands.contains(previous));
+        let task = prefixes::parse_ref(&arg)
+            .filter(|_| follows_command)
+            .and_then(|(prefix, id)| Some((prefixes::find_project(data, prefix)?, id)));
+
+        match task {
+            Some((project, id)) => {
+                expanded.push(project.name.clone());
+                expanded.push(id.to_string());
+            }
+            None => expanded.push(arg),
+        }
+    }
+    expanded
+}
+
+/// Replace `--match <text>` with the ID of the only task whose description
+/// contains `text`, and with its project too if none was given.
+fn expand_match(
+    mut args: Vec<String>,
+    data: &[Project],
+    commands: &[String],
+) -> Option<Vec<String>> {
+    let position = args
+        .iter()
+        .position(|arg| arg == "--match" || arg.starts_with("--match="))?;
+    let flag = args.remove(position);
+    let text = match flag.strip_prefix("--match=") {
+        Some(text) => text.to_string(),
+        None if position < args.len() => args.remove(position),
+        None => {
+            println!("{}", tr!("--match needs the text to look for."));
+            return None;
+        }
+    };
+
+    let Some(command) = args[..position]
+        .iter()
+        .position(|arg| commands.contains(arg))
+    else {
+        println!(
+            "{}",
+            tr!("--match can only be used with commands taking a task ID.")
+        );
+        return None;
+    };
+
+    // The project comes between the command and `--match`, if at all.
+    let project = args[command + 1..position]
+        .iter()
+        .find(|arg| !arg.starts_with('-'));
+    let project_given = project.is_some();
+    let projects: Vec<&Project> = match project {
+        Some(name) => match data.iter().find(|p| &p.name == name) {
+            Some(project) => vec![project],
+            None => {
+                println!("{}", tr!("Project '{}' not found.", name));
+                return None;
+            }
+        },
+        None => data.iter().collect(),
+    };
+
+    let needle = text.to_lowercase();
+    let mut candidates: Vec<(&Project, &Task)> = projects
+        .iter()
+        .flat_map(|project| project.tasks.iter().map(move |task| (*project, task)))
+        .filter(|(_, task)| task.description.to_lowercase().contains(&needle))
+        .collect();
+    // An exact match wins over tasks merely containing the text.
+    if candidates.len() > 1 {
+        if let Some(exact) = candidates
+            .iter()
+            .find(|(_, task)| task.description.to_lowercase() == needle)
+        {
+            candidates = vec![*exact];
+        }
+    }
+
+    match candidates.as_slice() {
+        [] => {
+            println!("{}", tr!("No task matches '{}'.", text));
+            None
+        }
+        [(project, task)] => {
+            if project_given {
+                args.insert(command + 2, task.id.to_string());
+            } else {
+                args.insert(command + 1, project.name.clone());
+                args.insert(command + 2, task.id.to_string());
+            }
+            Some(args)
+        }
+        _ => {
+            println!("{}", tr!("Several tasks match '{}':", text));
+            for (project, task) in candidates {
+                println!(
+                    "    {}: {}",
+                    project.task_ref(task.id),
+                    privacy::description(task)
+                );
+            }
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    /// Project `web` with tasks 2 (completed), 3, 5 and 7, in that order.
+    fn data() -> Vec<Project> {
+        let task = |id, completed| Task {
+            id,
+            completed,
+            ..Default::default()
+        };
+        vec![Project {
+            name: "web".to_string(),
+            tasks: vec![
+                task(2, true),
+                task(3, false),
+                task(5, false),
+                task(7, false),
+            ],
+            ..Default::default()
+        }]
+    }
+
+    fn expand(line: &str) -> Option<Vec<Vec<String>>> {
+        expand_smart_ids(args(line), &data(), &task_commands(&Cli::command()))
+    }
+
+    #[test]
+    fn ranges_have_two_ends() {
+        assert_eq!(parse_range("3..7"), Some(("3", "7")));
+        assert_eq!(
+            parse_range("first-pending..last"),
+            Some(("first-pending", "last"))
+        );
+        for arg in ["3..", "..7", "3...7", "3..x", "a..b", "3", "3-7"] {
+            assert_eq!(parse_range(arg), None, "'{}' is a range", arg);
+        }
+    }
+
+    #[test]
+    fn last_and_first_pending_become_ids() {
+        assert_eq!(
+            expand("pt complete-task web last"),
+            Some(vec![args("pt complete-task web 7")])
+        );
+        assert_eq!(
+            expand("pt complete-task web first-pending"),
+            Some(vec![args("pt complete-task web 3")])
+        );
+    }
+
+    #[test]
+    fn ranges_run_once_for_each_task_in_them() {
+        assert_eq!(
+            expand("pt complete-task web 3..6"),
+            Some(vec![
+                args("pt complete-task web 3"),
+                args("pt complete-task web 5"),
+            ])
+        );
+        assert_eq!(
+            expand("pt complete-task web first-pending..last"),
+            Some(vec![
+                args("pt complete-task web 3"),
+                args("pt complete-task web 5"),
+                args("pt complete-task web 7"),
+            ])
+        );
+    }
+
+    #[test]
+    fn shorthands_without_tasks_are_refused() {
+        assert_eq!(expand("pt complete-task web 8..9"), None);
+        assert_eq!(expand("pt complete-task nowhere last"), None);
+    }
+
+    #[test]
+    fn shorthands_elsewhere_are_left_alone() {
+        for line in [
+            "pt add-task web last",
+            "pt complete-task web 3",
+            "pt complete-task --force last",
+        ] {
+            assert_eq!(expand(line), Some(vec![args(line)]));
+        }
+    }
+
+    #[test]
+    fn the_focus_goes_before_a_task_id() {
+        let commands = task_commands(&Cli::command());
+        let insert = |line| insert_focus(args(line), "web", &commands);
+        assert_eq!(insert("pt complete-task 3"), args("pt complete-task web 3"));
+        assert_eq!(
+            insert("pt complete-task last"),
+            args("pt complete-task web last")
+        );
+        assert_eq!(
+            insert("pt complete-task 3..5"),
+            args("pt complete-task web 3..5")
+        );
+        // A project is already there.
+        assert_eq!(
+            insert("pt complete-task api 3"),
+            args("pt complete-task api 3")
+        );
+        assert_eq!(insert("pt add-task 3"), args("pt add-task 3"));
+    }
+}
@@ -0,0 +1,133 @@
+//! URLs and local files attached to tasks.
+
+use std::{path::Path, process::Command};
+
+use crate::{history::TaskEvent, hyperlinks, i18n::tr, load_data, privacy, save_data};
+
+/// Turn `target` into what gets stored: existing local paths are made
+/// absolute so they keep working from any directory, anything else (URLs,
+/// ticket links...) is kept verbatim.
+fn normalize_target(target: &str) -> String {
+    let path = Path::new(target);
+    if path.exists() {
+        if let Ok(absolute) = path.canonicalize() {
+            return absolute.to_string_lossy().into_owned();
+        }
+    }
+    target.to_string()
+}
+
+pub fn attach(project_name: &str, task_id: u32, target: &str) {
+    let mut data = load_data();
+    if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
+        if let Some(task) = project.tasks.iter_mut().find(|t| t.id == task_id) {
+            let target = normalize_target(target);
+            if task.attachments.contains(&target) {
+                println!(
+                    "{}",
+                    tr!("'{}' is already attached to task {}.", target, task_id)
+                );
+                return;
+            }
+            task.attachments.push(target.clone());
+            task.record(TaskEvent::Edited, Some(format!("attached {}", target)));
+            save_data(&data);
+            println!(
+                "{}",
+                tr!(
+                    "Attached '{}' to task {} in project '{}'.",
+                    target,
+                    task_id,
+                    project_name
+                )
+            );
+        } else {
+            println!(
+                "{}",
+                tr!("Task {} not found in project '{}'.", task_id, project_name)
+            );
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
+
+pub fn list_attachments(project_name: &str, task_id: u32) {
+    let data = load_data();
+    if let Some(project) = data.iter().find(|p| p.name == project_name) {
+        if let Some(task) = project.tasks.iter().find(|t| t.id == task_id) {
+            if task.attachments.is_empty() {
+                println!("{}", tr!("Task {} has no attachments.", task_id));
+                return;
+            }
+            println!(
+                "{}",
+                tr!(
+                    "Attachments of task {}: {}",
+                    task_id,
+                    privacy::description(task)
+                )
+            );
+            for (i, attachment) in task.attachments.iter().enumerate() {
+                println!(
+                    "    {}. {}",
+                    i + 1,
+                    hyperlinks::attachment(attachment, attachment)
+                );
+            }
+        } else {
+            println!(
+                "{}",
+                tr!("Task {} not found in project '{}'.", task_id, project_name)
+            );
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
+
+/// The platform's way of opening a file or URL with its default handler.
+fn open_command(target: &str) -> Command {
+    if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg(target);
+        command
+    } else if cfg!(windows) {
+        // `start` is a shell builtin, and its first quoted argument is the
+        // window title.
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", "", target]);
+        command
+    } else {
+        let mut command = Command::new("xdg-open");
+        command.arg(target);
+        command
+    }
+}
+
+/// Open the `index`-th (1-based) attachment of a task with the system's
+/// default handler.
+pub fn open(project_name: &str, task_id: u32, index: usize) {
+    let data = load_data();
+    if let Some(project) = data.iter().find(|p| p.name == project_name) {
+        if let Some(task) = project.tasks.iter().find(|t| t.id == task_id) {
+            let Some(attachment) = index.checked_sub(1).and_then(|i| task.attachments.get(i))
+            else {
+                println!("{}", tr!("Task {} has no attachment #{}.", task_id, index));
+                return;
+            };
+
+            match open_command(attachment).spawn() {
+                Ok(_) => println!("{}", tr!("Opening '{}'...", attachment)),
+                Err(e) => println!("{}", tr!("Failed to open '{}': {}", attachment, e)),
+            }
+        } else {
+            println!(
+                "{}",
+                tr!("Task {} not found in project '{}'.", task_id, project_name)
+            );
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
@@ -0,0 +1,607 @@
+//! Encrypted backups of the data, and of its archive, to a WebDAV server or
+//! an S3-compatible bucket, set with the `backup-url` setting.
+//!
+//! Every save starts a detached `backup wait`, which makes a backup once
+//! `backup-interval` has passed since the last one, so that a burst of
+//! changes ends up in a single backup. Each backup is a new file named
+//! after the workspace and the time it was taken; old ones are left to the
+//! storage's own retention rules.
+//!
+//! Backups are gzip-compressed JSON, encrypted with AES-256-GCM under a key
+//! derived from `PT_BACKUP_PASSPHRASE`. Nothing is sent without it.
+
+use base64::prelude::*;
+use chrono::{DateTime, Local, Utc};
+use clap::Subcommand;
+use dialoguer::Confirm;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use hmac::{Hmac, KeyInit, Mac};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{ErrorKind, Read},
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, SystemTime},
+};
+use tracing::{info, warn};
+
+use crate::{
+    archive, config::load_config, durations, get_config_dir, i18n::tr, load_data, lock::Lock,
+    picker, save_data, storage, workspace, Project,
+};
+
+/// Minutes between backups, unless `backup-interval` says otherwise.
+const DEFAULT_INTERVAL: u32 = 60;
+
+/// Starts backup files, followed by the format version.
+const MAGIC: &[u8; 4] = b"PTBK";
+const VERSION: u8 = 1;
+
+/// PBKDF2 rounds deriving the key from the passphrase, stored in each
+/// backup so that it can be raised later.
+const ITERATIONS: u32 = 600_000;
+/// The most rounds a backup may ask for, well above what it will ever need,
+/// so that a damaged or forged one can't keep a restore busy for hours.
+const MAX_ITERATIONS: u32 = 20 * ITERATIONS;
+const SALT_LEN: usize = 16;
+
+/// What a backup holds.
+#[derive(Serialize, Deserialize)]
+struct Backup {
+    workspace: String,
+    taken_at: DateTime<Local>,
+    projects: Vec<Project>,
+    #[serde(default)]
+    archive: Vec<Project>,
+}
+
+#[derive(Subcommand)]
+pub enum BackupCommand {
+    /// Make a backup right away.
+    Now,
+    /// Show where backups go and how the last one went.
+    Status,
+    /// Replace the data with that of a downloaded backup file.
+    Restore { file: PathBuf },
+    /// Make the next backup once it's due; started after every change.
+    #[command(hide = true)]
+    Wait,
+}
+
+pub fn run(command: &BackupCommand) {
+    match command {
+        BackupCommand::Now => backup_now(),
+        BackupCommand::Status => status(),
+        BackupCommand::Restore { file } => restore(file),
+        BackupCommand::Wait => wait(),
+    }
+}
+
+/// Where backups are sent.
+enum Target {
+    /// A WebDAV collection, as an `http(s)://` URL.
+    WebDav(String),
+    /// A bucket and a key prefix, from `s3://bucket/prefix`.
+    S3 { bucket: String, prefix: String },
+}
+
+/// Parse a `backup-url`.
+///
+/// Meant to be used when setting it, hence the `String` error.
+pub fn parse_target(url: &str) -> Result<(), String> {
+    target(url).map(drop)
+}
+
+fn target(url: &str) -> Result<Target, String> {
+    if let Some(rest) = url.strip_prefix("s3://") {
+        let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            return Err(tr!("expected s3://bucket/prefix, got '{}'", url));
+        }
+        let prefix = prefix.trim_matches('/');
+        return Ok(Target::S3 {
+            bucket: bucket.to_string(),
+            prefix: if prefix.is_empty() {
+                String::new()
+            } else {
+                format!("{}/", prefix)
+            },
+        });
+    }
+    if url.starts_with("https://") || url.starts_with("http://") {
+        return Ok(Target::WebDav(format!("{}/", url.trim_end_matches('/'))));
+    }
+    Err(tr!(
+        "expected an https:// WebDAV URL or s3://bucket/prefix, got '{}'",
+        url
+    ))
+}
+
+fn interval() -> Duration {
+    let minutes = load_config()
+        .effective()
+        .backup_interval
+        .unwrap_or(DEFAULT_INTERVAL);
+    Duration::from_secs(minutes as u64 * 60)
+}
+
+/// How the last backups of each workspace went.
+#[derive(Serialize, Deserialize, Default)]
+struct State {
+    /// When the last backup was made.
+    #[serde(default)]
+    last: BTreeMap<String, DateTime<Local>>,
+    /// When the last backup was attempted, successfully or not.
+    #[serde(default)]
+    attempted: BTreeMap<String, DateTime<Local>>,
+    #[serde(default)]
+    errors: BTreeMap<String, String>,
+}
+
+fn get_state_path() -> PathBuf {
+    get_config_dir().join("backup-state.json")
+}
+
+fn load_state() -> State {
+    match fs::read(get_state_path()) {
+        Ok(content) => serde_json::from_slice(&content).unwrap_or_default(),
+        Err(e) if e.kind() == ErrorKind::NotFound => State::default(),
+        Err(e) => panic!("Unable to read backup state: {}", e),
+    }
+}
+
+fn record(result: &Result<String, String>) {
+    let mut state = load_state();
+    let name = workspace::active().to_string();
+    state.attempted.insert(name.clone(), Local::now());
+    match result {
+        Ok(_) => {
+            state.last.insert(name.clone(), Local::now());
+            state.errors.remove(&name);
+        }
+        Err(e) => {
+            state.errors.insert(name, e.clone());
+        }
+    }
+    let content = serde_json::to_vec_pretty(&state).expect("Unable to serialize backup state.");
+    fs::write(get_state_path(), content).expect("Unable to write backup state.");
+}
+
+/// Start a detached `backup wait` if backups are on, as done after every
+/// save.
+pub fn schedule() {
+    if load_config().effective().backup_url.is_none() {
+        return;
+    }
+    let Ok(exe) = std::env::current_exe() else {
+        return;
+    };
+    let spawned = Command::new(exe)
+        .args(["--workspace", workspace::active()])
+        .args(["backup", "wait"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    if let Err(e) = spawned {
+        eprintln!("{}", tr!("Failed to start the backup: {}", e));
+    }
+}
+
+/// Wait for the next backup to be due and make it, again as long as the
+/// data keeps changing meanwhile. A single `backup wait` runs at a time.
+fn wait() {
+    let lock_path = get_config_dir().join(format!("backup-{}.lock", workspace::active()));
+    // Held for up to an interval, so only stale well after that.
+    let lock = Lock::acquire(lock_path, interval() * 2);
+    let Some(_lock) = lock else {
+        return;
+    };
+
+    loop {
+        // Failed attempts count too, not to retry on every change.
+        if let Some(last) = load_state().attempted.get(workspace::active()) {
+            let since = (Local::now() - *last).to_std().unwrap_or_default();
+            if let Some(left) = interval().checked_sub(since) {
+                thread::sleep(left);
+            }
+        }
+
+        let started = SystemTime::now();
+        let result = upload();
+        match &result {
+            Ok(name) => info!("backed up to {}", name),
+            Err(e) => warn!("backup failed: {}", e),
+        }
+        record(&result);
+        if result.is_err() {
+            return;
+        }
+
+        // Changes saved during the upload go in the next backup.
+        let changed =
+            storage::last_modified(workspace::active()).is_some_and(|modified| modified > started);
+        if !changed {
+            return;
+        }
+    }
+}
+
+fn backup_now() {
+    let result = upload();
+    record(&result);
+    match result {
+        Ok(name) => println!("{}", tr!("Backed up to '{}'.", name)),
+        Err(e) => println!("{}", tr!("Backup failed: {}", e)),
+    }
+}
+
+fn status() {
+    let settings = load_config().effective();
+    let Some(url) = settings.backup_url else {
+        println!(
+            "{}",
+            tr!("Backups are off, set backup-url to turn them on.")
+        );
+        return;
+    };
+    println!("{}", tr!("Backups go to {}.", url));
+    println!(
+        "{}",
+        tr!(
+            "At most one every {}.",
+            durations::format_span(settings.backup_interval.unwrap_or(DEFAULT_INTERVAL))
+        )
+    );
+
+    let state = load_state();
+    match state.last.get(workspace::active()) {
+        Some(last) => println!("{}", tr!("Last backup: {}.", last.format("%Y-%m-%d %H:%M"))),
+        None => println!("{}", tr!("No backup made yet.")),
+    }
+    if let Some(error) = state.errors.get(workspace::active()) {
+        println!("{}", tr!("The last attempt failed: {}", error));
+    }
+    if passphrase().is_err() {
+        println!(
+            "{}",
+            tr!("PT_BACKUP_PASSPHRASE is not set, no backup will be made.")
+        );
+    }
+}
+
+fn passphrase() -> Result<String, String> {
+    std::env::var("PT_BACKUP_PASSPHRASE")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| tr!("PT_BACKUP_PASSPHRASE is not set, backups are never sent unencrypted."))
+}
+
+fn key(passphrase: &str, salt: &[u8], iterations: u32) -> LessSafeKey {
+    let mut key = [0; 32];
+    let iterations = NonZeroU32::new(iterations).unwrap_or(NonZeroU32::MIN);
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &key).expect("Key has the right length"))
+}
+
+/// `MAGIC`, the version, the rounds, the salt, the nonce and the sealed
+/// `content`.
+fn encrypt(content: &[u8], passphrase: &str) -> Vec<u8> {
+    seal(content, passphrase, ITERATIONS)
+}
+
+fn seal(content: &[u8], passphrase: &str, iterations: u32) -> Vec<u8> {
+    let random = SystemRandom::new();
+    let mut salt = [0; SALT_LEN];
+    let mut nonce = [0; NONCE_LEN];
+    random
+        .fill(&mut salt)
+        .and_then(|_| random.fill(&mut nonce))
+        .expect("Unable to generate random bytes.");
+
+    let mut sealed = content.to_vec();
+    key(passphrase, &salt, iterations)
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::empty(),
+            &mut sealed,
+        )
+        .expect("Unable to encrypt backup.");
+
+    let mut output = Vec::with_capacity(MAGIC.len() + 5 + SALT_LEN + NONCE_LEN + sealed.len());
+    output.extend_from_slice(MAGIC);
+    output.push(VERSION);
+    output.extend_from_slice(&iterations.to_be_bytes());
+    output.extend_from_slice(&salt);
+    output.extend_from_slice(&nonce);
+    output.extend_from_slice(&sealed);
+    output
+}
+
+fn decrypt(content: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let not_a_backup = || tr!("This isn't a project-tracker backup.");
+    let rest = content.strip_prefix(MAGIC).ok_or_else(not_a_backup)?;
+    let (&version, rest) = rest.split_first().ok_or_else(not_a_backup)?;
+    if version > VERSION {
+        return Err(tr!(
+            "This backup was made by a newer version of project-tracker, update to restore it."
+        ));
+    }
+    if rest.len() < 4 + SALT_LEN + NONCE_LEN {
+        return Err(not_a_backup());
+    }
+    let (iterations, rest) = rest.split_at(4);
+    let (salt, rest) = rest.split_at(SALT_LEN);
+    let (nonce, sealed) = rest.split_at(NONCE_LEN);
+    let iterations = u32::from_be_bytes(iterations.try_into().expect("Four bytes"));
+    if iterations > MAX_ITERATIONS {
+        return Err(tr!(
+            "This backup asks for {} key derivation rounds, more than the {} allowed: it's damaged, or wasn't made by project-tracker.",
+            iterations,
+            MAX_ITERATIONS
+        ));
+    }
+
+    let mut opened = sealed.to_vec();
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| not_a_backup())?;
+    let plain = key(passphrase, salt, iterations)
+        .open_in_place(nonce, Aad::empty(), &mut opened)
+        .map_err(|_| tr!("Wrong passphrase, or the backup is damaged."))?;
+    Ok(plain.to_vec())
+}
+
+/// Make a backup of the active workspace, returning the name it was sent
+/// under.
+fn upload() -> Result<String, String> {
+    let url = load_config()
+        .effective()
+        .backup_url
+        .ok_or_else(|| tr!("Backups are off, set backup-url to turn them on."))?;
+    let target = target(&url)?;
+    let passphrase = passphrase()?;
+
+    let backup = Backup {
+        workspace: workspace::active().to_string(),
+        taken_at: Local::now(),
+        projects: load_data(),
+        archive: archive::load_archive(),
+    };
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    serde_json::to_writer(&mut encoder, &backup).expect("Unable to serialize backup.");
+    let compressed = encoder.finish().expect("Unable to compress backup.");
+    let content = encrypt(&compressed, &passphrase);
+
+    let name = format!(
+        "{}-{}.ptbackup",
+        workspace::active(),
+        backup.taken_at.format("%Y%m%d-%H%M%S")
+    );
+    match target {
+        Target::WebDav(base) => put_webdav(&format!("{}{}", base, name), &content)?,
+        Target::S3 { bucket, prefix } => put_s3(&bucket, &format!("{}{}", prefix, name), &content)?,
+    }
+    Ok(name)
+}
+
+fn agent() -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(120)))
+        .build()
+        .into()
+}
+
+/// Upload to WebDAV, with the `PT_BACKUP_USER` and `PT_BACKUP_PASSWORD`
+/// credentials if set.
+fn put_webdav(url: &str, content: &[u8]) -> Result<(), String> {
+    let mut request = agent()
+        .put(url)
+        .header("Content-Type", "application/octet-stream");
+    if let Ok(user) = std::env::var("PT_BACKUP_USER") {
+        let password = std::env::var("PT_BACKUP_PASSWORD").unwrap_or_default();
+        let credentials = BASE64_STANDARD.encode(format!("{}:{}", user, password));
+        request = request.header("Authorization", &format!("Basic {}", credentials));
+    }
+    request
+        .send(content)
+        .map(drop)
+        .map_err(|e| tr!("Unable to upload to {}: {}", url, e))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// `key` with everything but unreserved characters and slashes escaped, as
+/// S3 signatures expect.
+fn encode_key(key: &str) -> String {
+    key.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                (b as char).to_string()
+            }
+            b => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Upload to an S3-compatible bucket, signed with AWS Signature Version 4.
+/// Credentials, region and endpoint come from the usual `AWS_*` variables.
+fn put_s3(bucket: &str, key: &str, content: &[u8]) -> Result<(), String> {
+    let (Ok(access_key), Ok(secret_key)) = (
+        std::env::var("AWS_ACCESS_KEY_ID"),
+        std::env::var("AWS_SECRET_ACCESS_KEY"),
+    ) else {
+        return Err(tr!(
+            "AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY must be set for S3 backups."
+        ));
+    };
+    let region = std::env::var("AWS_REGION")
+        .or_else(|_| std::env::var("AWS_DEFAULT_REGION"))
+        .unwrap_or_else(|_| "us-east-1".to_string());
+    let endpoint = std::env::var("AWS_ENDPOINT_URL")
+        .unwrap_or_else(|_| format!("https://s3.{}.amazonaws.com", region));
+    let endpoint = endpoint.trim_end_matches('/');
+    let host = endpoint
+        .split_once("://")
+        .map_or(endpoint, |(_, rest)| rest);
+
+    // Path-style addressing works with every S3-compatible service.
+    let path = format!("/{}/{}", bucket, encode_key(key));
+    let now = Utc::now();
+    let timestamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let payload_hash = hex(&Sha256::digest(content));
+
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "PUT\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        path, host, payload_hash, timestamp, signed_headers, payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        timestamp,
+        scope,
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+    let signing_key = ["s3", "aws4_request"].iter().fold(
+        hmac(
+            &hmac(format!("AWS4{}", secret_key).as_bytes(), &date),
+            &region,
+        ),
+        |key, part| hmac(&key, part),
+    );
+    let signature = hex(&hmac(&signing_key, &string_to_sign));
+
+    let url = format!("{}{}", endpoint, path);
+    agent()
+        .put(&url)
+        .header("x-amz-date", &timestamp)
+        .header("x-amz-content-sha256", &payload_hash)
+        .header(
+            "Authorization",
+            &format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                access_key, scope, signed_headers, signature
+            ),
+        )
+        .send(content)
+        .map(drop)
+        .map_err(|e| tr!("Unable to upload to {}: {}", url, e))
+}
+
+fn read_backup(file: &Path) -> Result<Backup, String> {
+    let content = fs::read(file).map_err(|e| tr!("Unable to read '{}': {}", file.display(), e))?;
+    let compressed = decrypt(&content, &passphrase()?)?;
+    let mut json = Vec::new();
+    GzDecoder::new(&compressed[..])
+        .read_to_end(&mut json)
+        .map_err(|e| tr!("Unable to read '{}': {}", file.display(), e))?;
+    serde_json::from_slice(&json).map_err(|e| tr!("Unable to read '{}': {}", file.display(), e))
+}
+
+fn restore(file: &Path) {
+    let backup = match read_backup(file) {
+        Ok(backup) => backup,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+
+    if !load_data().is_empty() {
+        let replace = picker::is_interactive()
+            && Confirm::new()
+                .with_prompt(tr!(
+                    "Replace the data of workspace '{}' with the backup taken on {}?",
+                    workspace::active(),
+                    backup.taken_at.format("%Y-%m-%d %H:%M")
+                ))
+                .default(false)
+                .interact()
+                .unwrap_or(false);
+        if !replace {
+            println!("{}", tr!("Nothing was changed."));
+            return;
+        }
+    }
+
+    save_data(&backup.projects);
+    archive::save_archive(&backup.archive);
+    println!(
+        "{}",
+        tr!(
+            "Restored {} projects from the backup taken on {}.",
+            backup.projects.len(),
+            backup.taken_at.format("%Y-%m-%d %H:%M")
+        )
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &[u8] = b"some projects";
+
+    /// A backup quicker to make than a real one, with fewer rounds.
+    fn quick_backup() -> Vec<u8> {
+        seal(CONTENT, "correct horse", 1000)
+    }
+
+    #[test]
+    fn backups_decrypt_with_their_passphrase() {
+        let sealed = encrypt(CONTENT, "correct horse");
+        assert_ne!(&sealed[MAGIC.len()..], CONTENT);
+        assert_eq!(decrypt(&sealed, "correct horse").as_deref(), Ok(CONTENT));
+    }
+
+    #[test]
+    fn backups_dont_decrypt_with_another_passphrase() {
+        let sealed = quick_backup();
+        assert_eq!(decrypt(&sealed, "correct horse").as_deref(), Ok(CONTENT));
+        assert!(decrypt(&sealed, "battery staple").is_err());
+    }
+
+    #[test]
+    fn damaged_backups_are_refused() {
+        let sealed = quick_backup();
+        let mut damaged = sealed.clone();
+        *damaged.last_mut().unwrap() ^= 1;
+        assert!(decrypt(&damaged, "correct horse").is_err());
+        assert!(decrypt(&sealed[..sealed.len() / 2], "correct horse").is_err());
+        assert!(decrypt(b"PK\x03\x04 a zip file", "correct horse").is_err());
+    }
+
+    #[test]
+    fn backups_asking_for_too_many_rounds_are_refused_right_away() {
+        let mut sealed = quick_backup();
+        let rounds = MAGIC.len() + 1;
+        sealed[rounds..rounds + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+        let started = std::time::Instant::now();
+        assert!(decrypt(&sealed, "correct horse").is_err());
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
+}
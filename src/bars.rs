@@ -0,0 +1,93 @@
+//! Progress bars, drawn the way the `bar-style`, `bar-color` and
+//! `bar-width` settings say, or with plain ASCII under `--ascii` for
+//! terminals and log viewers that garble anything else. Plain output has
+//! only the percentage.
+
+use clap::ValueEnum;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::{config::load_config, output, progress, theme::Themed};
+
+/// Width of the bar, in characters, unless `bar-width` says otherwise.
+const DEFAULT_WIDTH: u32 = 20;
+
+/// Characters the bar is drawn with.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum BarStyle {
+    /// `[█████     ]`
+    Blocks,
+    /// `[●●●●●○○○○○]`
+    Dots,
+    /// `[#####-----]`
+    Ascii,
+}
+
+impl BarStyle {
+    /// The filled and empty characters.
+    fn chars(self) -> (&'static str, &'static str) {
+        match self {
+            BarStyle::Blocks => ("█", " "),
+            BarStyle::Dots => ("●", "○"),
+            BarStyle::Ascii => ("#", "-"),
+        }
+    }
+}
+
+/// How the filled part of the bar is colored.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum BarColor {
+    Green,
+    /// Red, then yellow, then green as the project gets done.
+    Gradient,
+    None,
+}
+
+static ASCII_FLAG: OnceLock<bool> = OnceLock::new();
+
+/// Record whether `--ascii` was given.
+pub fn set_ascii(ascii: bool) {
+    let _ = ASCII_FLAG.set(ascii);
+}
+
+/// Whether to stick to ASCII characters, with `--ascii` or plain output.
+pub fn is_ascii() -> bool {
+    ASCII_FLAG.get().copied().unwrap_or(false) || output::is_plain()
+}
+
+/// `[█████     ] 50%` for `progress` between 0 and 1.
+pub fn progress_bar(progress: f64) -> String {
+    if output::is_plain() {
+        return format!("{}%", progress::percent(progress));
+    }
+    let settings = load_config().effective();
+    let style = if is_ascii() {
+        BarStyle::Ascii
+    } else {
+        settings.bar_style.unwrap_or(BarStyle::Blocks)
+    };
+    let width = settings.bar_width.unwrap_or(DEFAULT_WIDTH) as usize;
+    let percentage = progress::percent(progress);
+
+    let (filled_char, empty_char) = style.chars();
+    let filled = ((progress * width as f64).round() as usize).min(width);
+    let filled_bar = filled_char.repeat(filled);
+    let filled_bar = match settings.bar_color.unwrap_or(BarColor::Green) {
+        BarColor::Green => filled_bar.good(),
+        BarColor::Gradient if percentage < 34 => filled_bar.bad(),
+        BarColor::Gradient if percentage < 67 => filled_bar.warn(),
+        BarColor::Gradient => filled_bar.good(),
+        BarColor::None => filled_bar.normal(),
+    };
+    let empty_bar = empty_char.repeat(width - filled);
+
+    format!(
+        "[{}{}] {}%",
+        filled_bar,
+        empty_bar,
+        percentage.to_string().bold().yellow()
+    )
+}
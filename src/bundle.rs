@@ -0,0 +1,149 @@
+//! Bundles: one project with everything about its tasks (details, custom
+//! fields, attachment paths, checklists, time logged and history), as a
+//! gzip-compressed JSON file to hand over to someone else's tracker.
+
+use chrono::{DateTime, Local};
+use clap::Subcommand;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{hooks, i18n::tr, load_data, save_data, Event, Project};
+
+/// Version of the bundle format, bumped whenever older versions of the
+/// tracker couldn't read new bundles.
+const VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Bundle {
+    version: u32,
+    exported_at: DateTime<Local>,
+    project: Project,
+}
+
+#[derive(Subcommand)]
+pub enum BundleCommand {
+    /// Write a project to a bundle file, e.g. website.ptb.
+    Export { project: String, file: PathBuf },
+    /// Add the project in a bundle file to the tracker.
+    Import {
+        file: PathBuf,
+        /// Name to give the project, instead of the one it had.
+        #[arg(long = "as")]
+        name: Option<String>,
+    },
+}
+
+pub fn run(command: &BundleCommand) {
+    match command {
+        BundleCommand::Export { project, file } => export(project, file),
+        BundleCommand::Import { file, name } => import(file, name.as_deref()),
+    }
+}
+
+fn export(project_name: &str, file: &Path) {
+    let data = load_data();
+    let Some(project) = data.into_iter().find(|p| p.name == project_name) else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+        return;
+    };
+
+    let bundle = Bundle {
+        version: VERSION,
+        exported_at: Local::now(),
+        project,
+    };
+    let content = serde_json::to_vec(&bundle).expect("Unable to serialize bundle.");
+    let written = File::create(file).and_then(|file| {
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        encoder.write_all(&content)?;
+        encoder.finish().map(drop)
+    });
+    match written {
+        Ok(()) => println!(
+            "{}",
+            tr!(
+                "Project '{}' bundled into '{}' ({} tasks).",
+                project_name,
+                file.display(),
+                bundle.project.tasks.len()
+            )
+        ),
+        Err(e) => println!("{}", tr!("Unable to write '{}': {}", file.display(), e)),
+    }
+}
+
+fn read(file: &Path) -> Result<Bundle, String> {
+    let mut content = Vec::new();
+    File::open(file)
+        .and_then(|file| GzDecoder::new(file).read_to_end(&mut content))
+        .map_err(|e| tr!("Unable to read '{}': {}", file.display(), e))?;
+
+    // Look at the version first, a newer bundle may not parse at all.
+    #[derive(Deserialize)]
+    struct Header {
+        version: u32,
+    }
+    let header: Header = serde_json::from_slice(&content)
+        .map_err(|e| tr!("'{}' isn't a project bundle: {}", file.display(), e))?;
+    if header.version > VERSION {
+        return Err(tr!(
+            "'{}' was made by a newer version of project-tracker, update to import it.",
+            file.display()
+        ));
+    }
+    serde_json::from_slice(&content)
+        .map_err(|e| tr!("'{}' isn't a project bundle: {}", file.display(), e))
+}
+
+fn import(file: &Path, name: Option<&str>) {
+    let mut project = match read(file) {
+        Ok(bundle) => bundle.project,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    if let Some(name) = name {
+        project.name = name.to_string();
+    }
+
+    let mut data = load_data();
+    if data.iter().any(|p| p.name == project.name) {
+        println!(
+            "{}",
+            tr!(
+                "Project '{}' already exists, use --as to import it under another name.",
+                project.name
+            )
+        );
+        return;
+    }
+    // Prefixes are unique across the tracker.
+    if let Some(prefix) = &project.prefix {
+        if data.iter().any(|p| p.prefix.as_ref() == Some(prefix)) {
+            println!(
+                "{}",
+                tr!(
+                    "Prefix '{}' is already in use, the project was imported without one.",
+                    prefix
+                )
+            );
+            project.prefix = None;
+        }
+    }
+
+    let tasks = project.tasks.len();
+    data.push(project);
+    save_data(&data);
+    let project = data.last().expect("Project was just added");
+    println!(
+        "{}",
+        tr!("Project '{}' imported with {} tasks.", project.name, tasks)
+    );
+    hooks::run(Event::ProjectAdd(project));
+}
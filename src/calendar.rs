@@ -0,0 +1,133 @@
+//! Month and week calendars of pending tasks by due date.
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use colored::Colorize;
+use std::collections::BTreeMap;
+
+use crate::{
+    contexts, dates, display, i18n::tr, load_data, task_details, theme::Themed, Project, Task,
+};
+
+/// Pending tasks with a due date in `from..=to`, grouped by that date.
+fn due_between(
+    data: &[Project],
+    from: NaiveDate,
+    to: NaiveDate,
+) -> BTreeMap<NaiveDate, Vec<(&str, &Task)>> {
+    let mut days: BTreeMap<NaiveDate, Vec<(&str, &Task)>> = BTreeMap::new();
+    for project in data {
+        let pending = project
+            .tasks
+            .iter()
+            .filter(|t| !t.completed && contexts::shown(t));
+        for task in pending {
+            if let Some(due) = task.due.filter(|due| (from..=to).contains(due)) {
+                days.entry(due).or_default().push((&project.name, task));
+            }
+        }
+    }
+    days
+}
+
+/// Print a month grid around `date`, with the number of pending tasks due
+/// on each day.
+pub fn show_month(date: NaiveDate) {
+    let first = date.with_day(1).unwrap();
+    let last = first
+        .checked_add_months(Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .unwrap();
+
+    let data = load_data();
+    let days = due_between(&data, first, last);
+    let today = dates::today();
+
+    println!("{:^42}", first.format("%B %Y").to_string().bold());
+    println!(
+        " {}",
+        ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]
+            .map(|d| format!("{:<6}", d))
+            .join("")
+            .trim_end()
+    );
+
+    let mut line = " ".to_string();
+    line.push_str(&" ".repeat(6 * first.weekday().num_days_from_monday() as usize));
+
+    for day in first.iter_days().take_while(|d| *d <= last) {
+        let count = days.get(&day).map_or(0, Vec::len);
+        let number = format!("{:>2}", day.day());
+        let number = if day == today {
+            number.reversed().to_string()
+        } else {
+            number
+        };
+        let marker = match count {
+            0 => "    ".to_string(),
+            n => {
+                let marker = format!("({})", n);
+                // Pad before coloring, escape codes would throw off the width.
+                let padding = " ".repeat(4usize.saturating_sub(marker.len()));
+                let marker = if day < today {
+                    marker.bad()
+                } else if day == today {
+                    marker.warn()
+                } else {
+                    marker.good()
+                };
+                format!("{}{}", marker, padding)
+            }
+        };
+        line.push_str(&format!("{}{}", number, marker));
+
+        if day.weekday() == Weekday::Sun {
+            println!("{}", line.trim_end());
+            line = " ".to_string();
+        }
+    }
+    if !line.trim().is_empty() {
+        println!("{}", line.trim_end());
+    }
+
+    let total: usize = days.values().map(Vec::len).sum();
+    println!();
+    match total {
+        0 => println!("{}", tr!("No tasks due this month.")),
+        1 => println!("{}", tr!("1 task due this month.")),
+        n => println!("{}", tr!("{} tasks due this month.", n)),
+    }
+}
+
+/// Print each day of the week around `date`, with the tasks due that day.
+pub fn show_week(date: NaiveDate) {
+    let monday = date - Days::new(date.weekday().num_days_from_monday() as u64);
+    let sunday = monday + Days::new(6);
+
+    let data = load_data();
+    let days = due_between(&data, monday, sunday);
+    let today = dates::today();
+
+    for day in monday.iter_days().take(7) {
+        let header = day.format("%a %Y-%m-%d").to_string();
+        if day == today {
+            println!("{}", header.bold().yellow());
+        } else {
+            println!("{}", header.bold());
+        }
+
+        match days.get(&day) {
+            Some(tasks) => {
+                for (project, task) in tasks {
+                    println!(
+                        "    {}#{}: {}{}",
+                        project,
+                        task.id,
+                        display::description(task),
+                        task_details(task)
+                    );
+                }
+            }
+            None => println!("    -"),
+        }
+    }
+}
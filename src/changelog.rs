@@ -0,0 +1,155 @@
+//! `changelog`: a project's completed tasks as release notes, in the
+//! Keep a Changelog format (https://keepachangelog.com), grouped by their
+//! tags: `#bug` tasks are Fixed, `#feature` ones Added and so on, and
+//! anything else Changed.
+
+use chrono::{DateTime, Local, NaiveTime, TimeZone};
+use std::process::Command;
+
+use crate::{dates, i18n::tr, load_data, privacy, suggest, Task};
+
+/// The sections of a changelog, in their usual order, with the tags
+/// putting tasks in each.
+const SECTIONS: &[(&str, &[&str])] = &[
+    ("Added", &["added", "feature", "feat", "new"]),
+    ("Changed", &[]),
+    ("Deprecated", &["deprecated"]),
+    ("Removed", &["removed"]),
+    ("Fixed", &["fixed", "fix", "bug", "bugfix"]),
+    ("Security", &["security"]),
+];
+
+/// The section `task` goes in, Changed unless one of its tags says
+/// otherwise.
+fn section(task: &Task) -> &'static str {
+    SECTIONS
+        .iter()
+        .find(|(_, tags)| task.tags.iter().any(|tag| tags.contains(&tag.as_str())))
+        .map_or("Changed", |(name, _)| name)
+}
+
+/// The heading of the section called `name`, in the user's language.
+fn section_title(name: &str) -> String {
+    match name {
+        "Added" => tr!("Added"),
+        "Deprecated" => tr!("Deprecated"),
+        "Removed" => tr!("Removed"),
+        "Fixed" => tr!("Fixed"),
+        "Security" => tr!("Security"),
+        _ => tr!("Changed"),
+    }
+}
+
+/// The line of `task` in the changelog, masked if it's private as
+/// changelogs are published.
+fn entry(task: &Task) -> String {
+    format!("- {}", privacy::public_description(task).trim())
+}
+
+/// The most recent tag of the repository in the current directory.
+fn latest_tag() -> Option<String> {
+    let output = Command::new("git")
+        .args(["describe", "--tags", "--abbrev=0"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let tag = String::from_utf8(output.stdout).ok()?;
+    Some(tag.trim().to_string()).filter(|tag| !tag.is_empty())
+}
+
+/// When `since`, a date or a git tag, was; the latest tag if not given.
+fn start(since: Option<&str>) -> Result<Option<DateTime<Local>>, String> {
+    let Some(since) = since.map(str::to_string).or_else(latest_tag) else {
+        return Ok(None);
+    };
+    if let Some(time) = suggest::commit_time(&since) {
+        return Ok(Some(time));
+    }
+    let date = dates::parse_date(&since).map_err(|_| {
+        tr!(
+            "'{}' is neither a date nor a tag of this repository.",
+            since
+        )
+    })?;
+    Ok(Local
+        .from_local_datetime(&date.and_time(NaiveTime::MIN))
+        .earliest())
+}
+
+/// Print the changelog of `project_name` since `since`, a date or a git tag,
+/// as version `release` or as unreleased.
+pub fn changelog(project_name: &str, since: Option<&str>, release: Option<&str>) {
+    let since = match start(since) {
+        Ok(since) => since,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    let data = load_data();
+    let Some(project) = data.iter().find(|p| p.name == project_name) else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+        return;
+    };
+    let mut completed: Vec<&Task> = project
+        .tasks
+        .iter()
+        .filter(|t| t.completed)
+        .filter(|t| since.is_none_or(|since| t.completed_at.is_some_and(|at| at > since)))
+        .collect();
+    if completed.is_empty() {
+        let message = match since {
+            Some(since) => tr!(
+                "No tasks completed in '{}' since {}.",
+                project_name,
+                since.format("%Y-%m-%d %H:%M")
+            ),
+            None => tr!("No tasks completed in '{}' yet.", project_name),
+        };
+        println!("{}", message);
+        return;
+    }
+    completed.sort_by_key(|t| t.completed_at);
+
+    match release {
+        Some(version) => println!("## [{}] - {}", version, dates::today()),
+        None => println!("## [{}]", tr!("Unreleased")),
+    }
+    for (name, _) in SECTIONS {
+        let tasks: Vec<&&Task> = completed.iter().filter(|t| section(t) == *name).collect();
+        if tasks.is_empty() {
+            continue;
+        }
+        println!("\n### {}", section_title(name));
+        for task in tasks {
+            println!("{}", entry(task));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn private_tasks_are_masked() {
+        let task = |private| Task {
+            description: " fix the login bug ".to_string(),
+            private,
+            ..Default::default()
+        };
+        assert_eq!(entry(&task(false)), "- fix the login bug");
+        assert_eq!(entry(&task(true)), format!("- {}", privacy::MASK));
+    }
+
+    #[test]
+    fn tags_pick_the_section() {
+        let task = |tag: &str| Task {
+            tags: vec![tag.to_string()],
+            ..Default::default()
+        };
+        assert_eq!(section(&task("bug")), "Fixed");
+        assert_eq!(section(&task("feature")), "Added");
+        assert_eq!(section(&task("chore")), "Changed");
+    }
+}
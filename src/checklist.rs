@@ -0,0 +1,175 @@
+//! Lightweight checklists inside a task. A task whose checklist isn't all
+//! ticked is only completed with `--cascade`, which ticks the rest, or with
+//! `--force`, which leaves it as it is.
+
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+
+use crate::{history::TaskEvent, i18n::tr, load_data, privacy, save_data, storage, theme, Task};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChecklistItem {
+    pub text: String,
+    pub done: bool,
+}
+
+#[derive(Subcommand)]
+pub enum ChecklistCommand {
+    /// Add an item to a task's checklist.
+    Add {
+        project: String,
+        task_id: u32,
+        item: String,
+    },
+    /// Check or uncheck a checklist item.
+    Toggle {
+        project: String,
+        task_id: u32,
+        /// Number of the item, as shown by `checklist list`.
+        index: usize,
+    },
+    /// Remove an item from a task's checklist.
+    Remove {
+        project: String,
+        task_id: u32,
+        /// Number of the item, as shown by `checklist list`.
+        index: usize,
+    },
+    /// Show a task's checklist.
+    List { project: String, task_id: u32 },
+}
+
+impl Task {
+    /// Checklist progress as `(done, total)`.
+    pub fn checklist_progress(&self) -> (usize, usize) {
+        let done = self.checklist.iter().filter(|item| item.done).count();
+        (done, self.checklist.len())
+    }
+
+    /// Whether the checklist lets the task be completed: all ticked, ticked
+    /// now with `cascade`, or left unticked with `--force`. Says why not.
+    pub fn allows_completion(&mut self, cascade: bool) -> bool {
+        let (done, total) = self.checklist_progress();
+        if done == total || storage::is_forced() && !cascade {
+            return true;
+        }
+        if cascade {
+            for item in &mut self.checklist {
+                item.done = true;
+            }
+            self.record(
+                TaskEvent::Edited,
+                Some(format!("ticked {} checklist items", total - done)),
+            );
+            return true;
+        }
+        println!(
+            "{}",
+            tr!(
+                "Task {} has {} unticked checklist items: tick them too with --cascade, or leave them with --force.",
+                self.id,
+                total - done
+            )
+        );
+        false
+    }
+}
+
+pub fn run(command: &ChecklistCommand) {
+    match command {
+        ChecklistCommand::Add {
+            project,
+            task_id,
+            item,
+        } => edit(project, *task_id, |task| {
+            task.checklist.push(ChecklistItem {
+                text: item.clone(),
+                done: false,
+            });
+            Some(format!("checklist item added: {}", item))
+        }),
+        ChecklistCommand::Toggle {
+            project,
+            task_id,
+            index,
+        } => edit(project, *task_id, |task| {
+            let item = index
+                .checked_sub(1)
+                .and_then(|i| task.checklist.get_mut(i))?;
+            item.done = !item.done;
+            let state = if item.done { "checked" } else { "unchecked" };
+            Some(format!("checklist item {}: {}", state, item.text))
+        }),
+        ChecklistCommand::Remove {
+            project,
+            task_id,
+            index,
+        } => edit(project, *task_id, |task| {
+            let i = index.checked_sub(1).filter(|i| *i < task.checklist.len())?;
+            let item = task.checklist.remove(i);
+            Some(format!("checklist item removed: {}", item.text))
+        }),
+        ChecklistCommand::List { project, task_id } => list(project, *task_id),
+    }
+}
+
+/// Apply `change` to a task and save. `change` returns a description of what
+/// it did, or `None` if the checklist item it refers to doesn't exist.
+fn edit(project_name: &str, task_id: u32, change: impl FnOnce(&mut Task) -> Option<String>) {
+    let mut data = load_data();
+    if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
+        if let Some(task) = project.tasks.iter_mut().find(|t| t.id == task_id) {
+            let Some(detail) = change(task) else {
+                println!("{}", tr!("No such checklist item in task {}.", task_id));
+                return;
+            };
+            task.record(TaskEvent::Edited, Some(detail.clone()));
+            let (done, total) = task.checklist_progress();
+            save_data(&data);
+            println!(
+                "{}",
+                tr!("Task {}: {} ({}/{}).", task_id, detail, done, total)
+            );
+        } else {
+            println!(
+                "{}",
+                tr!("Task {} not found in project '{}'.", task_id, project_name)
+            );
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
+
+fn list(project_name: &str, task_id: u32) {
+    let data = load_data();
+    if let Some(project) = data.iter().find(|p| p.name == project_name) {
+        if let Some(task) = project.tasks.iter().find(|t| t.id == task_id) {
+            let (done, total) = task.checklist_progress();
+            println!(
+                "{}",
+                tr!(
+                    "Checklist of task {}: {} ({}/{})",
+                    task_id,
+                    privacy::description(task),
+                    done,
+                    total
+                )
+            );
+            if task.checklist.is_empty() {
+                println!("    {}", tr!("No checklist items yet."));
+            }
+            for (i, item) in task.checklist.iter().enumerate() {
+                let checkbox = theme::mark(item.done);
+                println!("    {} {}. {}", checkbox, i + 1, item.text);
+            }
+        } else {
+            println!(
+                "{}",
+                tr!("Task {} not found in project '{}'.", task_id, project_name)
+            );
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
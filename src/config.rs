@@ -0,0 +1,921 @@
+//! User configuration, stored in `config.json` in the config directory.
+//!
+//! Settings can be set globally, for a single workspace or for a single
+//! project, the more specific ones taking precedence, and over all of them
+//! for a single run with `PT_<KEY>` environment variables, as in
+//! `PT_THEME=mono`.
+
+use clap::{Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, env, fs, io::ErrorKind, path::PathBuf, sync::OnceLock};
+use tracing::{debug, trace};
+
+use crate::{
+    aliases::Aliases,
+    backup,
+    bars::{BarColor, BarStyle},
+    contexts, durations, get_config_dir, i18n,
+    i18n::tr,
+    load_data,
+    progress::Weighting,
+    storage,
+    storage::DataFormat,
+    table,
+    table::Column,
+    templates::Template,
+    theme::Theme,
+    webhooks::Webhook,
+    wip::WipPolicy,
+    workspace, Priority,
+};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Settings {
+    /// Show a desktop notification when a task is completed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notify: Option<bool>,
+    /// Play a sound when a task is completed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sound: Option<bool>,
+    /// Format the data file is stored in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<DataFormat>,
+    /// Keep each project in a file of its own, so saving only writes the
+    /// projects that changed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub split_projects: Option<bool>,
+    /// Directory the data files are kept in, instead of the config
+    /// directory, e.g. a synced folder.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<PathBuf>,
+    /// Workspace used without `--workspace`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_workspace: Option<String>,
+    /// Maximum number of tasks in progress at once.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wip_limit: Option<u32>,
+    /// Whether going over the WIP limit is refused or only warned about.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wip_policy: Option<WipPolicy>,
+    /// Refuse any command that would change the data.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_only: Option<bool>,
+    /// Archive completed tasks after this many days.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive_completed_after: Option<u32>,
+    /// Language of the messages, e.g. `pt-BR`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Only show tasks in this context, e.g. `@office`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Where `digest --email` sends the digest.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_to: Option<String>,
+    /// Sender of the digest, `email-to` if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_from: Option<String>,
+    /// SMTP server to send the digest through, e.g.
+    /// `smtps://user@smtp.example.com`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub smtp_url: Option<String>,
+    /// Show the completion streak above the overview.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub streak_banner: Option<bool>,
+    /// Append what's done to `project-tracker.log` in the config directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_file: Option<bool>,
+    /// Project used by commands given none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_project: Option<String>,
+    /// Project every command defaults to and the overview is limited to,
+    /// set with `focus`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focus: Option<String>,
+    /// Have `add-task` create the project when it doesn't exist, as with
+    /// `--create`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_projects: Option<bool>,
+    /// Priority of tasks added without one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_priority: Option<Priority>,
+    /// Ask to type the project's name before deleting it, deleting its
+    /// tasks or completing several at once; meant to be set per project.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protected: Option<bool>,
+    /// Keys the overview sorts each project's tasks on, none for their
+    /// manual order.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overview_sort: Option<Vec<Column>>,
+    /// Tint pending tasks yellow once they're this many days old.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age_warn_after: Option<u32>,
+    /// Tint pending tasks red once they're this many days old.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age_alert_after: Option<u32>,
+    /// Escalate tasks overdue by more than this many days.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub escalate_after: Option<u32>,
+    /// Characters progress bars are drawn with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bar_style: Option<BarStyle>,
+    /// How progress bars are colored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bar_color: Option<BarColor>,
+    /// Width of progress bars, in characters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bar_width: Option<u32>,
+    /// What tasks count for in progress: all the same, or their estimate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress_weighting: Option<Weighting>,
+    /// Colors of listings: default, colorblind, high-contrast or mono.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<Theme>,
+    /// Show Nerd Font icons for task status and priority.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icons: Option<bool>,
+    /// Make links clickable, whether or not the terminal seems to support
+    /// it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hyperlinks: Option<bool>,
+    /// Where project names link to, with `{project}` for the name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_url: Option<String>,
+    /// Name changes to tasks are attributed to, for a tracker shared with
+    /// others.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Where backups are sent: a WebDAV URL or `s3://bucket/prefix`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_url: Option<String>,
+    /// Minutes to wait at least between two backups.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_interval: Option<u32>,
+}
+
+/// Every key `config set` accepts, with a short description.
+const KEYS: &[(&str, &str)] = &[
+    (
+        "notify",
+        "desktop notification on task completion (true/false)",
+    ),
+    ("sound", "play a sound on task completion (true/false)"),
+    ("format", "data file format (json/toml/yaml)"),
+    (
+        "split-projects",
+        "keep each project in a file of its own (true/false)",
+    ),
+    (
+        "data-dir",
+        "directory the data is kept in, e.g. a synced folder",
+    ),
+    ("default-workspace", "workspace used without --workspace"),
+    ("wip-limit", "maximum number of tasks in progress"),
+    ("wip-policy", "over the WIP limit: warn or refuse"),
+    ("read-only", "refuse changes to the data (true/false)"),
+    (
+        "archive-completed-after",
+        "archive completed tasks after this long (e.g. 30d)",
+    ),
+    ("language", "language of the messages (e.g. en, pt-BR)"),
+    ("context", "only show tasks in this context (e.g. @office)"),
+    ("email-to", "address `digest --email` sends to"),
+    ("email-from", "sender of the digest, email-to if unset"),
+    (
+        "smtp-url",
+        "SMTP server for the digest, e.g. smtps://user@host",
+    ),
+    (
+        "streak-banner",
+        "show the completion streak above the overview (true/false)",
+    ),
+    (
+        "log-file",
+        "append a debug log to project-tracker.log (true/false)",
+    ),
+    (
+        "default-project",
+        "project add-task, list-tasks and others use when given none",
+    ),
+    (
+        "focus",
+        "project every command defaults to, and the overview shows alone",
+    ),
+    (
+        "create-projects",
+        "add-task creates projects that don't exist (true/false)",
+    ),
+    (
+        "default-priority",
+        "priority of tasks added without one: low, medium or high",
+    ),
+    (
+        "protected",
+        "type the project's name to delete it or its tasks (true/false)",
+    ),
+    (
+        "overview-sort",
+        "sort keys of the overview (e.g. status,priority,due) or manual",
+    ),
+    (
+        "age-warn-after",
+        "tint pending tasks yellow from this age (e.g. 2w)",
+    ),
+    (
+        "age-alert-after",
+        "tint pending tasks red from this age (e.g. 30d)",
+    ),
+    (
+        "escalate-after",
+        "escalate tasks overdue by more than this (e.g. 3d)",
+    ),
+    (
+        "bar-style",
+        "progress bar characters: blocks, dots or ascii",
+    ),
+    ("bar-color", "progress bar color: green, gradient or none"),
+    ("bar-width", "progress bar width, in characters"),
+    (
+        "progress-weighting",
+        "what tasks count for in progress: count or estimate",
+    ),
+    (
+        "theme",
+        "colors: default, colorblind, high-contrast or mono",
+    ),
+    (
+        "icons",
+        "Nerd Font icons for status and priority (true/false)",
+    ),
+    (
+        "hyperlinks",
+        "clickable links, detected by default (true/false)",
+    ),
+    (
+        "project-url",
+        "where project names link to, {project} for the name",
+    ),
+    (
+        "user",
+        "name task changes are attributed to, in a shared tracker",
+    ),
+    (
+        "backup-url",
+        "back up to a WebDAV URL or s3://bucket/prefix",
+    ),
+    ("backup-interval", "time between backups at least (e.g. 1h)"),
+];
+
+/// The keys that can be set for a single project.
+const PER_PROJECT_KEYS: &[&str] = &[
+    "notify",
+    "sound",
+    "wip-limit",
+    "wip-policy",
+    "archive-completed-after",
+    "overview-sort",
+    "default-priority",
+    "protected",
+    "project-url",
+];
+
+/// `name`, provided there's a project by that name.
+fn existing_project(name: &str) -> Result<String, String> {
+    if load_data().iter().any(|p| p.name == name) {
+        Ok(name.to_string())
+    } else {
+        Err(tr!("no project named '{}'", name))
+    }
+}
+
+/// Settings that only make sense for all workspaces at once.
+const GLOBAL_KEYS: &[&str] = &["data-dir", "default-workspace"];
+
+fn existing_workspace(name: &str) -> Result<String, String> {
+    if workspace::exists(name) {
+        Ok(name.to_string())
+    } else {
+        Err(tr!("no workspace named '{}'", name))
+    }
+}
+
+/// `value` as an absolute path, `~` standing for the home directory.
+pub fn parse_dir(value: &str) -> Result<PathBuf, String> {
+    let path = match value.strip_prefix("~/").or((value == "~").then_some("")) {
+        Some(rest) => dirs::home_dir()
+            .ok_or_else(|| tr!("no home directory to put the data in"))?
+            .join(rest),
+        None => PathBuf::from(value),
+    };
+    if path.is_absolute() {
+        Ok(path)
+    } else {
+        Err(tr!("expected an absolute path, got '{}'", value))
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Ok(true),
+        "false" | "no" | "off" | "0" => Ok(false),
+        _ => Err(tr!("expected true or false, got '{}'", value)),
+    }
+}
+
+impl Settings {
+    fn set(&mut self, key: &str, value: Option<&str>) -> Result<(), String> {
+        match key {
+            "notify" => self.notify = value.map(parse_bool).transpose()?,
+            "sound" => self.sound = value.map(parse_bool).transpose()?,
+            "format" => {
+                self.format = value
+                    .map(|v| {
+                        DataFormat::from_str(v, true).map_err(|_| tr!("unknown format '{}'", v))
+                    })
+                    .transpose()?
+            }
+            "split-projects" => self.split_projects = value.map(parse_bool).transpose()?,
+            "data-dir" => self.data_dir = value.map(parse_dir).transpose()?,
+            "default-workspace" => {
+                self.default_workspace = value.map(existing_workspace).transpose()?
+            }
+            "wip-limit" => {
+                self.wip_limit = value
+                    .map(|v| match v.parse() {
+                        Ok(limit) if limit > 0 => Ok(limit),
+                        _ => Err(tr!("expected a positive number, got '{}'", v)),
+                    })
+                    .transpose()?
+            }
+            "wip-policy" => {
+                self.wip_policy = value
+                    .map(|v| {
+                        WipPolicy::from_str(v, true)
+                            .map_err(|_| tr!("expected warn or refuse, got '{}'", v))
+                    })
+                    .transpose()?
+            }
+            "read-only" => self.read_only = value.map(parse_bool).transpose()?,
+            "archive-completed-after" => {
+                self.archive_completed_after = value.map(durations::parse_days).transpose()?
+            }
+            "language" => {
+                self.language = value
+                    .map(|v| {
+                        if i18n::is_available(v) {
+                            Ok(v.to_string())
+                        } else {
+                            Err(tr!("no translation available for '{}'", v))
+                        }
+                    })
+                    .transpose()?
+            }
+            "context" => self.context = value.map(contexts::normalize),
+            "email-to" => self.email_to = value.map(str::to_string),
+            "email-from" => self.email_from = value.map(str::to_string),
+            "smtp-url" => self.smtp_url = value.map(str::to_string),
+            "streak-banner" => self.streak_banner = value.map(parse_bool).transpose()?,
+            "log-file" => self.log_file = value.map(parse_bool).transpose()?,
+            "default-project" => self.default_project = value.map(existing_project).transpose()?,
+            "focus" => self.focus = value.map(existing_project).transpose()?,
+            "create-projects" => self.create_projects = value.map(parse_bool).transpose()?,
+            "default-priority" => {
+                self.default_priority = value
+                    .map(|v| {
+                        Priority::from_str(v, true)
+                            .map_err(|_| tr!("expected low, medium or high, got '{}'", v))
+                    })
+                    .transpose()?
+            }
+            "protected" => self.protected = value.map(parse_bool).transpose()?,
+            "overview-sort" => {
+                self.overview_sort = value
+                    .map(|v| {
+                        if v.eq_ignore_ascii_case("manual") {
+                            Ok(Vec::new())
+                        } else {
+                            table::parse_columns(v)
+                        }
+                    })
+                    .transpose()?
+            }
+            "age-warn-after" => {
+                self.age_warn_after = value.map(durations::parse_days).transpose()?
+            }
+            "age-alert-after" => {
+                self.age_alert_after = value.map(durations::parse_days).transpose()?
+            }
+            "escalate-after" => {
+                self.escalate_after = value.map(durations::parse_days).transpose()?
+            }
+            "bar-style" => {
+                self.bar_style = value
+                    .map(|v| {
+                        BarStyle::from_str(v, true)
+                            .map_err(|_| tr!("expected blocks, dots or ascii, got '{}'", v))
+                    })
+                    .transpose()?
+            }
+            "bar-color" => {
+                self.bar_color = value
+                    .map(|v| {
+                        BarColor::from_str(v, true)
+                            .map_err(|_| tr!("expected green, gradient or none, got '{}'", v))
+                    })
+                    .transpose()?
+            }
+            "progress-weighting" => {
+                self.progress_weighting = value
+                    .map(|v| {
+                        Weighting::from_str(v, true)
+                            .map_err(|_| tr!("expected count or estimate, got '{}'", v))
+                    })
+                    .transpose()?
+            }
+            "bar-width" => {
+                self.bar_width = value
+                    .map(|v| match v.parse() {
+                        Ok(width) if width > 0 => Ok(width),
+                        _ => Err(tr!("expected a positive number, got '{}'", v)),
+                    })
+                    .transpose()?
+            }
+            "theme" => {
+                self.theme = value
+                    .map(|v| {
+                        Theme::from_str(v, true).map_err(|_| {
+                            tr!(
+                                "expected default, colorblind, high-contrast or mono, got '{}'",
+                                v
+                            )
+                        })
+                    })
+                    .transpose()?
+            }
+            "icons" => self.icons = value.map(parse_bool).transpose()?,
+            "hyperlinks" => self.hyperlinks = value.map(parse_bool).transpose()?,
+            "project-url" => self.project_url = value.map(str::to_string),
+            "user" => self.user = value.map(str::to_string),
+            "backup-url" => {
+                self.backup_url = value
+                    .map(|v| backup::parse_target(v).map(|_| v.to_string()))
+                    .transpose()?
+            }
+            "backup-interval" => {
+                self.backup_interval = value.map(durations::parse_span).transpose()?
+            }
+            _ => return Err(tr!("unknown setting '{}'", key)),
+        }
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        match key {
+            "notify" => self.notify.map(|v| v.to_string()),
+            "sound" => self.sound.map(|v| v.to_string()),
+            "format" => self.format.map(|v| v.extension().to_string()),
+            "split-projects" => self.split_projects.map(|v| v.to_string()),
+            "data-dir" => self.data_dir.as_ref().map(|v| v.display().to_string()),
+            "default-workspace" => self.default_workspace.clone(),
+            "wip-limit" => self.wip_limit.map(|v| v.to_string()),
+            "wip-policy" => self
+                .wip_policy
+                .and_then(|v| v.to_possible_value())
+                .map(|v| v.get_name().to_string()),
+            "read-only" => self.read_only.map(|v| v.to_string()),
+            "archive-completed-after" => self.archive_completed_after.map(|v| format!("{}d", v)),
+            "language" => self.language.clone(),
+            "context" => self.context.clone(),
+            "email-to" => self.email_to.clone(),
+            "email-from" => self.email_from.clone(),
+            "smtp-url" => self.smtp_url.clone(),
+            "streak-banner" => self.streak_banner.map(|v| v.to_string()),
+            "log-file" => self.log_file.map(|v| v.to_string()),
+            "default-project" => self.default_project.clone(),
+            "focus" => self.focus.clone(),
+            "create-projects" => self.create_projects.map(|v| v.to_string()),
+            "default-priority" => self.default_priority.map(|v| v.to_string()),
+            "protected" => self.protected.map(|v| v.to_string()),
+            "overview-sort" => self.overview_sort.as_ref().map(|keys| {
+                if keys.is_empty() {
+                    "manual".to_string()
+                } else {
+                    keys.iter()
+                        .filter_map(|key| key.to_possible_value())
+                        .map(|key| key.get_name().to_string())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                }
+            }),
+            "age-warn-after" => self.age_warn_after.map(|v| format!("{}d", v)),
+            "age-alert-after" => self.age_alert_after.map(|v| format!("{}d", v)),
+            "escalate-after" => self.escalate_after.map(|v| format!("{}d", v)),
+            "bar-style" => self
+                .bar_style
+                .and_then(|v| v.to_possible_value())
+                .map(|v| v.get_name().to_string()),
+            "bar-color" => self
+                .bar_color
+                .and_then(|v| v.to_possible_value())
+                .map(|v| v.get_name().to_string()),
+            "bar-width" => self.bar_width.map(|v| v.to_string()),
+            "progress-weighting" => self
+                .progress_weighting
+                .and_then(|v| v.to_possible_value())
+                .map(|v| v.get_name().to_string()),
+            "theme" => self
+                .theme
+                .and_then(|v| v.to_possible_value())
+                .map(|v| v.get_name().to_string()),
+            "icons" => self.icons.map(|v| v.to_string()),
+            "hyperlinks" => self.hyperlinks.map(|v| v.to_string()),
+            "project-url" => self.project_url.clone(),
+            "user" => self.user.clone(),
+            "backup-url" => self.backup_url.clone(),
+            "backup-interval" => self.backup_interval.map(durations::format_span),
+            _ => None,
+        }
+    }
+
+    /// `self`, with anything it leaves unset taken from `fallback`.
+    fn or(&self, fallback: &Settings) -> Settings {
+        Settings {
+            notify: self.notify.or(fallback.notify),
+            sound: self.sound.or(fallback.sound),
+            format: self.format.or(fallback.format),
+            split_projects: self.split_projects.or(fallback.split_projects),
+            data_dir: self.data_dir.clone().or(fallback.data_dir.clone()),
+            default_workspace: self
+                .default_workspace
+                .clone()
+                .or(fallback.default_workspace.clone()),
+            wip_limit: self.wip_limit.or(fallback.wip_limit),
+            wip_policy: self.wip_policy.or(fallback.wip_policy),
+            read_only: self.read_only.or(fallback.read_only),
+            archive_completed_after: self
+                .archive_completed_after
+                .or(fallback.archive_completed_after),
+            language: self.language.clone().or(fallback.language.clone()),
+            context: self.context.clone().or(fallback.context.clone()),
+            email_to: self.email_to.clone().or(fallback.email_to.clone()),
+            email_from: self.email_from.clone().or(fallback.email_from.clone()),
+            smtp_url: self.smtp_url.clone().or(fallback.smtp_url.clone()),
+            streak_banner: self.streak_banner.or(fallback.streak_banner),
+            log_file: self.log_file.or(fallback.log_file),
+            default_project: self
+                .default_project
+                .clone()
+                .or(fallback.default_project.clone()),
+            focus: self.focus.clone().or(fallback.focus.clone()),
+            create_projects: self.create_projects.or(fallback.create_projects),
+            default_priority: self.default_priority.or(fallback.default_priority),
+            protected: self.protected.or(fallback.protected),
+            overview_sort: self
+                .overview_sort
+                .clone()
+                .or(fallback.overview_sort.clone()),
+            age_warn_after: self.age_warn_after.or(fallback.age_warn_after),
+            age_alert_after: self.age_alert_after.or(fallback.age_alert_after),
+            escalate_after: self.escalate_after.or(fallback.escalate_after),
+            bar_style: self.bar_style.or(fallback.bar_style),
+            bar_color: self.bar_color.or(fallback.bar_color),
+            bar_width: self.bar_width.or(fallback.bar_width),
+            progress_weighting: self.progress_weighting.or(fallback.progress_weighting),
+            theme: self.theme.or(fallback.theme),
+            icons: self.icons.or(fallback.icons),
+            hyperlinks: self.hyperlinks.or(fallback.hyperlinks),
+            project_url: self.project_url.clone().or(fallback.project_url.clone()),
+            user: self.user.clone().or(fallback.user.clone()),
+            backup_url: self.backup_url.clone().or(fallback.backup_url.clone()),
+            backup_interval: self.backup_interval.or(fallback.backup_interval),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Config {
+    /// Saved filter expressions, by name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub views: BTreeMap<String, String>,
+    /// Task templates, by name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, Template>,
+    /// URLs task events are POSTed to.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<Webhook>,
+    /// Command and project aliases.
+    #[serde(skip_serializing_if = "Aliases::is_empty")]
+    pub aliases: Aliases,
+    /// Global settings.
+    pub settings: Settings,
+    /// Per-workspace settings, overriding the global ones.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub workspaces: BTreeMap<String, Settings>,
+    /// Per-project settings, by workspace and then project, overriding the
+    /// workspace ones.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub projects: BTreeMap<String, BTreeMap<String, Settings>>,
+}
+
+/// The environment variable overriding `key`, as in `PT_WIP_LIMIT`.
+fn env_var(key: &str) -> String {
+    format!("PT_{}", key.to_uppercase().replace('-', "_"))
+}
+
+/// Settings given in `PT_<KEY>` environment variables.
+fn environment() -> &'static Settings {
+    static ENVIRONMENT: OnceLock<Settings> = OnceLock::new();
+    ENVIRONMENT.get_or_init(|| {
+        let mut settings = Settings::default();
+        // Tests run the same whatever the environment.
+        if cfg!(test) {
+            return settings;
+        }
+        for (key, _) in KEYS {
+            let Ok(value) = env::var(env_var(key)) else {
+                continue;
+            };
+            // Checking projects and workspaces needs the data, which reads
+            // the settings, so these are taken as they are. So is the
+            // language, read on its own by the translations, see
+            // `Config::language`.
+            match *key {
+                "default-project" => settings.default_project = Some(value),
+                "default-workspace" => settings.default_workspace = Some(value),
+                "focus" => settings.focus = Some(value),
+                "language" => settings.language = Some(value),
+                _ => {
+                    if let Err(e) = settings.set(key, Some(&value)) {
+                        eprintln!("{}", tr!("Ignoring {}: {}", env_var(key), e));
+                    }
+                }
+            }
+        }
+        settings
+    })
+}
+
+/// Where a setting is changed.
+enum Scope<'a> {
+    Global,
+    Workspace,
+    Project(&'a str),
+}
+
+impl Config {
+    /// Settings in effect for the active workspace.
+    pub fn effective(&self) -> Settings {
+        self.effective_for(None)
+    }
+
+    /// Settings in effect for `project` in the active workspace, or for the
+    /// workspace as a whole.
+    pub fn effective_for(&self, project: Option<&str>) -> Settings {
+        environment().or(&self.configured_for(project))
+    }
+
+    /// The language in effect. Unlike the other settings, it's found
+    /// without the environment ones, whose errors need it to be translated.
+    pub fn language(&self) -> Option<String> {
+        env::var(env_var("language"))
+            .ok()
+            .or(self.configured_for(None).language)
+    }
+
+    /// The settings from the config file in effect for `project`, or for
+    /// the workspace as a whole.
+    fn configured_for(&self, project: Option<&str>) -> Settings {
+        let mut settings = self.settings.clone();
+        if let Some(local) = self.workspaces.get(workspace::active()) {
+            settings = local.or(&settings);
+        }
+        let project_settings = project.and_then(|project| {
+            self.projects
+                .get(workspace::active())
+                .and_then(|projects| projects.get(project))
+        });
+        if let Some(project_settings) = project_settings {
+            settings = project_settings.or(&settings);
+        }
+        settings
+    }
+
+    /// Settings in effect for all workspaces, for those only set globally.
+    pub fn global(&self) -> Settings {
+        environment().or(&self.settings)
+    }
+
+    /// The layer the value of `key` in effect for `project` comes from.
+    fn source(&self, key: &str, project: Option<&str>) -> String {
+        let is_set = |settings: Option<&Settings>| settings.is_some_and(|s| s.get(key).is_some());
+        let project_settings = project.and_then(|project| {
+            self.projects
+                .get(workspace::active())
+                .and_then(|projects| projects.get(project))
+        });
+        if is_set(Some(environment())) {
+            env_var(key)
+        } else if is_set(project_settings) {
+            tr!("project")
+        } else if is_set(self.workspaces.get(workspace::active())) {
+            tr!("workspace")
+        } else if is_set(Some(&self.settings)) {
+            tr!("global")
+        } else {
+            tr!("default")
+        }
+    }
+
+    fn layer(&mut self, scope: &Scope) -> &mut Settings {
+        match scope {
+            Scope::Global => &mut self.settings,
+            Scope::Workspace => self
+                .workspaces
+                .entry(workspace::active().to_string())
+                .or_default(),
+            Scope::Project(project) => self
+                .projects
+                .entry(workspace::active().to_string())
+                .or_default()
+                .entry(project.to_string())
+                .or_default(),
+        }
+    }
+}
+
+fn get_config_file_path() -> PathBuf {
+    get_config_dir().join("config.json")
+}
+
+/// Whether settings were ever saved, as they are from the first run on.
+pub fn is_saved() -> bool {
+    get_config_file_path().exists()
+}
+
+pub fn load_config() -> Config {
+    trace!("reading {}", get_config_file_path().display());
+    match fs::read_to_string(get_config_file_path()) {
+        Ok(content) => serde_json::from_str(&content).expect("Unable to parse config file."),
+        Err(e) if e.kind() == ErrorKind::NotFound => Config::default(),
+        Err(e) => panic!("Unable to read config file: {}", e),
+    }
+}
+
+pub fn save_config(config: &Config) {
+    let content = serde_json::to_string_pretty(config).expect("Unable to serialize config.");
+    debug!("writing {}", get_config_file_path().display());
+    fs::write(get_config_file_path(), content).expect("Unable to write config file.");
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Change a setting.
+    Set {
+        key: String,
+        value: String,
+        /// Only change it for the current workspace.
+        #[arg(long)]
+        local: bool,
+        /// Only change it for this project of the current workspace.
+        #[arg(long, conflicts_with = "local")]
+        project: Option<String>,
+    },
+    /// Remove a setting, falling back to the default.
+    Unset {
+        key: String,
+        /// Only remove it from the current workspace.
+        #[arg(long)]
+        local: bool,
+        /// Only remove it from this project of the current workspace.
+        #[arg(long, conflicts_with = "local")]
+        project: Option<String>,
+    },
+    /// Show the value of a setting in the current workspace.
+    Get {
+        key: String,
+        /// Show the value in effect for this project.
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Show all settings in effect for the current workspace.
+    Show {
+        /// Show the settings in effect for this project.
+        #[arg(long)]
+        project: Option<String>,
+        /// Also show where each value comes from: the environment, the
+        /// project, the workspace, the global settings or the default.
+        #[arg(long)]
+        effective: bool,
+    },
+}
+
+pub fn run(command: &ConfigCommand) {
+    match command {
+        ConfigCommand::Set {
+            key,
+            value,
+            local,
+            project,
+        } => set(key, Some(value), scope(*local, project.as_deref())),
+        ConfigCommand::Unset {
+            key,
+            local,
+            project,
+        } => set(key, None, scope(*local, project.as_deref())),
+        ConfigCommand::Get { key, project } => {
+            match load_config().effective_for(project.as_deref()).get(key) {
+                Some(value) => println!("{}", value),
+                None if KEYS.iter().any(|(k, _)| k == key) => {
+                    println!("{}", tr!("{} is not set.", key))
+                }
+                None => println!("{}", tr!("Unknown setting '{}'.", key)),
+            }
+        }
+        ConfigCommand::Show { project, effective } => show(project.as_deref(), *effective),
+    }
+}
+
+fn scope(local: bool, project: Option<&str>) -> Scope<'_> {
+    match project {
+        Some(project) => Scope::Project(project),
+        None if local => Scope::Workspace,
+        None => Scope::Global,
+    }
+}
+
+fn set(key: &str, value: Option<&str>, scope: Scope) {
+    if let Scope::Project(project) = scope {
+        if !load_data().iter().any(|p| p.name == project) {
+            println!("{}", tr!("Project '{}' not found.", project));
+            return;
+        }
+        let known = KEYS.iter().any(|(k, _)| *k == key);
+        if known && !PER_PROJECT_KEYS.contains(&key) {
+            println!("{}", tr!("'{}' can't be set for a single project.", key));
+            return;
+        }
+    }
+    if matches!(scope, Scope::Workspace) && GLOBAL_KEYS.contains(&key) {
+        println!("{}", tr!("'{}' can only be set globally.", key));
+        return;
+    }
+    let data_dir = workspace::data_dir();
+
+    let mut config = load_config();
+    if let Err(e) = config.layer(&scope).set(key, value) {
+        println!("{}", tr!("Unable to change setting: {}", e));
+        return;
+    }
+    save_config(&config);
+
+    let scope = match scope {
+        Scope::Global => String::new(),
+        Scope::Workspace => tr!(" for workspace '{}'", workspace::active()),
+        Scope::Project(project) => tr!(" for project '{}'", project),
+    };
+    match value {
+        Some(value) => println!("{}", tr!("{} set to '{}'{}.", key, value, scope)),
+        None => println!("{}", tr!("{} unset{}.", key, scope)),
+    }
+
+    if key == "format" || key == "split-projects" {
+        storage::convert();
+    }
+    if key == "data-dir" {
+        workspace::move_data(&data_dir);
+    }
+}
+
+fn show(project: Option<&str>, effective: bool) {
+    let config = load_config();
+    let settings = config.effective_for(project);
+    match project {
+        Some(project) => println!(
+            "{}",
+            tr!(
+                "Settings for project '{}' in workspace '{}':",
+                project,
+                workspace::active()
+            )
+        ),
+        None => println!(
+            "{}",
+            tr!("Settings for workspace '{}':", workspace::active())
+        ),
+    }
+    let width = KEYS.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+    for (key, description) in KEYS {
+        let value = settings.get(key).unwrap_or_else(|| "-".to_string());
+        if effective {
+            let source = format!("({})", config.source(key, project));
+            println!(
+                "    {:<width$} {:<8} {:<18} {}",
+                key, value, source, description
+            );
+        } else {
+            println!("    {:<width$} {:<8} {}", key, value, description);
+        }
+    }
+}
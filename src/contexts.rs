@@ -0,0 +1,170 @@
+//! GTD contexts: where a task can be done, such as `@home`, `@office` or
+//! `@errand`, kept apart from tags.
+//!
+//! Setting a context with `context set @office` makes every listing only
+//! show the tasks in it, until it's cleared with `context clear`.
+
+use clap::Subcommand;
+use std::{collections::BTreeMap, sync::OnceLock};
+
+use crate::{
+    config::{load_config, save_config},
+    history::TaskEvent,
+    i18n::tr,
+    load_data, save_data, Task,
+};
+
+#[derive(Subcommand)]
+pub enum ContextCommand {
+    /// Only show tasks in this context from now on, e.g. @office.
+    Set { context: String },
+    /// Show tasks in every context again.
+    Clear,
+    /// Show the context currently set.
+    Show,
+    /// List the contexts in use, with their number of pending tasks.
+    List,
+    /// Add contexts to a task.
+    Add {
+        project: String,
+        task_id: u32,
+        #[arg(required = true)]
+        contexts: Vec<String>,
+    },
+    /// Remove contexts from a task.
+    Remove {
+        project: String,
+        task_id: u32,
+        #[arg(required = true)]
+        contexts: Vec<String>,
+    },
+}
+
+static ACTIVE: OnceLock<Option<String>> = OnceLock::new();
+
+/// Contexts are stored lowercase with a leading `@`, whether or not one was
+/// typed.
+pub fn normalize(context: &str) -> String {
+    format!("@{}", context.trim().trim_start_matches('@').to_lowercase())
+}
+
+/// The context set with `context set`, if any.
+pub fn active() -> Option<&'static str> {
+    ACTIVE
+        .get_or_init(|| load_config().effective().context)
+        .as_deref()
+}
+
+/// Whether `task` is in `context`, or `context` is `None`.
+pub fn in_context(task: &Task, context: Option<&str>) -> bool {
+    context.is_none_or(|context| task.contexts.iter().any(|c| c == context))
+}
+
+/// Whether `task` should be listed, given the context currently set.
+pub fn shown(task: &Task) -> bool {
+    in_context(task, active())
+}
+
+pub fn run(command: &ContextCommand) {
+    match command {
+        ContextCommand::Set { context } => set_active(Some(&normalize(context))),
+        ContextCommand::Clear => set_active(None),
+        ContextCommand::Show => match active() {
+            Some(context) => println!("{}", context),
+            None => println!("{}", tr!("No context set.")),
+        },
+        ContextCommand::List => list_contexts(),
+        ContextCommand::Add {
+            project,
+            task_id,
+            contexts,
+        } => change_contexts(project, *task_id, contexts, false),
+        ContextCommand::Remove {
+            project,
+            task_id,
+            contexts,
+        } => change_contexts(project, *task_id, contexts, true),
+    }
+}
+
+fn set_active(context: Option<&str>) {
+    if context == Some("@") {
+        println!("{}", tr!("A context needs a name, e.g. @office."));
+        return;
+    }
+
+    let mut config = load_config();
+    config.settings.context = context.map(str::to_string);
+    save_config(&config);
+
+    match context {
+        Some(context) => println!("{}", tr!("Only showing tasks in {} from now on.", context)),
+        None => println!("{}", tr!("Showing tasks in every context again.")),
+    }
+}
+
+fn list_contexts() {
+    let data = load_data();
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for task in data.iter().flat_map(|p| &p.tasks) {
+        for context in &task.contexts {
+            *counts.entry(context).or_default() += usize::from(!task.completed);
+        }
+    }
+
+    if counts.is_empty() {
+        println!("{}", tr!("No tasks have a context yet."));
+        return;
+    }
+
+    println!("{}", tr!("Contexts:"));
+    for (context, pending) in counts {
+        let marker = if active() == Some(context) { "*" } else { " " };
+        println!(" {} {} ({})", marker, context, pending);
+    }
+}
+
+fn change_contexts(project_name: &str, task_id: u32, contexts: &[String], remove: bool) {
+    let mut data = load_data();
+    if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
+        if let Some(task) = project.tasks.iter_mut().find(|t| t.id == task_id) {
+            let mut changed = Vec::new();
+            for context in contexts.iter().map(|c| normalize(c)) {
+                let present = task.contexts.contains(&context);
+                if remove && present {
+                    task.contexts.retain(|c| *c != context);
+                    changed.push(context);
+                } else if !remove && !present && context != "@" {
+                    task.contexts.push(context.clone());
+                    changed.push(context);
+                }
+            }
+
+            if changed.is_empty() {
+                println!("{}", tr!("Task {} left unchanged.", task_id));
+                return;
+            }
+
+            let list = changed.join(" ");
+            let detail = if remove {
+                format!("removed from {}", list)
+            } else {
+                format!("added to {}", list)
+            };
+            task.record(TaskEvent::Edited, Some(detail));
+            save_data(&data);
+            if remove {
+                println!("{}", tr!("Task {} removed from {}.", task_id, list));
+            } else {
+                println!("{}", tr!("Task {} added to {}.", task_id, list));
+            }
+        } else {
+            println!(
+                "{}",
+                tr!("Task {} not found in project '{}'.", task_id, project_name)
+            );
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
@@ -0,0 +1,68 @@
+//! Parsing of human-friendly dates such as `tomorrow`, `next friday` or
+//! `2 weeks ago`, on top of the usual ISO `2025-07-01`.
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use chrono_english::{parse_date_string, Dialect};
+
+use crate::i18n::tr;
+
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Parse a date typed on the command line.
+///
+/// Meant to be used as a clap `value_parser`, hence the `String` error.
+pub fn parse_date(input: &str) -> Result<NaiveDate, String> {
+    let normalized = input.trim().to_lowercase();
+
+    if let Ok(date) = NaiveDate::parse_from_str(&normalized, "%Y-%m-%d") {
+        return Ok(date);
+    }
+
+    // A couple of forms people naturally type that chrono-english doesn't
+    // understand on its own.
+    let normalized = normalized.strip_prefix("in ").unwrap_or(&normalized);
+    match normalized {
+        "next week" => return Ok(today() + Duration::weeks(1)),
+        "next month" => return parse_date("1 month"),
+        _ => {}
+    }
+
+    parse_date_string(normalized, Local::now(), Dialect::Uk)
+        .map(|date| date.date_naive())
+        .map_err(|_| {
+            tr!(
+                "could not understand the date '{}' (try e.g. 2025-07-01, tomorrow, \"next friday\", \"2 weeks ago\")",
+                input
+            )
+        })
+}
+
+/// Parse a date and time typed on the command line, such as
+/// `2025-07-01 09:00` or `tomorrow 14:30`.
+pub fn parse_datetime(input: &str) -> Result<DateTime<Local>, String> {
+    let error = || {
+        tr!(
+            "could not understand the time '{}' (try e.g. \"2025-07-01 09:00\" or \"tomorrow 14:30\")",
+            input
+        )
+    };
+    let input = input.trim();
+
+    let naive = NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M")
+        .ok()
+        .or_else(|| {
+            let (date, time) = input.rsplit_once(' ')?;
+            let time = NaiveTime::parse_from_str(time, "%H:%M").ok()?;
+            Some(parse_date(date).ok()?.and_time(time))
+        });
+    if let Some(naive) = naive {
+        return Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .ok_or_else(error);
+    }
+
+    parse_date_string(&input.to_lowercase(), Local::now(), Dialect::Uk).map_err(|_| error())
+}
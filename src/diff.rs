@@ -0,0 +1,157 @@
+//! Differences between two versions of the data, shown diff-style before
+//! anything gets written by bulk operations.
+
+use colored::Colorize;
+use serde_json::Value;
+
+use crate::{bars, i18n::tr, theme::Themed, Project, Task};
+
+pub enum Change<'a> {
+    ProjectAdded(&'a str),
+    ProjectRemoved(&'a str),
+    /// A project whose tasks were put in a different order.
+    ProjectReordered(&'a str),
+    TaskAdded(&'a str, &'a Task),
+    TaskRemoved(&'a str, &'a Task),
+    /// A task that got completed, whatever else changed with it.
+    TaskCompleted(&'a str, &'a Task),
+    /// A task that exists on both sides, with the names of the fields that
+    /// differ.
+    TaskModified(&'a str, &'a Task, Vec<String>),
+}
+
+/// Names of the fields that differ between two versions of a task. The
+/// history is left out, it only ever grows along with other changes.
+pub fn changed_fields(before: &Task, after: &Task) -> Vec<String> {
+    let (Ok(Value::Object(before)), Ok(Value::Object(after))) =
+        (serde_json::to_value(before), serde_json::to_value(after))
+    else {
+        return Vec::new();
+    };
+
+    let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter(|key| *key != "history" && before.get(*key) != after.get(*key))
+        .cloned()
+        .collect()
+}
+
+/// Everything that changed from `before` to `after`, project by project.
+pub fn diff<'a>(before: &'a [Project], after: &'a [Project]) -> Vec<Change<'a>> {
+    let mut changes = Vec::new();
+
+    for project in after {
+        let Some(old) = before.iter().find(|p| p.name == project.name) else {
+            changes.push(Change::ProjectAdded(&project.name));
+            for task in &project.tasks {
+                changes.push(Change::TaskAdded(&project.name, task));
+            }
+            continue;
+        };
+
+        for task in &project.tasks {
+            match old.tasks.iter().find(|t| t.id == task.id) {
+                Some(old_task) if task.completed && !old_task.completed => {
+                    changes.push(Change::TaskCompleted(&project.name, task))
+                }
+                Some(old_task) => {
+                    let fields = changed_fields(old_task, task);
+                    if !fields.is_empty() {
+                        changes.push(Change::TaskModified(&project.name, task, fields));
+                    }
+                }
+                None => changes.push(Change::TaskAdded(&project.name, task)),
+            }
+        }
+        for task in &old.tasks {
+            if !project.tasks.iter().any(|t| t.id == task.id) {
+                changes.push(Change::TaskRemoved(&project.name, task));
+            }
+        }
+
+        // Order of the tasks on both sides, leaving out added and removed ones.
+        let order = |tasks: &[Task], others: &[Task]| -> Vec<u32> {
+            tasks
+                .iter()
+                .map(|t| t.id)
+                .filter(|id| others.iter().any(|t| t.id == *id))
+                .collect()
+        };
+        if order(&old.tasks, &project.tasks) != order(&project.tasks, &old.tasks) {
+            changes.push(Change::ProjectReordered(&project.name));
+        }
+    }
+
+    for project in before {
+        if !after.iter().any(|p| p.name == project.name) {
+            changes.push(Change::ProjectRemoved(&project.name));
+            for task in &project.tasks {
+                changes.push(Change::TaskRemoved(&project.name, task));
+            }
+        }
+    }
+
+    changes
+}
+
+/// One line describing `change`.
+pub fn describe(change: &Change) -> String {
+    match change {
+        Change::ProjectAdded(name) => format!("+ project '{}'", name),
+        Change::ProjectRemoved(name) => format!("- project '{}'", name),
+        Change::ProjectReordered(name) => format!("~ project '{}': tasks reordered", name),
+        Change::TaskAdded(project, task) => {
+            format!("+ {}#{}: {}", project, task.id, task.description)
+        }
+        Change::TaskRemoved(project, task) => {
+            format!("- {}#{}: {}", project, task.id, task.description)
+        }
+        Change::TaskCompleted(project, task) => {
+            let mark = if bars::is_ascii() { "x" } else { "✓" };
+            format!("{} {}#{}: {}", mark, project, task.id, task.description)
+        }
+        Change::TaskModified(project, task, fields) => format!(
+            "~ {}#{}: {} ({})",
+            project,
+            task.id,
+            task.description,
+            fields.join(", ")
+        ),
+    }
+}
+
+/// Print `changes` one per line: added in green, removed in red, completed
+/// in cyan and modified in yellow.
+pub fn print(changes: &[Change]) {
+    if changes.is_empty() {
+        println!("{}", tr!("No changes."));
+        return;
+    }
+
+    for change in changes {
+        let line = describe(change);
+        let line = match change {
+            Change::ProjectAdded(_) | Change::TaskAdded(..) => line.good(),
+            Change::ProjectRemoved(_) | Change::TaskRemoved(..) => line.bad(),
+            Change::TaskCompleted(..) => line.cyan(),
+            Change::ProjectReordered(_) | Change::TaskModified(..) => line.warn(),
+        };
+        println!("{}", line);
+    }
+}
+
+/// Number of tasks `(added, removed, modified)` in `changes`.
+pub fn count(changes: &[Change]) -> (usize, usize, usize) {
+    let (mut added, mut removed, mut modified) = (0, 0, 0);
+    for change in changes {
+        match change {
+            Change::TaskAdded(..) => added += 1,
+            Change::TaskRemoved(..) => removed += 1,
+            Change::TaskModified(..) | Change::TaskCompleted(..) => modified += 1,
+            Change::ProjectAdded(_) | Change::ProjectRemoved(_) | Change::ProjectReordered(_) => {}
+        }
+    }
+    (added, removed, modified)
+}
@@ -0,0 +1,188 @@
+//! A daily or weekly summary of what's overdue, due soon and got done,
+//! printed or sent by email, e.g. from a cron job every morning.
+//!
+//! With `--email`, the digest goes through the `smtp-url` server, with the
+//! password taken from `PT_SMTP_PASSWORD` if it's not in the URL. Without
+//! a server, the email is written to stdout, ready for `sendmail -t`.
+
+use chrono::{Duration, Local};
+use clap::ValueEnum;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    Message, SmtpTransport, Transport,
+};
+
+use crate::{config::load_config, dates, i18n::tr, load_data, privacy, Project, Task};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Period {
+    Day,
+    Week,
+}
+
+impl Period {
+    fn days(self) -> i64 {
+        match self {
+            Period::Day => 1,
+            Period::Week => 7,
+        }
+    }
+}
+
+fn section(out: &mut String, title: &str, tasks: &[(&Project, &Task)], email: bool) {
+    if tasks.is_empty() {
+        return;
+    }
+    out.push_str(&format!("\n{} ({}):\n", title, tasks.len()));
+    for (project, task) in tasks {
+        let due = task
+            .due
+            .map_or(String::new(), |due| format!(" ({})", tr!("due {}", due)));
+        out.push_str(&format!(
+            "  - {}: {}{}\n",
+            project.task_ref(task.id),
+            if email {
+                privacy::public_description(task)
+            } else {
+                privacy::description(task)
+            },
+            due
+        ));
+    }
+}
+
+/// The digest as plain text, along with its subject. Private tasks are
+/// masked in emails, and in the terminal with `--redacted`.
+fn compose(data: &[Project], period: Period, email: bool) -> (String, String) {
+    let today = dates::today();
+    let soon = today + Duration::days(period.days());
+    let since = Local::now() - Duration::days(period.days());
+
+    let tasks = || {
+        data.iter()
+            .flat_map(|project| project.tasks.iter().map(move |task| (project, task)))
+    };
+    let overdue: Vec<_> = tasks()
+        .filter(|(_, t)| !t.completed && t.due.is_some_and(|due| due < today))
+        .collect();
+    let due_soon: Vec<_> = tasks()
+        .filter(|(_, t)| !t.completed && t.due.is_some_and(|due| (today..=soon).contains(&due)))
+        .collect();
+    let completed: Vec<_> = tasks()
+        .filter(|(_, t)| t.completed_at.is_some_and(|at| at >= since))
+        .collect();
+
+    let subject = match period {
+        Period::Day => tr!("Project tracker digest for {}", today),
+        Period::Week => tr!("Project tracker digest for the week to {}", today),
+    };
+
+    let mut body = format!("{}\n", subject);
+    if overdue.is_empty() && due_soon.is_empty() && completed.is_empty() {
+        body.push_str(&format!(
+            "\n{}\n",
+            tr!("Nothing due and nothing completed.")
+        ));
+    }
+    section(&mut body, &tr!("Overdue"), &overdue, email);
+    section(&mut body, &tr!("Due soon"), &due_soon, email);
+    section(&mut body, &tr!("Completed"), &completed, email);
+
+    (subject, body)
+}
+
+fn parse_mailbox(setting: &str, address: &str) -> Result<Mailbox, String> {
+    address
+        .parse()
+        .map_err(|e| tr!("Invalid {} address '{}': {}", setting, address, e))
+}
+
+fn send(subject: String, body: String) -> Result<(), String> {
+    let settings = load_config().effective();
+    let Some(to) = settings.email_to else {
+        return Err(tr!(
+            "Set where to send the digest first, with `config set email-to <address>`."
+        ));
+    };
+    let from = settings.email_from.unwrap_or_else(|| to.clone());
+
+    let message = Message::builder()
+        .from(parse_mailbox("email-from", &from)?)
+        .to(parse_mailbox("email-to", &to)?)
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN)
+        .body(body)
+        .map_err(|e| e.to_string())?;
+
+    let Some(url) = settings.smtp_url else {
+        print!("{}", String::from_utf8_lossy(&message.formatted()));
+        return Ok(());
+    };
+
+    let mut transport = SmtpTransport::from_url(&url).map_err(|e| e.to_string())?;
+    if let Ok(password) = std::env::var("PT_SMTP_PASSWORD") {
+        // `smtps://user@host:465`
+        let user = url
+            .split_once("://")
+            .and_then(|(_, rest)| rest.split_once('@'))
+            .map_or("", |(user, _)| user.split(':').next().unwrap_or(user));
+        transport = transport.credentials((user.to_string(), password).into());
+    }
+    transport
+        .build()
+        .send(&message)
+        .map(drop)
+        .map_err(|e| e.to_string())?;
+    println!("{}", tr!("Digest sent to {}.", to));
+    Ok(())
+}
+
+pub fn digest(period: Period, email: bool) {
+    let (subject, body) = compose(&load_data(), period, email);
+
+    if !email {
+        print!("{}", body);
+        return;
+    }
+    if let Err(e) = send(subject, body) {
+        println!("{}", tr!("Unable to send the digest: {}", e));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data() -> Vec<Project> {
+        let yesterday = dates::today() - Duration::days(1);
+        let task = |id, description: &str, private| Task {
+            id,
+            description: description.to_string(),
+            private,
+            due: Some(yesterday),
+            ..Default::default()
+        };
+        vec![Project {
+            name: "home".to_string(),
+            tasks: vec![
+                task(1, "water the plants", false),
+                task(2, "see the doctor", true),
+            ],
+            ..Default::default()
+        }]
+    }
+
+    #[test]
+    fn emails_mask_private_tasks() {
+        let (_, body) = compose(&data(), Period::Day, true);
+        assert!(body.contains("water the plants"), "{}", body);
+        assert!(!body.contains("doctor"), "{}", body);
+        assert!(body.contains(privacy::MASK), "{}", body);
+    }
+
+    #[test]
+    fn printed_digests_show_private_tasks_unless_redacted() {
+        let (_, body) = compose(&data(), Period::Day, false);
+        assert!(body.contains("see the doctor"), "{}", body);
+    }
+}
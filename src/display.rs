@@ -0,0 +1,57 @@
+//! How tasks stand out in listings, the same in all of them. A task overdue
+//! by more than the `escalate-after` setting (a week by default) is
+//! escalated: its description shows in bold red, the overview counts it in
+//! a banner, and `agenda` lists it first.
+
+use colored::{ColoredString, Colorize};
+use std::sync::OnceLock;
+
+use crate::{
+    config::load_config, contexts, dates, i18n::tr, privacy, snooze, theme::Themed, Project, Task,
+};
+
+/// Default number of days a task can be overdue before it's escalated.
+const ESCALATE_AFTER: u32 = 7;
+
+/// The `escalate-after` setting, read once since every listed task needs it.
+fn escalate_after() -> u32 {
+    static ESCALATE: OnceLock<u32> = OnceLock::new();
+    *ESCALATE.get_or_init(|| {
+        load_config()
+            .effective()
+            .escalate_after
+            .unwrap_or(ESCALATE_AFTER)
+    })
+}
+
+/// Whether `task` is pending and overdue by more than `escalate-after` days.
+pub fn is_escalated(task: &Task) -> bool {
+    !task.completed
+        && task
+            .due
+            .is_some_and(|due| (dates::today() - due).num_days() > i64::from(escalate_after()))
+}
+
+/// The description of `task`, in bold red when it's escalated.
+pub fn description(task: &Task) -> ColoredString {
+    let description = privacy::description(task);
+    if is_escalated(task) {
+        description.bad().bold()
+    } else {
+        description.normal()
+    }
+}
+
+/// "3 tasks overdue!", counting the escalated tasks of `data`, if any.
+pub fn escalation_banner(data: &[Project]) -> Option<String> {
+    let escalated = data
+        .iter()
+        .flat_map(|p| &p.tasks)
+        .filter(|t| is_escalated(t) && contexts::shown(t) && snooze::shown(t))
+        .count();
+    match escalated {
+        0 => None,
+        1 => Some(tr!("1 task overdue!")),
+        n => Some(tr!("{} tasks overdue!", n)),
+    }
+}
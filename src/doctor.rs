@@ -0,0 +1,496 @@
+//! `doctor`: checks the data for what commands don't expect to find in it,
+//! usually left by editing the data file by hand or by an interrupted
+//! sync: duplicate task IDs, projects following projects that are gone,
+//! completion dates that don't add up, archived tasks of deleted projects,
+//! stray control characters. With `--fix`, the problems that can be
+//! repaired without guessing are.
+
+use chrono::Local;
+use colored::Colorize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    archive, history::TaskEvent, i18n::tr, load_data, remote, save_data, storage, theme::Themed,
+    workspace, Project, Task,
+};
+
+struct Issue {
+    message: String,
+    /// Whether `--fix` repairs it.
+    fixable: bool,
+}
+
+#[derive(Default)]
+struct Issues(Vec<Issue>);
+
+impl Issues {
+    /// A problem `--fix` repairs, already repaired in the data checked,
+    /// which is only saved with `--fix`.
+    fn repaired(&mut self, message: String) {
+        self.0.push(Issue {
+            message,
+            fixable: true,
+        });
+    }
+
+    fn by_hand(&mut self, message: String) {
+        self.0.push(Issue {
+            message,
+            fixable: false,
+        });
+    }
+
+    fn repairable(&self) -> usize {
+        self.0.iter().filter(|issue| issue.fixable).count()
+    }
+}
+
+/// Check `text`, found in `place`, for stray characters, and remove those.
+fn check_text(text: &mut String, place: &str, issues: &mut Issues) {
+    if text.contains(char::REPLACEMENT_CHARACTER) {
+        issues.by_hand(tr!(
+            "{}: '{}' has characters that were lost to a bad encoding.",
+            place,
+            text
+        ));
+    }
+    if text.contains(char::is_control) {
+        issues.repaired(tr!(
+            "{}: control characters in {}.",
+            place,
+            format!("{:?}", text)
+        ));
+        // Line breaks and tabs most likely separated words.
+        *text = text
+            .replace(['\n', '\r', '\t'], " ")
+            .replace(char::is_control, "");
+    }
+}
+
+fn check_task(task: &mut Task, task_ref: &str, issues: &mut Issues) {
+    let now = Local::now();
+    match (task.completed, task.completed_at) {
+        (true, None) => {
+            task.completed_at = Some(
+                task.history
+                    .iter()
+                    .rev()
+                    .find(|entry| entry.event == TaskEvent::Completed)
+                    .map_or(now, |entry| entry.at),
+            );
+            issues.repaired(tr!("{}: completed, but not when.", task_ref));
+        }
+        (false, Some(_)) => {
+            task.completed_at = None;
+            issues.repaired(tr!("{}: pending, but with a completion date.", task_ref));
+        }
+        (true, Some(at)) if at > now => issues.by_hand(tr!(
+            "{}: completed in the future, on {}.",
+            task_ref,
+            at.format("%Y-%m-%d %H:%M")
+        )),
+        _ => {}
+    }
+    if let (Some(created), Some(completed)) = (task.created_at(), task.completed_at) {
+        if completed < created {
+            issues.by_hand(tr!(
+                "{}: completed on {}, before it was created on {}.",
+                task_ref,
+                completed.format("%Y-%m-%d %H:%M"),
+                created.format("%Y-%m-%d %H:%M")
+            ));
+        }
+    }
+    if task.completed && task.in_progress {
+        task.in_progress = false;
+        issues.repaired(tr!("{}: both completed and in progress.", task_ref));
+    }
+    if task.completed && task.snoozed_until.is_some() {
+        task.snoozed_until = None;
+        issues.repaired(tr!("{}: completed, but snoozed.", task_ref));
+    }
+
+    check_text(&mut task.description, task_ref, issues);
+    for text in task.tags.iter_mut().chain(task.contexts.iter_mut()) {
+        check_text(text, task_ref, issues);
+    }
+    if task.description.trim().is_empty() {
+        issues.by_hand(tr!("{}: no description.", task_ref));
+    }
+}
+
+/// Check the task IDs of `project`, given the highest ID of its archived
+/// tasks, and give tasks whose ID is taken a new one.
+fn check_ids(project: &mut Project, archived: Option<u32>, issues: &mut Issues) {
+    let mut seen = BTreeSet::new();
+    let mut taken = Vec::new();
+    for (index, task) in project.tasks.iter().enumerate() {
+        if task.id == 0 || !seen.insert(task.id) {
+            taken.push(index);
+        }
+    }
+    // Archived tasks keep their IDs, which mustn't be given out again.
+    let highest = project.tasks.iter().map(|t| t.id).chain(archived).max();
+    if let Some(highest) = highest.filter(|&highest| project.next_id <= highest) {
+        issues.repaired(tr!(
+            "{}: the next task ID, {}, was already used.",
+            project.name,
+            project.next_id
+        ));
+        project.next_id = highest + 1;
+    }
+
+    for index in taken {
+        issues.repaired(tr!(
+            "{}: task ID {} is taken, the task '{}' needs another.",
+            project.name,
+            project.tasks[index].id,
+            project.tasks[index].description
+        ));
+        project.tasks[index].id = project.allocate_id();
+    }
+}
+
+fn check_projects(data: &mut [Project], archive: &[Project], issues: &mut Issues) {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for project in data.iter() {
+        *counts.entry(&project.name).or_default() += 1;
+    }
+    for (name, count) in counts.iter().filter(|(_, count)| **count > 1) {
+        issues.by_hand(tr!("{} projects are named '{}'.", count, name));
+    }
+    let names: BTreeSet<String> = data.iter().map(|p| p.name.clone()).collect();
+
+    for project in data.iter_mut() {
+        if project.name.contains(char::is_control)
+            || project.name.contains(char::REPLACEMENT_CHARACTER)
+        {
+            issues.by_hand(tr!(
+                "Project '{}' has characters that don't belong in a name, rename it.",
+                project.name
+            ));
+        }
+
+        let name = project.name.clone();
+        project.after.retain(|after| {
+            let dangling = *after == name || !names.contains(after);
+            if dangling {
+                issues.repaired(tr!(
+                    "{}: follows project '{}', which doesn't exist.",
+                    name,
+                    after
+                ));
+            }
+            !dangling
+        });
+
+        let archived = archive
+            .iter()
+            .filter(|p| p.name == project.name)
+            .flat_map(|p| &p.tasks)
+            .map(|t| t.id)
+            .max();
+        check_ids(project, archived, issues);
+
+        for index in 0..project.tasks.len() {
+            let task_ref = project.task_ref(project.tasks[index].id);
+            check_task(&mut project.tasks[index], &task_ref, issues);
+        }
+    }
+}
+
+/// Check the archive against `data`, dropping the archived tasks of projects
+/// that no longer exist.
+fn check_archive(data: &[Project], archive: &mut Vec<Project>, issues: &mut Issues) {
+    archive.retain(|archived| {
+        let orphaned = !data.iter().any(|p| p.name == archived.name);
+        if orphaned {
+            issues.repaired(tr!(
+                "The archive has {} tasks of project '{}', which doesn't exist.",
+                archived.tasks.len(),
+                archived.name
+            ));
+        }
+        !orphaned
+    });
+
+    for archived in archive.iter() {
+        let Some(project) = data.iter().find(|p| p.name == archived.name) else {
+            continue;
+        };
+        for task in &archived.tasks {
+            if project.tasks.iter().any(|t| t.id == task.id) {
+                issues.by_hand(tr!(
+                    "{}: both in the data and in the archive.",
+                    project.task_ref(task.id)
+                ));
+            }
+        }
+    }
+}
+
+/// Problems with the data file itself, which can't be repaired here.
+fn check_file(issues: &mut Issues) {
+    if remote::is_active() {
+        return;
+    }
+    for path in storage::data_files(workspace::active()) {
+        let content = std::fs::read(&path).expect("Unable to read data file.");
+        if let Err(e) = std::str::from_utf8(&content) {
+            issues.by_hand(tr!(
+                "{} isn't valid UTF-8 from byte {} on.",
+                path.display(),
+                e.valid_up_to()
+            ));
+        }
+    }
+    if storage::is_corrupt() {
+        issues.by_hand(tr!(
+            "The data file can't be read entirely, see the copy kept aside when it was loaded."
+        ));
+    }
+}
+
+pub fn doctor(fix: bool) {
+    let mut data = load_data();
+    let mut issues = Issues::default();
+    check_file(&mut issues);
+
+    let archive = archive::try_load_archive();
+    let mut archive = match archive {
+        Ok(archive) => Some(archive),
+        Err(e) => {
+            issues.by_hand(tr!("The archive can't be read: {}", e));
+            None
+        }
+    };
+    let fixes_archive = match &mut archive {
+        Some(archive) => {
+            check_projects(&mut data, archive, &mut issues);
+            let before = archive.len();
+            check_archive(&data, archive, &mut issues);
+            archive.len() != before
+        }
+        None => {
+            check_projects(&mut data, &[], &mut issues);
+            false
+        }
+    };
+
+    let tasks: usize = data.iter().map(|p| p.tasks.len()).sum();
+    println!(
+        "{}",
+        tr!("Checked {} projects and {} tasks.", data.len(), tasks).dimmed()
+    );
+    if issues.0.is_empty() {
+        println!("{}", tr!("No problems found.").good());
+        return;
+    }
+
+    for issue in &issues.0 {
+        let (mark, note) = match (issue.fixable, fix) {
+            (true, true) => ("✓".good(), tr!("fixed")),
+            (true, false) => ("✗".warn(), tr!("fixable")),
+            (false, _) => ("✗".bad(), tr!("fix by hand")),
+        };
+        println!(
+            "{} {} {}",
+            mark,
+            issue.message,
+            format!("({})", note).dimmed()
+        );
+    }
+
+    let fixable = issues.repairable();
+    let by_hand = issues.0.len() - fixable;
+    if !fix {
+        println!(
+            "{}",
+            tr!(
+                "{} problems, {} of which `doctor --fix` can fix.",
+                issues.0.len(),
+                fixable
+            )
+        );
+        return;
+    }
+    if fixable > 0 {
+        save_data(&data);
+        if let Some(archive) = archive.filter(|_| fixes_archive) {
+            archive::save_archive(&archive);
+        }
+    }
+    println!(
+        "{}",
+        tr!(
+            "Fixed {} problems, {} left to fix by hand.",
+            fixable,
+            by_hand
+        )
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, NaiveDate};
+
+    use crate::history::HistoryEntry;
+
+    fn task(id: u32, description: &str) -> Task {
+        Task {
+            id,
+            description: description.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn project(name: &str, tasks: Vec<Task>) -> Project {
+        Project {
+            name: name.to_string(),
+            next_id: tasks.iter().map(|t| t.id + 1).max().unwrap_or(1),
+            tasks,
+            ..Default::default()
+        }
+    }
+
+    /// Whether each issue is repaired, in order.
+    fn fixable(issues: &Issues) -> Vec<bool> {
+        issues.0.iter().map(|issue| issue.fixable).collect()
+    }
+
+    #[test]
+    fn control_characters_are_removed() {
+        let mut issues = Issues::default();
+        let mut text = "Fix\nthe\tlogin\u{7}form".to_string();
+        check_text(&mut text, "web#1", &mut issues);
+        assert_eq!(text, "Fix the loginform");
+        assert_eq!(fixable(&issues), [true]);
+
+        let mut issues = Issues::default();
+        let mut text = "Caf\u{fffd} menu".to_string();
+        check_text(&mut text, "web#1", &mut issues);
+        assert_eq!(text, "Caf\u{fffd} menu");
+        assert_eq!(fixable(&issues), [false]);
+
+        let mut issues = Issues::default();
+        check_text(&mut "Café menu".to_string(), "web#1", &mut issues);
+        assert!(issues.0.is_empty());
+    }
+
+    #[test]
+    fn completion_dates_are_made_to_add_up() {
+        let completed_at = Local::now() - Duration::hours(3);
+        let mut missing = Task {
+            completed: true,
+            history: vec![HistoryEntry {
+                at: completed_at,
+                event: TaskEvent::Completed,
+                detail: None,
+                by: None,
+            }],
+            in_progress: true,
+            snoozed_until: NaiveDate::from_ymd_opt(2030, 1, 1),
+            ..task(1, "Done")
+        };
+        let mut issues = Issues::default();
+        check_task(&mut missing, "web#1", &mut issues);
+        assert_eq!(missing.completed_at, Some(completed_at));
+        assert!(!missing.in_progress);
+        assert_eq!(missing.snoozed_until, None);
+        assert_eq!(fixable(&issues), [true, true, true]);
+
+        let mut pending = Task {
+            completed_at: Some(completed_at),
+            ..task(2, "Pending")
+        };
+        let mut issues = Issues::default();
+        check_task(&mut pending, "web#2", &mut issues);
+        assert_eq!(pending.completed_at, None);
+        assert_eq!(fixable(&issues), [true]);
+
+        let mut future = Task {
+            completed: true,
+            completed_at: Some(Local::now() + Duration::days(2)),
+            ..task(3, " ")
+        };
+        let mut issues = Issues::default();
+        check_task(&mut future, "web#3", &mut issues);
+        assert_eq!(fixable(&issues), [false, false]);
+    }
+
+    #[test]
+    fn tasks_whose_id_is_taken_get_a_new_one() {
+        let mut web = project("web", vec![task(1, "One"), task(2, "Two"), task(1, "Copy")]);
+        web.tasks.push(task(0, "No ID"));
+        let mut issues = Issues::default();
+        // Tasks up to 5 were archived.
+        check_ids(&mut web, Some(5), &mut issues);
+
+        let ids: Vec<(u32, &str)> = web
+            .tasks
+            .iter()
+            .map(|t| (t.id, t.description.as_str()))
+            .collect();
+        assert_eq!(ids, [(1, "One"), (2, "Two"), (6, "Copy"), (7, "No ID")]);
+        assert_eq!(web.next_id, 8);
+        assert_eq!(fixable(&issues), [true, true, true]);
+    }
+
+    #[test]
+    fn projects_only_follow_projects_that_exist() {
+        let mut data = vec![
+            Project {
+                after: vec!["api".to_string(), "gone".to_string(), "web".to_string()],
+                ..project("web", vec![task(1, "One")])
+            },
+            project("api", Vec::new()),
+            project("api", Vec::new()),
+        ];
+        let mut issues = Issues::default();
+        check_projects(&mut data, &[], &mut issues);
+        assert_eq!(data[0].after, ["api"]);
+        // The duplicate name first, then what follows nothing.
+        assert_eq!(fixable(&issues), [false, true, true]);
+    }
+
+    #[test]
+    fn archived_tasks_of_deleted_projects_are_dropped() {
+        let data = vec![project("web", vec![task(1, "One")])];
+        let mut archive = vec![
+            project("web", vec![task(1, "Archived too")]),
+            project("gone", vec![task(1, "Old")]),
+        ];
+        let mut issues = Issues::default();
+        check_archive(&data, &mut archive, &mut issues);
+        assert_eq!(archive.len(), 1);
+        assert_eq!(archive[0].name, "web");
+        assert_eq!(fixable(&issues), [true, false]);
+    }
+
+    #[test]
+    fn fixed_data_has_nothing_left_to_fix() {
+        let mut data = vec![Project {
+            after: vec!["gone".to_string()],
+            next_id: 1,
+            ..project(
+                "web",
+                vec![
+                    Task {
+                        completed: true,
+                        in_progress: true,
+                        ..task(1, "Fix\nit")
+                    },
+                    task(1, "Copy"),
+                ],
+            )
+        }];
+        let mut issues = Issues::default();
+        check_projects(&mut data, &[], &mut issues);
+        assert!(issues.repairable() > 0);
+
+        let mut again = Issues::default();
+        check_projects(&mut data, &[], &mut again);
+        assert!(again.0.is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+//! Catching tasks added twice: a new task whose description is nearly that
+//! of a pending one in the same project is only added with `--force`, or
+//! when confirmed. The existing task can be bumped to the top instead.
+
+use dialoguer::Select;
+
+use crate::{history::TaskEvent, i18n::tr, picker, storage, theme::Themed, Project, Task};
+
+/// How alike two descriptions must be, from 0 to 1, to be taken as the same.
+const THRESHOLD: f64 = 0.85;
+
+/// Lowercase words of `description`, without punctuation.
+fn normalize(description: &str) -> String {
+    description
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Edit distance between `a` and `b`, in characters.
+fn distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            current.push(
+                (previous[j] + cost)
+                    .min(previous[j + 1] + 1)
+                    .min(current[j] + 1),
+            );
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// How alike two descriptions are, from 0 to 1, ignoring case, punctuation
+/// and the order of words.
+fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (normalize(a), normalize(b));
+    let mut a_words: Vec<&str> = a.split(' ').collect();
+    let mut b_words: Vec<&str> = b.split(' ').collect();
+    a_words.sort_unstable();
+    b_words.sort_unstable();
+
+    let ratio = |a: &str, b: &str| {
+        let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+        let longest = a.len().max(b.len());
+        if longest == 0 {
+            return 1.0;
+        }
+        1.0 - distance(&a, &b) as f64 / longest as f64
+    };
+    ratio(&a, &b).max(ratio(&a_words.join(" "), &b_words.join(" ")))
+}
+
+/// The pending task of `project` most like `description`, if any is alike
+/// enough to be the same.
+pub fn find<'a>(project: &'a Project, description: &str) -> Option<&'a Task> {
+    project
+        .tasks
+        .iter()
+        .filter(|t| !t.completed)
+        .map(|t| (t, similarity(&t.description, description)))
+        .filter(|(_, similarity)| *similarity >= THRESHOLD)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(t, _)| t)
+}
+
+/// What to do with a task about to be added.
+pub enum Verdict {
+    Add,
+    /// The task it duplicates was bumped instead.
+    Bumped,
+    Skip,
+}
+
+/// Whether to add `description` to `project`, warning when it looks like a
+/// task already there and, when interactive, offering to bump that task to
+/// the top of the project instead.
+pub fn check(project: &mut Project, description: &str) -> Verdict {
+    if storage::is_forced() {
+        return Verdict::Add;
+    }
+    let Some(existing) = find(project, description) else {
+        return Verdict::Add;
+    };
+    let existing_id = existing.id;
+    println!(
+        "{}",
+        tr!(
+            "'{}' looks like task {}: {}",
+            description,
+            existing_id,
+            existing.description
+        )
+        .warn()
+    );
+
+    if !picker::is_interactive() {
+        println!("{}", tr!("Not added, use --force to add it anyway."));
+        return Verdict::Skip;
+    }
+    let choices = [
+        tr!("Bump task {} to the top instead", existing_id),
+        tr!("Add it anyway"),
+        tr!("Skip it"),
+    ];
+    match Select::new().items(&choices).default(0).interact_opt() {
+        Ok(Some(0)) => {
+            bump(project, existing_id);
+            Verdict::Bumped
+        }
+        Ok(Some(1)) => Verdict::Add,
+        _ => {
+            println!("{}", tr!("Not added."));
+            Verdict::Skip
+        }
+    }
+}
+
+/// Move a task to the top of its project, as wanted again.
+fn bump(project: &mut Project, task_id: u32) {
+    let Some(position) = project.tasks.iter().position(|t| t.id == task_id) else {
+        return;
+    };
+    let mut task = project.tasks.remove(position);
+    task.record(TaskEvent::Edited, Some("bumped".to_string()));
+    project.tasks.insert(0, task);
+    println!("{}", tr!("Task {} moved to the top.", task_id));
+}
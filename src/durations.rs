@@ -0,0 +1,259 @@
+//! Parsing of durations such as `90m`, `1h30m` or `2d`, and showing them
+//! back the same way, `90m` as `1h30m`.
+//!
+//! Durations are of two kinds. Effort, for estimates and time logged,
+//! counts working days of 8 hours and weeks of 5 of them. Spans of time,
+//! for snoozing, reminders and backups, count days of 24 hours and weeks
+//! of 7 days.
+
+use crate::i18n::tr;
+
+/// Minutes in a working day, for effort given in days.
+const MINUTES_PER_WORKING_DAY: u32 = 8 * 60;
+const WORKING_DAYS_PER_WEEK: u32 = 5;
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// Examples of durations, for errors.
+fn examples() -> String {
+    tr!("try e.g. 90m, 1h30m or 2d")
+}
+
+/// The units, largest first, with the names they can be typed as.
+const UNITS: &[(char, &[&str])] = &[
+    ('w', &["w", "wk", "wks", "week", "weeks"]),
+    ('d', &["d", "day", "days"]),
+    ('h', &["h", "hr", "hrs", "hour", "hours"]),
+    ('m', &["m", "min", "mins", "minute", "minutes"]),
+];
+
+/// Minutes in `unit`, counting working days for effort.
+fn minutes_in(unit: char, effort: bool) -> u32 {
+    let day = if effort {
+        MINUTES_PER_WORKING_DAY
+    } else {
+        MINUTES_PER_DAY
+    };
+    match unit {
+        'w' if effort => WORKING_DAYS_PER_WEEK * day,
+        'w' => 7 * day,
+        'd' => day,
+        'h' => 60,
+        _ => 1,
+    }
+}
+
+fn parse(input: &str, effort: bool) -> Result<u32, String> {
+    let text = input.trim().to_lowercase();
+    if text.is_empty() {
+        return Err(tr!("the duration is empty ({})", examples()));
+    }
+    // A bare number is taken as hours.
+    let text = match text.parse::<f64>() {
+        Ok(_) => format!("{}h", text),
+        Err(_) => text,
+    };
+
+    let mut minutes = 0.0;
+    let mut seen: Vec<char> = Vec::new();
+    let mut rest = text.as_str();
+    while !rest.is_empty() {
+        rest = rest.trim_start();
+        let number_len = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let (number, after) = rest.split_at(number_len);
+        let after = after.trim_start();
+        let name_len = after
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(after.len());
+        let (name, after) = after.split_at(name_len);
+
+        if number.is_empty() {
+            let what = if name.is_empty() {
+                after.chars().next().map(String::from).unwrap_or_default()
+            } else {
+                name.to_string()
+            };
+            return Err(tr!(
+                "expected a number before '{}' in '{}' ({})",
+                what,
+                input.trim(),
+                examples()
+            ));
+        }
+        let value: f64 = number
+            .parse()
+            .map_err(|_| tr!("'{}' in '{}' is not a number", number, input.trim()))?;
+        if name.is_empty() {
+            // Say what was likely meant, from the unit before the number.
+            let hint = match seen.last() {
+                Some('w') => tr!("; did you mean {}d?", text.trim()),
+                Some('d') => tr!("; did you mean {}h?", text.trim()),
+                Some('h') => tr!("; did you mean {}m?", text.trim()),
+                _ => format!(" ({})", examples()),
+            };
+            return Err(tr!(
+                "'{}' in '{}' has no unit{}",
+                number,
+                input.trim(),
+                hint
+            ));
+        }
+        let Some(&(unit, _)) = UNITS.iter().find(|(_, names)| names.contains(&name)) else {
+            return Err(tr!(
+                "unknown unit '{}' in '{}': use m, h, d or w ({})",
+                name,
+                input.trim(),
+                examples()
+            ));
+        };
+        if seen.contains(&unit) {
+            return Err(tr!("'{}' is given twice in '{}'", unit, input.trim()));
+        }
+        seen.push(unit);
+        minutes += value * f64::from(minutes_in(unit, effort));
+        rest = after;
+    }
+
+    if minutes > f64::from(u32::MAX) {
+        return Err(tr!("the duration '{}' is too long", input.trim()));
+    }
+    Ok(minutes.round() as u32)
+}
+
+/// Parse an effort such as `45m`, `2h`, `1.5h`, `1h30m` or `2d` (working
+/// days of 8 hours) into minutes. A bare number is taken as hours.
+///
+/// Meant to be used as a clap `value_parser`, hence the `String` error.
+pub fn parse_duration(input: &str) -> Result<u32, String> {
+    parse(input, true)
+}
+
+/// Parse a span of time such as `90m`, `12h` or `2d` (days of 24 hours)
+/// into minutes.
+pub fn parse_span(input: &str) -> Result<u32, String> {
+    parse(input, false)
+}
+
+/// Parse a number of days, as in `30d`, `4w` or just `30`.
+///
+/// Meant to be used as a clap `value_parser`, hence the `String` error.
+pub fn parse_days(input: &str) -> Result<u32, String> {
+    if let Ok(days) = input.trim().parse::<u32>() {
+        return Ok(days);
+    }
+    let minutes = parse_span(input)?;
+    if minutes % MINUTES_PER_DAY != 0 {
+        return Err(tr!(
+            "expected a number of days such as 30d or 4w, got '{}'",
+            input.trim()
+        ));
+    }
+    Ok(minutes / MINUTES_PER_DAY)
+}
+
+/// Whether `input` is meant as a duration rather than a date: it starts
+/// with a number and ends with a unit, as in `3d` or `1h30m`.
+pub fn looks_like_duration(input: &str) -> bool {
+    let input = input.trim();
+    input.starts_with(|c: char| c.is_ascii_digit())
+        && input.ends_with(|c: char| c.is_alphabetic())
+        && !input.contains(' ')
+}
+
+fn format(minutes: u32, day: u32) -> String {
+    let (days, hours, minutes) = (minutes / day, minutes % day / 60, minutes % 60);
+    let mut text = String::new();
+    if days > 0 {
+        text.push_str(&format!("{}d", days));
+    }
+    if hours > 0 {
+        text.push_str(&format!("{}h", hours));
+    }
+    if minutes > 0 || text.is_empty() {
+        text.push_str(&format!("{}m", minutes));
+    }
+    text
+}
+
+/// Format an effort in minutes as `2d`, `1d4h`, `1h30m` or `45m`.
+pub fn format_duration(minutes: u32) -> String {
+    format(minutes, MINUTES_PER_WORKING_DAY)
+}
+
+/// Format a span of time in minutes as `2d`, `1d12h`, `1h30m` or `45m`.
+pub fn format_span(minutes: u32) -> String {
+    format(minutes, MINUTES_PER_DAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minutes_hours_and_both() {
+        assert_eq!(parse_duration("90m"), Ok(90));
+        assert_eq!(parse_duration("1h30m"), Ok(90));
+        assert_eq!(parse_duration("1.5h"), Ok(90));
+        assert_eq!(parse_duration("1 hour 30 mins"), Ok(90));
+        assert_eq!(parse_duration(" 2H "), Ok(120));
+    }
+
+    #[test]
+    fn days_and_weeks_count_working_time_for_effort_only() {
+        assert_eq!(parse_duration("2d"), Ok(2 * 8 * 60));
+        assert_eq!(parse_span("2d"), Ok(2 * 24 * 60));
+        assert_eq!(parse_duration("1w"), Ok(5 * 8 * 60));
+        assert_eq!(parse_span("1w"), Ok(7 * 24 * 60));
+    }
+
+    #[test]
+    fn bare_numbers_are_hours() {
+        assert_eq!(parse_duration("2"), Ok(120));
+        assert_eq!(parse_span("0.5"), Ok(30));
+    }
+
+    #[test]
+    fn bad_durations_are_refused() {
+        for input in ["", "  ", "h", "1x", "1h2h", "1m1m", "1..5h", "h30"] {
+            assert!(parse_duration(input).is_err(), "'{}' was accepted", input);
+        }
+    }
+
+    #[test]
+    fn overflowing_durations_are_refused() {
+        assert!(parse_duration("9999999w").is_err());
+        assert!(parse_span("99999999999m").is_err());
+        assert_eq!(parse_span("4294967295m"), Ok(u32::MAX));
+    }
+
+    #[test]
+    fn a_missing_unit_gets_a_hint_from_the_one_before() {
+        let hint = |input| parse_duration(input).unwrap_err();
+        assert!(hint("1h30").contains("1h30m"));
+        assert!(hint("1d4").contains("1d4h"));
+        assert!(hint("1w2").contains("1w2d"));
+        // Without a unit before, only examples can help.
+        assert!(hint("90 m 5").contains("90m, 1h30m"));
+    }
+
+    #[test]
+    fn days_alone() {
+        assert_eq!(parse_days("30"), Ok(30));
+        assert_eq!(parse_days("30d"), Ok(30));
+        assert_eq!(parse_days("4w"), Ok(28));
+        assert!(parse_days("36h").is_err());
+    }
+
+    #[test]
+    fn formatted_durations_parse_back() {
+        assert_eq!(format_duration(0), "0m");
+        assert_eq!(format_duration(90), "1h30m");
+        assert_eq!(format_duration(2 * 8 * 60), "2d");
+        assert_eq!(format_span(36 * 60), "1d12h");
+        for minutes in [1, 45, 60, 90, 479, 480, 12 * 60, 3 * 24 * 60 + 7] {
+            assert_eq!(parse_duration(&format_duration(minutes)), Ok(minutes));
+            assert_eq!(parse_span(&format_span(minutes)), Ok(minutes));
+        }
+    }
+}
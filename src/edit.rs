@@ -0,0 +1,403 @@
+//! Editing a whole project at once in `$EDITOR`, like `git rebase -i` for
+//! tasks: each line is a task, written as for `do` after a checkbox and the
+//! task's ID.
+//!
+//! ```text
+//! [ ] 12 fix the login bug !high due:2026-07-01 #auth
+//! [~] 13 write the release notes ~2h
+//! [x] 14 update the changelog
+//! ```
+//!
+//! Changing a line edits its task, removing it deletes the task and a line
+//! without an ID adds one. The tasks end up in the order of the lines.
+//!
+//! A number right after the checkbox is always the task's ID, so a new
+//! task's description starting with one is written with a backslash, as in
+//! `[ ] \3 apples`, the way details are escaped.
+
+use dialoguer::Confirm;
+use std::{collections::HashSet, env, fs, process::Command};
+
+use crate::{
+    diff, hooks, i18n::tr, load_data, picker, prefixes, quickadd, save_data, storage, wip, Event,
+    Project, Status, Task, TaskEvent,
+};
+
+/// A task line, as read back from the file.
+struct Line {
+    id: Option<u32>,
+    status: Status,
+    description: String,
+    details: Task,
+    /// The line itself, to tell whether it was changed at all.
+    text: String,
+}
+
+fn checkbox(status: Status) -> &'static str {
+    match status {
+        Status::Todo => "[ ]",
+        Status::InProgress => "[~]",
+        Status::Done => "[x]",
+    }
+}
+
+fn format_line(project: &Project, task: &Task) -> String {
+    format!(
+        "{} {} {}",
+        checkbox(task.status()),
+        project.display_id(task.id),
+        quickadd::format_details(&task.description, task)
+    )
+}
+
+fn format_project(project: &Project) -> String {
+    let help = [
+        tr!(
+            "Tasks of project '{}': [ ] to do, [~] in progress, [x] done.",
+            project.name
+        ),
+        tr!("Edit a line to change its task, remove it to delete the task, or add"),
+        tr!("a line without an ID for a new task. Lines starting with # are ignored."),
+        tr!("A new task starting with a number needs a backslash, as in \\3 apples."),
+    ];
+    let mut content: String = help.iter().map(|line| format!("# {}\n", line)).collect();
+    content.push('\n');
+    for task in &project.tasks {
+        content.push_str(&format_line(project, task));
+        content.push('\n');
+    }
+    content
+}
+
+/// The task ID `word` stands for, `12` or `WEB-12` in a project with that
+/// prefix.
+fn parse_id(project: &Project, word: &str) -> Option<u32> {
+    if let Ok(id) = word.parse() {
+        return Some(id);
+    }
+    let (prefix, id) = prefixes::parse_ref(word)?;
+    project
+        .prefix
+        .as_deref()
+        .is_some_and(|p| p.eq_ignore_ascii_case(prefix))
+        .then_some(id)
+}
+
+fn parse_line(project: &Project, text: &str) -> Result<Line, String> {
+    let (status, rest) = match text.get(..3) {
+        Some("[ ]") => (Status::Todo, &text[3..]),
+        Some("[~]") => (Status::InProgress, &text[3..]),
+        Some("[x]") | Some("[X]") => (Status::Done, &text[3..]),
+        _ => (Status::Todo, text),
+    };
+    let rest = rest.trim_start();
+    let (first, after) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let (id, rest) = match parse_id(project, first) {
+        Some(id) => (Some(id), after),
+        None => (None, rest),
+    };
+
+    let (description, details) = quickadd::parse_details(rest)?;
+    if description.is_empty() {
+        return Err(tr!("The task needs a description."));
+    }
+    Ok(Line {
+        id,
+        status,
+        description,
+        details,
+        text: text.to_string(),
+    })
+}
+
+/// Read the edited file back, stopping at the first line that doesn't make
+/// sense.
+fn parse(project: &Project, content: &str) -> Result<Vec<Line>, String> {
+    let mut lines = Vec::new();
+    let mut seen = HashSet::new();
+    for (number, text) in content.lines().enumerate() {
+        let text = text.trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+        let line = parse_line(project, text).map_err(|e| tr!("Line {}: {}", number + 1, e))?;
+        if let Some(id) = line.id {
+            if !project.tasks.iter().any(|t| t.id == id) {
+                return Err(tr!(
+                    "Line {}: there's no task {}, leave the ID out to add a new task, or write \\{} for a new task starting with that number.",
+                    number + 1,
+                    id,
+                    id
+                ));
+            }
+            if !seen.insert(id) {
+                return Err(tr!("Line {}: task {} is listed twice.", number + 1, id));
+            }
+        }
+        lines.push(line);
+    }
+    Ok(lines)
+}
+
+/// Set the status of `task` as if it had been moved there with a command.
+fn set_status(task: &mut Task, status: Status) {
+    if task.status() == status {
+        return;
+    }
+    match status {
+        Status::Done => task.complete(),
+        Status::InProgress | Status::Todo => {
+            if task.completed {
+                task.reopen();
+            }
+            task.in_progress = status == Status::InProgress;
+            task.record(TaskEvent::Moved, Some(format!("to {}", status)));
+        }
+    }
+}
+
+/// Apply the edited `lines` to `project`.
+fn apply(project: &mut Project, lines: Vec<Line>) {
+    let mut old = std::mem::take(&mut project.tasks);
+    for line in lines {
+        let Some(id) = line.id else {
+            let task = project.add_task(&line.description, &line.details);
+            set_status(task, line.status);
+            continue;
+        };
+
+        let position = old
+            .iter()
+            .position(|t| t.id == id)
+            .expect("IDs were checked");
+        let mut task = old.remove(position);
+        if line.text != format_line(project, &task) {
+            let before = task.clone();
+            // Keep the description as it was when only its spacing changed.
+            if line.description
+                != task
+                    .description
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            {
+                task.description = line.description;
+            }
+            task.priority = line.details.priority;
+            task.due = line.details.due;
+            task.estimate = line.details.estimate;
+            task.tags = line.details.tags;
+            task.contexts = line.details.contexts;
+            let fields = diff::changed_fields(&before, &task);
+            if !fields.is_empty() {
+                task.record(TaskEvent::Edited, Some(fields.join(", ")));
+            }
+            set_status(&mut task, line.status);
+        }
+        project.tasks.push(task);
+    }
+}
+
+/// Open `content` in the user's editor and return what they saved.
+fn run_editor(project_name: &str, content: &str) -> Option<String> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let path = env::temp_dir().join(format!(
+        "project-tracker-{}-{}.txt",
+        project_name.replace(|c: char| !c.is_alphanumeric(), "_"),
+        std::process::id()
+    ));
+    fs::write(&path, content).expect("Unable to write temporary file.");
+
+    // The editor may come with arguments, as in `code --wait`.
+    let mut words = shlex::split(&editor).unwrap_or_default().into_iter();
+    let status = Command::new(words.next().unwrap_or(editor))
+        .args(words)
+        .arg(&path)
+        .status();
+    let edited = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+
+    match status {
+        Ok(status) if status.success() => Some(edited.expect("Unable to read temporary file.")),
+        Ok(_) => {
+            println!("{}", tr!("The editor failed, nothing was changed."));
+            None
+        }
+        Err(e) => {
+            println!("{}", tr!("Unable to start the editor: {}", e));
+            None
+        }
+    }
+}
+
+pub fn edit(project_name: &str) {
+    let before = load_data();
+    let Some(project) = before.iter().find(|p| p.name == project_name) else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+        return;
+    };
+
+    let mut content = format_project(project);
+    let lines = loop {
+        let Some(edited) = run_editor(project_name, &content) else {
+            return;
+        };
+        match parse(project, &edited) {
+            Ok(lines) => break lines,
+            Err(e) => {
+                println!("{}", e);
+                let again = picker::is_interactive()
+                    && Confirm::new()
+                        .with_prompt(tr!("Edit again?"))
+                        .default(true)
+                        .interact()
+                        .unwrap_or(false);
+                if !again {
+                    println!("{}", tr!("Nothing was changed."));
+                    return;
+                }
+                content = edited;
+            }
+        }
+    };
+
+    let mut data = before.clone();
+    let project = data
+        .iter_mut()
+        .find(|p| p.name == project_name)
+        .expect("Project was found above");
+    let in_progress = |project: &Project| {
+        project
+            .tasks
+            .iter()
+            .filter(|t| t.status() == Status::InProgress)
+            .count()
+    };
+    let was_in_progress = in_progress(project);
+    apply(project, lines);
+    let now_in_progress = in_progress(project);
+    if now_in_progress > was_in_progress && !wip::allows_another(project_name, now_in_progress - 1)
+    {
+        println!("{}", tr!("Nothing was changed."));
+        return;
+    }
+
+    let changes = diff::diff(&before, &data);
+    if changes.is_empty() {
+        println!("{}", tr!("Nothing was changed."));
+        return;
+    }
+    // A dry run shows the changes by itself.
+    if !storage::is_dry_run() {
+        diff::print(&changes);
+    }
+    save_data(&data);
+
+    for change in &changes {
+        match change {
+            diff::Change::TaskAdded(project, task) => hooks::run(Event::TaskAdd(project, task)),
+            diff::Change::TaskCompleted(project, task) => {
+                hooks::run(Event::TaskComplete(project, task))
+            }
+            diff::Change::TaskRemoved(project, task) => {
+                hooks::run(Event::TaskDelete(project, task.id))
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    use crate::Priority;
+
+    fn project() -> Project {
+        let mut project = Project {
+            name: "web".to_string(),
+            ..Default::default()
+        };
+        let details = Task {
+            priority: Some(Priority::High),
+            due: NaiveDate::from_ymd_opt(2026, 7, 1),
+            estimate: Some(90),
+            tags: vec!["auth".to_string()],
+            contexts: vec!["@office".to_string()],
+            ..Default::default()
+        };
+        project.add_task("fix the login bug", &details);
+        project.add_task("3 apples", &Task::default());
+        project.add_task("not a #tag, nor !high, @home or ~2h", &Task::default());
+        project.add_task(r"a \backslash and due:today", &Task::default());
+        project
+            .add_task("done already", &Task::default())
+            .complete();
+        project.add_task("under way", &Task::default()).in_progress = true;
+        project
+    }
+
+    fn assert_same(line: &Line, task: &Task) {
+        assert_eq!(line.id, Some(task.id));
+        assert_eq!(line.status, task.status());
+        assert_eq!(line.description, task.description);
+        assert_eq!(line.details.priority, task.priority);
+        assert_eq!(line.details.due, task.due);
+        assert_eq!(line.details.estimate, task.estimate);
+        assert_eq!(line.details.tags, task.tags);
+        assert_eq!(line.details.contexts, task.contexts);
+    }
+
+    #[test]
+    fn formatted_lines_parse_back_to_their_task() {
+        let mut project = project();
+        for prefix in [None, Some("WEB".to_string())] {
+            project.prefix = prefix;
+            for task in &project.tasks {
+                let text = format_line(&project, task);
+                let line = parse_line(&project, &text).expect("The line parses");
+                assert_same(&line, task);
+                assert_eq!(line.text, text);
+            }
+        }
+    }
+
+    #[test]
+    fn a_formatted_project_parses_back_unchanged() {
+        let mut project = project();
+        let lines = parse(&project, &format_project(&project)).expect("The project parses");
+        assert_eq!(lines.len(), project.tasks.len());
+        for (line, task) in lines.iter().zip(&project.tasks) {
+            assert_same(line, task);
+        }
+
+        let before = serde_json::to_value(&project.tasks).unwrap();
+        apply(&mut project, lines);
+        assert_eq!(serde_json::to_value(&project.tasks).unwrap(), before);
+    }
+
+    #[test]
+    fn a_number_after_the_checkbox_is_the_id() {
+        let project = project();
+        let line = parse_line(&project, "[ ] 2 apples").expect("The line parses");
+        assert_eq!(line.id, Some(2));
+        assert_eq!(line.description, "apples");
+
+        let error = parse(&project, "[ ] 12 apples")
+            .err()
+            .expect("There's no task 12");
+        assert!(error.contains(r"\12"), "{}", error);
+    }
+
+    #[test]
+    fn an_escaped_number_starts_a_new_task() {
+        let project = project();
+        let line = parse_line(&project, r"[ ] \12 apples #fruit").expect("The line parses");
+        assert_eq!(line.id, None);
+        assert_eq!(line.description, "12 apples");
+        assert_eq!(line.details.tags, ["fruit"]);
+    }
+}
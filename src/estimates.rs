@@ -0,0 +1,78 @@
+//! Time estimates for tasks, e.g. `30m`, `2h` or `1h30m`, see `durations`.
+
+use crate::{
+    durations::{format_duration, parse_duration},
+    history::TaskEvent,
+    i18n::tr,
+    load_data, save_data, Task,
+};
+
+/// Estimated effort left and in total over `tasks`, in minutes, or `None`
+/// when none of them has an estimate.
+pub fn effort<'a>(tasks: impl IntoIterator<Item = &'a Task>) -> Option<(u32, u32)> {
+    let estimated: Vec<&Task> = tasks.into_iter().filter(|t| t.estimate.is_some()).collect();
+    if estimated.is_empty() {
+        return None;
+    }
+    let remaining = estimated
+        .iter()
+        .filter(|t| !t.completed)
+        .filter_map(|t| t.estimate)
+        .sum();
+    let total = estimated.iter().filter_map(|t| t.estimate).sum();
+    Some((remaining, total))
+}
+
+/// `12h remaining / 30h total`.
+pub fn format_effort((remaining, total): (u32, u32)) -> String {
+    tr!(
+        "{} remaining / {} total",
+        format_duration(remaining),
+        format_duration(total)
+    )
+}
+
+pub fn set_estimate(project_name: &str, task_id: u32, estimate: &str) {
+    let estimate = if estimate.eq_ignore_ascii_case("none") {
+        None
+    } else {
+        match parse_duration(estimate) {
+            Ok(minutes) => Some(minutes),
+            Err(e) => {
+                println!("{}", tr!("Invalid estimate: {}", e));
+                return;
+            }
+        }
+    };
+
+    let mut data = load_data();
+    if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
+        if let Some(task) = project.tasks.iter_mut().find(|t| t.id == task_id) {
+            task.estimate = estimate;
+            let detail = match estimate {
+                Some(minutes) => format!("estimate set to {}", format_duration(minutes)),
+                None => "estimate cleared".to_string(),
+            };
+            task.record(TaskEvent::Edited, Some(detail));
+            save_data(&data);
+            match estimate {
+                Some(minutes) => println!(
+                    "{}",
+                    tr!(
+                        "Task {} is now estimated at {}.",
+                        task_id,
+                        format_duration(minutes)
+                    )
+                ),
+                None => println!("{}", tr!("Task {} no longer has an estimate.", task_id)),
+            }
+        } else {
+            println!(
+                "{}",
+                tr!("Task {} not found in project '{}'.", task_id, project_name)
+            );
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
@@ -0,0 +1,167 @@
+//! Exporting the tracker for consumption by other tools.
+
+use clap::ValueEnum;
+use std::io::{stdout, IsTerminal, Write};
+
+use chrono::Local;
+
+use crate::{i18n::tr, load_data, pdf, privacy, progress, Project, Task};
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    /// A list of projects, in the same shape as the data file.
+    Json,
+    /// A self-contained HTML status page.
+    Html,
+    /// A printable status report, one page per project.
+    Pdf,
+}
+
+/// Print all projects, or only `project_name`, to stdout, with the
+/// descriptions of private tasks masked.
+pub fn export(project_name: Option<&str>, format: ExportFormat) {
+    let data = load_data();
+    let projects: Vec<&Project> = data
+        .iter()
+        .filter(|p| project_name.is_none_or(|name| p.name == name))
+        .collect();
+
+    if let Some(name) = project_name {
+        if projects.is_empty() {
+            println!("{}", tr!("Project '{}' not found.", name));
+            return;
+        }
+    }
+
+    let masked = privacy::masked(&projects);
+    let projects: Vec<&Project> = masked.iter().collect();
+    match format {
+        ExportFormat::Json => {
+            let content =
+                serde_json::to_string_pretty(&projects).expect("Unable to serialize data.");
+            println!("{}", content);
+        }
+        ExportFormat::Html => print!("{}", html(&projects)),
+        ExportFormat::Pdf => write_pdf(&projects),
+    }
+}
+
+const STYLE: &str = "
+body { font-family: system-ui, sans-serif; max-width: 48rem; margin: 2rem auto; padding: 0 1rem; color: #222; }
+h1 { font-size: 1.5rem; }
+footer { color: #888; font-size: 0.8rem; margin-top: 2rem; }
+details { border: 1px solid #ddd; border-radius: 6px; margin: 0.75rem 0; padding: 0.5rem 1rem; }
+summary { cursor: pointer; font-weight: 600; display: flex; align-items: center; gap: 1rem; }
+summary .name { flex: 1; }
+.bar { width: 10rem; height: 0.6rem; background: #eee; border-radius: 3px; overflow: hidden; }
+.bar span { display: block; height: 100%; background: #3a3; }
+.percent { width: 3rem; text-align: right; font-variant-numeric: tabular-nums; }
+ul { list-style: none; padding-left: 0; }
+li { padding: 0.2rem 0; }
+li.done .description { text-decoration: line-through; color: #888; }
+.tag { color: #07a; margin-left: 0.4rem; }
+.priority-high { color: #c22; }
+.priority-medium { color: #b80; }
+.priority-low { color: #27c; }
+.due { color: #888; margin-left: 0.4rem; }
+";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html_task(task: &Task) -> String {
+    let mut item = format!(
+        "<li class=\"{}\">{} <span class=\"description\">{}</span>",
+        if task.completed { "done" } else { "pending" },
+        if task.completed { "&#9745;" } else { "&#9744;" },
+        escape(&task.description)
+    );
+    if let Some(priority) = task.priority {
+        item.push_str(&format!(
+            " <span class=\"priority-{0}\">!{0}</span>",
+            priority
+        ));
+    }
+    for tag in &task.tags {
+        item.push_str(&format!(" <span class=\"tag\">#{}</span>", escape(tag)));
+    }
+    if let Some(due) = task.due {
+        item.push_str(&format!(
+            " <span class=\"due\">{}</span>",
+            escape(&tr!("due {}", due))
+        ));
+    }
+    item.push_str("</li>\n");
+    item
+}
+
+/// A standalone page with a collapsible section per project.
+fn html(projects: &[&Project]) -> String {
+    let mut page = String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n");
+    let title = escape(&tr!("Project status"));
+    page.push_str(&format!(
+        "<meta charset=\"utf-8\">\n<title>{}</title>\n",
+        title
+    ));
+    page.push_str(&format!("<style>{}</style>\n</head>\n<body>\n", STYLE));
+    page.push_str(&format!("<h1>{}</h1>\n", title));
+
+    for project in projects {
+        let percent = progress::percent(project.progress());
+
+        // Projects with work left start expanded.
+        let open = if project.tasks.iter().any(|t| !t.completed) {
+            " open"
+        } else {
+            ""
+        };
+        page.push_str(&format!("<details{}>\n<summary>", open));
+        page.push_str(&format!(
+            "<span class=\"name\">{}</span><span class=\"bar\"><span style=\"width: {}%\"></span></span><span class=\"percent\">{}%</span>",
+            escape(&project.name),
+            percent,
+            percent
+        ));
+        page.push_str("</summary>\n");
+
+        if project.tasks.is_empty() {
+            page.push_str(&format!("<p>{}</p>\n", escape(&tr!("No tasks yet."))));
+        } else {
+            page.push_str("<ul>\n");
+            for task in &project.tasks {
+                page.push_str(&html_task(task));
+            }
+            page.push_str("</ul>\n");
+        }
+        page.push_str("</details>\n");
+    }
+
+    let generated = tr!("Generated on {}.", Local::now().format("%Y-%m-%d %H:%M"));
+    page.push_str(&format!(
+        "<footer>{}</footer>\n</body>\n</html>\n",
+        escape(&generated)
+    ));
+    page
+}
+
+/// Write the PDF report to stdout, which had better not be the terminal.
+fn write_pdf(projects: &[&Project]) {
+    if projects.is_empty() {
+        println!("{}", tr!("No projects to export."));
+        return;
+    }
+    if stdout().is_terminal() {
+        println!(
+            "{}",
+            tr!("Redirect the PDF to a file, e.g. `export --format pdf > report.pdf`.")
+        );
+        return;
+    }
+    stdout()
+        .write_all(&pdf::report(projects))
+        .expect("Unable to write the PDF.");
+}
@@ -0,0 +1,55 @@
+//! Arbitrary key/value metadata on tasks.
+
+use crate::{history::TaskEvent, i18n::tr, load_data, save_data, Task};
+
+/// Parse a `key=value` filter, as given to `list-tasks --where`.
+pub fn parse_filter(input: &str) -> Result<(String, String), String> {
+    match input.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(tr!("expected key=value, got '{}'", input)),
+    }
+}
+
+impl Task {
+    /// Whether the task has all the given `(key, value)` fields.
+    pub fn matches_fields(&self, filters: &[(String, String)]) -> bool {
+        filters
+            .iter()
+            .all(|(key, value)| self.fields.get(key) == Some(value))
+    }
+}
+
+/// Set field `key` of a task to `value`, or remove it if `value` is `None`.
+pub fn set_field(project_name: &str, task_id: u32, key: &str, value: Option<&str>) {
+    let mut data = load_data();
+    if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
+        if let Some(task) = project.tasks.iter_mut().find(|t| t.id == task_id) {
+            match value {
+                Some(value) => {
+                    task.fields.insert(key.to_string(), value.to_string());
+                    task.record(TaskEvent::Edited, Some(format!("{} set to {}", key, value)));
+                    save_data(&data);
+                    println!("{}", tr!("Task {}: {} set to '{}'.", task_id, key, value));
+                }
+                None => {
+                    if task.fields.remove(key).is_none() {
+                        println!("{}", tr!("Task {} has no field '{}'.", task_id, key));
+                        return;
+                    }
+                    task.record(TaskEvent::Edited, Some(format!("{} removed", key)));
+                    save_data(&data);
+                    println!("{}", tr!("Task {}: {} removed.", task_id, key));
+                }
+            }
+        } else {
+            println!(
+                "{}",
+                tr!("Task {} not found in project '{}'.", task_id, project_name)
+            );
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
@@ -0,0 +1,453 @@
+//! Filter expressions over tasks, e.g.
+//! `status:todo AND (tag:infra OR priority:high) AND due<7d`.
+//!
+//! An expression is made of terms combined with `AND`, `OR`, `NOT` and
+//! parentheses; terms next to each other are implicitly `AND`ed. A term is
+//! either `key<op>value` or a bare word, which matches the description.
+//!
+//! | key                | values                                    |
+//! |--------------------|-------------------------------------------|
+//! | `status`           | `todo`, `in-progress`, `done`, or `pending` for anything not done |
+//! | `due`              | a date (`2025-07-01`, `tomorrow`, `7d`...) or `none` |
+//! | `priority`         | `low`, `medium`, `high` or `none`         |
+//! | `tag`              | a tag                                     |
+//! | `context`          | a context, e.g. `@home`                   |
+//! | `project`          | a project or group name                   |
+//! | `id`               | a task ID                                 |
+//! | `text`             | a substring of the description            |
+//! | anything else      | the value of that custom field            |
+//!
+//! `<op>` is `:` or `=` for equality, or `<`, `<=`, `>`, `>=` (optionally
+//! after a `:`, as in `due:<2025-07-01`) for ordered keys: `due`, `priority`
+//! and `id`.
+
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use std::cmp::Ordering;
+
+use crate::{contexts, dates, groups, i18n::tr, Priority, Status, Task};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    fn matches(self, ordering: Ordering) -> bool {
+        match self {
+            Op::Eq => ordering == Ordering::Equal,
+            Op::Lt => ordering == Ordering::Less,
+            Op::Le => ordering != Ordering::Greater,
+            Op::Gt => ordering == Ordering::Greater,
+            Op::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Predicate {
+    Status(Status),
+    Pending,
+    Due(Op, Option<NaiveDate>),
+    Priority(Op, Option<Priority>),
+    Tag(String),
+    Context(String),
+    Project(String),
+    Id(Op, u32),
+    Text(String),
+    Field(String, String),
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Not(Box<Node>),
+    Term(Predicate),
+}
+
+/// A parsed filter expression.
+#[derive(Debug, Clone)]
+pub struct Filter(Node);
+
+#[derive(Debug)]
+enum Token {
+    Open,
+    Close,
+    Word { text: String, quoted: bool },
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            _ => {
+                let mut text = String::new();
+                let mut quoted = false;
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    }
+                    chars.next();
+                    if c == '"' {
+                        // Only a fully quoted word is plain text; a quote
+                        // after a key (`due<"next friday"`) is just a value.
+                        quoted = text.is_empty();
+                        loop {
+                            match chars.next() {
+                                Some('"') => break,
+                                Some(c) => text.push(c),
+                                None => return Err(tr!("unterminated quote")),
+                            }
+                        }
+                    } else {
+                        text.push(c);
+                    }
+                }
+                tokens.push(Token::Word { text, quoted });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Split `key<op>value` into its parts, or return `None` for a bare word.
+fn split_term(word: &str) -> Option<(&str, Op, &str)> {
+    let start = word.find([':', '=', '<', '>'])?;
+    let (key, rest) = word.split_at(start);
+
+    let rest = rest.strip_prefix([':', '=']).unwrap_or(rest);
+    let (op, value) = if let Some(value) = rest.strip_prefix("<=") {
+        (Op::Le, value)
+    } else if let Some(value) = rest.strip_prefix(">=") {
+        (Op::Ge, value)
+    } else if let Some(value) = rest.strip_prefix('<') {
+        (Op::Lt, value)
+    } else if let Some(value) = rest.strip_prefix('>') {
+        (Op::Gt, value)
+    } else {
+        (Op::Eq, rest)
+    };
+
+    Some((key, op, value))
+}
+
+fn parse_priority(value: &str) -> Result<Option<Priority>, String> {
+    if value.eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+    Priority::from_str(value, true)
+        .map(Some)
+        .map_err(|_| tr!("unknown priority '{}'", value))
+}
+
+fn parse_predicate(word: &str) -> Result<Predicate, String> {
+    let Some((key, op, value)) = split_term(word) else {
+        return Ok(Predicate::Text(word.to_lowercase()));
+    };
+    let lower_key = key.to_lowercase();
+
+    if op != Op::Eq && !matches!(lower_key.as_str(), "due" | "priority" | "id") {
+        return Err(tr!("'{}' can only be compared for equality", key));
+    }
+
+    let predicate = match lower_key.as_str() {
+        "status" => match value.to_lowercase().as_str() {
+            "pending" | "open" => Predicate::Pending,
+            "completed" => Predicate::Status(Status::Done),
+            status => Predicate::Status(
+                Status::from_str(status, true).map_err(|_| tr!("unknown status '{}'", value))?,
+            ),
+        },
+        "due" if value.eq_ignore_ascii_case("none") => Predicate::Due(op, None),
+        "due" => Predicate::Due(op, Some(dates::parse_date(value)?)),
+        "priority" => Predicate::Priority(op, parse_priority(value)?),
+        "tag" => Predicate::Tag(value.to_string()),
+        "context" => Predicate::Context(contexts::normalize(value)),
+        "project" => Predicate::Project(value.to_string()),
+        "id" => Predicate::Id(
+            op,
+            value
+                .parse()
+                .map_err(|_| tr!("invalid task ID '{}'", value))?,
+        ),
+        "text" | "desc" | "description" => Predicate::Text(value.to_lowercase()),
+        "" => return Err(tr!("missing key in '{}'", word)),
+        _ => Predicate::Field(key.to_string(), value.to_string()),
+    };
+
+    Ok(predicate)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(
+            self.tokens.get(self.pos),
+            Some(Token::Word { text, quoted: false }) if text.eq_ignore_ascii_case(keyword)
+        )
+    }
+
+    fn parse_or(&mut self) -> Result<Node, String> {
+        let mut filter = self.parse_and()?;
+        while self.peek_keyword("or") {
+            self.pos += 1;
+            filter = Node::Or(Box::new(filter), Box::new(self.parse_and()?));
+        }
+        Ok(filter)
+    }
+
+    fn parse_and(&mut self) -> Result<Node, String> {
+        let mut filter = self.parse_not()?;
+        loop {
+            if self.peek_keyword("and") {
+                self.pos += 1;
+            } else if self.peek_keyword("or")
+                || matches!(self.tokens.get(self.pos), None | Some(Token::Close))
+            {
+                break;
+            }
+            filter = Node::And(Box::new(filter), Box::new(self.parse_not()?));
+        }
+        Ok(filter)
+    }
+
+    fn parse_not(&mut self) -> Result<Node, String> {
+        if self.peek_keyword("not") {
+            self.pos += 1;
+            return Ok(Node::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Node, String> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        match token {
+            Some(Token::Open) => {
+                let filter = self.parse_or()?;
+                match self.tokens.get(self.pos) {
+                    Some(Token::Close) => {
+                        self.pos += 1;
+                        Ok(filter)
+                    }
+                    _ => Err(tr!("missing closing parenthesis")),
+                }
+            }
+            Some(Token::Word { text, quoted: true }) => {
+                Ok(Node::Term(Predicate::Text(text.to_lowercase())))
+            }
+            Some(Token::Word { text, .. }) => Ok(Node::Term(parse_predicate(text)?)),
+            Some(Token::Close) => Err(tr!("unexpected ')'")),
+            None => Err(tr!("unexpected end of filter")),
+        }
+    }
+}
+
+/// Parse a filter expression.
+///
+/// Meant to be used as a clap `value_parser`, hence the `String` error.
+pub fn parse(input: &str) -> Result<Filter, String> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        pos: 0,
+    };
+    let root = parser.parse_or()?;
+    if parser.pos < parser.tokens.len() {
+        return Err(tr!("unexpected ')'"));
+    }
+    Ok(Filter(root))
+}
+
+/// Compare an optional value against an optional bound, where a missing
+/// value only ever equals a missing bound.
+fn compare_optional<T: Ord>(value: Option<T>, op: Op, bound: Option<T>) -> bool {
+    match (value, bound) {
+        (Some(value), Some(bound)) => op.matches(value.cmp(&bound)),
+        (None, None) => op == Op::Eq,
+        _ => false,
+    }
+}
+
+impl Filter {
+    pub fn matches(&self, project: &str, task: &Task) -> bool {
+        self.0.matches(project, task)
+    }
+}
+
+impl Node {
+    fn matches(&self, project: &str, task: &Task) -> bool {
+        match self {
+            Node::And(a, b) => a.matches(project, task) && b.matches(project, task),
+            Node::Or(a, b) => a.matches(project, task) || b.matches(project, task),
+            Node::Not(filter) => !filter.matches(project, task),
+            Node::Term(predicate) => match predicate {
+                Predicate::Status(status) => task.status() == *status,
+                Predicate::Pending => !task.completed,
+                Predicate::Due(op, date) => compare_optional(task.due, *op, *date),
+                Predicate::Priority(op, priority) => {
+                    compare_optional(task.priority, *op, *priority)
+                }
+                Predicate::Tag(tag) => task.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)),
+                Predicate::Context(context) => task.contexts.contains(context),
+                Predicate::Project(name) => groups::has_prefix(project, name),
+                Predicate::Id(op, id) => op.matches(task.id.cmp(id)),
+                Predicate::Text(text) => task.description.to_lowercase().contains(text),
+                Predicate::Field(key, value) => task.fields.get(key) == Some(value),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn task() -> Task {
+        Task {
+            id: 7,
+            description: "Renew the TLS certificate and key".to_string(),
+            in_progress: true,
+            due: NaiveDate::from_ymd_opt(2026, 7, 1),
+            priority: Some(Priority::Medium),
+            tags: vec!["infra".to_string()],
+            contexts: vec!["@office".to_string()],
+            fields: BTreeMap::from([("client".to_string(), "acme".to_string())]),
+            ..Default::default()
+        }
+    }
+
+    fn matches(expression: &str) -> bool {
+        parse(expression)
+            .unwrap_or_else(|e| panic!("'{}' doesn't parse: {}", expression, e))
+            .matches("work/ops", &task())
+    }
+
+    #[test]
+    fn terms_match_their_keys() {
+        for expression in [
+            "status:in-progress",
+            "status:pending",
+            "due:2026-07-01",
+            "priority:medium",
+            "tag:INFRA",
+            "context:office",
+            "context:@Office",
+            "project:work",
+            "project:work/ops",
+            "id=7",
+            "text:tls",
+            "certificate",
+            "client:acme",
+        ] {
+            assert!(matches(expression), "'{}' doesn't match", expression);
+        }
+        for expression in [
+            "status:todo",
+            "status:done",
+            "due:none",
+            "priority:none",
+            "tag:web",
+            "project:wor",
+            "id:8",
+            "client:other",
+            "budget:1",
+        ] {
+            assert!(!matches(expression), "'{}' matches", expression);
+        }
+    }
+
+    #[test]
+    fn ordered_keys_compare() {
+        for expression in [
+            "due<2026-07-02",
+            "due:<=2026-07-01",
+            "due>=2026-07-01",
+            "priority>low",
+            "priority<high",
+            "id>6",
+            "id<=7",
+        ] {
+            assert!(matches(expression), "'{}' doesn't match", expression);
+        }
+        for expression in ["due<2026-07-01", "due>2026-07-01", "priority>=high", "id<7"] {
+            assert!(!matches(expression), "'{}' matches", expression);
+        }
+        assert!(matches("due<7d"), "relative dates are from today");
+        // Tasks without a due date or priority only equal `none`.
+        let undated = Task::default();
+        assert!(!parse("due<2030-01-01").unwrap().matches("web", &undated));
+        assert!(!parse("priority<high").unwrap().matches("web", &undated));
+    }
+
+    #[test]
+    fn operators_combine_terms() {
+        assert!(matches("tag:infra AND priority:medium"));
+        assert!(matches("tag:infra priority:medium"));
+        assert!(!matches("tag:infra and priority:high"));
+        assert!(matches("tag:web OR priority:medium"));
+        assert!(matches("NOT tag:web"));
+        assert!(!matches("not not tag:web"));
+        assert!(matches("status:todo or tag:infra and id:7"));
+        assert!(!matches("(status:todo or tag:infra) and id:8"));
+        assert!(matches(
+            "status:in-progress AND (tag:web OR priority:medium) AND due<2027-01-01"
+        ));
+    }
+
+    #[test]
+    fn quoted_words_are_text() {
+        assert!(matches("\"tls certificate\""));
+        assert!(!matches("\"tag:infra\""));
+        assert!(matches("tag:infra \"and\""), "a quoted keyword is a word");
+        assert!(!matches("tag:web \"or\" id:7"));
+        assert!(!matches("\"renew it\""));
+        // A quote after a key is only its value.
+        assert!(matches("client:\"acme\""));
+    }
+
+    #[test]
+    fn bad_expressions_are_refused() {
+        for expression in [
+            "",
+            "(tag:infra",
+            "tag:infra)",
+            ")",
+            "tag:infra and",
+            "not",
+            "\"unterminated",
+            "status:sleeping",
+            "priority:urgent",
+            "due:someday-maybe",
+            "id:seven",
+            ":value",
+            "tag<infra",
+            "client>acme",
+        ] {
+            assert!(parse(expression).is_err(), "'{}' parses", expression);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Focus sessions: `focus <project>` makes every command default to that
+//! project, as in `complete-task 3`, and limits the overview to it, until
+//! `focus --clear`. The project is kept in the `focus` setting.
+
+use crate::{
+    config::{load_config, save_config},
+    i18n::tr,
+    load_data,
+};
+
+/// The project focused on, if any.
+pub fn active() -> Option<String> {
+    load_config().effective().focus
+}
+
+/// The project commands given none use: the one focused on, otherwise the
+/// `default-project` setting.
+pub fn default_project() -> Option<String> {
+    let settings = load_config().effective();
+    settings.focus.or(settings.default_project)
+}
+
+/// Show the project focused on, focus on `project` or, with `clear`, stop
+/// focusing.
+pub fn focus(project: Option<&str>, clear: bool) {
+    if clear {
+        set(None);
+        return;
+    }
+    match project {
+        Some(project) if load_data().iter().any(|p| p.name == project) => set(Some(project)),
+        Some(project) => println!("{}", tr!("Project '{}' not found.", project)),
+        None => match active() {
+            Some(project) => println!("{}", tr!("Focused on {}.", project)),
+            None => println!("{}", tr!("Not focused on any project.")),
+        },
+    }
+}
+
+fn set(project: Option<&str>) {
+    let mut config = load_config();
+    config.settings.focus = project.map(str::to_string);
+    save_config(&config);
+
+    match project {
+        Some(project) => println!(
+            "{}",
+            tr!(
+                "Focused on {}: commands default to it and the overview only shows it.",
+                project
+            )
+        ),
+        None => println!("{}", tr!("Showing every project again.")),
+    }
+}
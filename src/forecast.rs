@@ -0,0 +1,178 @@
+//! `forecast`: when a project should be done at the pace tasks have been
+//! getting completed, with a range from how much that pace varies week to
+//! week, and whether the date slipped since last week.
+//!
+//! Last week's forecast isn't stored anywhere: it's worked out again from
+//! the tasks as they were then, going by when they were created and
+//! completed.
+
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use colored::Colorize;
+
+use crate::{i18n::tr, load_data, theme::Themed, Project, Task};
+
+/// Weeks of history the pace is measured over, at most.
+const WEEKS: i64 = 8;
+
+/// A project's expected completion date, and the range around it.
+struct Forecast {
+    pending: usize,
+    /// Tasks completed per week on average.
+    pace: f64,
+    date: NaiveDate,
+    earliest: NaiveDate,
+    /// `None` when the pace varies so much that it could drop to nothing.
+    latest: Option<NaiveDate>,
+}
+
+fn completed_by(task: &Task, at: DateTime<Local>) -> bool {
+    // Tasks completed before completion times were recorded count as
+    // completed long ago.
+    task.completed && task.completed_at.is_none_or(|done| done <= at)
+}
+
+/// Tasks completed in each of the weeks before `at`, most recent first,
+/// going back no further than the project's first task.
+fn weekly_completions(project: &Project, at: DateTime<Local>) -> Vec<usize> {
+    let first = project.tasks.iter().filter_map(|t| t.created_at()).min();
+    let weeks = first.map_or(WEEKS, |first| {
+        ((at - first).num_days() as f64 / 7.0)
+            .ceil()
+            .clamp(1.0, WEEKS as f64) as i64
+    });
+    (0..weeks)
+        .map(|week| {
+            let end = at - Duration::weeks(week);
+            let start = end - Duration::weeks(1);
+            project
+                .tasks
+                .iter()
+                .filter(|t| {
+                    t.completed_at
+                        .is_some_and(|done| done > start && done <= end)
+                })
+                .count()
+        })
+        .collect()
+}
+
+fn forecast(project: &Project, at: DateTime<Local>) -> Option<Forecast> {
+    let pending = project
+        .tasks
+        .iter()
+        .filter(|t| t.created_at().is_none_or(|created| created <= at))
+        .filter(|t| !completed_by(t, at))
+        .count();
+    let weeks = weekly_completions(project, at);
+    let pace = weeks.iter().sum::<usize>() as f64 / weeks.len() as f64;
+    if pending == 0 || pace == 0.0 {
+        return None;
+    }
+    let variance = weeks
+        .iter()
+        .map(|&done| (done as f64 - pace).powi(2))
+        .sum::<f64>()
+        / weeks.len() as f64;
+    // How far off the average pace may be: over many weeks, good and bad
+    // ones even out, so this narrows as more weeks are measured.
+    let spread = (variance / weeks.len() as f64).sqrt();
+
+    let day = at.date_naive();
+    let eta = |pace: f64| day + Duration::days((pending as f64 / pace * 7.0).ceil() as i64);
+    Some(Forecast {
+        pending,
+        pace,
+        date: eta(pace),
+        earliest: eta(pace + spread),
+        latest: (pace - spread > 0.0).then(|| eta(pace - spread)),
+    })
+}
+
+fn show_forecast(project: &Project, now: DateTime<Local>) {
+    let Some(forecast) = forecast(project, now) else {
+        if project.tasks.iter().all(|t| t.completed) {
+            println!("{}: {}", project.name.bold(), tr!("nothing left to do."));
+        } else {
+            println!(
+                "{}: {}",
+                project.name.bold(),
+                tr!(
+                    "no tasks completed in the last {} weeks, so no forecast.",
+                    WEEKS
+                )
+            );
+        }
+        return;
+    };
+
+    println!(
+        "{}: {}",
+        project.name.bold(),
+        tr!(
+            "{} tasks left. At your {} tasks/week pace, done around {}.",
+            forecast.pending,
+            format!("{:.1}", forecast.pace),
+            forecast.date
+        )
+    );
+    let range = match forecast.latest {
+        Some(latest) => tr!("Likely between {} and {}.", forecast.earliest, latest),
+        None => tr!(
+            "{} at the earliest; the pace varies too much to tell how late.",
+            forecast.earliest
+        ),
+    };
+    println!("    {}", range.dimmed());
+
+    if let Some(deadline) = project.deadline.filter(|d| forecast.date > *d) {
+        println!(
+            "    {}",
+            tr!("That's after the deadline, {}.", deadline).bad()
+        );
+    }
+    if let Some(before) = forecast_a_week_ago(project, now) {
+        let slipped = (forecast.date - before).num_days();
+        if slipped > 0 {
+            println!(
+                "    {}",
+                tr!(
+                    "Slipped {} days since last week, when it was {}.",
+                    slipped,
+                    before
+                )
+                .warn()
+            );
+        }
+    }
+}
+
+fn forecast_a_week_ago(project: &Project, now: DateTime<Local>) -> Option<NaiveDate> {
+    forecast(project, now - Duration::weeks(1)).map(|f| f.date)
+}
+
+/// Forecast `project`, or every project with tasks left.
+pub fn show(project_name: Option<&str>) {
+    let data = load_data();
+    let now = Local::now();
+    let projects: Vec<&Project> = match project_name {
+        Some(name) => match data.iter().find(|p| p.name == name) {
+            Some(project) => vec![project],
+            None => {
+                println!("{}", tr!("Project '{}' not found.", name));
+                return;
+            }
+        },
+        None => data
+            .iter()
+            .filter(|p| p.tasks.iter().any(|t| !t.completed))
+            .collect(),
+    };
+    if projects.is_empty() {
+        println!("{}", tr!("No projects with tasks left."));
+        return;
+    }
+
+    for project in projects {
+        show_forecast(project, now);
+    }
+}
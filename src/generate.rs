@@ -0,0 +1,143 @@
+//! `generate`: made-up projects and tasks, to try the tracker, or measure
+//! it, on as much data as one likes. The same seed always gives the same
+//! data.
+
+use chrono::{Duration, Local};
+
+use crate::{
+    history::{HistoryEntry, TaskEvent},
+    i18n::tr,
+    load_data, save_data, Priority, Project, Task,
+};
+
+const VERBS: &[&str] = &[
+    "fix", "write", "review", "update", "refactor", "test", "document", "deploy", "remove",
+    "design",
+];
+const THINGS: &[&str] = &[
+    "login page",
+    "search index",
+    "billing report",
+    "onboarding emails",
+    "API client",
+    "cache layer",
+    "release notes",
+    "database migration",
+    "settings screen",
+    "error messages",
+];
+const TAGS: &[&str] = &["bug", "infra", "docs", "ui", "quick", "backend"];
+
+/// A small xorshift generator: good enough for made-up data, and the same
+/// for a given seed everywhere.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len() as u64) as usize]
+    }
+
+    /// True once in `n` times.
+    fn one_in(&mut self, n: u64) -> bool {
+        self.below(n) == 0
+    }
+}
+
+fn task(random: &mut Random, id: u32) -> Task {
+    let now = Local::now();
+    let created = now - Duration::minutes(random.below(180 * 24 * 60) as i64);
+    let mut task = Task {
+        id,
+        description: format!("{} the {}", random.pick(VERBS), random.pick(THINGS)),
+        priority: match random.below(4) {
+            0 => Some(Priority::Low),
+            1 => Some(Priority::Medium),
+            2 => Some(Priority::High),
+            _ => None,
+        },
+        due: random
+            .one_in(3)
+            .then(|| (now + Duration::days(random.below(120) as i64 - 30)).date_naive()),
+        estimate: random.one_in(2).then(|| 15 * (1 + random.below(16) as u32)),
+        tags: (0..random.below(3))
+            .map(|_| random.pick(TAGS).to_string())
+            .collect(),
+        history: vec![HistoryEntry {
+            at: created,
+            event: TaskEvent::Created,
+            detail: None,
+            by: None,
+        }],
+        ..Default::default()
+    };
+    task.tags.dedup();
+
+    if random.one_in(2) {
+        let completed = created + (now - created) / (1 + random.below(4) as i32);
+        task.completed = true;
+        task.completed_at = Some(completed);
+        task.history.push(HistoryEntry {
+            at: completed,
+            event: TaskEvent::Completed,
+            detail: None,
+            by: None,
+        });
+    } else {
+        task.in_progress = random.one_in(5);
+    }
+    task
+}
+
+/// Add `projects` made-up projects with `tasks` tasks between them to the
+/// active workspace, which must be empty so no real data gets mixed up
+/// with them.
+pub fn generate(projects: u32, tasks: u32, seed: u64) {
+    let mut data = load_data();
+    if !data.is_empty() {
+        println!(
+            "{}",
+            tr!("The workspace already has projects, generate data in an empty one (e.g. --workspace bench).")
+        );
+        return;
+    }
+    if projects == 0 {
+        println!("{}", tr!("Nothing to generate."));
+        return;
+    }
+
+    // Zero would stay zero forever.
+    let mut random = Random(seed.max(1));
+    data = (1..=projects)
+        .map(|number| Project {
+            name: format!("project-{}", number),
+            prefix: None,
+            deadline: None,
+            weekly_goal: None,
+            after: Vec::new(),
+            next_id: 1,
+            tasks: Vec::new(),
+        })
+        .collect();
+    for number in 0..tasks {
+        let project = &mut data[(number % projects) as usize];
+        let id = project.allocate_id();
+        project.tasks.push(task(&mut random, id));
+    }
+
+    save_data(&data);
+    println!(
+        "{}",
+        tr!("Generated {} projects with {} tasks.", projects, tasks)
+    );
+}
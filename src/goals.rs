@@ -0,0 +1,158 @@
+//! Project goals: a target completion date and a weekly throughput goal,
+//! and whether the project is on track to meet them.
+
+use chrono::{Duration, Local};
+
+use crate::{
+    dates, durations::format_duration, i18n::tr, load_data, save_data, theme::Themed, Project,
+};
+
+/// Tasks completed per week over the last four weeks.
+fn recent_pace(project: &Project) -> f64 {
+    let since = Local::now() - Duration::weeks(4);
+    let done = project
+        .tasks
+        .iter()
+        .filter(|t| t.completed_at.is_some_and(|at| at >= since))
+        .count();
+    done as f64 / 4.0
+}
+
+/// Tasks completed in the last seven days.
+fn done_this_week(project: &Project) -> usize {
+    let since = Local::now() - Duration::weeks(1);
+    project
+        .tasks
+        .iter()
+        .filter(|t| t.completed_at.is_some_and(|at| at >= since))
+        .count()
+}
+
+/// A one-line summary of how the project is doing against its goals, or
+/// `None` if it doesn't have any.
+pub fn goal_summary(project: &Project) -> Option<String> {
+    if project.deadline.is_none() && project.weekly_goal.is_none() {
+        return None;
+    }
+
+    let pending: Vec<_> = project.tasks.iter().filter(|t| !t.completed).collect();
+    let mut parts = Vec::new();
+    let mut on_track = true;
+
+    let remaining = match pending.len() {
+        1 => tr!("1 task left"),
+        n => tr!("{} tasks left", n),
+    };
+    let estimate: u32 = pending.iter().filter_map(|t| t.estimate).sum();
+    if estimate > 0 {
+        parts.push(format!("{} (~{})", remaining, format_duration(estimate)));
+    } else {
+        parts.push(remaining);
+    }
+
+    if let Some(deadline) = project.deadline {
+        let days_left = (deadline - dates::today()).num_days();
+        if days_left < 0 {
+            parts.push(tr!("deadline {} passed", deadline));
+            on_track = pending.is_empty();
+        } else {
+            parts.push(tr!("{} days until {}", days_left, deadline));
+            // Finishing on the deadline day itself still counts.
+            let weeks_left = (days_left + 1) as f64 / 7.0;
+            let needed = pending.len() as f64 / weeks_left;
+            let pace = recent_pace(project);
+            if !pending.is_empty() {
+                parts.push(tr!(
+                    "need {}/week, doing {}",
+                    format!("{:.1}", needed),
+                    format!("{:.1}", pace)
+                ));
+            }
+            on_track &= pace >= needed;
+        }
+    }
+
+    if let Some(goal) = project.weekly_goal {
+        let done = done_this_week(project);
+        parts.push(tr!("{}/{} this week", done, goal));
+        on_track &= done >= goal as usize || pending.is_empty();
+    }
+
+    let verdict = if pending.is_empty() {
+        tr!("done").good()
+    } else if on_track {
+        tr!("on track").good()
+    } else {
+        tr!("behind").bad()
+    };
+    Some(format!("{}: {}", parts.join(", "), verdict))
+}
+
+pub fn set_goal(project_name: &str, deadline: Option<&str>, weekly: Option<&str>) {
+    let deadline = match deadline {
+        Some(date) if date.eq_ignore_ascii_case("none") => Some(None),
+        Some(date) => match dates::parse_date(date) {
+            Ok(date) => Some(Some(date)),
+            Err(e) => {
+                println!("{}", tr!("Invalid date: {}", e));
+                return;
+            }
+        },
+        None => None,
+    };
+    let weekly = match weekly {
+        Some(goal) if goal.eq_ignore_ascii_case("none") => Some(None),
+        Some(goal) => match goal.parse::<u32>() {
+            Ok(goal) if goal > 0 => Some(Some(goal)),
+            _ => {
+                println!(
+                    "{}",
+                    tr!(
+                        "Invalid weekly goal '{}': use a number of tasks or none.",
+                        goal
+                    )
+                );
+                return;
+            }
+        },
+        None => None,
+    };
+
+    let mut data = load_data();
+    let Some(project) = data.iter_mut().find(|p| p.name == project_name) else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+        return;
+    };
+
+    if let Some(deadline) = deadline {
+        project.deadline = deadline;
+        match deadline {
+            Some(date) => println!(
+                "{}",
+                tr!("Project '{}' is now due on {}.", project_name, date)
+            ),
+            None => println!(
+                "{}",
+                tr!("Project '{}' no longer has a deadline.", project_name)
+            ),
+        }
+    }
+    if let Some(weekly) = weekly {
+        project.weekly_goal = weekly;
+        match weekly {
+            Some(goal) => println!(
+                "{}",
+                tr!(
+                    "Project '{}' now aims for {} tasks a week.",
+                    project_name,
+                    goal
+                )
+            ),
+            None => println!(
+                "{}",
+                tr!("Project '{}' no longer has a weekly goal.", project_name)
+            ),
+        }
+    }
+    save_data(&data);
+}
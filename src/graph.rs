@@ -0,0 +1,191 @@
+//! Progress of each project over time, sampled whenever the data is saved
+//! and kept in `<data file>.progress.json`, next to the data file.
+//!
+//! Archived tasks still count, so archiving old completed tasks doesn't
+//! look like progress going backwards.
+
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io::ErrorKind, path::PathBuf};
+
+use crate::{
+    archive, dates,
+    i18n::tr,
+    load_data,
+    progress::{self, Weighting},
+    storage::DataFormat,
+    theme::Themed,
+    workspace, Project, Task,
+};
+
+/// Rows of the graph, from 10% to 100%.
+const HEIGHT: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+struct Sample {
+    at: DateTime<Local>,
+    completed: usize,
+    total: usize,
+    /// Progress weighted by estimate, when any task had one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    by_estimate: Option<f64>,
+}
+
+impl Sample {
+    fn percent(&self) -> usize {
+        match self.by_estimate {
+            Some(share) if progress::weighting() == Weighting::Estimate => progress::percent(share),
+            _ => (self.completed * 100).checked_div(self.total).unwrap_or(0),
+        }
+    }
+}
+
+/// Samples of each project, oldest first.
+type History = BTreeMap<String, Vec<Sample>>;
+
+fn get_history_path(workspace_name: &str) -> PathBuf {
+    workspace::data_file_path(workspace_name, DataFormat::Json).with_extension("progress.json")
+}
+
+fn load_history() -> History {
+    match fs::read(get_history_path(workspace::active())) {
+        Ok(content) => serde_json::from_slice(&content).expect("Unable to parse progress history."),
+        Err(e) if e.kind() == ErrorKind::NotFound => History::new(),
+        Err(e) => panic!("Unable to read progress history: {}", e),
+    }
+}
+
+/// Delete the progress history of a workspace being deleted.
+pub fn remove(workspace_name: &str) {
+    if let Err(e) = fs::remove_file(get_history_path(workspace_name)) {
+        if e.kind() != ErrorKind::NotFound {
+            panic!("Unable to remove progress history: {}", e);
+        }
+    }
+}
+
+/// Sample the progress of the projects in `data`, just saved, for those
+/// whose progress changed since the last sample.
+pub fn record(data: &[Project]) {
+    let archive = archive::load_archive();
+    let mut history = load_history();
+    let now = Local::now();
+    let mut changed = false;
+
+    history.retain(|name, _| data.iter().any(|p| &p.name == name));
+    for project in data {
+        let tasks: Vec<&Task> = archive
+            .iter()
+            .filter(|p| p.name == project.name)
+            .chain([project])
+            .flat_map(|p| &p.tasks)
+            .collect();
+        let completed = tasks.iter().filter(|t| t.completed).count();
+        let total = tasks.len();
+        // Kept whatever the weighting, for the graph to follow it if it
+        // changes.
+        let by_estimate = tasks
+            .iter()
+            .any(|t| t.estimate.is_some())
+            .then(|| progress::completion_by(tasks.iter().copied(), Weighting::Estimate));
+
+        let samples = history.entry(project.name.clone()).or_default();
+        if samples.last().is_some_and(|last| {
+            (last.completed, last.total, last.by_estimate) == (completed, total, by_estimate)
+        }) {
+            continue;
+        }
+        samples.push(Sample {
+            at: now,
+            completed,
+            total,
+            by_estimate,
+        });
+        changed = true;
+    }
+
+    if changed {
+        let content = serde_json::to_vec(&history).expect("Unable to serialize progress history.");
+        fs::write(get_history_path(workspace::active()), content)
+            .expect("Unable to write progress history.");
+    }
+}
+
+/// Progress at the end of `day`: the last sample taken by then.
+fn percent_on(samples: &[Sample], day: NaiveDate) -> Option<usize> {
+    samples
+        .iter()
+        .take_while(|s| s.at.date_naive() <= day)
+        .last()
+        .map(Sample::percent)
+}
+
+/// Draw the progress of a project over the past `weeks`, one column a day.
+pub fn graph(project_name: &str, weeks: u32) {
+    if !load_data().iter().any(|p| p.name == project_name) {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+        return;
+    }
+    let history = load_history();
+    let Some(samples) = history.get(project_name).filter(|s| !s.is_empty()) else {
+        println!(
+            "{}",
+            tr!("No progress recorded for project '{}' yet.", project_name)
+        );
+        return;
+    };
+
+    let today = dates::today();
+    let start = today - Duration::weeks(i64::from(weeks.max(1))) + Duration::days(1);
+    let days: Vec<NaiveDate> = start.iter_days().take_while(|d| *d <= today).collect();
+    let percents: Vec<Option<usize>> = days.iter().map(|d| percent_on(samples, *d)).collect();
+
+    println!("{}", tr!("Progress of project '{}':", project_name));
+    for row in (1..=HEIGHT).rev() {
+        let threshold = row * 100 / HEIGHT;
+        let line: String = percents
+            .iter()
+            .map(|percent| match percent {
+                Some(p) if *p + 100 / HEIGHT / 2 >= threshold => '#',
+                _ => ' ',
+            })
+            .collect();
+        let label = if row == HEIGHT || row == HEIGHT / 2 {
+            format!("{}%", threshold)
+        } else {
+            String::new()
+        };
+        println!("{:>5} |{}", label.dimmed(), line.trim_end().good());
+    }
+
+    // A tick at the start of every week, counting back from today.
+    let axis: String = (0..days.len())
+        .map(|i| {
+            if (days.len() - i).is_multiple_of(7) {
+                '+'
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    println!("{:>5} +{}", "0%".dimmed(), axis);
+    let first = start.format("%Y-%m-%d").to_string();
+    let last = today.format("%Y-%m-%d").to_string();
+    let gap = (days.len() + 1)
+        .saturating_sub(first.len() + last.len())
+        .max(1);
+    println!("{:>5} {}{}{}", "", first, " ".repeat(gap), last);
+
+    if let Some(latest) = samples.last() {
+        println!(
+            "{}",
+            tr!(
+                "Now {}% ({}/{} tasks completed).",
+                latest.percent(),
+                latest.completed,
+                latest.total
+            )
+        );
+    }
+}
@@ -0,0 +1,61 @@
+//! Project groups: a project named `clients/acme/website` lives in the
+//! `clients/acme` group, which itself lives in `clients`.
+
+use std::collections::BTreeMap;
+
+use crate::{bars, Project};
+
+/// Whether `name` is a usable project path: no empty components, so no
+/// leading, trailing or doubled slashes.
+pub fn is_valid_path(name: &str) -> bool {
+    name.split('/').all(|part| !part.trim().is_empty())
+}
+
+/// Whether `project` is `prefix` itself or lives somewhere below it.
+pub fn has_prefix(project: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    project == prefix
+        || project
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+#[derive(Default)]
+struct Node {
+    is_project: bool,
+    children: BTreeMap<String, Node>,
+}
+
+impl Node {
+    fn insert(&mut self, path: &str) {
+        let node = path.split('/').fold(self, |node, part| {
+            node.children.entry(part.to_string()).or_default()
+        });
+        node.is_project = true;
+    }
+
+    fn print(&self, indent: &str) {
+        let count = self.children.len();
+        for (i, (name, child)) in self.children.iter().enumerate() {
+            let last = i + 1 == count;
+            let (branch, next_indent) = match (last, bars::is_ascii()) {
+                (true, false) => ("└── ", "    "),
+                (false, false) => ("├── ", "│   "),
+                (true, true) => ("`-- ", "    "),
+                (false, true) => ("|-- ", "|   "),
+            };
+            let suffix = if child.is_project { "" } else { "/" };
+            println!("{}{}{}{}", indent, branch, name, suffix);
+            child.print(&format!("{}{}", indent, next_indent));
+        }
+    }
+}
+
+/// Print the given projects as a tree of their groups.
+pub fn print_tree<'a>(projects: impl IntoIterator<Item = &'a Project>) {
+    let mut root = Node::default();
+    for project in projects {
+        root.insert(&project.name);
+    }
+    root.print(" ");
+}
@@ -0,0 +1,182 @@
+//! Append-only history of everything that happens to a task.
+
+use chrono::{DateTime, Local, NaiveDate};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::{fmt, sync::OnceLock};
+
+use crate::{config::load_config, i18n::tr, load_data, privacy, theme::Themed, Task};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskEvent {
+    Created,
+    Edited,
+    Completed,
+    Reopened,
+    Moved,
+    /// Closed in favor of the tasks it was split into.
+    Split,
+}
+
+impl fmt::Display for TaskEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            TaskEvent::Created => tr!("created"),
+            TaskEvent::Edited => tr!("edited"),
+            TaskEvent::Completed => tr!("completed"),
+            TaskEvent::Reopened => tr!("reopened"),
+            TaskEvent::Moved => tr!("moved"),
+            TaskEvent::Split => tr!("split"),
+        };
+        f.write_str(&name)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryEntry {
+    pub at: DateTime<Local>,
+    pub event: TaskEvent,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Who did it, as set with the `user` setting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub by: Option<String>,
+}
+
+/// The `user` changes are attributed to, if set.
+fn user() -> Option<&'static str> {
+    static USER: OnceLock<Option<String>> = OnceLock::new();
+    USER.get_or_init(|| load_config().effective().user)
+        .as_deref()
+}
+
+impl Task {
+    /// Append `event` to the task's history, timestamped now and
+    /// attributed to the `user`.
+    pub fn record(&mut self, event: TaskEvent, detail: Option<String>) {
+        self.history.push(HistoryEntry {
+            at: Local::now(),
+            event,
+            detail,
+            by: user().map(str::to_string),
+        });
+    }
+
+    /// When the task was created, if that was recorded.
+    pub fn created_at(&self) -> Option<DateTime<Local>> {
+        self.history
+            .iter()
+            .find(|entry| matches!(entry.event, TaskEvent::Created))
+            .map(|entry| entry.at)
+    }
+
+    /// Who created the task, if that was recorded.
+    pub fn created_by(&self) -> Option<&str> {
+        self.history
+            .iter()
+            .find(|entry| matches!(entry.event, TaskEvent::Created))
+            .and_then(|entry| entry.by.as_deref())
+    }
+
+    /// Who completed the task, if it's completed and that was recorded.
+    pub fn completed_by(&self) -> Option<&str> {
+        if !self.completed {
+            return None;
+        }
+        self.history
+            .iter()
+            .rev()
+            .find(|entry| matches!(entry.event, TaskEvent::Completed))
+            .and_then(|entry| entry.by.as_deref())
+    }
+
+    /// When the task was last changed in any way, if that was recorded.
+    pub fn updated_at(&self) -> Option<DateTime<Local>> {
+        self.history
+            .last()
+            .map(|entry| entry.at)
+            .or(self.completed_at)
+    }
+}
+
+fn print_entry(prefix: &str, entry: &HistoryEntry) {
+    let event = match entry.event {
+        TaskEvent::Created => entry.event.to_string().minor(),
+        TaskEvent::Edited => entry.event.to_string().warn(),
+        TaskEvent::Completed => entry.event.to_string().good(),
+        TaskEvent::Reopened => entry.event.to_string().bad(),
+        TaskEvent::Moved | TaskEvent::Split => entry.event.to_string().magenta(),
+    };
+    let detail = entry
+        .detail
+        .as_ref()
+        .map_or(String::new(), |d| format!(": {}", d));
+    let by = entry.by.as_ref().map_or(String::new(), |by| {
+        format!(" {}", tr!("(by {})", by).dimmed())
+    });
+    println!(
+        "    {} {}{}{}{}",
+        entry.at.format("%Y-%m-%d %H:%M"),
+        prefix,
+        event,
+        detail,
+        by
+    );
+}
+
+pub fn show_history(project_name: &str, task_id: u32) {
+    let data = load_data();
+    if let Some(project) = data.iter().find(|p| p.name == project_name) {
+        if let Some(task) = project.tasks.iter().find(|t| t.id == task_id) {
+            println!(
+                "{}",
+                tr!(
+                    "History of task {}: {}",
+                    task_id,
+                    privacy::description(task)
+                )
+            );
+            if task.history.is_empty() {
+                println!("    {}", tr!("No history recorded."));
+            }
+            for entry in &task.history {
+                print_entry("", entry);
+            }
+        } else {
+            println!(
+                "{}",
+                tr!("Task {} not found in project '{}'.", task_id, project_name)
+            );
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
+
+/// Print every recorded event across all projects, oldest first.
+pub fn show_log(since: Option<NaiveDate>) {
+    let data = load_data();
+
+    let mut entries: Vec<(String, &HistoryEntry)> = data
+        .iter()
+        .flat_map(|project| {
+            project.tasks.iter().flat_map(move |task| {
+                task.history
+                    .iter()
+                    .map(move |entry| (format!("{}#{} ", project.name, task.id), entry))
+            })
+        })
+        .filter(|(_, entry)| since.is_none_or(|since| entry.at.date_naive() >= since))
+        .collect();
+    entries.sort_by_key(|(_, entry)| entry.at);
+
+    if entries.is_empty() {
+        println!("{}", tr!("No history recorded."));
+        return;
+    }
+
+    for (prefix, entry) in entries {
+        print_entry(&prefix, entry);
+    }
+}
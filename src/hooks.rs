@@ -0,0 +1,127 @@
+//! Lifecycle hooks.
+//!
+//! Any executable inside the `hooks/` config directory named after an
+//! event (e.g. `on-task-complete`) is run whenever that event happens, with
+//! the event itself serialized as JSON on its stdin. The same payload is
+//! also POSTed to the webhooks registered for the event, private tasks
+//! masked.
+
+use serde_json::{json, Value};
+use std::{
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+use tracing::{debug, trace};
+
+use crate::{get_config_dir, i18n::tr, privacy, storage, webhooks, Project, Task};
+
+pub enum Event<'a> {
+    ProjectAdd(&'a Project),
+    ProjectDelete(&'a str),
+    TaskAdd(&'a str, &'a Task),
+    TaskComplete(&'a str, &'a Task),
+    TaskReopen(&'a str, &'a Task),
+    TaskDelete(&'a str, u32),
+}
+
+impl Event<'_> {
+    /// Name of the event, as used for the hook executable (without the
+    /// `on-` prefix).
+    fn name(&self) -> &'static str {
+        match self {
+            Event::ProjectAdd(_) => "project-add",
+            Event::ProjectDelete(_) => "project-delete",
+            Event::TaskAdd(..) => "task-add",
+            Event::TaskComplete(..) => "task-complete",
+            Event::TaskReopen(..) => "task-reopen",
+            Event::TaskDelete(..) => "task-delete",
+        }
+    }
+
+    /// The event as JSON, with private tasks masked when it's to leave the
+    /// machine.
+    fn payload(&self, masked: bool) -> Value {
+        let mut payload = match self {
+            Event::ProjectAdd(project) if masked => {
+                json!({ "project": privacy::masked(&[project]).remove(0) })
+            }
+            Event::ProjectAdd(project) => json!({ "project": project }),
+            Event::ProjectDelete(project) => json!({ "project": project }),
+            Event::TaskAdd(project, task)
+            | Event::TaskComplete(project, task)
+            | Event::TaskReopen(project, task) => {
+                let task = if masked {
+                    privacy::masked_task(task)
+                } else {
+                    (*task).clone()
+                };
+                json!({ "project": project, "task": task })
+            }
+            Event::TaskDelete(project, task_id) => {
+                json!({ "project": project, "task_id": task_id })
+            }
+        };
+        payload["event"] = json!(self.name());
+        payload
+    }
+}
+
+fn get_hooks_dir() -> PathBuf {
+    get_config_dir().join("hooks")
+}
+
+/// Run the hook registered for `event`, if there is one, and queue it for
+/// its webhooks.
+///
+/// Hooks are best-effort: a missing hook is silently ignored, and a failing
+/// one only produces a warning, never aborting the command that triggered it.
+pub fn run(event: Event) {
+    // Nothing really happened.
+    if storage::is_dry_run() {
+        return;
+    }
+
+    // Webhooks post elsewhere, hooks run here.
+    webhooks::enqueue(event.name(), &event.payload(true));
+
+    let hook = get_hooks_dir().join(format!("on-{}", event.name()));
+    if !hook.is_file() {
+        trace!("no hook at {}", hook.display());
+        return;
+    }
+    debug!("running hook {}", hook.display());
+
+    let child = Command::new(&hook)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .spawn();
+
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("{}", tr!("Failed to run hook '{}': {}", hook.display(), e));
+            return;
+        }
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook is free to ignore its input, so a broken pipe is fine here.
+        let _ = stdin.write_all(event.payload(false).to_string().as_bytes());
+    }
+
+    match child.wait() {
+        Ok(status) if !status.success() => {
+            eprintln!(
+                "{}",
+                tr!("Hook '{}' exited with {}", hook.display(), status)
+            );
+        }
+        Err(e) => eprintln!(
+            "{}",
+            tr!("Failed to wait for hook '{}': {}", hook.display(), e)
+        ),
+        _ => {}
+    }
+}
@@ -0,0 +1,81 @@
+//! Clickable links, for terminals that support OSC 8 hyperlinks: attached
+//! URLs and files, and project names when the `project-url` setting says
+//! where they lead, say the project's repository.
+//!
+//! Support is guessed from the environment the usual way, since terminals
+//! can't be asked; the `hyperlinks` setting overrides the guess. Nothing is
+//! ever linked when the output isn't a terminal, so pipes get plain text.
+
+use std::{
+    env,
+    fmt::Display,
+    io::{stdout, IsTerminal},
+    sync::OnceLock,
+};
+
+use crate::{config::load_config, output};
+
+/// Whether the terminal is known to support hyperlinks, from what it
+/// tells about itself.
+fn detected() -> bool {
+    let var = |name: &str| env::var(name).unwrap_or_default();
+    if let Ok(forced) = env::var("FORCE_HYPERLINK") {
+        return forced != "0";
+    }
+    let program = var("TERM_PROGRAM");
+    let term = var("TERM");
+    matches!(
+        program.as_str(),
+        "iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "Hyper" | "rio"
+    ) || ["kitty", "alacritty", "foot", "ghostty", "wezterm"]
+        .iter()
+        .any(|name| term.contains(name))
+        || var("VTE_VERSION").parse::<u32>().is_ok_and(|v| v >= 5000)
+        || !var("WT_SESSION").is_empty()
+        || !var("KONSOLE_VERSION").is_empty()
+        || !var("KITTY_WINDOW_ID").is_empty()
+}
+
+pub fn supported() -> bool {
+    static SUPPORTED: OnceLock<bool> = OnceLock::new();
+    *SUPPORTED.get_or_init(|| {
+        stdout().is_terminal()
+            && !output::is_plain()
+            && load_config()
+                .effective()
+                .hyperlinks
+                .unwrap_or_else(detected)
+    })
+}
+
+/// `text` linking to `url`, when links are supported.
+pub fn link(text: impl Display, url: &str) -> String {
+    if supported() {
+        format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Where an attachment leads: URLs as they are, files as `file://` URLs.
+fn attachment_url(target: &str) -> String {
+    if target.contains("://") || target.starts_with("mailto:") {
+        target.to_string()
+    } else {
+        format!("file://{}", target.replace('%', "%25").replace(' ', "%20"))
+    }
+}
+
+/// `text` linking to the attachment `target`.
+pub fn attachment(text: impl Display, target: &str) -> String {
+    link(text, &attachment_url(target))
+}
+
+/// `text` linking to where the `project-url` setting says project `name`
+/// leads, with `{project}` replaced by the name, if it's set.
+pub fn project(text: impl Display, name: &str) -> String {
+    match load_config().effective_for(Some(name)).project_url {
+        Some(url) if supported() => link(text, &url.replace("{project}", name)),
+        _ => text.to_string(),
+    }
+}
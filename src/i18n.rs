@@ -0,0 +1,183 @@
+//! Translation of user-facing messages, gettext style: messages are written
+//! in English in the code, and looked up in the catalog of the user's
+//! language, falling back to the English text.
+//!
+//! The language is the `language` setting or, without it, the one from the
+//! `LC_ALL`, `LC_MESSAGES` or `LANG` environment variables. Catalogs are
+//! `.po` files: the built-in ones under `locales/`, and any placed in the
+//! `locales/` directory inside the config directory, which take precedence.
+//!
+//! Translations refer to the arguments with `{}` in order, or with `{0}`,
+//! `{1}`... when the wording needs them in a different order.
+
+use std::{collections::HashMap, fmt::Display, fs, path::PathBuf, sync::OnceLock};
+
+use crate::{config::load_config, get_config_dir};
+
+/// Catalogs shipped with the binary, by language tag.
+const BUILT_IN: &[(&str, &str)] = &[("pt-BR", include_str!("../locales/pt-BR.po"))];
+
+/// Translate a message, formatting its `{}` placeholders with the
+/// arguments, as `format!` would.
+macro_rules! tr {
+    ($msgid:literal $(,)?) => {
+        $crate::i18n::translate($msgid, &[])
+    };
+    ($msgid:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::translate($msgid, &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+pub(crate) use tr;
+
+static CATALOG: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+fn get_locales_dir() -> PathBuf {
+    get_config_dir().join("locales")
+}
+
+/// Turn `pt_BR.UTF-8` and the like into a language tag such as `pt-BR`.
+fn normalize(locale: &str) -> Option<String> {
+    let locale = locale.split(['.', '@']).next()?.trim().replace('_', "-");
+    match locale.as_str() {
+        "" | "C" | "POSIX" => None,
+        _ => Some(locale),
+    }
+}
+
+fn detect_language() -> Option<String> {
+    if let Some(language) = load_config().language() {
+        return Some(language);
+    }
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|value| normalize(&value))
+}
+
+/// The catalog for `language`, trying `pt` after `pt-BR`.
+fn load_catalog(language: &str) -> Option<String> {
+    let base = language.split('-').next().unwrap_or(language);
+    [language, base].iter().find_map(|tag| {
+        let user_catalog = get_locales_dir().join(format!("{}.po", tag));
+        fs::read_to_string(user_catalog).ok().or_else(|| {
+            BUILT_IN
+                .iter()
+                .find(|(built_in, _)| built_in.eq_ignore_ascii_case(tag))
+                .map(|(_, content)| content.to_string())
+        })
+    })
+}
+
+/// Languages a catalog is available for, for validating the setting.
+pub fn is_available(language: &str) -> bool {
+    language.eq_ignore_ascii_case("en") || load_catalog(language).is_some()
+}
+
+/// Unquote a `.po` string, handling its escapes.
+fn unquote(text: &str) -> String {
+    let text = text.trim();
+    let text = text
+        .strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .unwrap_or(text);
+    let mut unquoted = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unquoted.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unquoted.push('\n'),
+            Some('t') => unquoted.push('\t'),
+            Some(c) => unquoted.push(c),
+            None => {}
+        }
+    }
+    unquoted
+}
+
+/// Parse the `msgid`/`msgstr` pairs of a `.po` file, skipping untranslated
+/// messages.
+fn parse_po(content: &str) -> HashMap<String, String> {
+    let mut catalog = HashMap::new();
+    let mut msgid: Option<String> = None;
+    let mut msgstr: Option<String> = None;
+
+    let mut flush = |msgid: &mut Option<String>, msgstr: &mut Option<String>| {
+        if let (Some(id), Some(text)) = (msgid.take(), msgstr.take()) {
+            if !id.is_empty() && !text.is_empty() {
+                catalog.insert(id, text);
+            }
+        }
+    };
+
+    for line in content.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix("msgid ") {
+            flush(&mut msgid, &mut msgstr);
+            msgid = Some(unquote(rest));
+        } else if let Some(rest) = line.strip_prefix("msgstr ") {
+            msgstr = Some(unquote(rest));
+        } else if line.starts_with('"') {
+            // A continuation of whichever string came last.
+            match (&mut msgid, &mut msgstr) {
+                (_, Some(text)) => text.push_str(&unquote(line)),
+                (Some(id), None) => id.push_str(&unquote(line)),
+                _ => {}
+            }
+        }
+    }
+    flush(&mut msgid, &mut msgstr);
+
+    catalog
+}
+
+fn catalog() -> &'static HashMap<String, String> {
+    CATALOG.get_or_init(|| {
+        detect_language()
+            .and_then(|language| load_catalog(&language))
+            .map(|content| parse_po(&content))
+            .unwrap_or_default()
+    })
+}
+
+/// Look `msgid` up and fill in its placeholders. Use the `tr!` macro
+/// rather than calling this directly.
+pub fn translate(msgid: &str, args: &[&dyn Display]) -> String {
+    let template = catalog().get(msgid).map_or(msgid, String::as_str);
+
+    let mut translated = String::new();
+    let mut next = 0;
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                translated.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                translated.push('}');
+            }
+            '{' => {
+                let mut index = String::new();
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        break;
+                    }
+                    index.push(c);
+                }
+                let index = index.parse().unwrap_or_else(|_| {
+                    next += 1;
+                    next - 1
+                });
+                if let Some(arg) = args.get(index) {
+                    translated.push_str(&arg.to_string());
+                }
+            }
+            c => translated.push(c),
+        }
+    }
+    translated
+}
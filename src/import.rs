@@ -0,0 +1,606 @@
+//! Bulk import of projects and tasks from other files or tools.
+//!
+//! Tasks from other tools come without IDs of ours, and get new ones in
+//! the project they go to. Those with no project of their own go to one
+//! named after the file imported, or to the inbox from stdin.
+
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use clap::ValueEnum;
+use serde_json::Value;
+use std::{
+    fs,
+    io::{self, Read},
+    path::Path,
+};
+
+use crate::{
+    dates, diff, history::TaskEvent, i18n::tr, inbox::INBOX, load_data, merge, privacy, save_data,
+    tags, Priority, Project, Task,
+};
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ImportFormat {
+    /// A list of projects, in the same shape as the data file.
+    Json,
+    /// Markdown task lists, `- [ ] task` or `- [x] done`, under a heading
+    /// per project.
+    Markdown,
+    /// A Todoist project exported as CSV, its sections becoming groups.
+    Todoist,
+    /// The output of TaskWarrior's `task export`.
+    Taskwarrior,
+}
+
+impl ImportFormat {
+    /// The tool or format's name, for task histories.
+    fn name(self) -> &'static str {
+        match self {
+            ImportFormat::Json => "JSON",
+            ImportFormat::Markdown => "Markdown",
+            ImportFormat::Todoist => "Todoist",
+            ImportFormat::Taskwarrior => "TaskWarrior",
+        }
+    }
+}
+
+/// `project`, added to `projects` if it isn't there yet.
+fn project<'a>(projects: &'a mut Vec<Project>, name: &str) -> &'a mut Project {
+    match projects.iter().position(|p| p.name == name) {
+        Some(index) => &mut projects[index],
+        None => {
+            projects.push(Project {
+                name: name.to_string(),
+                ..Default::default()
+            });
+            projects.last_mut().expect("A project was just added")
+        }
+    }
+}
+
+/// A task new to the tracker, its ID to be given when merged.
+fn imported(description: &str, format: ImportFormat) -> Task {
+    let mut task = Task {
+        description: description.trim().to_string(),
+        ..Default::default()
+    };
+    task.record(
+        TaskEvent::Created,
+        Some(format!("imported from {}", format.name())),
+    );
+    task
+}
+
+fn parse_markdown(content: &str, default_project: &str) -> Vec<Project> {
+    let mut projects = Vec::new();
+    let mut current = default_project.to_string();
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('#') {
+            let heading = line.trim_start_matches('#').trim();
+            if !heading.is_empty() {
+                current = heading.to_string();
+            }
+            continue;
+        }
+        let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) else {
+            continue;
+        };
+        let (completed, description) = match item.get(..4) {
+            Some("[ ] ") => (false, &item[4..]),
+            Some("[x] " | "[X] ") => (true, &item[4..]),
+            _ => continue,
+        };
+        if description.trim().is_empty() {
+            continue;
+        }
+        let mut task = imported(description, ImportFormat::Markdown);
+        task.completed = completed;
+        project(&mut projects, &current).tasks.push(task);
+    }
+    projects
+}
+
+/// The rows of a CSV file, with quoted fields.
+fn csv_rows(content: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+/// Todoist's CSV export: a `TYPE` column telling sections from tasks,
+/// `CONTENT` with `@labels` in it, `PRIORITY` from 1, the most urgent, to
+/// 4, and `DATE` as typed in Todoist.
+fn parse_todoist(content: &str, default_project: &str) -> Result<Vec<Project>, String> {
+    let rows = csv_rows(content.trim_start_matches('\u{feff}'));
+    let Some((header, rows)) = rows.split_first() else {
+        return Ok(Vec::new());
+    };
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("no {} column, is this a Todoist CSV export?", name))
+    };
+    let (kind, text, priority, date) = (
+        column("TYPE")?,
+        column("CONTENT")?,
+        column("PRIORITY")?,
+        column("DATE")?,
+    );
+
+    let mut projects = Vec::new();
+    let mut current = default_project.to_string();
+    for row in rows {
+        let field = |index: usize| row.get(index).map_or("", |f| f.trim());
+        match field(kind) {
+            "section" => current = format!("{}/{}", default_project, field(text)),
+            "task" => {
+                let (labels, words): (Vec<&str>, Vec<&str>) = field(text)
+                    .split_whitespace()
+                    .partition(|word| word.len() > 1 && word.starts_with('@'));
+                let mut task = imported(&words.join(" "), ImportFormat::Todoist);
+                task.tags = labels
+                    .iter()
+                    .map(|label| tags::normalize(&label[1..]))
+                    .collect();
+                task.priority = match field(priority) {
+                    "1" => Some(Priority::High),
+                    "2" => Some(Priority::Medium),
+                    "3" => Some(Priority::Low),
+                    _ => None,
+                };
+                // Recurring dates such as "every monday" are left out.
+                task.due = dates::parse_date(field(date)).ok();
+                project(&mut projects, &current).tasks.push(task);
+            }
+            _ => {}
+        }
+    }
+    Ok(projects)
+}
+
+/// A TaskWarrior date, as in `20250701T093000Z`.
+fn taskwarrior_date(value: &Value) -> Option<DateTime<Local>> {
+    let date = NaiveDateTime::parse_from_str(value.as_str()?, "%Y%m%dT%H%M%SZ").ok()?;
+    Some(DateTime::<Utc>::from_naive_utc_and_offset(date, Utc).with_timezone(&Local))
+}
+
+/// TaskWarrior's `task export`: its dotted projects become groups, and
+/// deleted tasks and recurring templates are left out.
+fn parse_taskwarrior(content: &str, default_project: &str) -> Result<Vec<Project>, String> {
+    let exported: Vec<Value> = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let mut projects = Vec::new();
+    for entry in exported {
+        let status = entry["status"].as_str().unwrap_or("pending");
+        if matches!(status, "deleted" | "recurring") {
+            continue;
+        }
+        let Some(description) = entry["description"].as_str() else {
+            continue;
+        };
+        let mut task = imported(description, ImportFormat::Taskwarrior);
+        if let (Some(entered), Some(created)) =
+            (taskwarrior_date(&entry["entry"]), task.history.last_mut())
+        {
+            created.at = entered;
+        }
+        if status == "completed" {
+            task.completed = true;
+            task.completed_at = taskwarrior_date(&entry["end"]);
+        }
+        task.due = taskwarrior_date(&entry["due"]).map(|due| due.date_naive());
+        task.priority = match entry["priority"].as_str() {
+            Some("H") => Some(Priority::High),
+            Some("M") => Some(Priority::Medium),
+            Some("L") => Some(Priority::Low),
+            _ => None,
+        };
+        task.tags = entry["tags"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(tags::normalize)
+            .collect();
+        let name = entry["project"]
+            .as_str()
+            .map_or(default_project.to_string(), |p| p.replace('.', "/"));
+        project(&mut projects, &name).tasks.push(task);
+    }
+    Ok(projects)
+}
+
+fn parse(
+    content: &str,
+    format: ImportFormat,
+    default_project: &str,
+) -> Result<Vec<Project>, String> {
+    match format {
+        ImportFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+        ImportFormat::Markdown => Ok(parse_markdown(content, default_project)),
+        ImportFormat::Todoist => parse_todoist(content, default_project),
+        ImportFormat::Taskwarrior => parse_taskwarrior(content, default_project),
+    }
+}
+
+/// Merge `incoming` into `data`: unknown projects are added as a whole,
+/// tasks of existing projects are matched by ID and updated if they're a
+/// newer version of the same task, appended if the project doesn't have
+/// them yet. Tasks without an ID, or whose ID is already another task's,
+/// get a new one.
+fn merge(data: &mut Vec<Project>, incoming: Vec<Project>) {
+    for mut project in incoming {
+        let Some(existing) = data.iter_mut().find(|p| p.name == project.name) else {
+            for index in 0..project.tasks.len() {
+                if project.tasks[index].id == 0 {
+                    project.tasks[index].id = project.allocate_id();
+                }
+            }
+            data.push(project);
+            continue;
+        };
+
+        for mut task in project.tasks {
+            let current = existing
+                .tasks
+                .iter_mut()
+                .find(|t| task.id != 0 && t.id == task.id);
+            // Tasks without a history can't be told apart from others.
+            let same_task = |current: &Task, task: &Task| {
+                !current.history.is_empty()
+                    && !task.history.is_empty()
+                    && merge::same_task(current, task)
+            };
+            match current {
+                None if task.id != 0 => existing.tasks.push(task),
+                // An older version, or the same, as when an export is
+                // imported back. Exports mask private tasks, which can't
+                // update anything.
+                Some(current)
+                    if same_task(current, &task)
+                        && (merge::continues(current, &task) || privacy::is_masked(&task)) => {}
+                Some(current) if same_task(current, &task) && merge::continues(&task, current) => {
+                    *current = task;
+                }
+                _ => {
+                    task.id = existing.allocate_id();
+                    existing.tasks.push(task);
+                }
+            }
+        }
+    }
+}
+
+/// Import from `file`, or from stdin when no file is given. With `dry_run`,
+/// only show what would change.
+pub fn import(file: Option<&Path>, format: ImportFormat, dry_run: bool) {
+    let content = match file {
+        Some(file) => fs::read_to_string(file),
+        None => {
+            let mut content = String::new();
+            io::stdin().read_to_string(&mut content).map(|_| content)
+        }
+    };
+    let content = match content {
+        Ok(content) => content,
+        Err(e) => {
+            println!("{}", tr!("Unable to read import data: {}", e));
+            return;
+        }
+    };
+
+    let default_project = file
+        .and_then(Path::file_stem)
+        .map_or(INBOX.to_string(), |stem| {
+            stem.to_string_lossy().into_owned()
+        });
+    let incoming = match parse(&content, format, &default_project) {
+        Ok(incoming) => incoming,
+        Err(e) => {
+            println!("{}", tr!("Unable to parse import data: {}", e));
+            return;
+        }
+    };
+
+    let before = load_data();
+    let mut data = before.clone();
+    merge(&mut data, incoming);
+
+    let changes = diff::diff(&before, &data);
+    diff::print(&changes);
+    if dry_run {
+        println!("{}", tr!("Dry run, nothing was imported."));
+        return;
+    }
+    save_data(&data);
+
+    let (added, _, modified) = diff::count(&changes);
+    println!(
+        "{}",
+        tr!("Imported {} new tasks, updated {}.", added, modified)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone};
+
+    use crate::history::HistoryEntry;
+
+    /// The tasks of each project, as `(description, completed)`.
+    fn listed(projects: &[Project]) -> Vec<(&str, Vec<(&str, bool)>)> {
+        projects
+            .iter()
+            .map(|p| {
+                let tasks = p
+                    .tasks
+                    .iter()
+                    .map(|t| (t.description.as_str(), t.completed))
+                    .collect();
+                (p.name.as_str(), tasks)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn markdown_task_lists_are_read_by_heading() {
+        let projects = parse_markdown(
+            "- [ ] Loose task\n\
+             # Web\n\
+             Some notes, not a task.\n\
+             - [x] Fix the login form\n\
+             \x20 * [ ]  Indented, with spaces \n\
+             - [ ] \n\
+             - plain item\n\
+             ## \n\
+             - [X] Still web\n\
+             ### API\n\
+             - [ ] Rate limits",
+            "notes",
+        );
+        assert_eq!(
+            listed(&projects),
+            [
+                ("notes", vec![("Loose task", false)]),
+                (
+                    "Web",
+                    vec![
+                        ("Fix the login form", true),
+                        ("Indented, with spaces", false),
+                        ("Still web", true),
+                    ]
+                ),
+                ("API", vec![("Rate limits", false)]),
+            ]
+        );
+        let created = &projects[0].tasks[0].history[0];
+        assert_eq!(created.event, TaskEvent::Created);
+        assert_eq!(created.detail.as_deref(), Some("imported from Markdown"));
+    }
+
+    #[test]
+    fn csv_fields_may_be_quoted() {
+        assert_eq!(
+            csv_rows("a,\"b, c\",\"say \"\"hi\"\"\"\r\n\"two\nlines\",,last"),
+            [
+                vec!["a", "b, c", "say \"hi\""],
+                vec!["two\nlines", "", "last"],
+            ]
+        );
+        assert!(csv_rows("").is_empty());
+    }
+
+    #[test]
+    fn todoist_exports_are_read_with_their_sections() {
+        let projects = parse_todoist(
+            "\u{feff}TYPE,CONTENT,DESCRIPTION,PRIORITY,INDENT,DATE\n\
+             task,Buy milk @errands @home,,4,1,2026-07-03\n\
+             section,Backend,,,,\n\
+             task,\"Fix the API, again\",,1,1,every monday\n\
+             note,Not a task,,,,\n\
+             task,Write docs @ here,,3,1,\n",
+            "chores",
+        )
+        .unwrap();
+        assert_eq!(
+            listed(&projects),
+            [
+                ("chores", vec![("Buy milk", false)]),
+                (
+                    "chores/Backend",
+                    vec![("Fix the API, again", false), ("Write docs @ here", false)]
+                ),
+            ]
+        );
+        let milk = &projects[0].tasks[0];
+        assert_eq!(milk.tags, ["errands", "home"]);
+        assert_eq!(milk.priority, None);
+        assert_eq!(milk.due, NaiveDate::from_ymd_opt(2026, 7, 3));
+        let api = &projects[1].tasks[0];
+        assert_eq!(api.priority, Some(Priority::High));
+        assert_eq!(api.due, None);
+        assert_eq!(projects[1].tasks[1].priority, Some(Priority::Low));
+
+        let error = parse_todoist("TYPE,CONTENT,DATE\ntask,x,\n", "chores").unwrap_err();
+        assert!(error.contains("PRIORITY"), "{}", error);
+    }
+
+    #[test]
+    fn taskwarrior_exports_are_read_without_deleted_tasks() {
+        let projects = parse_taskwarrior(
+            r#"[
+                {"description": "Fix the login form", "status": "pending",
+                 "project": "work.web", "priority": "H", "tags": ["bug", "ui"],
+                 "entry": "20260701T093000Z", "due": "20260710T120000Z"},
+                {"description": "Deploy", "status": "completed",
+                 "project": "work.web", "end": "20260702T100000Z"},
+                {"description": "Gone", "status": "deleted"},
+                {"description": "Every week", "status": "recurring"},
+                {"status": "pending"},
+                {"description": "Call the bank", "priority": "L"}
+            ]"#,
+            "inbox",
+        )
+        .unwrap();
+        assert_eq!(
+            listed(&projects),
+            [
+                (
+                    "work/web",
+                    vec![("Fix the login form", false), ("Deploy", true)]
+                ),
+                ("inbox", vec![("Call the bank", false)]),
+            ]
+        );
+        let login = &projects[0].tasks[0];
+        assert_eq!(login.priority, Some(Priority::High));
+        assert_eq!(login.tags, ["bug", "ui"]);
+        assert_eq!(
+            login.history[0].at,
+            Utc.with_ymd_and_hms(2026, 7, 1, 9, 30, 0).unwrap()
+        );
+        assert!(login.due.is_some());
+        let deploy = &projects[0].tasks[1];
+        assert_eq!(
+            deploy.completed_at,
+            Some(Utc.with_ymd_and_hms(2026, 7, 2, 10, 0, 0).unwrap().into())
+        );
+        assert_eq!(projects[1].tasks[0].priority, Some(Priority::Low));
+
+        assert!(parse_taskwarrior("{\"not\": \"a list\"}", "inbox").is_err());
+    }
+
+    /// A task created at `hour` with `edits` more changes, an hour apart.
+    fn task(id: u32, description: &str, hour: u32, edits: u32) -> Task {
+        let history = (0..=edits)
+            .map(|i| HistoryEntry {
+                at: Local.with_ymd_and_hms(2026, 7, 1, hour + i, 0, 0).unwrap(),
+                event: if i == 0 {
+                    TaskEvent::Created
+                } else {
+                    TaskEvent::Edited
+                },
+                detail: None,
+                by: None,
+            })
+            .collect();
+        Task {
+            id,
+            description: description.to_string(),
+            history,
+            ..Default::default()
+        }
+    }
+
+    fn project(name: &str, tasks: Vec<Task>) -> Project {
+        Project {
+            name: name.to_string(),
+            next_id: tasks.iter().map(|t| t.id + 1).max().unwrap_or(1),
+            tasks,
+            ..Default::default()
+        }
+    }
+
+    fn ids(project: &Project) -> Vec<(u32, &str)> {
+        project
+            .tasks
+            .iter()
+            .map(|t| (t.id, t.description.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn new_projects_are_added_with_ids_for_their_tasks() {
+        // New IDs go after the ones the tasks came with.
+        let mut data = vec![project("web", vec![task(1, "Fix", 9, 0)])];
+        merge(
+            &mut data,
+            vec![project(
+                "api",
+                vec![
+                    task(0, "One", 9, 0),
+                    task(5, "Kept", 9, 0),
+                    task(0, "Two", 9, 0),
+                ],
+            )],
+        );
+        assert_eq!(ids(&data[1]), [(6, "One"), (5, "Kept"), (7, "Two")]);
+    }
+
+    #[test]
+    fn imported_tasks_update_existing_ones_only_when_newer() {
+        let mut data = vec![project(
+            "web",
+            vec![
+                task(1, "Current", 9, 1),
+                task(2, "Current", 9, 1),
+                task(3, "Other task", 9, 0),
+            ],
+        )];
+        merge(
+            &mut data,
+            vec![project(
+                "web",
+                vec![
+                    task(1, "Older", 9, 0),
+                    task(2, "Newer", 9, 2),
+                    task(3, "Clashing", 10, 0),
+                    task(0, "No ID", 9, 0),
+                    task(9, "Unknown ID", 9, 0),
+                ],
+            )],
+        );
+        assert_eq!(
+            ids(&data[0]),
+            [
+                (1, "Current"),
+                (2, "Newer"),
+                (3, "Other task"),
+                (4, "Clashing"),
+                (5, "No ID"),
+                (9, "Unknown ID"),
+            ]
+        );
+        assert_eq!(data[0].allocate_id(), 10);
+    }
+
+    #[test]
+    fn masked_private_tasks_update_nothing() {
+        let mut data = vec![project(
+            "web",
+            vec![Task {
+                private: true,
+                ..task(1, "Talk to HR", 9, 0)
+            }],
+        )];
+        let exported = privacy::masked_task(&Task {
+            private: true,
+            ..task(1, "Talk to HR", 9, 1)
+        });
+        merge(&mut data, vec![project("web", vec![exported])]);
+        assert_eq!(ids(&data[0]), [(1, "Talk to HR")]);
+    }
+}
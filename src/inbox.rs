@@ -0,0 +1,172 @@
+//! The inbox: `in "call the bank"` drops a thought into the `inbox`
+//! project without deciding anything about it, and `triage` later goes
+//! through what's there, moving each item to the project it belongs to,
+//! with a priority, or deleting it.
+
+use colored::Colorize;
+use dialoguer::{theme::ColorfulTheme, FuzzySelect, Select};
+
+use crate::{
+    add_tasks, complete_task, delete_task, history::TaskEvent, i18n::tr, load_data, picker,
+    save_data, sync, Priority, Project, Task,
+};
+
+/// Name of the project captured items go to.
+pub const INBOX: &str = "inbox";
+
+/// Add `text` to the inbox, creating it the first time.
+pub fn capture(text: &str) {
+    add_tasks(INBOX, &[text.to_string()], &Task::default(), true);
+}
+
+/// What to do with an inbox item.
+enum Decision {
+    Move(String, Option<Priority>),
+    Complete,
+    Delete,
+    Keep,
+    Stop,
+}
+
+fn ask(task: &Task, projects: &[String]) -> Decision {
+    let theme = ColorfulTheme::default();
+    let choices = [
+        tr!("Move to a project"),
+        tr!("Complete, it's done already"),
+        tr!("Delete"),
+        tr!("Keep it in the inbox"),
+        tr!("Stop triaging"),
+    ];
+    let choice = Select::with_theme(&theme)
+        .with_prompt(&task.description)
+        .items(&choices)
+        .default(0)
+        .interact_opt()
+        .ok()
+        .flatten();
+
+    match choice {
+        Some(0) if projects.is_empty() => {
+            println!("{}", tr!("There are no projects to move it to yet."));
+            Decision::Keep
+        }
+        Some(0) => {
+            let Some(project) = FuzzySelect::with_theme(&theme)
+                .with_prompt(tr!("Project"))
+                .items(projects)
+                .default(0)
+                .interact_opt()
+                .ok()
+                .flatten()
+            else {
+                return Decision::Keep;
+            };
+            let priorities = [tr!("none"), tr!("low"), tr!("medium"), tr!("high")];
+            let priority = Select::with_theme(&theme)
+                .with_prompt(tr!("Priority"))
+                .items(&priorities)
+                .default(0)
+                .interact_opt()
+                .ok()
+                .flatten();
+            let priority = match priority {
+                Some(1) => Some(Priority::Low),
+                Some(2) => Some(Priority::Medium),
+                Some(3) => Some(Priority::High),
+                _ => None,
+            };
+            Decision::Move(projects[project].clone(), priority)
+        }
+        Some(1) => Decision::Complete,
+        Some(2) => Decision::Delete,
+        Some(3) => Decision::Keep,
+        _ => Decision::Stop,
+    }
+}
+
+/// Move the inbox item `task_id` to `project_name`, under a new ID there.
+fn move_to(data: &mut [Project], task_id: u32, project_name: &str, priority: Option<Priority>) {
+    let inbox = data
+        .iter_mut()
+        .find(|p| p.name == INBOX)
+        .expect("The inbox exists");
+    let Some(mut task) = inbox.remove_task(task_id) else {
+        return;
+    };
+    let project = data
+        .iter_mut()
+        .find(|p| p.name == project_name)
+        .expect("Projects are picked among existing ones");
+    task.id = project.allocate_id();
+    task.priority = priority.or(task.priority);
+    task.record(TaskEvent::Edited, Some(format!("moved from the {}", INBOX)));
+    println!(
+        "{}",
+        tr!(
+            "Moved '{}' to {}.",
+            task.description,
+            project.task_ref(task.id)
+        )
+    );
+    project.tasks.push(task);
+}
+
+/// Go through the pending inbox items, asking what to do with each.
+pub fn triage() {
+    if !picker::is_interactive() {
+        println!("{}", tr!("Triaging needs an interactive terminal."));
+        return;
+    }
+
+    let data = load_data();
+    let Some(inbox) = data.iter().find(|p| p.name == INBOX) else {
+        println!("{}", tr!("The inbox is empty."));
+        return;
+    };
+    let items: Vec<u32> = inbox
+        .tasks
+        .iter()
+        .filter(|t| !t.completed)
+        .map(|t| t.id)
+        .collect();
+    if items.is_empty() {
+        println!("{}", tr!("The inbox is empty."));
+        return;
+    }
+    let projects: Vec<String> = data
+        .iter()
+        .map(|p| p.name.clone())
+        .filter(|name| name != INBOX)
+        .collect();
+
+    let total = items.len();
+    let mut triaged = 0;
+    for (index, task_id) in items.into_iter().enumerate() {
+        // Each item is saved once decided, so stopping halfway loses
+        // nothing.
+        let mut data = load_data();
+        let Some(task) = data
+            .iter()
+            .find(|p| p.name == INBOX)
+            .and_then(|p| p.tasks.iter().find(|t| t.id == task_id))
+        else {
+            continue;
+        };
+        println!("{}", tr!("Item {} of {}", index + 1, total).dimmed());
+
+        match ask(task, &projects) {
+            Decision::Move(project, priority) => {
+                move_to(&mut data, task_id, &project, priority);
+                save_data(&data);
+                sync::record_deletion(INBOX, Some(task_id));
+            }
+            Decision::Complete => complete_task(INBOX, task_id),
+            Decision::Delete => delete_task(INBOX, task_id),
+            Decision::Keep => continue,
+            Decision::Stop => break,
+        }
+        triaged += 1;
+    }
+
+    println!("{}", tr!("Triaged {} of {} items.", triaged, total));
+}
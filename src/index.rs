@@ -0,0 +1,186 @@
+//! A small inverted index over task descriptions, kept next to the data
+//! file so `search` doesn't have to scan every task of large trackers.
+//!
+//! It's updated incrementally whenever the data is saved: only tasks whose
+//! description changed are split into terms again. Searching brings it up
+//! to date first too, in case the data file was edited by hand.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, HashMap, HashSet},
+    fs,
+    hash::{Hash, Hasher},
+    io::ErrorKind,
+    path::PathBuf,
+};
+
+use crate::{storage, storage::DataFormat, workspace, Project};
+
+#[derive(Serialize, Deserialize, Default)]
+struct Doc {
+    /// Hash of the description the terms were taken from.
+    hash: u64,
+    terms: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Index {
+    /// Indexed tasks, by `project#id`.
+    docs: BTreeMap<String, Doc>,
+    /// Tasks containing each term, by `project#id`.
+    terms: BTreeMap<String, BTreeSet<String>>,
+}
+
+fn get_index_path() -> PathBuf {
+    workspace::data_file_path(workspace::active(), DataFormat::Json).with_extension("index.json")
+}
+
+fn key(project: &str, task_id: u32) -> String {
+    format!("{}#{}", project, task_id)
+}
+
+fn hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The distinct lowercase words of `text`.
+fn tokenize(text: &str) -> Vec<String> {
+    let terms: BTreeSet<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    terms.into_iter().collect()
+}
+
+impl Index {
+    fn remove(&mut self, key: &str) {
+        let Some(doc) = self.docs.remove(key) else {
+            return;
+        };
+        for term in doc.terms {
+            if let Some(keys) = self.terms.get_mut(&term) {
+                keys.remove(key);
+                if keys.is_empty() {
+                    self.terms.remove(&term);
+                }
+            }
+        }
+    }
+
+    /// Bring the index in line with `data`. Returns whether anything changed.
+    fn update(&mut self, data: &[Project]) -> bool {
+        let mut changed = false;
+        let mut seen = HashSet::new();
+
+        for project in data {
+            for task in &project.tasks {
+                let key = key(&project.name, task.id);
+                let hash = hash(&task.description);
+                if self.docs.get(&key).is_none_or(|doc| doc.hash != hash) {
+                    self.remove(&key);
+                    let terms = tokenize(&task.description);
+                    for term in &terms {
+                        self.terms
+                            .entry(term.clone())
+                            .or_default()
+                            .insert(key.clone());
+                    }
+                    self.docs.insert(key.clone(), Doc { hash, terms });
+                    changed = true;
+                }
+                seen.insert(key);
+            }
+        }
+
+        let removed: Vec<String> = self
+            .docs
+            .keys()
+            .filter(|key| !seen.contains(*key))
+            .cloned()
+            .collect();
+        changed |= !removed.is_empty();
+        for key in removed {
+            self.remove(&key);
+        }
+
+        changed
+    }
+}
+
+fn load() -> Index {
+    match fs::read(get_index_path()) {
+        // A broken index is simply rebuilt.
+        Ok(content) => serde_json::from_slice(&content).unwrap_or_default(),
+        Err(e) if e.kind() == ErrorKind::NotFound => Index::default(),
+        Err(e) => panic!("Unable to read search index: {}", e),
+    }
+}
+
+fn save(index: &Index) {
+    let content = serde_json::to_vec(index).expect("Unable to serialize search index.");
+    fs::write(get_index_path(), content).expect("Unable to write search index.");
+}
+
+/// Update the index after `data` has been saved.
+pub fn update(data: &[Project]) {
+    let mut index = load();
+    if index.update(data) {
+        save(&index);
+    }
+}
+
+/// Remove the index of a workspace, along with its data.
+pub fn remove(workspace_name: &str) {
+    let path =
+        workspace::data_file_path(workspace_name, DataFormat::Json).with_extension("index.json");
+    if let Err(e) = fs::remove_file(path) {
+        if e.kind() != ErrorKind::NotFound {
+            panic!("Unable to remove search index: {}", e);
+        }
+    }
+}
+
+/// IDs of the tasks, by project, whose description may contain `text`:
+/// every word of `text` is part of one of their terms. `None` when `text`
+/// has no words to look up, so every task may match.
+pub fn candidates(data: &[Project], text: &str) -> Option<HashMap<String, HashSet<u32>>> {
+    let mut index = load();
+    if index.update(data) && !storage::is_read_only() {
+        save(&index);
+    }
+
+    let words = tokenize(text);
+    if words.is_empty() {
+        return None;
+    }
+
+    let mut matching: Option<BTreeSet<&String>> = None;
+    for word in &words {
+        let keys: BTreeSet<&String> = index
+            .terms
+            .iter()
+            .filter(|(term, _)| term.contains(word.as_str()))
+            .flat_map(|(_, keys)| keys)
+            .collect();
+        matching = Some(match matching {
+            Some(matching) => matching.intersection(&keys).copied().collect(),
+            None => keys,
+        });
+    }
+
+    let mut candidates: HashMap<String, HashSet<u32>> = HashMap::new();
+    for key in matching.unwrap_or_default() {
+        if let Some((project, id)) = key.rsplit_once('#') {
+            if let Ok(id) = id.parse() {
+                candidates
+                    .entry(project.to_string())
+                    .or_default()
+                    .insert(id);
+            }
+        }
+    }
+    Some(candidates)
+}
@@ -0,0 +1,214 @@
+//! Links between related tasks, usually of different projects, like an
+//! infrastructure task and the app task waiting on it: `link-task web:3
+//! infra:7` links both ways, listings show the tasks a task is linked to
+//! after a `↔`, and `links web 3` follows the links from a task.
+
+use colored::{ColoredString, Colorize};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    bars, history::TaskEvent, i18n::tr, load_data, prefixes, privacy, save_data, theme, Project,
+    Task,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TaskLink {
+    pub project: String,
+    pub id: u32,
+}
+
+impl std::fmt::Display for TaskLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}#{}", self.project, self.id)
+    }
+}
+
+/// The task `word` refers to: `project:3`, `project#3` or `WEB-3`.
+fn parse_ref(data: &[Project], word: &str) -> Result<TaskLink, String> {
+    let split = word.rsplit_once(':').or_else(|| word.rsplit_once('#'));
+    let (project, id) = match split {
+        Some((project, id)) => match id.parse() {
+            Ok(id) => (project, id),
+            Err(_) => return Err(tr!("Invalid task ID '{}'.", id)),
+        },
+        None => match prefixes::parse_ref(word) {
+            Some((prefix, id)) => match prefixes::find_project(data, prefix) {
+                Some(project) => (project.name.as_str(), id),
+                None => return Err(tr!("No project has the prefix '{}'.", prefix)),
+            },
+            None => {
+                return Err(tr!(
+                    "Expected a task as project:id or PREFIX-ID, got '{}'.",
+                    word
+                ))
+            }
+        },
+    };
+
+    let Some(found) = data.iter().find(|p| p.name == project) else {
+        return Err(tr!("Project '{}' not found.", project));
+    };
+    if !found.tasks.iter().any(|t| t.id == id) {
+        return Err(tr!("Task {} not found in project '{}'.", id, project));
+    }
+    Ok(TaskLink {
+        project: project.to_string(),
+        id,
+    })
+}
+
+/// Link the tasks `first` and `second` refer to, or with `remove` unlink
+/// them.
+pub fn link_tasks(first: &str, second: &str, remove: bool) {
+    let mut data = load_data();
+    let (first, second) = match (parse_ref(&data, first), parse_ref(&data, second)) {
+        (Ok(first), Ok(second)) => (first, second),
+        (Err(e), _) | (_, Err(e)) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    if first == second {
+        println!("{}", tr!("A task can't be linked to itself."));
+        return;
+    }
+
+    let mut changed = false;
+    for (from, to) in [(&first, &second), (&second, &first)] {
+        let task = data
+            .iter_mut()
+            .find(|p| p.name == from.project)
+            .and_then(|p| p.tasks.iter_mut().find(|t| t.id == from.id))
+            .expect("Both tasks were found");
+        let linked = task.links.contains(to);
+        if remove && linked {
+            task.links.retain(|link| link != to);
+            task.record(TaskEvent::Edited, Some(format!("unlinked from {}", to)));
+            changed = true;
+        } else if !remove && !linked {
+            task.links.push(to.clone());
+            task.links.sort();
+            task.record(TaskEvent::Edited, Some(format!("linked to {}", to)));
+            changed = true;
+        }
+    }
+
+    match (changed, remove) {
+        (false, false) => println!("{}", tr!("{} and {} are already linked.", first, second)),
+        (false, true) => println!("{}", tr!("{} and {} aren't linked.", first, second)),
+        (true, false) => {
+            save_data(&data);
+            println!("{}", tr!("Linked {} and {}.", first, second));
+        }
+        (true, true) => {
+            save_data(&data);
+            println!("{}", tr!("Unlinked {} and {}.", first, second));
+        }
+    }
+}
+
+/// Drop the links to `project`'s task `task_id`, or to any of its tasks,
+/// from the other tasks, once deleted.
+pub fn unlink_deleted(data: &mut [Project], project: &str, task_id: Option<u32>) {
+    for task in data.iter_mut().flat_map(|p| p.tasks.iter_mut()) {
+        task.links
+            .retain(|link| link.project != project || task_id.is_some_and(|id| id != link.id));
+    }
+}
+
+/// Point the links to `project`'s tasks at where they went, `ids` mapping
+/// their old IDs to their new ones in `new_project`.
+pub fn relink(data: &mut [Project], project: &str, new_project: &str, ids: &BTreeMap<u32, u32>) {
+    for task in data.iter_mut().flat_map(|p| p.tasks.iter_mut()) {
+        for link in task.links.iter_mut().filter(|link| link.project == project) {
+            if let Some(id) = ids.get(&link.id) {
+                link.project = new_project.to_string();
+                link.id = *id;
+            }
+        }
+    }
+}
+
+/// `↔ infra#7, api#2` for a task with links, for listings.
+pub fn marker(task: &Task) -> Option<ColoredString> {
+    if task.links.is_empty() {
+        return None;
+    }
+    let arrow = if bars::is_ascii() { "<->" } else { "↔" };
+    let links: Vec<String> = task.links.iter().map(TaskLink::to_string).collect();
+    Some(format!("{} {}", arrow, links.join(", ")).cyan())
+}
+
+/// Print the task `task_id` of `project_name` and the tasks it's linked to,
+/// then the ones those are linked to and so on, each under the task it was
+/// reached from.
+pub fn show_links(project_name: &str, task_id: u32) {
+    let data = load_data();
+    let Some(project) = data.iter().find(|p| p.name == project_name) else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+        return;
+    };
+    let Some(task) = project.tasks.iter().find(|t| t.id == task_id) else {
+        println!(
+            "{}",
+            tr!("Task {} not found in project '{}'.", task_id, project_name)
+        );
+        return;
+    };
+    if task.links.is_empty() {
+        println!(
+            "{}",
+            tr!(
+                "Task {} of project '{}' has no links.",
+                task_id,
+                project_name
+            )
+        );
+        return;
+    }
+
+    let start = TaskLink {
+        project: project_name.to_string(),
+        id: task_id,
+    };
+    let mut seen = BTreeSet::from([start.clone()]);
+    let mut stack = vec![(start, 0)];
+    while let Some((link, depth)) = stack.pop() {
+        let found = data
+            .iter()
+            .find(|p| p.name == link.project)
+            .and_then(|p| Some((p, p.tasks.iter().find(|t| t.id == link.id)?)));
+        let indent = "    ".repeat(depth);
+        let arrow = match depth {
+            0 => "",
+            _ if bars::is_ascii() => "<-> ",
+            _ => "↔ ",
+        };
+        let Some((project, task)) = found else {
+            println!(
+                "{}{}{} {}",
+                indent,
+                arrow,
+                link,
+                tr!("(deleted or archived)").dimmed()
+            );
+            continue;
+        };
+        println!(
+            "{}{}{} {}: {}",
+            indent,
+            arrow,
+            theme::checkbox(task.completed),
+            project.task_ref(task.id).bold(),
+            privacy::description(task)
+        );
+
+        // Pushed in reverse, to be shown in order.
+        for next in task.links.iter().rev() {
+            if seen.insert(next.clone()) {
+                stack.push((next.clone(), depth + 1));
+            }
+        }
+    }
+}
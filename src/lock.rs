@@ -0,0 +1,42 @@
+//! Lock files, keeping two processes from doing the same work at once.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::ErrorKind,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+/// A lock file, held until dropped.
+pub struct Lock(PathBuf);
+
+impl Lock {
+    /// Take the lock at `path`, unless another process holds it. A lock
+    /// file older than `stale_after` is taken to be left over from a crash.
+    pub fn acquire(path: PathBuf, stale_after: Duration) -> Option<Lock> {
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Some(Lock(path)),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    let stale = fs::metadata(&path)
+                        .and_then(|m| m.modified())
+                        .ok()
+                        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                        .is_some_and(|age| age > stale_after);
+                    if !stale {
+                        return None;
+                    }
+                    let _ = fs::remove_file(&path);
+                }
+                Err(e) => panic!("Unable to create lock file: {}", e),
+            }
+        }
+        None
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
@@ -0,0 +1,81 @@
+//! Diagnostics through `tracing`: which files were read and written, how
+//! long that took and what was done to the data, for finding out where a
+//! task went.
+//!
+//! `-v` prints what's done on stderr, `-vv` also the files used and the
+//! timings. With the `log-file` setting on, all of it is also appended to
+//! `project-tracker.log` in the config directory, whatever the verbosity.
+
+use std::{
+    fs::OpenOptions,
+    io::{self, IsTerminal},
+    path::PathBuf,
+    sync::{Mutex, OnceLock},
+};
+use tracing_subscriber::{
+    filter::LevelFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt, Layer,
+};
+
+use crate::{config::load_config, get_config_dir, i18n::tr};
+
+fn get_log_path() -> PathBuf {
+    get_config_dir().join("project-tracker.log")
+}
+
+static VERBOSITY: OnceLock<u8> = OnceLock::new();
+
+/// How many times `-v` was given.
+pub fn verbosity() -> u8 {
+    VERBOSITY.get().copied().unwrap_or(0)
+}
+
+/// Start recording, at the level `-v` was repeated for.
+pub fn init(verbosity: u8) {
+    let _ = VERBOSITY.set(verbosity);
+    let level = match verbosity {
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::INFO,
+        2 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    };
+    let stderr = fmt::layer()
+        .with_writer(io::stderr)
+        .with_ansi(io::stderr().is_terminal())
+        .with_target(false)
+        .without_time()
+        .with_filter(level);
+
+    let file = if load_config().effective().log_file == Some(true) {
+        match OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(get_log_path())
+        {
+            Ok(file) => Some(file),
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        "Unable to open log file '{}': {}",
+                        get_log_path().display(),
+                        e
+                    )
+                );
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let file = file.map(|file| {
+        fmt::layer()
+            .with_writer(Mutex::new(file))
+            .with_ansi(false)
+            .with_filter(LevelFilter::DEBUG)
+    });
+
+    tracing_subscriber::registry()
+        .with(stderr)
+        .with(file)
+        .init();
+}
@@ -1,307 +1,2588 @@
-use clap::{Parser, Subcommand};
+use chrono::{DateTime, Local, NaiveDate};
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
-use std::{
-    env,
-    fs::{self, OpenOptions},
-    io::{Read, Write},
-    path::PathBuf,
-};
+use std::{collections::BTreeMap, fs, io, path::PathBuf, sync::OnceLock};
+use tracing::{debug, info};
 
-fn get_data_file_path() -> PathBuf {
-    let home_dir = env::var("HOME").expect("Could not find $HOME environment variable");
+mod achievements;
+mod agenda;
+mod aging;
+mod aliases;
+mod archive;
+mod args;
+mod attachments;
+mod backup;
+mod bars;
+mod bundle;
+mod calendar;
+mod changelog;
+mod checklist;
+mod config;
+mod contexts;
+mod dates;
+mod diff;
+mod digest;
+mod display;
+mod doctor;
+mod duplicates;
+mod durations;
+mod edit;
+mod estimates;
+mod export;
+mod fields;
+mod filter;
+mod focus;
+mod forecast;
+mod generate;
+mod goals;
+mod graph;
+mod groups;
+mod history;
+mod hooks;
+mod hyperlinks;
+mod i18n;
+mod import;
+mod inbox;
+mod index;
+mod links;
+mod lock;
+mod logging;
+mod merge;
+mod mirror;
+mod notify;
+mod output;
+mod pdf;
+mod pick;
+mod picker;
+mod pipe;
+mod prefixes;
+mod privacy;
+mod progress;
+#[cfg(test)]
+mod proptests;
+mod protect;
+mod quickadd;
+mod reminders;
+mod remote;
+mod reorder;
+mod report;
+mod review;
+mod roadmap;
+mod scan;
+mod search;
+mod serve;
+mod setup;
+mod shell;
+mod slack;
+mod snapshot;
+mod snooze;
+mod split;
+mod stale;
+mod storage;
+mod suggest;
+mod sync;
+mod table;
+mod tags;
+mod template;
+mod templates;
+mod theme;
+mod timelog;
+mod today;
+mod views;
+mod waiting;
+mod webhooks;
+mod webui;
+mod wip;
+mod workspace;
 
-    let config_dir = PathBuf::from(home_dir).join(".config/project-tracker");
+use aliases::AliasCommand;
+use backup::BackupCommand;
+use bundle::BundleCommand;
+use checklist::{ChecklistCommand, ChecklistItem};
+use config::{load_config, ConfigCommand};
+use contexts::ContextCommand;
+use export::ExportFormat;
+use filter::Filter;
+use history::{HistoryEntry, TaskEvent};
+use hooks::Event;
+use i18n::tr;
+use import::ImportFormat;
+use links::TaskLink;
+use reminders::Reminder;
+use report::ReportCommand;
+use snapshot::SnapshotCommand;
+use storage::{load_data, save_data};
+use table::Column;
+use template::TaskTemplate;
+use templates::TemplateCommand;
+use theme::Themed;
+use timelog::{GroupBy, ReportFormat, TimeEntry};
+use views::ViewCommand;
+use waiting::{Waiting, WaitingCommand};
+use webhooks::WebhookCommand;
+use workspace::WorkspaceCommand;
 
-    fs::create_dir_all(&config_dir).expect("Failed to create config directory");
+/// Where everything used to live before per-platform directories were used.
+fn get_legacy_config_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".config/project-tracker"))
+}
+
+/// Move the legacy config directory to `config_dir`, if there's one to move.
+/// Returns the directory to use, which stays the legacy one if moving fails.
+fn migrate_legacy_config_dir(config_dir: PathBuf) -> PathBuf {
+    let Some(legacy_dir) = get_legacy_config_dir() else {
+        return config_dir;
+    };
+    if legacy_dir == config_dir || !legacy_dir.is_dir() || config_dir.exists() {
+        return config_dir;
+    }
+
+    if let Some(parent) = config_dir.parent() {
+        fs::create_dir_all(parent).expect("Failed to create config directory");
+    }
+    match fs::rename(&legacy_dir, &config_dir) {
+        Ok(()) => {
+            eprintln!(
+                "{}",
+                tr!(
+                    "Moved '{}' to '{}'.",
+                    legacy_dir.display(),
+                    config_dir.display()
+                )
+            );
+            config_dir
+        }
+        Err(e) => {
+            eprintln!(
+                "{}",
+                tr!(
+                    "Unable to move '{}' to '{}' ({}), still using the old location.",
+                    legacy_dir.display(),
+                    config_dir.display(),
+                    e
+                )
+            );
+            legacy_dir
+        }
+    }
+}
+
+/// The platform's config directory for the tracker: `~/.config` on Linux,
+/// `~/Library/Application Support` on macOS and `%APPDATA%` on Windows.
+/// Tests get an empty one of their own instead, away from the user's
+/// settings and data.
+fn get_config_dir() -> PathBuf {
+    static CONFIG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+    CONFIG_DIR
+        .get_or_init(|| {
+            let config_dir = if cfg!(test) {
+                let config_dir = std::env::temp_dir()
+                    .join(format!("project-tracker-tests-{}", std::process::id()));
+                let _ = fs::remove_dir_all(&config_dir);
+                config_dir
+            } else {
+                migrate_legacy_config_dir(
+                    dirs::config_dir()
+                        .expect("Could not find the configuration directory")
+                        .join("project-tracker"),
+                )
+            };
+
+            fs::create_dir_all(&config_dir).expect("Failed to create config directory");
 
-    config_dir.join("data.json")
+            config_dir
+        })
+        .clone()
 }
 
 #[derive(Parser)]
 #[command(name = "Project Tracker")]
 #[command(about = "A simple CLI tool to keep track of your projects")]
-struct CLI {
+#[command(
+    after_help = "Commands taking a project and a task ID also accept PREFIX-ID (e.g. WEB-12) \
+                  for projects with a prefix, or --match <text> instead of the task ID."
+)]
+struct Cli {
+    #[command(flatten)]
+    globals: GlobalArgs,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
+/// Options taken by every command, before or after it.
+#[derive(Args, Clone, Default, PartialEq)]
+struct GlobalArgs {
+    /// Workspace to operate on.
+    #[arg(long, global = true, env = "PT_WORKSPACE")]
+    workspace: Option<String>,
+
+    /// Work on the data of a tracker running `serve` at this URL instead of
+    /// the local data.
+    #[arg(long, global = true, env = "PT_REMOTE")]
+    remote: Option<String>,
+
+    /// Token for the `--remote` tracker, as set there in PT_API_TOKEN.
+    // Not `requires = "remote"`: a global `--remote` given before the
+    // command isn't seen by it when checking, and the token is only used
+    // with it anyway.
+    #[arg(long, global = true, env = "PT_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+
+    /// Refuse any command that would change the data.
+    #[arg(long, global = true)]
+    read_only: bool,
+
+    /// Only show what the command would change, without writing anything.
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// List snoozed tasks too.
+    #[arg(long, global = true)]
+    include_snoozed: bool,
+
+    /// Draw progress bars and tables with ASCII characters only.
+    #[arg(long, global = true)]
+    ascii: bool,
+
+    /// Plain output, without colors, progress bars or anything but ASCII;
+    /// the default when the output isn't a terminal.
+    #[arg(long, global = true)]
+    plain: bool,
+
+    /// Mask the descriptions of private tasks, e.g. when screen-sharing.
+    #[arg(long, global = true)]
+    redacted: bool,
+
+    /// Save even over a data file that couldn't be read entirely, keeping
+    /// only the projects recovered from it, and add tasks that look like
+    /// ones already there.
+    #[arg(long, global = true)]
+    force: bool,
+
+    /// Print what's done on stderr, and who created and completed tasks in
+    /// listings; -vv also the files used and timings.
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Add a new project.
     AddProject {
-        /// Name of the project.
+        /// Name of the project, use slashes to put it in a group (e.g. clients/acme/website).
         name: String,
     },
+    /// Add a new project with a copy of another project's tasks.
+    CloneProject {
+        source: String,
+        destination: String,
+        /// Start the copied tasks over: not completed, no time logged and
+        /// checklists unticked.
+        #[arg(long)]
+        reset: bool,
+    },
     /// List all projects.
-    ListProjects,
+    ListProjects {
+        /// Show projects as a tree of their groups.
+        #[arg(long)]
+        tree: bool,
+        /// Only show projects in this group.
+        #[arg(long)]
+        prefix: Option<String>,
+    },
+    /// Show every project with its progress and tasks, as when run
+    /// without a command.
+    Overview {
+        /// Show the projects of these workspaces together instead, e.g.
+        /// `work,personal`, labeled with the workspace each is from.
+        #[arg(long, value_delimiter = ',')]
+        workspaces: Vec<String>,
+    },
+    /// Print a project's progress on a single line, for shell prompts and
+    /// status bars.
+    Status {
+        /// Only count this project's tasks.
+        #[arg(long)]
+        project: Option<String>,
+        /// Placeholders: {project}, {completed}, {total}, {remaining},
+        /// {in_progress}, {overdue} and {percent}.
+        #[arg(long, value_parser = progress::parse_template, default_value = progress::DEFAULT_FORMAT)]
+        format: progress::Template,
+    },
+    /// Print the number of overdue and pending tasks, as in `3!/12`, for
+    /// shell prompts; cached so the prompt stays fast.
+    PromptSegment {
+        /// Only count this project's tasks.
+        #[arg(long)]
+        project: Option<String>,
+        /// Seconds the cached segment is reused for, unless the data changes.
+        #[arg(long, default_value_t = 60)]
+        max_age: u64,
+    },
     /// Add a task to a project.
     AddTask {
-        project: String,
-        description: String,
+        /// Project to add the task to, the `default-project` if only the
+        /// description is given.
+        project: Option<String>,
+        description: Option<String>,
+        /// Read task descriptions from stdin, one per line.
+        #[arg(long, conflicts_with = "description")]
+        stdin: bool,
+        /// Due date, e.g. 2025-07-01, tomorrow or "next friday".
+        #[arg(long, value_parser = dates::parse_date)]
+        due: Option<NaiveDate>,
+        #[arg(short, long, value_enum)]
+        priority: Option<Priority>,
+        /// Tag the task, can be repeated.
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+        /// Context the task can be done in, e.g. @home; can be repeated.
+        #[arg(short, long = "context")]
+        contexts: Vec<String>,
+        /// Mask the task's description in exports and `--redacted` listings.
+        #[arg(long)]
+        private: bool,
+        /// Create the project if it doesn't exist, as the `create-projects`
+        /// setting does.
+        #[arg(long)]
+        create: bool,
+        /// Fill in a task template saved with `template save`, instead of
+        /// giving a description.
+        #[arg(long, conflicts_with_all = ["description", "stdin"])]
+        template: Option<String>,
+        /// Value of a template variable, e.g. ticket=ABC-123; can be repeated.
+        #[arg(long = "var", value_parser = fields::parse_filter, requires = "template")]
+        vars: Vec<(String, String)>,
     },
-    /// List all tasks in a project.
+    /// Add a task and its details written as one line, as in
+    /// "work: fix the login bug !high due:friday #auth".
+    Do {
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        text: Vec<String>,
+    },
+    /// Capture a task in the inbox, to triage later.
+    In {
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        text: Vec<String>,
+    },
+    /// Go through the inbox, moving each item to a project, completing or
+    /// deleting it.
+    Triage,
+    /// List all tasks in a project (pick one interactively if omitted).
     ListTasks {
-        project: String,
+        project: Option<String>,
+        /// Only show tasks whose custom field matches, e.g. client=acme.
+        #[arg(long = "where", value_parser = fields::parse_filter)]
+        filters: Vec<(String, String)>,
+        /// Only show tasks matching a filter expression, e.g. "status:todo AND tag:infra".
+        #[arg(long, value_parser = filter::parse)]
+        filter: Option<Filter>,
+        /// Only show tasks in this context, instead of the one set with
+        /// `context set`.
+        #[arg(long)]
+        context: Option<String>,
+        /// Show the tasks as an aligned table.
+        #[arg(long)]
+        table: bool,
+        /// Print each task through a template instead, e.g. "{id}\t{status}\t{desc}".
+        /// Placeholders: {project}, {ref}, {id}, {desc}, {status}, {priority},
+        /// {due}, {scheduled}, {estimate}, {spent}, {tags}, {contexts} and
+        /// {completed}.
+        #[arg(long, value_parser = template::parse_task_template, conflicts_with = "table")]
+        format: Option<TaskTemplate>,
+        /// Print only `project:id` for each task, for commands reading
+        /// tasks with `--stdin`.
+        #[arg(long, conflicts_with_all = ["table", "format"])]
+        ids: bool,
+        /// Columns of the table, e.g. id,desc,priority,due,status.
+        #[arg(long, value_delimiter = ',', requires = "table")]
+        columns: Vec<Column>,
+        /// Sort the tasks on a column: highest priority and earliest dates
+        /// come first.
+        #[arg(long)]
+        sort: Option<Column>,
+        /// Reverse the order of the tasks.
+        #[arg(long)]
+        reverse: bool,
+    },
+    /// Search tasks across all projects.
+    Search {
+        /// Text to look for in task descriptions.
+        text: String,
+        /// Only show tasks matching a filter expression, e.g. "status:todo AND tag:infra".
+        #[arg(long, value_parser = filter::parse)]
+        filter: Option<Filter>,
+        /// Print each task through a template, as for `list-tasks`.
+        #[arg(long, value_parser = template::parse_task_template)]
+        format: Option<TaskTemplate>,
+        /// Print only `project:id` for each task, as for `list-tasks`.
+        #[arg(long, conflicts_with = "format")]
+        ids: bool,
     },
-    /// Mark a task as complete
+    /// Mark a task as complete (pick it interactively if omitted)
     CompleteTask {
+        project: Option<String>,
+        task_id: Option<u32>,
+        /// Tick any number of the project's pending tasks to complete.
+        #[arg(short, long, conflicts_with = "task_id")]
+        interactive: bool,
+        /// Read the tasks to complete from stdin, as `project:id` lines.
+        #[arg(long, conflicts_with_all = ["project", "task_id", "interactive"])]
+        stdin: bool,
+        /// Tick what's left of the tasks' checklists too, without which
+        /// tasks with unticked items aren't completed unless `--force`d.
+        #[arg(long)]
+        cascade: bool,
+    },
+    /// Move a task to another status
+    MoveTask {
         project: String,
         task_id: u32,
+        #[arg(value_enum)]
+        status: Status,
     },
-    /// Delete a project
-    DeleteProject {
+    /// Mark a completed task as pending again
+    ReopenTask {
+        #[arg(required_unless_present = "stdin")]
+        project: Option<String>,
+        #[arg(required_unless_present = "stdin")]
+        task_id: Option<u32>,
+        /// Read the tasks to reopen from stdin, as `project:id` lines.
+        #[arg(long, conflicts_with_all = ["project", "task_id"])]
+        stdin: bool,
+    },
+    /// Edit all tasks of a project at once in $EDITOR.
+    Edit { project: Option<String> },
+    /// Move a task one place up in its project's order.
+    MoveUp { project: String, task_id: u32 },
+    /// Move a task one place down in its project's order.
+    MoveDown { project: String, task_id: u32 },
+    /// Move a task right before, or after, another one of the project.
+    #[command(group(ArgGroup::new("place").required(true)))]
+    Reorder {
         project: String,
+        task_id: u32,
+        /// The task to move it before.
+        #[arg(long, group = "place")]
+        before: Option<u32>,
+        /// The task to move it after.
+        #[arg(long, group = "place")]
+        after: Option<u32>,
     },
+    /// Delete a project
+    DeleteProject { project: String },
     /// Delete a task from a projectg
     DeleteTask {
+        #[arg(required_unless_present = "stdin")]
+        project: Option<String>,
+        #[arg(required_unless_present = "stdin")]
+        task_id: Option<u32>,
+        /// Read the tasks to delete from stdin, as `project:id` lines.
+        #[arg(long, conflicts_with_all = ["project", "task_id"])]
+        stdin: bool,
+    },
+    /// Split a task into several, keeping its tags, contexts, priority and
+    /// due date.
+    SplitTask {
+        project: String,
+        task_id: u32,
+        /// Descriptions of the new tasks.
+        #[arg(required = true)]
+        parts: Vec<String>,
+        /// Delete the original task instead of closing it as split.
+        #[arg(long)]
+        remove_original: bool,
+    },
+    /// Set or clear the due date of a task.
+    SetDue {
+        project: String,
+        task_id: u32,
+        /// Due date (e.g. 2025-07-01, tomorrow, "next friday"), or `none` to clear it.
+        date: String,
+    },
+    /// Get reminded of a task at a given time.
+    RemindAt {
+        project: String,
+        task_id: u32,
+        /// When (e.g. "2025-07-01 09:00", "tomorrow 14:30", or 90m from
+        /// now), or `none` to drop the reminders not fired yet.
+        when: String,
+    },
+    /// Fire the reminders whose time has come.
+    Remind {
+        /// Keep running, firing reminders as their time comes.
+        #[arg(long)]
+        watch: bool,
+    },
+    /// Set a custom field on a task.
+    SetField {
+        project: String,
+        task_id: u32,
+        key: String,
+        value: String,
+    },
+    /// Remove a custom field from a task.
+    UnsetField {
+        project: String,
+        task_id: u32,
+        key: String,
+    },
+    /// Schedule a task for a given day, independently of its due date.
+    Plan {
         project: String,
         task_id: u32,
+        /// Day to work on the task (e.g. today, tomorrow, friday), or `none` to unschedule it.
+        date: String,
+    },
+    /// Hide a task from listings until a given day.
+    Snooze {
+        project: String,
+        task_id: u32,
+        /// Day the task comes back (e.g. monday, "in 2 weeks", 3d), or
+        /// `none` to bring it back now.
+        #[arg(long)]
+        until: String,
+    },
+    /// Pick a pending task at random, among those that fit.
+    Pick {
+        /// Pick among this project's tasks only.
+        project: Option<String>,
+        /// Only tasks estimated to take at most this long (e.g. 30m, 1h).
+        #[arg(long, value_parser = durations::parse_duration)]
+        max_estimate: Option<u32>,
+        /// Only tasks with this tag (can be repeated).
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+    },
+    /// Show tasks in progress, scheduled for today, and due today.
+    Today,
+    /// List the pending tasks of all projects together.
+    Agenda {
+        /// Keys to sort on, in order (default: due,priority,project).
+        #[arg(long, value_delimiter = ',')]
+        sort: Vec<agenda::SortKey>,
+        /// Only show tasks matching a filter expression, e.g. "tag:infra".
+        #[arg(long, value_parser = filter::parse)]
+        filter: Option<Filter>,
+        /// Print each task through a template, as for `list-tasks`.
+        #[arg(long, value_parser = template::parse_task_template)]
+        format: Option<TaskTemplate>,
+        /// Print only `project:id` for each task, as for `list-tasks`.
+        #[arg(long, conflicts_with = "format")]
+        ids: bool,
+    },
+    /// Show a month calendar of pending tasks by due date.
+    Calendar {
+        /// Show the week's tasks day by day instead.
+        #[arg(long)]
+        week: bool,
+        /// Any date in the month (or week) to show, defaults to today.
+        #[arg(long, value_parser = dates::parse_date)]
+        date: Option<NaiveDate>,
+    },
+    /// Show the history of a task.
+    History { project: String, task_id: u32 },
+    /// Show everything that happened across all projects.
+    Log {
+        /// Only show events from this date on, e.g. 2025-07-01 or "2 weeks ago".
+        #[arg(long, value_parser = dates::parse_date)]
+        since: Option<NaiveDate>,
+    },
+    /// Go through the overdue and stale tasks, and the projects with
+    /// nothing left to do, project by project, deciding what to do with
+    /// each.
+    Review {
+        /// Number of days without any change after which a task is stale.
+        #[arg(long, default_value_t = 14)]
+        days: u32,
+    },
+    /// List pending tasks nobody has touched in a while, oldest first.
+    Stale {
+        /// Minimum number of days without any change.
+        #[arg(long, default_value_t = 14)]
+        days: u32,
+        /// Also tag the stale tasks, e.g. `--auto-tag stale`.
+        #[arg(long, value_name = "TAG")]
+        auto_tag: Option<String>,
+    },
+    /// Set or clear the priority of a task.
+    SetPriority {
+        project: String,
+        task_id: u32,
+        /// low, medium, high, or `none` to clear it.
+        priority: String,
+    },
+    /// Mark a task as private, its description masked in exports and
+    /// `--redacted` listings.
+    Private {
+        project: String,
+        task_id: u32,
+        /// Make the task public again.
+        #[arg(long)]
+        off: bool,
+    },
+    /// Set or clear the time estimate of a task.
+    SetEstimate {
+        project: String,
+        task_id: u32,
+        /// A duration such as 45m, 2h, 1h30m or 2d, or `none` to clear it.
+        estimate: String,
+    },
+    /// Log time spent on a task.
+    LogTime {
+        project: String,
+        task_id: u32,
+        /// A duration such as 45m, 2h or 1h30m.
+        #[arg(value_parser = durations::parse_duration)]
+        duration: u32,
+        /// Day the time was spent, today by default.
+        #[arg(long, value_parser = dates::parse_date)]
+        date: Option<NaiveDate>,
+        /// What the time was spent on.
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// Report the time logged per task, e.g. for invoicing.
+    TimeReport {
+        /// Only count time logged from this date on.
+        #[arg(long, value_parser = dates::parse_date)]
+        from: Option<NaiveDate>,
+        /// Only count time logged up to this date.
+        #[arg(long, value_parser = dates::parse_date)]
+        to: Option<NaiveDate>,
+        /// Hourly rate, to compute amounts.
+        #[arg(long)]
+        rate: Option<f64>,
+        #[arg(long, value_enum, default_value_t = ReportFormat::Csv)]
+        format: ReportFormat,
+        #[arg(long, value_enum, default_value_t = GroupBy::Project)]
+        group_by: GroupBy,
+    },
+    /// Set or clear the prefix of a project's task IDs, e.g. WEB for WEB-12.
+    SetPrefix {
+        project: String,
+        /// Letters or digits, starting with a letter, or `none` to clear it.
+        prefix: String,
+    },
+    /// Record that a project follows another on the roadmap.
+    SetAfter {
+        project: String,
+        /// The project it follows, or `none` to follow nothing.
+        after: String,
+    },
+    /// List projects in the order they follow each other, with their progress.
+    Roadmap,
+    /// Link two related tasks, usually of different projects, given as
+    /// project:id or PREFIX-ID.
+    LinkTask {
+        first: String,
+        second: String,
+        /// Unlink them instead.
+        #[arg(long)]
+        remove: bool,
+    },
+    /// Show the tasks a task is linked to, and the ones those are linked
+    /// to in turn.
+    Links { project: String, task_id: u32 },
+    /// Estimate when a project will be done at the recent pace, or every
+    /// project with tasks left.
+    Forecast { project: Option<String> },
+    /// Set a project's target completion date and/or weekly goal.
+    #[command(group(ArgGroup::new("goal").required(true).multiple(true)))]
+    SetGoal {
+        project: String,
+        /// Target completion date, or `none` to clear it.
+        #[arg(long, group = "goal")]
+        deadline: Option<String>,
+        /// Number of tasks to complete each week, or `none` to clear it.
+        #[arg(long, group = "goal")]
+        weekly: Option<String>,
+    },
+    /// Add tags to a task.
+    Tag {
+        project: String,
+        task_id: u32,
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// Remove tags from a task.
+    Untag {
+        project: String,
+        task_id: u32,
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// Attach a URL or a local file to a task.
+    Attach {
+        project: String,
+        task_id: u32,
+        /// URL or path of the file to attach.
+        target: String,
+    },
+    /// List the attachments of a task.
+    Attachments { project: String, task_id: u32 },
+    /// Open an attachment of a task in the default application.
+    Open {
+        project: String,
+        task_id: u32,
+        /// Which attachment to open, as numbered by `attachments`.
+        #[arg(default_value_t = 1)]
+        index: usize,
+    },
+    /// Manage the checklist inside a task.
+    Checklist {
+        #[command(subcommand)]
+        command: ChecklistCommand,
+    },
+    /// Mark a task as waiting on someone else, or list the tasks waiting.
+    #[command(args_conflicts_with_subcommands = true)]
+    Waiting {
+        #[arg(requires = "task_id")]
+        project: Option<String>,
+        task_id: Option<u32>,
+        /// What the task is waiting for, e.g. "reply from vendor".
+        #[arg(long = "for", value_name = "WHAT")]
+        what: Option<String>,
+        /// Day the wait started, today if not given.
+        #[arg(long, value_parser = dates::parse_date)]
+        since: Option<NaiveDate>,
+        /// Stop waiting, the wait being over.
+        #[arg(long, conflicts_with_all = ["what", "since"])]
+        done: bool,
+        #[command(subcommand)]
+        command: Option<WaitingCommand>,
+    },
+    /// Show tasks matching a saved view, or manage saved views.
+    #[command(args_conflicts_with_subcommands = true)]
+    View {
+        /// Name of the view to show.
+        name: Option<String>,
+        #[command(subcommand)]
+        command: Option<ViewCommand>,
+    },
+    /// Import projects and tasks from a file or stdin.
+    Import {
+        #[arg(required_unless_present = "stdin")]
+        file: Option<PathBuf>,
+        /// Read the data to import from stdin.
+        #[arg(long, conflicts_with = "file")]
+        stdin: bool,
+        #[arg(long, value_enum, default_value_t = ImportFormat::Json)]
+        format: ImportFormat,
+    },
+    /// Fill an empty workspace with made-up projects and tasks, e.g. to see
+    /// how the tracker fares with lots of data.
+    Generate {
+        #[arg(long, default_value_t = 10)]
+        projects: u32,
+        /// Number of tasks, spread over the projects.
+        #[arg(long, default_value_t = 1000)]
+        tasks: u32,
+        /// The same seed always generates the same data.
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+    },
+    /// Add the `- [ ]` items of TODO.md files to a project, updating the
+    /// tasks added by earlier scans.
+    Scan {
+        /// A TODO.md file, or a directory to search for them.
+        path: PathBuf,
+        /// Project to add the tasks to, named after the path by default.
+        #[arg(long)]
+        project: Option<String>,
+        /// Also add `TODO:` comments found in source files.
+        #[arg(long)]
+        code: bool,
+    },
+    /// Merge another copy of the data file, e.g. a sync conflict, into this one.
+    Merge { file: PathBuf },
+    /// Move all the tasks of a project into another, then delete it.
+    MergeProjects {
+        /// Project to merge and delete.
+        source: String,
+        /// Project to merge it into.
+        target: String,
+    },
+    /// Move old completed tasks to the archive file.
+    Autoclean {
+        /// Archive tasks completed this long ago, e.g. 30d, instead of
+        /// following the `archive-completed-after` setting.
+        #[arg(long, value_parser = durations::parse_days, value_name = "DAYS")]
+        after: Option<u32>,
+    },
+    /// Completion streaks and badges earned.
+    Stats,
+    /// Draw a project's progress over the past weeks.
+    Graph {
+        project: Option<String>,
+        /// How many weeks back to go.
+        #[arg(long, default_value_t = 8)]
+        weeks: u32,
+    },
+    /// Summary of overdue, soon due and recently completed tasks.
+    Digest {
+        #[arg(long, value_enum, default_value_t = digest::Period::Day)]
+        period: digest::Period,
+        /// Send it by email, through the `smtp-url` server or, without one,
+        /// by writing the email to stdout for sendmail.
+        #[arg(long)]
+        email: bool,
+    },
+    /// Draft a commit message from the tasks completed since the last
+    /// commit of the repository here, or since the last draft.
+    SuggestCommit {
+        /// Only list this project's tasks.
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Print a project's completed tasks as a Keep a Changelog section,
+    /// grouped by their tags.
+    Changelog {
+        project: String,
+        /// Date or git tag to start from, the latest tag of the repository
+        /// here if not given.
+        #[arg(long)]
+        since: Option<String>,
+        /// Version being released, instead of an Unreleased section.
+        #[arg(long, value_name = "VERSION")]
+        release: Option<String>,
+    },
+    /// Manage GTD contexts (@home, @office...) and the one listings are
+    /// limited to.
+    Context {
+        #[command(subcommand)]
+        command: ContextCommand,
+    },
+    /// Focus on one project: commands default to it and the overview only
+    /// shows it, until `focus --clear`. Shows the project focused on
+    /// without arguments.
+    Focus {
+        project: Option<String>,
+        /// Stop focusing, showing every project again.
+        #[arg(long, conflicts_with = "project")]
+        clear: bool,
+    },
+    /// Check the data for problems, such as duplicate task IDs or dates
+    /// that don't add up, and with --fix repair those that can be.
+    Doctor {
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Share a project with someone else's tracker as a bundle file.
+    Bundle {
+        #[command(subcommand)]
+        command: BundleCommand,
+    },
+    /// Back the data up to a WebDAV server or an S3 bucket, encrypted.
+    Backup {
+        #[command(subcommand)]
+        command: BackupCommand,
+    },
+    /// Save snapshots of the data and see what changed between them.
+    Snapshot {
+        #[command(subcommand)]
+        command: SnapshotCommand,
     },
+    /// Sum up the work done over a stretch of time, e.g. a month.
+    Report {
+        #[command(subcommand)]
+        command: ReportCommand,
+    },
+    /// Manage URLs task events are POSTed to.
+    Webhook {
+        #[command(subcommand)]
+        command: WebhookCommand,
+    },
+    /// Serve HTTP requests, answering Slack slash commands on `/slack`,
+    /// `--remote` clients on `/data` and browsers with a web UI on `/ui/`.
+    Serve {
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        addr: String,
+    },
+    /// Sync this workspace with other devices through a shared directory.
+    Sync { dir: PathBuf },
+    /// Export projects and tasks to stdout.
+    Export {
+        /// Only export this project.
+        #[arg(long)]
+        project: Option<String>,
+        #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+    },
+    /// Manage task templates, for `add-task --template`.
+    Template {
+        #[command(subcommand)]
+        command: TemplateCommand,
+    },
+    /// Define shorter names for commands and projects.
+    Alias {
+        #[command(subcommand)]
+        command: AliasCommand,
+    },
+    /// Show or change settings.
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Start an interactive shell.
+    Shell,
+    /// Set the tracker up step by step: where the data goes, the theme, the
+    /// default workspace and tasks to import, as on the first run.
+    Setup,
+    /// Manage workspaces.
+    Workspace {
+        #[command(subcommand)]
+        command: WorkspaceCommand,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+enum Priority {
+    Low,
+    Medium,
+    High,
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum Status {
+    Todo,
+    InProgress,
+    Done,
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            Status::Todo => "todo",
+            Status::InProgress => "in-progress",
+            Status::Done => "done",
+        };
+        f.write_str(name)
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 struct Task {
     id: u32,
     description: String,
     completed: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    in_progress: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    completed_at: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scheduled: Option<NaiveDate>,
+    /// Day the task comes back in listings, see `snooze`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    snoozed_until: Option<NaiveDate>,
+    /// Who or what the task is blocked on, see `waiting`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    waiting: Option<Waiting>,
+    /// Whether the description is masked in exports, see `privacy`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    private: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    priority: Option<Priority>,
+    /// Estimated effort, in minutes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    estimate: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    contexts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    checklist: Vec<ChecklistItem>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    fields: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    time_entries: Vec<TimeEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    reminders: Vec<Reminder>,
+    /// Related tasks, usually of other projects.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    links: Vec<TaskLink>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    history: Vec<HistoryEntry>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl Task {
+    fn status(&self) -> Status {
+        if self.completed {
+            Status::Done
+        } else if self.in_progress {
+            Status::InProgress
+        } else {
+            Status::Todo
+        }
+    }
+
+    /// Mark the task as completed now.
+    fn complete(&mut self) {
+        self.completed = true;
+        self.in_progress = false;
+        self.completed_at = Some(Local::now());
+        self.record(TaskEvent::Completed, None);
+    }
+
+    /// Mark the completed task as pending again.
+    fn reopen(&mut self) {
+        self.completed = false;
+        // The completion time lives on in the history entry.
+        let detail = self
+            .completed_at
+            .take()
+            .map(|at| format!("was completed on {}", at.format("%Y-%m-%d %H:%M")));
+        self.record(TaskEvent::Reopened, detail);
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 struct Project {
     name: String,
+    /// Short prefix for the project's task IDs, as in `WEB-12`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    prefix: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deadline: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    weekly_goal: Option<u32>,
+    /// Projects this one follows on the roadmap.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    after: Vec<String>,
+    /// ID of the next task added, so IDs of deleted tasks are never reused.
+    #[serde(default)]
+    next_id: u32,
     tasks: Vec<Task>,
 }
 
+impl Project {
+    /// Take the next task ID.
+    fn allocate_id(&mut self) -> u32 {
+        // Tasks may have come in with their own IDs, imported or merged.
+        let after_last = self.tasks.iter().map(|t| t.id + 1).max().unwrap_or(1);
+        let id = self.next_id.max(after_last);
+        self.next_id = id + 1;
+        id
+    }
+
+    /// Add a task with the details of `template`, under a new ID.
+    fn add_task(&mut self, description: &str, template: &Task) -> &mut Task {
+        let mut task = Task {
+            id: self.allocate_id(),
+            description: description.to_string(),
+            ..template.clone()
+        };
+        task.record(TaskEvent::Created, None);
+        self.tasks.push(task);
+        self.tasks.last_mut().expect("A task was just added")
+    }
+
+    /// Take a task out of the project, if it's there.
+    fn remove_task(&mut self, task_id: u32) -> Option<Task> {
+        let position = self.tasks.iter().position(|t| t.id == task_id)?;
+        Some(self.tasks.remove(position))
+    }
+
+    /// Share of the tasks completed, from 0 to 1, weighted as the
+    /// `progress-weighting` setting says.
+    fn progress(&self) -> f64 {
+        progress::completion(&self.tasks)
+    }
+}
+
 fn main() {
-    let cli = CLI::parse();
+    let args = aliases::expand(std::env::args().collect());
+    // The task ID shorthands are resolved against the data, from the
+    // workspace or the remote tracker the global options say.
+    let globals = args::global_options(&args);
+
+    if globals.remote.is_none() && setup::is_first_run() {
+        setup::wizard();
+    }
+    if !workspace::set_active(
+        globals
+            .workspace
+            .clone()
+            .or_else(|| load_config().global().default_workspace),
+    ) {
+        return;
+    }
+    storage::set_read_only(globals.read_only);
+    storage::set_dry_run(globals.dry_run);
+    storage::set_force(globals.force);
+    remote::set_remote(globals.remote.clone(), globals.token.clone());
+    bars::set_ascii(globals.ascii);
+    output::set_plain(globals.plain);
+    privacy::set_redacted(globals.redacted);
+    snooze::set_include_snoozed(globals.include_snoozed);
+    logging::init(globals.verbose);
+    shell::set_global_options(globals.clone());
+    theme::apply();
+    info!("running {}", args[1..].join(" "));
+    debug!(
+        "workspace '{}', config directory {}",
+        workspace::active(),
+        get_config_dir().display()
+    );
+
+    let manages_workspaces = args::command_name(&args).as_deref() == Some("workspace");
+    if !manages_workspaces && !workspace::exists(workspace::active()) {
+        println!(
+            "{}",
+            tr!(
+                "Workspace '{}' not found. Create it with `workspace create {}`.",
+                workspace::active(),
+                workspace::active()
+            )
+        );
+        return;
+    }
 
-    match &cli.command {
-        Some(Commands::AddProject { name }) => add_project(&name),
-        Some(Commands::ListProjects) => list_projects(),
+    let Some(lines) = args::expand(args) else {
+        return;
+    };
+    // Several command lines only differ by a task ID, from a range: they
+    // share the global options.
+    let clis: Vec<Cli> = lines.iter().map(Cli::parse_from).collect();
+    if !confirm_range(&clis) {
+        return;
+    }
+    for cli in &clis {
+        run(cli.command.as_ref());
+    }
+}
+
+/// Whether to go ahead with `clis`, the command lines a range of task IDs
+/// expanded to: completing a range of tasks is completing several at once,
+/// which protected projects confirm first.
+fn confirm_range(clis: &[Cli]) -> bool {
+    match clis.first().and_then(|cli| cli.command.as_ref()) {
+        Some(Commands::CompleteTask {
+            project: Some(project),
+            ..
+        }) if clis.len() > 1 => {
+            protect::confirm(project, &tr!("completing {} tasks at once", clis.len()))
+        }
+        _ => true,
+    }
+}
+
+impl Commands {
+    /// Whether the command may change the data, as opposed to only reading
+    /// it or changing settings.
+    fn modifies_data(&self) -> bool {
+        match self {
+            Commands::ListProjects { .. }
+            | Commands::Status { .. }
+            | Commands::PromptSegment { .. }
+            | Commands::Overview { .. }
+            | Commands::ListTasks { .. }
+            | Commands::Search { .. }
+            | Commands::Pick { .. }
+            | Commands::Today
+            | Commands::Agenda { .. }
+            | Commands::Roadmap
+            | Commands::Links { .. }
+            | Commands::Forecast { .. }
+            | Commands::Calendar { .. }
+            | Commands::History { .. }
+            | Commands::Log { .. }
+            | Commands::TimeReport { .. }
+            | Commands::Attachments { .. }
+            | Commands::Open { .. }
+            | Commands::View { .. }
+            | Commands::Export { .. }
+            | Commands::Config { .. }
+            | Commands::Template { .. }
+            | Commands::Alias { .. }
+            | Commands::Focus { .. }
+            | Commands::Snapshot { .. }
+            | Commands::Report { .. }
+            | Commands::Digest { .. }
+            | Commands::SuggestCommit { .. }
+            | Commands::Changelog { .. }
+            | Commands::Stats
+            | Commands::Graph { .. }
+            | Commands::Webhook { .. }
+            | Commands::Serve { .. }
+            | Commands::Shell
+            | Commands::Workspace { .. } => false,
+            Commands::Stale { auto_tag, .. } => auto_tag.is_some(),
+            Commands::Review { .. } => true,
+            Commands::Waiting { project, .. } => project.is_some(),
+            Commands::Doctor { fix } => *fix,
+            Commands::Bundle { command } => matches!(command, BundleCommand::Import { .. }),
+            Commands::Backup { command } => matches!(command, BackupCommand::Restore { .. }),
+            Commands::Checklist { command } => !matches!(command, ChecklistCommand::List { .. }),
+            Commands::Context { command } => {
+                matches!(
+                    command,
+                    ContextCommand::Add { .. } | ContextCommand::Remove { .. }
+                )
+            }
+            Commands::AddProject { .. }
+            | Commands::CloneProject { .. }
+            | Commands::AddTask { .. }
+            | Commands::Do { .. }
+            | Commands::In { .. }
+            | Commands::Triage
+            | Commands::CompleteTask { .. }
+            | Commands::MoveTask { .. }
+            | Commands::ReopenTask { .. }
+            | Commands::Edit { .. }
+            | Commands::MoveUp { .. }
+            | Commands::MoveDown { .. }
+            | Commands::Reorder { .. }
+            | Commands::DeleteProject { .. }
+            | Commands::DeleteTask { .. }
+            | Commands::SplitTask { .. }
+            | Commands::SetDue { .. }
+            | Commands::RemindAt { .. }
+            | Commands::Remind { .. }
+            | Commands::SetField { .. }
+            | Commands::UnsetField { .. }
+            | Commands::Plan { .. }
+            | Commands::Snooze { .. }
+            | Commands::SetPriority { .. }
+            | Commands::Private { .. }
+            | Commands::SetEstimate { .. }
+            | Commands::LogTime { .. }
+            | Commands::SetPrefix { .. }
+            | Commands::SetGoal { .. }
+            | Commands::SetAfter { .. }
+            | Commands::LinkTask { .. }
+            | Commands::Tag { .. }
+            | Commands::Untag { .. }
+            | Commands::Attach { .. }
+            | Commands::Import { .. }
+            | Commands::Setup
+            | Commands::Generate { .. }
+            | Commands::Scan { .. }
+            | Commands::Merge { .. }
+            | Commands::MergeProjects { .. }
+            | Commands::Autoclean { .. }
+            | Commands::Sync { .. } => true,
+        }
+    }
+
+    /// Whether `--dry-run` can show everything the command would change,
+    /// which it can't for commands changing settings, workspaces or other
+    /// files besides the data.
+    fn can_dry_run(&self) -> bool {
+        match self {
+            Commands::Workspace { command } => matches!(command, WorkspaceCommand::List),
+            Commands::Config { command } => {
+                matches!(
+                    command,
+                    ConfigCommand::Get { .. } | ConfigCommand::Show { .. }
+                )
+            }
+            Commands::View { command, .. } => {
+                matches!(command, None | Some(ViewCommand::List))
+            }
+            Commands::Context { command } => {
+                !matches!(command, ContextCommand::Set { .. } | ContextCommand::Clear)
+            }
+            Commands::Template { command } => matches!(command, TemplateCommand::List),
+            Commands::Focus { project, clear } => project.is_none() && !clear,
+            // Fixing may rewrite the archive too.
+            Commands::Doctor { fix } => !fix,
+            Commands::Snapshot { command } => !matches!(
+                command,
+                SnapshotCommand::Save { .. } | SnapshotCommand::Delete { .. }
+            ),
+            Commands::Webhook { command } => matches!(command, WebhookCommand::List),
+            Commands::Bundle { command } => matches!(command, BundleCommand::Import { .. }),
+            Commands::Backup { command } => matches!(command, BackupCommand::Status),
+            Commands::Alias { command } => matches!(command, AliasCommand::List),
+            Commands::Serve { .. } | Commands::Setup => false,
+            _ => true,
+        }
+    }
+}
+
+fn run(command: Option<&Commands>) {
+    if storage::is_dry_run() && command.is_some_and(|c| !c.can_dry_run()) {
+        println!(
+            "{}",
+            tr!("This command changes more than the data and can't be run with --dry-run.")
+        );
+        return;
+    }
+    if command.is_some_and(Commands::modifies_data)
+        && storage::is_read_only()
+        && !storage::is_dry_run()
+    {
+        println!(
+            "{}",
+            tr!(
+                "Workspace '{}' is read-only, nothing was changed.",
+                workspace::active()
+            )
+        );
+        return;
+    }
+
+    // Status lines are read by prompts, and exports and the like by other
+    // programs, which can't have anything else printed in the middle.
+    let skips_archival = matches!(
+        command,
+        Some(
+            Commands::Autoclean { .. }
+                | Commands::Status { .. }
+                | Commands::PromptSegment { .. }
+                | Commands::Webhook { .. }
+                | Commands::Export { .. }
+                | Commands::Changelog { .. }
+                | Commands::SuggestCommit { .. }
+        )
+    );
+    if !skips_archival && !storage::is_read_only() && !storage::is_dry_run() {
+        archive::archive_per_policy();
+    }
+
+    match command {
+        Some(Commands::AddProject { name }) => add_project(name),
+        Some(Commands::CloneProject {
+            source,
+            destination,
+            reset,
+        }) => clone_project(source, destination, *reset),
+        Some(Commands::ListProjects { tree, prefix }) => list_projects(*tree, prefix.as_deref()),
+        Some(Commands::Overview { workspaces }) if workspaces.is_empty() => {
+            list_all_projects_and_tasks()
+        }
+        Some(Commands::Overview { workspaces }) => mirror::overview(workspaces),
+        Some(Commands::PromptSegment { project, max_age }) => {
+            progress::show_segment(project.as_deref(), *max_age)
+        }
+        Some(Commands::Status { project, format }) => {
+            progress::show_status(project.as_deref(), format)
+        }
         Some(Commands::AddTask {
             project,
             description,
-        }) => add_task(&project, &description),
-        Some(Commands::ListTasks { project }) => list_tasks(&project),
-        Some(Commands::CompleteTask { project, task_id }) => complete_task(project, *task_id),
-        Some(Commands::DeleteTask { project, task_id }) => delete_task(project, *task_id),
+            stdin,
+            due,
+            priority,
+            tags,
+            contexts,
+            private,
+            create,
+            template: template_name,
+            vars,
+        }) => {
+            // With a single argument, it's the description, unless it comes
+            // from a template.
+            let (project, description) = match (project, description) {
+                (Some(project), None) if !*stdin && template_name.is_none() => {
+                    (None, Some(project.clone()))
+                }
+                (project, description) => (project.as_deref(), description.clone()),
+            };
+            let saved = match template_name.as_deref().map(templates::find).transpose() {
+                Ok(saved) => saved.unwrap_or_default(),
+                Err(e) => {
+                    println!("{}", e);
+                    return;
+                }
+            };
+            let description = match template_name {
+                Some(_) => match saved.expand(vars) {
+                    Ok(description) => Some(description),
+                    Err(e) => {
+                        println!("{}", e);
+                        return;
+                    }
+                },
+                None => description,
+            };
+            if description.is_none() && !*stdin {
+                println!("{}", tr!("No task description given."));
+                return;
+            }
+            let Some(project) = picker::resolve_project(project) else {
+                return;
+            };
+            let descriptions = match description {
+                Some(description) => vec![description],
+                None => read_descriptions_from_stdin(),
+            };
+            // What's given on the command line adds to the template.
+            let mut task_tags = saved.tags;
+            for tag in tags.iter().map(|t| tags::normalize(t)) {
+                if !task_tags.contains(&tag) {
+                    task_tags.push(tag);
+                }
+            }
+            let mut task_contexts = saved.contexts;
+            for context in contexts.iter().map(|c| contexts::normalize(c)) {
+                if !task_contexts.contains(&context) {
+                    task_contexts.push(context);
+                }
+            }
+            let template = Task {
+                due: *due,
+                priority: priority.or(saved.priority),
+                tags: task_tags,
+                contexts: task_contexts,
+                private: *private,
+                ..Default::default()
+            };
+            add_tasks(&project, &descriptions, &template, *create)
+        }
+        Some(Commands::Do { text }) => quickadd::quick_add(&text.join(" ")),
+        Some(Commands::In { text }) => inbox::capture(&text.join(" ")),
+        Some(Commands::Triage) => inbox::triage(),
+        Some(Commands::ListTasks {
+            project,
+            filters,
+            filter,
+            context,
+            table,
+            columns,
+            format,
+            ids,
+            sort,
+            reverse,
+        }) => {
+            if let Some(project) = picker::resolve_project(project.as_deref()) {
+                let context = context.as_deref().map(contexts::normalize);
+                let ids_template = pipe::ids_template();
+                let layout = match (format, columns.as_slice()) {
+                    _ if *ids => Layout::Template(&ids_template),
+                    (Some(template), _) => Layout::Template(template),
+                    (None, _) if !table => Layout::Lines,
+                    (None, []) => Layout::Table(table::DEFAULT_COLUMNS),
+                    (None, columns) => Layout::Table(columns),
+                };
+                list_tasks(
+                    &project,
+                    filters,
+                    filter.as_ref(),
+                    context.as_deref(),
+                    layout,
+                    *sort,
+                    *reverse,
+                )
+            }
+        }
+        Some(Commands::Search {
+            text,
+            filter,
+            format,
+            ids,
+        }) => {
+            let ids_template = pipe::ids_template();
+            let template = format.as_ref().or(ids.then_some(&ids_template));
+            search::search(text, filter.as_ref(), template)
+        }
+        Some(Commands::CompleteTask {
+            stdin: true,
+            cascade,
+            ..
+        }) => {
+            if let Some(groups) = pipe::read_ids() {
+                for (project, task_ids) in groups {
+                    complete_tasks(&project, &task_ids, *cascade)
+                }
+            }
+        }
+        Some(Commands::CompleteTask {
+            project,
+            task_id: _,
+            interactive: true,
+            cascade,
+            ..
+        }) => {
+            if let Some(project) = picker::resolve_project(project.as_deref()) {
+                if let Some(task_ids) = picker::pick_tasks(&project, |t| !t.completed) {
+                    complete_tasks(&project, &task_ids, *cascade)
+                }
+            }
+        }
+        Some(Commands::CompleteTask {
+            project,
+            task_id,
+            interactive: false,
+            cascade,
+            ..
+        }) => {
+            if let Some((project, task_id)) =
+                picker::resolve_task(project.as_deref(), *task_id, |t| !t.completed)
+            {
+                complete_tasks(&project, &[task_id], *cascade)
+            }
+        }
+        Some(Commands::MoveTask {
+            project,
+            task_id,
+            status,
+        }) => move_task(project, *task_id, *status),
+        Some(Commands::ReopenTask {
+            project,
+            task_id,
+            stdin,
+        }) => for_tasks(project, *task_id, *stdin, reopen_task),
+        Some(Commands::Edit { project }) => {
+            if let Some(project) = picker::resolve_project(project.as_deref()) {
+                edit::edit(&project)
+            }
+        }
+        Some(Commands::MoveUp { project, task_id }) => reorder::move_task(project, *task_id, true),
+        Some(Commands::MoveDown { project, task_id }) => {
+            reorder::move_task(project, *task_id, false)
+        }
+        Some(Commands::Reorder {
+            project,
+            task_id,
+            before,
+            after,
+        }) => match (before, after) {
+            (Some(before), _) => reorder::reorder(project, *task_id, *before, false),
+            (_, Some(after)) => reorder::reorder(project, *task_id, *after, true),
+            (None, None) => unreachable!("clap requires --before or --after"),
+        },
+        Some(Commands::DeleteTask {
+            project,
+            task_id,
+            stdin,
+        }) => for_tasks(project, *task_id, *stdin, delete_task),
         Some(Commands::DeleteProject { project }) => delete_project(project),
+        Some(Commands::SplitTask {
+            project,
+            task_id,
+            parts,
+            remove_original,
+        }) => split::split_task(project, *task_id, parts, *remove_original),
+        Some(Commands::SetDue {
+            project,
+            task_id,
+            date,
+        }) => set_due(project, *task_id, date),
+        Some(Commands::RemindAt {
+            project,
+            task_id,
+            when,
+        }) => reminders::remind_at(project, *task_id, when),
+        Some(Commands::Remind { watch }) => reminders::remind(*watch),
+        Some(Commands::SetField {
+            project,
+            task_id,
+            key,
+            value,
+        }) => fields::set_field(project, *task_id, key, Some(value)),
+        Some(Commands::UnsetField {
+            project,
+            task_id,
+            key,
+        }) => fields::set_field(project, *task_id, key, None),
+        Some(Commands::Snooze {
+            project,
+            task_id,
+            until,
+        }) => snooze::snooze(project, *task_id, until),
+        Some(Commands::Plan {
+            project,
+            task_id,
+            date,
+        }) => today::plan(project, *task_id, date),
+        Some(Commands::Agenda {
+            sort,
+            filter,
+            format,
+            ids,
+        }) => {
+            let sort = if sort.is_empty() {
+                agenda::DEFAULT_SORT
+            } else {
+                sort
+            };
+            let ids_template = pipe::ids_template();
+            let template = format.as_ref().or(ids.then_some(&ids_template));
+            agenda::show_agenda(sort, filter.as_ref(), template)
+        }
+        Some(Commands::Pick {
+            project,
+            max_estimate,
+            tags,
+        }) => pick::pick(project.as_deref(), *max_estimate, tags),
+        Some(Commands::Today) => today::show_today(),
+        Some(Commands::Calendar { week, date }) => {
+            let date = date.unwrap_or_else(dates::today);
+            if *week {
+                calendar::show_week(date)
+            } else {
+                calendar::show_month(date)
+            }
+        }
+        Some(Commands::History { project, task_id }) => history::show_history(project, *task_id),
+        Some(Commands::Log { since }) => history::show_log(*since),
+        Some(Commands::Review { days }) => review::review(*days),
+        Some(Commands::Stale { days, auto_tag }) => stale::show_stale(*days, auto_tag.as_deref()),
+        Some(Commands::SetPriority {
+            project,
+            task_id,
+            priority,
+        }) => set_priority(project, *task_id, priority),
+        Some(Commands::Private {
+            project,
+            task_id,
+            off,
+        }) => privacy::set_private(project, *task_id, !off),
+        Some(Commands::SetEstimate {
+            project,
+            task_id,
+            estimate,
+        }) => estimates::set_estimate(project, *task_id, estimate),
+        Some(Commands::LogTime {
+            project,
+            task_id,
+            duration,
+            date,
+            note,
+        }) => timelog::log_time(project, *task_id, *duration, *date, note.as_deref()),
+        Some(Commands::TimeReport {
+            from,
+            to,
+            rate,
+            format,
+            group_by,
+        }) => timelog::time_report(*from, *to, *rate, *format, *group_by),
+        Some(Commands::SetPrefix { project, prefix }) => prefixes::set_prefix(project, prefix),
+        Some(Commands::SetAfter { project, after }) => roadmap::set_after(project, after),
+        Some(Commands::LinkTask {
+            first,
+            second,
+            remove,
+        }) => links::link_tasks(first, second, *remove),
+        Some(Commands::Links { project, task_id }) => links::show_links(project, *task_id),
+        Some(Commands::Roadmap) => roadmap::show_roadmap(),
+        Some(Commands::Forecast { project }) => forecast::show(project.as_deref()),
+        Some(Commands::SetGoal {
+            project,
+            deadline,
+            weekly,
+        }) => goals::set_goal(project, deadline.as_deref(), weekly.as_deref()),
+        Some(Commands::Tag {
+            project,
+            task_id,
+            tags,
+        }) => tags::tag_task(project, *task_id, tags, false),
+        Some(Commands::Untag {
+            project,
+            task_id,
+            tags,
+        }) => tags::tag_task(project, *task_id, tags, true),
+        Some(Commands::Attach {
+            project,
+            task_id,
+            target,
+        }) => attachments::attach(project, *task_id, target),
+        Some(Commands::Attachments { project, task_id }) => {
+            attachments::list_attachments(project, *task_id)
+        }
+        Some(Commands::Open {
+            project,
+            task_id,
+            index,
+        }) => attachments::open(project, *task_id, *index),
+        Some(Commands::Checklist { command }) => checklist::run(command),
+        Some(Commands::Waiting {
+            project,
+            task_id,
+            what,
+            since,
+            done,
+            command,
+        }) => match (project, task_id, command) {
+            (Some(project), Some(task_id), None) if *done => {
+                waiting::stop_waiting(project, *task_id)
+            }
+            (Some(project), Some(task_id), None) => match what {
+                Some(what) => waiting::wait(project, *task_id, what, *since),
+                None => println!(
+                    "{}",
+                    tr!("Say what the task is waiting for with --for, or --done when it's over.")
+                ),
+            },
+            _ => waiting::list(),
+        },
+        Some(Commands::View { name, command }) => match (name, command) {
+            (_, Some(command)) => views::run(command),
+            (Some(name), None) => views::show_view(name),
+            (None, None) => views::run(&ViewCommand::List),
+        },
+        Some(Commands::Import { file, format, .. }) => {
+            import::import(file.as_deref(), *format, storage::is_dry_run())
+        }
+        Some(Commands::Generate {
+            projects,
+            tasks,
+            seed,
+        }) => generate::generate(*projects, *tasks, *seed),
+        Some(Commands::Scan {
+            path,
+            project,
+            code,
+        }) => scan::scan(path, project.as_deref(), *code, storage::is_dry_run()),
+        Some(Commands::Merge { file }) => merge::merge(file, storage::is_dry_run()),
+        Some(Commands::MergeProjects { source, target }) => {
+            merge::merge_projects(source, target, storage::is_dry_run())
+        }
+        Some(Commands::Autoclean { after }) => archive::autoclean(*after, storage::is_dry_run()),
+        Some(Commands::Stats) => achievements::stats(),
+        Some(Commands::Graph { project, weeks }) => {
+            if let Some(project) = picker::resolve_project(project.as_deref()) {
+                graph::graph(&project, *weeks)
+            }
+        }
+        Some(Commands::Digest { period, email }) => digest::digest(*period, *email),
+        Some(Commands::SuggestCommit { project }) => suggest::suggest_commit(project.as_deref()),
+        Some(Commands::Changelog {
+            project,
+            since,
+            release,
+        }) => changelog::changelog(project, since.as_deref(), release.as_deref()),
+        Some(Commands::Context { command }) => contexts::run(command),
+        Some(Commands::Focus { project, clear }) => focus::focus(project.as_deref(), *clear),
+        Some(Commands::Doctor { fix }) => doctor::doctor(*fix),
+        Some(Commands::Bundle { command }) => bundle::run(command),
+        Some(Commands::Backup { command }) => backup::run(command),
+        Some(Commands::Snapshot { command }) => snapshot::run(command),
+        Some(Commands::Report { command }) => report::run(command),
+        Some(Commands::Webhook { command }) => webhooks::run(command),
+        Some(Commands::Serve { addr }) => serve::serve(addr),
+        Some(Commands::Sync { dir }) => sync::sync(dir),
+        Some(Commands::Export { project, format }) => export::export(project.as_deref(), *format),
+        Some(Commands::Template { command }) => templates::run(command),
+        Some(Commands::Alias { command }) => aliases::run(command),
+        Some(Commands::Config { command }) => config::run(command),
+        Some(Commands::Shell) => shell::run_shell(),
+        Some(Commands::Setup) => setup::wizard(),
+        Some(Commands::Workspace { command }) => workspace::run(command),
         None => list_all_projects_and_tasks(),
     }
 }
 
 fn add_project(name: &str) {
+    if !groups::is_valid_path(name) {
+        println!(
+            "{}",
+            tr!(
+                "Invalid project name '{}': group names must not be empty.",
+                name
+            )
+        );
+        return;
+    }
+
     let mut data = load_data();
 
     if data.iter().any(|p| p.name == name) {
-        println!("Project with name '{}' already exists.", name);
+        println!("{}", tr!("Project with name '{}' already exists.", name));
         return;
     }
 
     let project = Project {
         name: name.to_string(),
-        tasks: Vec::new(),
+        ..Default::default()
     };
 
     data.push(project);
     save_data(&data);
 
-    println!("Project '{}' added", name);
+    println!("{}", tr!("Project '{}' added", name));
+    hooks::run(Event::ProjectAdd(data.last().unwrap()));
 }
 
-fn load_data() -> Vec<Project> {
-    let data_file = get_data_file_path();
-
-    let mut file = OpenOptions::new()
-        .read(true)
-        .write(true)
-        .create(true)
-        .open(&data_file)
-        .unwrap();
+fn clone_project(source: &str, destination: &str, reset: bool) {
+    if !groups::is_valid_path(destination) {
+        println!(
+            "{}",
+            tr!(
+                "Invalid project name '{}': group names must not be empty.",
+                destination
+            )
+        );
+        return;
+    }
 
-    let mut content = String::new();
-    file.read_to_string(&mut content)
-        .expect("Unable to read data file.");
+    let mut data = load_data();
 
-    if content.is_empty() {
-        return Vec::new();
-    } else {
-        serde_json::from_str(&content).expect("Unable to parse data file.")
+    if data.iter().any(|p| p.name == destination) {
+        println!(
+            "{}",
+            tr!("Project with name '{}' already exists.", destination)
+        );
+        return;
     }
-}
-
-fn save_data(data: &Vec<Project>) {
-    let data_file = get_data_file_path();
 
-    let content = serde_json::to_string_pretty(data).expect("Unable to serialize data.");
+    if let Some(source_project) = data.iter().find(|p| p.name == source) {
+        // Prefixes are unique, the clone gets its own with `set-prefix`.
+        let mut project = Project {
+            name: destination.to_string(),
+            prefix: None,
+            ..source_project.clone()
+        };
+        for task in &mut project.tasks {
+            task.history.clear();
+            task.record(TaskEvent::Created, None);
+            if reset {
+                task.completed = false;
+                task.in_progress = false;
+                task.completed_at = None;
+                task.time_entries.clear();
+                for item in &mut task.checklist {
+                    item.done = false;
+                }
+            }
+        }
 
-    let mut file = OpenOptions::new()
-        .write(true)
-        .truncate(true)
-        .open(&data_file)
-        .expect("Unable to open data file.");
+        data.push(project);
+        save_data(&data);
 
-    file.write_all(content.as_bytes())
-        .expect("Unable to write data file.");
+        println!(
+            "{}",
+            tr!(
+                "Project '{}' cloned to '{}' with {} tasks.",
+                source,
+                destination,
+                data.last().unwrap().tasks.len()
+            )
+        );
+        hooks::run(Event::ProjectAdd(data.last().unwrap()));
+    } else {
+        println!("{}", tr!("Project '{}' not found.", source));
+    }
 }
 
-fn list_projects() {
+fn list_projects(tree: bool, prefix: Option<&str>) {
     let data = load_data();
+    let projects: Vec<&Project> = data
+        .iter()
+        .filter(|p| prefix.is_none_or(|prefix| groups::has_prefix(&p.name, prefix)))
+        .collect();
 
-    if data.is_empty() {
-        println!("{}", "No projects found");
+    if projects.is_empty() {
+        println!("{}", tr!("No projects found"));
+    } else if tree {
+        println!("{}", tr!("Projects:"));
+        groups::print_tree(projects);
     } else {
-        println!("{}", "Projects:");
-        for project in data {
-            println!(" - {}", project.name);
+        println!("{}", tr!("Projects:"));
+        for project in projects {
+            println!(" - {}", hyperlinks::project(&project.name, &project.name));
         }
     }
 }
 
-fn add_task(project_name: &str, description: &str) {
+/// Add a task for each of `descriptions`, with the remaining fields taken
+/// from `template`, creating the project first if it doesn't exist and
+/// `create` or the `create-projects` setting says so.
+fn add_tasks(project_name: &str, descriptions: &[String], template: &Task, create: bool) {
     let mut data = load_data();
 
+    let create = create || load_config().effective().create_projects == Some(true);
+    let created = create && !data.iter().any(|p| p.name == project_name);
+    if created {
+        if !groups::is_valid_path(project_name) {
+            println!(
+                "{}",
+                tr!(
+                    "Invalid project name '{}': group names must not be empty.",
+                    project_name
+                )
+            );
+            return;
+        }
+        data.push(Project {
+            name: project_name.to_string(),
+            ..Default::default()
+        });
+    }
+
+    let mut template = template.clone();
+    if template.priority.is_none() {
+        template.priority = load_config()
+            .effective_for(Some(project_name))
+            .default_priority;
+    }
+
     if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
-        let new_id = project.tasks.last().map_or(1, |t| t.id + 1);
-        let task = Task {
-            id: new_id,
-            description: description.to_string(),
-            completed: false,
-        };
+        let mut added = Vec::new();
+        let mut bumped = false;
+        for description in descriptions {
+            match duplicates::check(project, description) {
+                duplicates::Verdict::Add => {}
+                duplicates::Verdict::Bumped => {
+                    bumped = true;
+                    continue;
+                }
+                duplicates::Verdict::Skip => continue,
+            }
+            added.push(project.add_task(description, &template).clone());
+        }
 
-        project.tasks.push(task);
+        if added.is_empty() && !bumped {
+            return;
+        }
         save_data(&data);
-        println!("Task {} added to project: '{}'.", description, project_name);
+        let project = data
+            .iter()
+            .find(|p| p.name == project_name)
+            .expect("The project was just saved");
+        if created {
+            println!("{}", tr!("Project '{}' added", project_name));
+            hooks::run(Event::ProjectAdd(project));
+        }
+        for task in &added {
+            println!(
+                "{}",
+                tr!(
+                    "Task {} added to project: '{}'.",
+                    task.description,
+                    project_name
+                )
+            );
+            hooks::run(Event::TaskAdd(project_name, task));
+        }
     } else {
-        println!("Project '{}' not found.", project_name);
+        println!("{}", tr!("Project '{}' not found.", project_name));
     }
 }
 
-fn list_tasks(project_name: &str) {
-    let data = load_data();
+/// Run `action` on the task given as arguments or, with `--stdin`, on each
+/// of those read from stdin.
+fn for_tasks(project: &Option<String>, task_id: Option<u32>, stdin: bool, action: fn(&str, u32)) {
+    if stdin {
+        if let Some(groups) = pipe::read_ids() {
+            for (project, task_ids) in groups {
+                for task_id in task_ids {
+                    action(&project, task_id)
+                }
+            }
+        }
+    } else if let (Some(project), Some(task_id)) = (project, task_id) {
+        action(project, task_id)
+    }
+}
 
-    if let Some(project) = data.iter().find(|p| p.name == project_name) {
-        println!("Tasks in project: {}:", project_name);
+/// Read one task description per line from stdin, skipping blank lines.
+fn read_descriptions_from_stdin() -> Vec<String> {
+    io::stdin()
+        .lines()
+        .map(|line| line.expect("Unable to read from stdin."))
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
 
-        if project.tasks.is_empty() {
-            println!("    {}", "No tasks yet")
-        } else {
-            for task in &project.tasks {
-                let checkbox = if task.completed { "[x]" } else { "[ ]" };
-                println!("    {} {}: {}", checkbox, task.id, task.description);
+/// How `list-tasks` prints tasks.
+enum Layout<'a> {
+    Lines,
+    Table(&'a [Column]),
+    /// Through a `--format` template, without headers or messages.
+    Template(&'a TaskTemplate),
+}
+
+fn list_tasks(
+    project_name: &str,
+    filters: &[(String, String)],
+    filter: Option<&Filter>,
+    context: Option<&str>,
+    layout: Layout,
+    sort: Option<Column>,
+    reverse: bool,
+) {
+    let project = storage::load_project(project_name);
+    let context = context.or(contexts::active());
+
+    if let Some(project) = &project {
+        if !matches!(layout, Layout::Template(_)) {
+            println!(
+                "{}",
+                tr!(
+                    "Tasks in project: {}:",
+                    hyperlinks::project(project_name, project_name)
+                )
+            );
+            if let Some(context) = context {
+                println!("{}", tr!("Only showing tasks in {}.", context).dimmed());
+            }
+        }
+
+        let mut tasks: Vec<&Task> = project
+            .tasks
+            .iter()
+            .filter(|t| t.matches_fields(filters))
+            .filter(|t| filter.is_none_or(|f| f.matches(project_name, t)))
+            .filter(|t| contexts::in_context(t, context) && snooze::shown(t))
+            .collect();
+        if let Some(column) = sort {
+            tasks.sort_by(|a, b| column.compare(a, b));
+        }
+        if reverse {
+            tasks.reverse();
+        }
+
+        match layout {
+            Layout::Template(template) => {
+                for task in tasks {
+                    println!("{}", template.render(project, task));
+                }
+            }
+            _ if project.tasks.is_empty() => println!("    {}", tr!("No tasks yet")),
+            _ if tasks.is_empty() => println!("    {}", tr!("No matching tasks")),
+            Layout::Table(columns) => table::print(project, &tasks, columns),
+            Layout::Lines => {
+                for task in tasks {
+                    let checkbox = theme::mark(task.completed);
+                    println!(
+                        "    {} {}: {}{}",
+                        checkbox,
+                        project.display_id(task.id),
+                        display::description(task),
+                        task_details(task)
+                    );
+                }
             }
         }
     } else {
-        println!("Project '{}' not found.", project_name);
+        println!("{}", tr!("Project '{}' not found.", project_name));
     }
 }
 
 fn complete_task(project_name: &str, task_id: u32) {
+    complete_tasks(project_name, &[task_id], false)
+}
+
+/// Complete all of `task_ids` at once, saving the data a single time, with
+/// `cascade` ticking what's left of their checklists.
+fn complete_tasks(project_name: &str, task_ids: &[u32], cascade: bool) {
+    if task_ids.len() > 1
+        && !protect::confirm(
+            project_name,
+            &tr!("completing {} tasks at once", task_ids.len()),
+        )
+    {
+        return;
+    }
+    let mut data = load_data();
+    if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
+        let mut completed = Vec::new();
+        for &task_id in task_ids {
+            if let Some(task) = project.tasks.iter_mut().find(|t| t.id == task_id) {
+                if task.completed {
+                    println!("{}", tr!("Task {} is already completed!", task_id));
+                    continue;
+                }
+                if !task.allows_completion(cascade) {
+                    continue;
+                }
+                task.complete();
+                completed.push(task.clone());
+            } else {
+                println!(
+                    "{}",
+                    tr!("Task {} not found in project '{}'.", task_id, project_name)
+                );
+            }
+        }
+        if completed.is_empty() {
+            return;
+        }
+
+        save_data(&data);
+        for task in &completed {
+            println!(
+                "{}",
+                tr!(
+                    "Task {} in project '{}' is now completed!",
+                    task.id,
+                    project_name
+                )
+            );
+            hooks::run(Event::TaskComplete(project_name, task));
+            notify::task_completed(project_name, task);
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
+
+fn move_task(project_name: &str, task_id: u32, status: Status) {
+    if status == Status::Done {
+        return complete_task(project_name, task_id);
+    }
+
     let mut data = load_data();
     if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
+        let in_progress = project
+            .tasks
+            .iter()
+            .filter(|t| t.in_progress && !t.completed)
+            .count();
         if let Some(task) = project.tasks.iter_mut().find(|t| t.id == task_id) {
-            if task.completed {
-                println!("Task {} is already completed!", task_id);
+            if task.status() == status {
+                println!("{}", tr!("Task {} is already {}.", task_id, status));
+                return;
+            }
+
+            if status == Status::InProgress && !wip::allows_another(project_name, in_progress) {
                 return;
             }
-            task.completed = true;
+
+            let reopened = task.completed;
+            if reopened {
+                task.reopen();
+            }
+            task.in_progress = status == Status::InProgress;
+            task.record(TaskEvent::Moved, Some(format!("to {}", status)));
+            let task = task.clone();
             save_data(&data);
             println!(
-                "Task {} in project '{}' is now completed!",
-                task_id, project_name
+                "{}",
+                tr!(
+                    "Task {} in project '{}' moved to {}.",
+                    task_id,
+                    project_name,
+                    status
+                )
             );
+            if reopened {
+                hooks::run(Event::TaskReopen(project_name, &task));
+            }
         } else {
-            println!("Task {} not found in project '{}'.", task_id, project_name);
+            println!(
+                "{}",
+                tr!("Task {} not found in project '{}'.", task_id, project_name)
+            );
         }
     } else {
-        println!("Project '{}' not found.", project_name);
+        println!("{}", tr!("Project '{}' not found.", project_name));
     }
 }
 
-pub fn delete_task(project_name: &str, task_id: u32) {
+fn reopen_task(project_name: &str, task_id: u32) {
     let mut data = load_data();
     if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
-        let initial_len = project.tasks.len();
+        if let Some(task) = project.tasks.iter_mut().find(|t| t.id == task_id) {
+            if !task.completed {
+                println!("{}", tr!("Task {} is not completed.", task_id));
+                return;
+            }
+            task.reopen();
+            let task = task.clone();
+            save_data(&data);
+            println!(
+                "{}",
+                tr!(
+                    "Task {} in project '{}' has been reopened.",
+                    task_id,
+                    project_name
+                )
+            );
+            hooks::run(Event::TaskReopen(project_name, &task));
+        } else {
+            println!(
+                "{}",
+                tr!("Task {} not found in project '{}'.", task_id, project_name)
+            );
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
 
-        // Retain tasks that do not have the task_id
-        project.tasks.retain(|t| t.id != task_id);
-        if project.tasks.len() < initial_len {
+fn set_due(project_name: &str, task_id: u32, date: &str) {
+    let due = if date.eq_ignore_ascii_case("none") {
+        None
+    } else {
+        match dates::parse_date(date) {
+            Ok(due) => Some(due),
+            Err(e) => {
+                println!("{}", tr!("Invalid date: {}", e));
+                return;
+            }
+        }
+    };
+
+    let mut data = load_data();
+    if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
+        if let Some(task) = project.tasks.iter_mut().find(|t| t.id == task_id) {
+            task.due = due;
+            let detail = match due {
+                Some(due) => format!("due date set to {}", due),
+                None => "due date cleared".to_string(),
+            };
+            task.record(TaskEvent::Edited, Some(detail));
+            save_data(&data);
+            match due {
+                Some(due) => println!("{}", tr!("Task {} is now due on {}.", task_id, due)),
+                None => println!("{}", tr!("Task {} no longer has a due date.", task_id)),
+            }
+        } else {
+            println!(
+                "{}",
+                tr!("Task {} not found in project '{}'.", task_id, project_name)
+            );
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
+
+fn set_priority(project_name: &str, task_id: u32, priority: &str) {
+    let priority = if priority.eq_ignore_ascii_case("none") {
+        None
+    } else {
+        match Priority::from_str(priority, true) {
+            Ok(priority) => Some(priority),
+            Err(_) => {
+                println!(
+                    "{}",
+                    tr!(
+                        "Invalid priority '{}': use low, medium, high or none.",
+                        priority
+                    )
+                );
+                return;
+            }
+        }
+    };
+
+    let mut data = load_data();
+    if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
+        if let Some(task) = project.tasks.iter_mut().find(|t| t.id == task_id) {
+            task.priority = priority;
+            let detail = match priority {
+                Some(priority) => format!("priority set to {}", priority),
+                None => "priority cleared".to_string(),
+            };
+            task.record(TaskEvent::Edited, Some(detail));
+            save_data(&data);
+            match priority {
+                Some(priority) => {
+                    println!("{}", tr!("Task {} now has {} priority.", task_id, priority))
+                }
+                None => println!("{}", tr!("Task {} no longer has a priority.", task_id)),
+            }
+        } else {
+            println!(
+                "{}",
+                tr!("Task {} not found in project '{}'.", task_id, project_name)
+            );
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
+
+pub fn delete_task(project_name: &str, task_id: u32) {
+    if !protect::confirm(project_name, &tr!("deleting task {}", task_id)) {
+        return;
+    }
+    let mut data = load_data();
+    if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
+        if project.remove_task(task_id).is_some() {
+            links::unlink_deleted(&mut data, project_name, Some(task_id));
             save_data(&data);
+            sync::record_deletion(project_name, Some(task_id));
             println!(
-                "Task {} has been deleted from project '{}'.",
-                task_id, project_name
+                "{}",
+                tr!(
+                    "Task {} has been deleted from project '{}'.",
+                    task_id,
+                    project_name
+                )
             );
+            hooks::run(Event::TaskDelete(project_name, task_id));
         } else {
-            println!("Task {} not found in project '{}'.", task_id, project_name)
+            println!(
+                "{}",
+                tr!("Task {} not found in project '{}'.", task_id, project_name)
+            )
         }
     } else {
-        println!("Project '{}' not found.", project_name);
+        println!("{}", tr!("Project '{}' not found.", project_name));
     }
 }
 
 pub fn delete_project(project_name: &str) {
+    if !protect::confirm(project_name, &tr!("deleting it")) {
+        return;
+    }
     let mut data = load_data();
     let initial_len = data.len();
 
     // Retain projects that do not have the project_name
     data.retain(|p| p.name != project_name);
     if data.len() < initial_len {
+        for project in &mut data {
+            project.after.retain(|after| after != project_name);
+        }
+        links::unlink_deleted(&mut data, project_name, None);
         save_data(&data);
-        println!("Project '{}' has been deleted.", project_name);
+        sync::record_deletion(project_name, None);
+        println!("{}", tr!("Project '{}' has been deleted.", project_name));
+        hooks::run(Event::ProjectDelete(project_name));
     } else {
-        println!("Project '{}' not found.", project_name);
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
+
+/// Print the tasks of all projects for which `keep` holds, each prefixed
+/// with its project or through `template`, and return how many were
+/// printed.
+fn print_matching_tasks(
+    data: &[Project],
+    template: Option<&TaskTemplate>,
+    keep: impl Fn(&Project, &Task) -> bool,
+) -> usize {
+    let mut count = 0;
+    for project in data {
+        let shown = project
+            .tasks
+            .iter()
+            .filter(|t| contexts::shown(t) && keep(project, t));
+        for task in shown {
+            count += 1;
+            if let Some(template) = template {
+                println!("{}", template.render(project, task));
+                continue;
+            }
+            let checkbox = theme::checkbox(task.completed);
+            println!(
+                "    {} {}: {}{}",
+                checkbox,
+                project.task_ref(task.id),
+                display::description(task),
+                task_details(task)
+            );
+        }
     }
+    count
 }
 
+/// Extra information shown after a task's description in listings.
+fn task_details(task: &Task) -> String {
+    let mut details = String::new();
+
+    if let Some(priority) = task.priority {
+        details.push_str(&format!(" {}", theme::priority(priority)));
+    }
+
+    for tag in &task.tags {
+        details.push_str(&format!(" {}", format!("#{}", tag).cyan()));
+    }
+
+    for context in &task.contexts {
+        details.push_str(&format!(" {}", context.magenta()));
+    }
+
+    if !task.fields.is_empty() {
+        let fields: Vec<String> = task
+            .fields
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect();
+        details.push_str(&format!(" {{{}}}", fields.join(", ")));
+    }
+
+    if !task.checklist.is_empty() {
+        let (done, total) = task.checklist_progress();
+        details.push_str(&format!(" [{}/{}]", done, total));
+    }
+
+    match (task.time_spent(), task.estimate) {
+        (0, None) => {}
+        (0, Some(estimate)) => {
+            details.push_str(&format!(" (~{})", durations::format_duration(estimate)))
+        }
+        (spent, None) => details.push_str(&format!(" ({})", durations::format_duration(spent))),
+        (spent, Some(estimate)) => details.push_str(&format!(
+            " ({}/~{})",
+            durations::format_duration(spent),
+            durations::format_duration(estimate)
+        )),
+    }
+
+    if task.in_progress && !task.completed {
+        details.push_str(&format!(" {}", tr!("(in progress)").magenta()));
+    }
+
+    if let Some(scheduled) = task.scheduled.filter(|_| !task.completed) {
+        details.push_str(&format!(" ({})", tr!("scheduled {}", scheduled)));
+    }
+
+    if let Some(due) = task.due {
+        let label = format!(" ({})", tr!("due {}", due));
+        let today = dates::today();
+        let label = if task.completed || due > today {
+            label.normal()
+        } else if due == today {
+            label.warn()
+        } else {
+            label.bad()
+        };
+        details.push_str(&label.to_string());
+    }
+
+    if let Some(age) = aging::marker(task) {
+        details.push_str(&format!(" {}", age));
+    }
+
+    if let Some(until) = task.snoozed_until.filter(|_| task.is_snoozed()) {
+        details.push_str(&format!(" {}", tr!("(snoozed until {})", until).dimmed()));
+    }
+
+    if let Some(waiting) = task.waiting.as_ref().filter(|_| !task.completed) {
+        details.push_str(&format!(" {}", waiting::marker(waiting).warn()));
+    }
+
+    if let Some(reminder) = task.reminders.iter().find(|r| !r.fired && !task.completed) {
+        let at = reminder.at.format("%Y-%m-%d %H:%M");
+        details.push_str(&format!(" ({})", tr!("reminder {}", at)));
+    }
+
+    match task.attachments.as_slice() {
+        [] => {}
+        [attachment] => details.push_str(&format!(
+            " ({})",
+            hyperlinks::attachment(tr!("1 attachment"), attachment)
+        )),
+        attachments => {
+            details.push_str(&format!(" ({})", tr!("{} attachments", attachments.len())))
+        }
+    }
+
+    if let Some(links) = links::marker(task) {
+        details.push_str(&format!(" {}", links));
+    }
+
+    if logging::verbosity() > 0 {
+        if let Some(by) = task.created_by() {
+            details.push_str(&format!(" {}", tr!("(added by {})", by).dimmed()));
+        }
+        if let Some(by) = task.completed_by() {
+            details.push_str(&format!(" {}", tr!("(completed by {})", by).dimmed()));
+        }
+    }
+
+    details
+}
+
+/// How the overview sorts each project's tasks, unless the
+/// `overview-sort` setting says otherwise.
+const DEFAULT_OVERVIEW_SORT: &[Column] = &[Column::Status, Column::Priority, Column::Due];
+
 fn list_all_projects_and_tasks() {
-    let data = load_data();
+    let mut data = load_data();
+    let focus = focus::active();
+    if let Some(focus) = &focus {
+        data.retain(|p| &p.name == focus);
+    }
     if data.is_empty() {
-        println!("No projects found.");
+        println!("{}", tr!("No projects found."));
         return;
     }
 
-    println!("Projects:");
+    if let Some(banner) = achievements::banner() {
+        println!("{}", banner.bold());
+    }
+    if let Some(banner) = display::escalation_banner(&data) {
+        println!("{}", banner.bad().bold());
+    }
+    println!("{}", tr!("Projects:"));
+    if let Some(focus) = &focus {
+        println!(
+            "{}",
+            tr!("Focused on {}, `focus --clear` shows every project.", focus).dimmed()
+        );
+    }
+    if let Some(context) = contexts::active() {
+        println!("{}", tr!("Only showing tasks in {}.", context).dimmed());
+    }
 
-    for project in data {
-        println!("Project: \"{}\"", project.name);
+    let total_effort = estimates::effort(data.iter().flat_map(|p| &p.tasks));
 
-        // Calculate concluded %.
-        let total_tasks = project.tasks.len();
-        let completed_tasks = project.tasks.iter().filter(|t| t.completed).count();
-        let progress = if total_tasks > 0 {
-            completed_tasks as f64 / total_tasks as f64
-        } else {
-            0.0
-        };
+    for project in &data {
+        println!(
+            "{}",
+            tr!(
+                "Project: \"{}\"",
+                hyperlinks::project(&project.name, &project.name)
+            )
+        );
+        print_project_overview(project);
+    }
 
-        // Build progress bar.
-        let bar_width = 20;
-        let filled = (progress * bar_width as f64).round() as usize;
-        let filled_bar = "█".repeat(filled).green();
-        let empty_bar = " ".repeat(bar_width - filled);
-        let percentage = (progress * 100.0) as u8;
-        let progress_bar = format!(
-            "[{}{}] {}%",
-            filled_bar,
-            empty_bar,
-            percentage.to_string().bold().yellow()
+    if let Some(effort) = total_effort {
+        println!(
+            "{}",
+            tr!("Total effort: {}", estimates::format_effort(effort))
         );
+    }
+}
 
-        println!("Progress: {}", progress_bar);
+/// Print the progress and tasks of `project` for the overview, after its
+/// name.
+fn print_project_overview(project: &Project) {
+    let progress_bar = bars::progress_bar(project.progress());
 
-        if project.tasks.is_empty() {
-            println!("    No tasks yet.");
-        } else {
-            for task in &project.tasks {
-                let checkbox = if task.completed {
-                    "[x]".green()
-                } else {
-                    "[ ]".red()
-                };
-                println!("    {} {}: {}", checkbox, task.id, task.description);
-            }
+    let progress_bar = match estimates::effort(&project.tasks) {
+        Some(effort) => format!("{}  {}", progress_bar, estimates::format_effort(effort)),
+        None => progress_bar,
+    };
+
+    println!("{}", tr!("Progress: {}", progress_bar));
+    if let Some(summary) = goals::goal_summary(project) {
+        println!("{}", tr!("Goal: {}", summary));
+    }
+
+    if project.tasks.is_empty() {
+        println!("    {}", tr!("No tasks yet."));
+    } else {
+        let mut tasks: Vec<&Task> = project
+            .tasks
+            .iter()
+            .filter(|t| contexts::shown(t) && snooze::shown(t))
+            .collect();
+        let sort = load_config()
+            .effective_for(Some(&project.name))
+            .overview_sort
+            .unwrap_or_else(|| DEFAULT_OVERVIEW_SORT.to_vec());
+        table::sort(&mut tasks, &sort);
+        for task in tasks {
+            let checkbox = theme::checkbox(task.completed);
+            println!(
+                "    {} {}: {}{}",
+                checkbox,
+                project.display_id(task.id),
+                display::description(task),
+                task_details(task)
+            );
         }
-        println!();
     }
+    println!();
 }
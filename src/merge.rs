@@ -0,0 +1,402 @@
+//! Merging another copy of the data file, such as the conflicted copies
+//! sync tools leave behind, into the active workspace.
+//!
+//! Projects are matched by name and tasks by ID, unless their histories
+//! show that each side added a task of its own under the same ID: the other
+//! file's then gets a new one. When both sides changed a task, its history
+//! tells whether one side simply has more recent changes on top of the
+//! other; only tasks changed on both sides are conflicts.
+//! Tasks deleted on one side come back, as nothing records deletions.
+//!
+//! `merge-projects` merges two projects of the same data instead, say the
+//! `web` and `website` that came out of inconsistent naming.
+
+use dialoguer::{theme::ColorfulTheme, Select};
+use std::{collections::BTreeMap, path::Path};
+
+use crate::{
+    diff,
+    history::TaskEvent,
+    hooks::{self, Event},
+    i18n::tr,
+    links, load_data, picker, protect, save_data, storage, sync, Project, Task,
+};
+
+pub enum Resolution {
+    Mine,
+    Theirs,
+    Both,
+}
+
+/// Whether `a` and `b` are the same, ignoring nothing.
+fn same(a: &Task, b: &Task) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// Whether the history of `older` is the beginning of the history of
+/// `newer`, meaning `newer` is `older` with more changes on top. Without
+/// any history to go by, it isn't.
+pub fn continues(newer: &Task, older: &Task) -> bool {
+    !older.history.is_empty()
+        && newer.history.len() >= older.history.len()
+        && older
+            .history
+            .iter()
+            .zip(&newer.history)
+            .all(|(a, b)| a.at == b.at && a.event == b.event && a.detail == b.detail)
+}
+
+/// Whether `a` and `b`, of the same ID, are the same task. Each copy may
+/// have added a task of its own under the next free ID, which the first
+/// entries of their histories tell apart. Tasks without any history can't
+/// be told apart, and are taken to be the same; unless they're identical,
+/// they're then settled as conflicts.
+pub fn same_task(a: &Task, b: &Task) -> bool {
+    match (a.history.first(), b.history.first()) {
+        (Some(a), Some(b)) => a.at == b.at && a.event == b.event,
+        _ => true,
+    }
+}
+
+fn resolve(project: &str, mine: &Task, theirs: &Task) -> Resolution {
+    println!(
+        "{}",
+        tr!(
+            "Conflict in {}#{}: '{}' here, '{}' in the other file.",
+            project,
+            mine.id,
+            mine.description,
+            theirs.description
+        )
+    );
+
+    if !picker::is_interactive() {
+        // Without anyone to ask, the most recently changed one wins.
+        return if theirs.updated_at() > mine.updated_at() {
+            println!(
+                "    {}",
+                tr!("Keeping the other file's version, it changed last.")
+            );
+            Resolution::Theirs
+        } else {
+            println!("    {}", tr!("Keeping this version, it changed last."));
+            Resolution::Mine
+        };
+    }
+
+    let choice = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(tr!("Keep"))
+        .items([
+            tr!("this version"),
+            tr!("the other file's version"),
+            tr!("both"),
+        ])
+        .default(0)
+        .interact_opt()
+        .ok()
+        .flatten();
+    match choice {
+        Some(1) => Resolution::Theirs,
+        Some(2) => Resolution::Both,
+        _ => Resolution::Mine,
+    }
+}
+
+fn merge_project(
+    mine: &mut Project,
+    theirs: Project,
+    resolve: &mut impl FnMut(&str, &Task, &Task) -> Resolution,
+) {
+    mine.prefix = mine.prefix.take().or(theirs.prefix);
+    mine.deadline = mine.deadline.or(theirs.deadline);
+    mine.weekly_goal = mine.weekly_goal.or(theirs.weekly_goal);
+    for after in theirs.after {
+        if !mine.after.contains(&after) {
+            mine.after.push(after);
+        }
+    }
+    mine.next_id = mine.next_id.max(theirs.next_id);
+
+    for task in theirs.tasks {
+        let Some(current) = mine.tasks.iter_mut().find(|t| t.id == task.id) else {
+            mine.tasks.push(task);
+            continue;
+        };
+        if !same_task(current, &task) {
+            let id = mine.allocate_id();
+            mine.tasks.push(Task { id, ..task });
+            continue;
+        }
+        if same(current, &task) || continues(current, &task) {
+            continue;
+        }
+        if continues(&task, current) {
+            *current = task;
+            continue;
+        }
+
+        match resolve(&mine.name, current, &task) {
+            Resolution::Mine => {}
+            Resolution::Theirs => *current = task,
+            Resolution::Both => {
+                let id = mine.allocate_id();
+                mine.tasks.push(Task { id, ..task });
+            }
+        }
+    }
+}
+
+/// Merge `theirs` into `data`, settling conflicting tasks with `resolve`.
+pub fn merge_data(
+    data: &mut Vec<Project>,
+    theirs: Vec<Project>,
+    mut resolve: impl FnMut(&str, &Task, &Task) -> Resolution,
+) {
+    for project in theirs {
+        match data.iter_mut().find(|p| p.name == project.name) {
+            Some(mine) => merge_project(mine, project, &mut resolve),
+            None => data.push(project),
+        }
+    }
+}
+
+pub fn merge(file: &Path, dry_run: bool) {
+    let theirs = match storage::read_data_file(file) {
+        Ok(theirs) => theirs,
+        Err(e) => {
+            println!("{}", tr!("Unable to read '{}': {}", file.display(), e));
+            return;
+        }
+    };
+
+    let before = load_data();
+    let mut data = before.clone();
+    merge_data(&mut data, theirs, resolve);
+
+    let changes = diff::diff(&before, &data);
+    diff::print(&changes);
+    if changes.is_empty() {
+        return;
+    }
+    if dry_run {
+        println!("{}", tr!("Dry run, nothing was merged."));
+        return;
+    }
+    save_data(&data);
+    println!("{}", tr!("Merged '{}'.", file.display()));
+}
+
+/// Move the tasks of project `source` into `target`, renumbered after the
+/// ones already there, along with its goals and roadmap position, then
+/// delete `source`.
+pub fn merge_projects(source: &str, target: &str, dry_run: bool) {
+    if source == target {
+        println!("{}", tr!("Can't merge a project into itself."));
+        return;
+    }
+    let before = load_data();
+    for name in [source, target] {
+        if !before.iter().any(|p| p.name == name) {
+            println!("{}", tr!("Project '{}' not found.", name));
+            return;
+        }
+    }
+    if !dry_run && !protect::confirm(source, &tr!("merging it into {}", target)) {
+        return;
+    }
+
+    let mut data = before.clone();
+    let position = data
+        .iter()
+        .position(|p| p.name == source)
+        .expect("The project was found above");
+    let merged = data.remove(position);
+    let destination = data
+        .iter_mut()
+        .find(|p| p.name == target)
+        .expect("The project was found above");
+
+    let mut ids = BTreeMap::new();
+    for mut task in merged.tasks {
+        let id = destination.allocate_id();
+        ids.insert(task.id, id);
+        task.record(
+            TaskEvent::Edited,
+            Some(format!("merged from {}#{}", source, task.id)),
+        );
+        task.id = id;
+        destination.tasks.push(task);
+    }
+    destination.deadline = destination.deadline.or(merged.deadline);
+    destination.weekly_goal = destination.weekly_goal.or(merged.weekly_goal);
+    for after in merged.after {
+        if after != target && !destination.after.contains(&after) {
+            destination.after.push(after);
+        }
+    }
+    for project in &mut data {
+        if project.after.iter().any(|after| after == source) {
+            project.after.retain(|after| after != source);
+            if project.name != target && !project.after.iter().any(|after| after == target) {
+                project.after.push(target.to_string());
+            }
+        }
+    }
+    links::relink(&mut data, source, target, &ids);
+
+    diff::print(&diff::diff(&before, &data));
+    if dry_run {
+        println!("{}", tr!("Dry run, nothing was merged."));
+        return;
+    }
+    save_data(&data);
+    sync::record_deletion(source, None);
+    println!(
+        "{}",
+        tr!(
+            "Merged {} tasks from '{}' into '{}', and deleted '{}'.",
+            ids.len(),
+            source,
+            target,
+            source
+        )
+    );
+    hooks::run(Event::ProjectDelete(source));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    use crate::history::HistoryEntry;
+
+    /// A task whose history has an entry for each of `events`, an hour
+    /// apart from `start`.
+    fn task(id: u32, description: &str, start: u32, events: &[TaskEvent]) -> Task {
+        let history = events
+            .iter()
+            .enumerate()
+            .map(|(i, &event)| HistoryEntry {
+                at: Local
+                    .with_ymd_and_hms(2026, 7, 1, start + i as u32, 0, 0)
+                    .unwrap(),
+                event,
+                detail: None,
+                by: None,
+            })
+            .collect();
+        Task {
+            id,
+            description: description.to_string(),
+            history,
+            ..Default::default()
+        }
+    }
+
+    fn project(tasks: Vec<Task>) -> Vec<Project> {
+        vec![Project {
+            name: "web".to_string(),
+            next_id: tasks.iter().map(|t| t.id + 1).max().unwrap_or(1),
+            tasks,
+            ..Default::default()
+        }]
+    }
+
+    fn descriptions(data: &[Project]) -> Vec<(u32, &str)> {
+        data[0]
+            .tasks
+            .iter()
+            .map(|t| (t.id, t.description.as_str()))
+            .collect()
+    }
+
+    /// Merge, failing if any task is a conflict.
+    fn merge_without_conflicts(data: &mut Vec<Project>, theirs: Vec<Project>) {
+        merge_data(data, theirs, |_, mine, theirs| {
+            panic!(
+                "'{}' and '{}' are a conflict",
+                mine.description, theirs.description
+            )
+        });
+    }
+
+    #[test]
+    fn tasks_without_history_are_conflicts_when_they_differ() {
+        let mut data = project(vec![task(6, "six", 0, &[])]);
+        let theirs = project(vec![task(6, "theirs six", 0, &[])]);
+
+        let mut conflicts = Vec::new();
+        merge_data(&mut data, theirs, |_, mine, theirs| {
+            conflicts.push((mine.description.clone(), theirs.description.clone()));
+            Resolution::Theirs
+        });
+        assert_eq!(conflicts, [("six".to_string(), "theirs six".to_string())]);
+        assert_eq!(descriptions(&data), [(6, "theirs six")]);
+    }
+
+    #[test]
+    fn identical_tasks_without_history_are_left_alone() {
+        let mut data = project(vec![task(6, "six", 0, &[])]);
+        merge_without_conflicts(&mut data, project(vec![task(6, "six", 0, &[])]));
+        assert_eq!(descriptions(&data), [(6, "six")]);
+    }
+
+    #[test]
+    fn tasks_added_on_each_side_under_one_id_are_both_kept() {
+        let mut data = project(vec![task(6, "mine", 1, &[TaskEvent::Created])]);
+        let theirs = project(vec![task(6, "theirs", 2, &[TaskEvent::Created])]);
+        merge_without_conflicts(&mut data, theirs);
+        assert_eq!(descriptions(&data), [(6, "mine"), (7, "theirs")]);
+        assert_eq!(data[0].next_id, 8);
+    }
+
+    #[test]
+    fn changes_on_top_of_the_other_side_win() {
+        let created = [TaskEvent::Created];
+        let edited = [TaskEvent::Created, TaskEvent::Edited];
+
+        // Theirs has more changes: it's taken.
+        let mut data = project(vec![task(6, "six", 0, &created)]);
+        merge_without_conflicts(&mut data, project(vec![task(6, "edited", 0, &edited)]));
+        assert_eq!(descriptions(&data), [(6, "edited")]);
+
+        // Mine has more changes: it stays.
+        let mut data = project(vec![task(6, "edited", 0, &edited)]);
+        merge_without_conflicts(&mut data, project(vec![task(6, "six", 0, &created)]));
+        assert_eq!(descriptions(&data), [(6, "edited")]);
+    }
+
+    #[test]
+    fn tasks_changed_on_both_sides_are_conflicts() {
+        let mut mine = task(6, "mine", 0, &[TaskEvent::Created, TaskEvent::Edited]);
+        mine.history[1].detail = Some("here".to_string());
+        let mut theirs = task(6, "theirs", 0, &[TaskEvent::Created, TaskEvent::Edited]);
+        theirs.history[1].detail = Some("there".to_string());
+
+        let mut data = project(vec![mine]);
+        let mut conflicts = 0;
+        merge_data(&mut data, project(vec![theirs]), |_, _, _| {
+            conflicts += 1;
+            Resolution::Both
+        });
+        assert_eq!(conflicts, 1);
+        assert_eq!(descriptions(&data), [(6, "mine"), (7, "theirs")]);
+    }
+
+    #[test]
+    fn new_tasks_and_projects_are_added() {
+        let mut data = project(vec![task(1, "one", 0, &[TaskEvent::Created])]);
+        let mut theirs = project(vec![
+            task(1, "one", 0, &[TaskEvent::Created]),
+            task(2, "two", 1, &[TaskEvent::Created]),
+        ]);
+        theirs.push(Project {
+            name: "api".to_string(),
+            ..Default::default()
+        });
+        merge_without_conflicts(&mut data, theirs);
+        assert_eq!(descriptions(&data), [(1, "one"), (2, "two")]);
+        assert_eq!(data[1].name, "api");
+    }
+}
@@ -0,0 +1,49 @@
+//! `overview --workspaces work,personal`: the overview of several
+//! workspaces at once, each project labeled with the workspace it's from,
+//! without merging their data files. It only ever reads them.
+
+use colored::Colorize;
+
+use crate::{estimates, i18n::tr, print_project_overview, storage, workspace, Project};
+
+pub fn overview(workspaces: &[String]) {
+    if let Some(missing) = workspaces.iter().find(|name| !workspace::exists(name)) {
+        println!("{}", tr!("Workspace '{}' not found.", missing));
+        return;
+    }
+    let data: Vec<(&String, Vec<Project>)> = workspaces
+        .iter()
+        .map(|name| (name, storage::load_workspace_data(name)))
+        .collect();
+    if data.iter().all(|(_, projects)| projects.is_empty()) {
+        println!("{}", tr!("No projects found."));
+        return;
+    }
+
+    println!(
+        "{}",
+        tr!("Projects in workspaces {}:", workspaces.join(", "))
+    );
+    for (name, projects) in &data {
+        for project in projects {
+            println!(
+                "{} {}",
+                tr!("Project: \"{}\"", project.name),
+                format!("[{}]", name).dimmed()
+            );
+            print_project_overview(project);
+        }
+    }
+
+    let total_effort = estimates::effort(
+        data.iter()
+            .flat_map(|(_, projects)| projects)
+            .flat_map(|p| &p.tasks),
+    );
+    if let Some(effort) = total_effort {
+        println!(
+            "{}",
+            tr!("Total effort: {}", estimates::format_effort(effort))
+        );
+    }
+}
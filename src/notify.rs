@@ -0,0 +1,122 @@
+//! Desktop notifications and sounds, for a little reward on completion and
+//! for reminders.
+
+use std::{
+    io::{self, Write},
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::{config::load_config, i18n::tr, privacy, storage, Task};
+
+/// Something that can let the user know an event happened.
+trait Notifier {
+    fn notify(&self, title: &str, body: &str) -> io::Result<()>;
+}
+
+/// Spawn `command` without waiting for it, so slow notification daemons
+/// never hold up the command line.
+fn spawn(mut command: Command) -> io::Result<()> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
+
+struct Desktop;
+
+impl Notifier for Desktop {
+    fn notify(&self, title: &str, body: &str) -> io::Result<()> {
+        if cfg!(target_os = "macos") {
+            let script = format!("display notification {:?} with title {:?}", body, title);
+            let mut command = Command::new("osascript");
+            command.args(["-e", &script]);
+            spawn(command)
+        } else if cfg!(windows) {
+            let script = format!(
+                "New-BurntToastNotification -Text '{}', '{}'",
+                title.replace('\'', "''"),
+                body.replace('\'', "''")
+            );
+            let mut command = Command::new("powershell");
+            command.args(["-NoProfile", "-Command", &script]);
+            spawn(command)
+        } else {
+            let mut command = Command::new("notify-send");
+            command.args(["--app-name=project-tracker", title, body]);
+            spawn(command)
+        }
+    }
+}
+
+struct Sound;
+
+impl Notifier for Sound {
+    fn notify(&self, _title: &str, _body: &str) -> io::Result<()> {
+        let (player, file) = if cfg!(target_os = "macos") {
+            ("afplay", "/System/Library/Sounds/Glass.aiff")
+        } else {
+            (
+                "paplay",
+                "/usr/share/sounds/freedesktop/stereo/complete.oga",
+            )
+        };
+
+        if Path::new(file).exists() {
+            let mut command = Command::new(player);
+            command.arg(file);
+            if spawn(command).is_ok() {
+                return Ok(());
+            }
+        }
+
+        // No sound player around, the terminal bell will have to do.
+        let mut stdout = io::stdout();
+        stdout.write_all(b"\x07")?;
+        stdout.flush()
+    }
+}
+
+fn notifiers() -> Vec<Box<dyn Notifier>> {
+    let settings = load_config().effective();
+    let mut notifiers: Vec<Box<dyn Notifier>> = Vec::new();
+    if settings.notify.unwrap_or(false) {
+        notifiers.push(Box::new(Desktop));
+    }
+    if settings.sound.unwrap_or(false) {
+        notifiers.push(Box::new(Sound));
+    }
+    notifiers
+}
+
+pub fn task_completed(project_name: &str, task: &Task) {
+    if storage::is_dry_run() {
+        return;
+    }
+    let title = tr!("Task completed in '{}'", project_name);
+    for notifier in notifiers() {
+        if let Err(e) = notifier.notify(&title, privacy::description(task)) {
+            eprintln!("{}", tr!("Unable to send notification: {}", e));
+        }
+    }
+}
+
+/// Remind the user of `task`. Reminders go to the desktop whatever the
+/// `notify` setting, that's all they're for.
+pub fn reminder(project_name: &str, task: &Task) {
+    if storage::is_dry_run() {
+        return;
+    }
+    let mut notifiers = notifiers();
+    if load_config().effective().notify != Some(true) {
+        notifiers.push(Box::new(Desktop));
+    }
+    let title = tr!("Reminder from '{}'", project_name);
+    for notifier in notifiers {
+        if let Err(e) = notifier.notify(&title, privacy::description(task)) {
+            eprintln!("{}", tr!("Unable to send notification: {}", e));
+        }
+    }
+}
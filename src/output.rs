@@ -0,0 +1,30 @@
+//! Plain output, for wherever decorations come out as garbage: with
+//! `--plain`, when stdout isn't a terminal, say it's redirected to a file,
+//! or in a dumb terminal, there are no colors, no progress bars and only
+//! ASCII characters. `CLICOLOR_FORCE` keeps them when piping on purpose,
+//! as into `less -R`.
+
+use std::{
+    env,
+    io::{stdout, IsTerminal},
+    sync::OnceLock,
+};
+
+static PLAIN_FLAG: OnceLock<bool> = OnceLock::new();
+
+/// Record whether `--plain` was given.
+pub fn set_plain(plain: bool) {
+    let _ = PLAIN_FLAG.set(plain);
+}
+
+/// Whether the output can't take anything but plain text.
+fn detected() -> bool {
+    let forced = env::var("CLICOLOR_FORCE").is_ok_and(|v| !v.is_empty() && v != "0");
+    let dumb = env::var("TERM").is_ok_and(|term| term == "dumb");
+    !forced && (dumb || !stdout().is_terminal())
+}
+
+pub fn is_plain() -> bool {
+    static PLAIN: OnceLock<bool> = OnceLock::new();
+    *PLAIN.get_or_init(|| PLAIN_FLAG.get().copied().unwrap_or(false) || detected())
+}
@@ -0,0 +1,349 @@
+//! `export --format pdf`: a printable status report, one A4 page per
+//! project with its progress bar and a table of its tasks.
+//!
+//! The PDF is written by hand, with the Helvetica fonts every PDF reader
+//! has built in, so text is limited to the Windows-1252 characters they
+//! cover; anything else shows as `?`.
+
+use chrono::Local;
+
+use crate::{dates, i18n::tr, progress, Priority, Project, Status, Task};
+
+const PAGE_WIDTH: f64 = 595.0;
+const PAGE_HEIGHT: f64 = 842.0;
+const MARGIN: f64 = 50.0;
+const ROW_HEIGHT: f64 = 16.0;
+/// Lowest a table row goes before the rest is left out.
+const TABLE_BOTTOM: f64 = 80.0;
+
+/// Widths of the printable ASCII characters in Helvetica, in thousandths
+/// of the font size, from the font's metrics.
+const WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Width of `text` in Helvetica at `size`, close enough for bold too.
+fn text_width(text: &str, size: f64) -> f64 {
+    let units: u32 = text
+        .chars()
+        .map(|c| match c {
+            ' '..='~' => u32::from(WIDTHS[c as usize - 32]),
+            _ => 556,
+        })
+        .sum();
+    f64::from(units) * size / 1000.0
+}
+
+/// `text` cut to fit in `width`, with an ellipsis if it had to be.
+fn fit(text: &str, width: f64, size: f64) -> String {
+    if text_width(text, size) <= width {
+        return text.to_string();
+    }
+    let mut fitted = String::new();
+    for c in text.chars() {
+        fitted.push(c);
+        if text_width(&fitted, size) + text_width("...", size) > width {
+            fitted.pop();
+            break;
+        }
+    }
+    format!("{}...", fitted.trim_end())
+}
+
+/// `text` as a PDF string literal, in the fonts' Windows-1252 encoding.
+fn literal(text: &str) -> Vec<u8> {
+    let mut bytes = vec![b'('];
+    for c in text.chars() {
+        let byte = match c {
+            '(' | ')' | '\\' => {
+                bytes.push(b'\\');
+                c as u8
+            }
+            ' '..='~' | '\u{a0}'..='\u{ff}' => c as u8,
+            '€' => 0x80,
+            '…' => 0x85,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            _ => b'?',
+        };
+        bytes.push(byte);
+    }
+    bytes.push(b')');
+    bytes
+}
+
+/// The drawing operators of a page.
+#[derive(Default)]
+struct Page(Vec<u8>);
+
+impl Page {
+    fn text(&mut self, x: f64, y: f64, size: f64, bold: bool, gray: f64, text: &str) {
+        let font = if bold { "F2" } else { "F1" };
+        self.0.extend(
+            format!(
+                "{:.2} g BT /{} {} Tf {:.1} {:.1} Td ",
+                gray, font, size, x, y
+            )
+            .bytes(),
+        );
+        self.0.extend(literal(text));
+        self.0.extend(b" Tj ET\n");
+    }
+
+    /// Text in a color, given as red, green and blue from 0 to 1.
+    fn colored_text(&mut self, x: f64, y: f64, size: f64, rgb: (f64, f64, f64), text: &str) {
+        self.0.extend(
+            format!(
+                "{:.2} {:.2} {:.2} rg BT /F1 {} Tf {:.1} {:.1} Td ",
+                rgb.0, rgb.1, rgb.2, size, x, y
+            )
+            .bytes(),
+        );
+        self.0.extend(literal(text));
+        self.0.extend(b" Tj ET\n");
+    }
+
+    fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, rgb: (f64, f64, f64)) {
+        self.0.extend(
+            format!(
+                "{:.2} {:.2} {:.2} rg {:.1} {:.1} {:.1} {:.1} re f\n",
+                rgb.0, rgb.1, rgb.2, x, y, width, height
+            )
+            .bytes(),
+        );
+    }
+
+    fn line(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, gray: f64) {
+        self.0.extend(
+            format!(
+                "{:.2} G 0.5 w {:.1} {:.1} m {:.1} {:.1} l S\n",
+                gray, x1, y1, x2, y2
+            )
+            .bytes(),
+        );
+    }
+}
+
+const GREEN: (f64, f64, f64) = (0.2, 0.6, 0.2);
+const RED: (f64, f64, f64) = (0.8, 0.13, 0.13);
+const LIGHT: (f64, f64, f64) = (0.93, 0.93, 0.93);
+const STRIPE: (f64, f64, f64) = (0.97, 0.97, 0.97);
+
+/// Where the columns of the task table start.
+const COLUMNS: [f64; 5] = [
+    MARGIN,
+    MARGIN + 40.0,
+    MARGIN + 315.0,
+    MARGIN + 385.0,
+    MARGIN + 440.0,
+];
+
+/// Titles of the columns of the task table, in the user's language.
+fn column_titles() -> [String; 5] {
+    [
+        tr!("ID"),
+        tr!("Task"),
+        tr!("Status"),
+        tr!("Priority"),
+        tr!("Due"),
+    ]
+}
+
+fn status(task: &Task) -> String {
+    match task.status() {
+        Status::Todo => tr!("To do"),
+        Status::InProgress => tr!("In progress"),
+        Status::Done => tr!("Done"),
+    }
+}
+
+fn project_page(project: &Project, generated: &str) -> Page {
+    let mut page = Page::default();
+    let mut y = PAGE_HEIGHT - MARGIN - 20.0;
+    page.text(MARGIN, y, 20.0, true, 0.1, &project.name);
+    y -= 18.0;
+    page.text(
+        MARGIN,
+        y,
+        9.0,
+        false,
+        0.5,
+        &tr!("Status report, {}", generated),
+    );
+
+    let total = project.tasks.len();
+    let done = project.tasks.iter().filter(|t| t.completed).count();
+    let progress = project.progress();
+    y -= 36.0;
+    let bar_width = PAGE_WIDTH - 2.0 * MARGIN - 60.0;
+    page.rect(MARGIN, y, bar_width, 12.0, LIGHT);
+    page.rect(MARGIN, y, bar_width * progress, 12.0, GREEN);
+    page.text(
+        MARGIN + bar_width + 10.0,
+        y + 2.0,
+        11.0,
+        true,
+        0.1,
+        &format!("{}%", progress::percent(progress)),
+    );
+    y -= 18.0;
+    let summary = match project.deadline {
+        Some(deadline) => tr!(
+            "{} of {} tasks completed, deadline {}",
+            done,
+            total,
+            deadline
+        ),
+        None => tr!("{} of {} tasks completed", done, total),
+    };
+    page.text(MARGIN, y, 10.0, false, 0.3, &summary);
+
+    y -= 36.0;
+    if project.tasks.is_empty() {
+        page.text(MARGIN, y, 11.0, false, 0.5, &tr!("No tasks yet."));
+        return page;
+    }
+    for (title, x) in column_titles().iter().zip(COLUMNS) {
+        page.text(x, y, 9.0, true, 0.3, title);
+    }
+    y -= 6.0;
+    page.line(MARGIN, y, PAGE_WIDTH - MARGIN, y, 0.6);
+
+    // Work left first, then what's done.
+    let mut tasks: Vec<&Task> = project.tasks.iter().collect();
+    tasks.sort_by_key(|t| t.completed);
+    let today = dates::today();
+    let description_width = COLUMNS[2] - COLUMNS[1] - 10.0;
+    for (index, task) in tasks.iter().enumerate() {
+        if y - ROW_HEIGHT < TABLE_BOTTOM {
+            let left = tasks.len() - index;
+            page.text(
+                MARGIN,
+                y - ROW_HEIGHT + 4.0,
+                9.0,
+                false,
+                0.5,
+                &tr!("... and {} more tasks", left),
+            );
+            break;
+        }
+        y -= ROW_HEIGHT;
+        if index % 2 == 1 {
+            page.rect(
+                MARGIN - 4.0,
+                y - 4.0,
+                PAGE_WIDTH - 2.0 * MARGIN + 8.0,
+                ROW_HEIGHT,
+                STRIPE,
+            );
+        }
+        let gray = if task.completed { 0.55 } else { 0.1 };
+        page.text(
+            COLUMNS[0],
+            y,
+            9.0,
+            false,
+            gray,
+            &project.display_id(task.id),
+        );
+        page.text(
+            COLUMNS[1],
+            y,
+            9.0,
+            false,
+            gray,
+            &fit(&task.description, description_width, 9.0),
+        );
+        page.text(COLUMNS[2], y, 9.0, false, gray, &status(task));
+        match task.priority {
+            Some(Priority::High) if !task.completed => {
+                page.colored_text(COLUMNS[3], y, 9.0, RED, "high")
+            }
+            Some(priority) => page.text(COLUMNS[3], y, 9.0, false, gray, &priority.to_string()),
+            None => {}
+        }
+        if let Some(due) = task.due {
+            if !task.completed && due < today {
+                page.colored_text(COLUMNS[4], y, 9.0, RED, &due.to_string());
+            } else {
+                page.text(COLUMNS[4], y, 9.0, false, gray, &due.to_string());
+            }
+        }
+    }
+    page
+}
+
+/// The report on `projects`, as the bytes of a PDF file.
+pub fn report(projects: &[&Project]) -> Vec<u8> {
+    let generated = Local::now().format("%Y-%m-%d %H:%M").to_string();
+    let pages: Vec<Page> = projects
+        .iter()
+        .map(|project| project_page(project, &generated))
+        .collect();
+
+    // Objects 1 to 4 are the catalog, the page tree and the two fonts,
+    // then each page and its content follow.
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 5 + 2 * i).collect();
+    let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+    let mut objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        )
+        .into_bytes(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_vec(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+            .to_vec(),
+    ];
+    for (page, id) in pages.into_iter().zip(&page_ids) {
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH,
+                PAGE_HEIGHT,
+                id + 1
+            )
+            .into_bytes(),
+        );
+        let mut stream = format!("<< /Length {} >>\nstream\n", page.0.len()).into_bytes();
+        stream.extend(page.0);
+        stream.extend(b"\nendstream");
+        objects.push(stream);
+    }
+
+    let mut pdf = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n", index + 1).bytes());
+        pdf.extend(object);
+        pdf.extend(b"\nendobj\n");
+    }
+    let xref = pdf.len();
+    pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).bytes());
+    for offset in offsets {
+        pdf.extend(format!("{:010} 00000 n \n", offset).bytes());
+    }
+    pdf.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .bytes(),
+    );
+    pdf
+}
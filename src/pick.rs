@@ -0,0 +1,67 @@
+//! `pick`: a pending task chosen at random among those that fit, say, the
+//! half hour before the next meeting.
+
+use colored::Colorize;
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::{contexts, display, durations, i18n::tr, load_data, snooze, tags, task_details, Task};
+
+/// Whether `task` is pending and fits in `max_estimate` minutes with all of
+/// `tags`. Without an estimate, a task can't be known to fit.
+fn fits(task: &Task, max_estimate: Option<u32>, tags: &[String]) -> bool {
+    !task.completed
+        && contexts::shown(task)
+        && snooze::shown(task)
+        && max_estimate.is_none_or(|max| task.estimate.is_some_and(|e| e <= max))
+        && tags.iter().all(|tag| task.tags.contains(tag))
+}
+
+fn random_index(len: usize) -> usize {
+    let mut bytes = [0; 8];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .expect("Unable to generate random bytes.");
+    (u64::from_le_bytes(bytes) % len as u64) as usize
+}
+
+pub fn pick(project_name: Option<&str>, max_estimate: Option<u32>, tags: &[String]) {
+    let data = load_data();
+    if let Some(name) = project_name {
+        if !data.iter().any(|p| p.name == name) {
+            println!("{}", tr!("Project '{}' not found.", name));
+            return;
+        }
+    }
+    let tags: Vec<String> = tags.iter().map(|t| tags::normalize(t)).collect();
+
+    let candidates: Vec<_> = data
+        .iter()
+        .filter(|p| project_name.is_none_or(|name| p.name == name))
+        .flat_map(|p| p.tasks.iter().map(move |t| (p, t)))
+        .filter(|(_, t)| fits(t, max_estimate, &tags))
+        .collect();
+    if candidates.is_empty() {
+        println!("{}", tr!("No pending task fits."));
+        return;
+    }
+
+    let (project, task) = candidates[random_index(candidates.len())];
+    println!(
+        "{} {}: {}{}",
+        tr!("Picked").bold(),
+        project.task_ref(task.id),
+        display::description(task),
+        task_details(task)
+    );
+    if let Some(max) = max_estimate {
+        println!(
+            "{}",
+            tr!(
+                "one of {} tasks fitting in {}",
+                candidates.len(),
+                durations::format_duration(max)
+            )
+            .dimmed()
+        );
+    }
+}
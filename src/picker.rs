@@ -0,0 +1,126 @@
+//! Interactive fuzzy pickers, used when a command is invoked without the
+//! project and/or task it operates on, and checklists for picking several
+//! tasks at once.
+
+use dialoguer::{theme::ColorfulTheme, FuzzySelect, MultiSelect};
+use std::io::{stdin, IsTerminal};
+
+use crate::{focus, i18n::tr, load_data, privacy, Task};
+
+/// Whether there's someone to ask, which there never is in tests.
+pub fn is_interactive() -> bool {
+    !cfg!(test) && stdin().is_terminal()
+}
+
+fn fuzzy_select(prompt: &str, items: &[String]) -> Option<usize> {
+    FuzzySelect::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .items(items)
+        .default(0)
+        .interact_opt()
+        .ok()
+        .flatten()
+}
+
+/// Return `project` if given, otherwise the project focused on or the
+/// `default-project` setting, and otherwise let the user pick one.
+pub fn resolve_project(project: Option<&str>) -> Option<String> {
+    if let Some(project) = project {
+        return Some(project.to_string());
+    }
+    if let Some(project) = focus::default_project() {
+        return Some(project);
+    }
+
+    if !is_interactive() {
+        println!("{}", tr!("No project given."));
+        return None;
+    }
+
+    let names: Vec<String> = load_data().into_iter().map(|p| p.name).collect();
+    if names.is_empty() {
+        println!("{}", tr!("No projects found."));
+        return None;
+    }
+
+    fuzzy_select(&tr!("Project"), &names).map(|i| names[i].clone())
+}
+
+/// Return the project and task ID if both are given, otherwise let the user
+/// pick whatever is missing. Only tasks matching `filter` are offered.
+pub fn resolve_task(
+    project: Option<&str>,
+    task_id: Option<u32>,
+    filter: impl Fn(&Task) -> bool,
+) -> Option<(String, u32)> {
+    let project_name = resolve_project(project)?;
+    if let Some(task_id) = task_id {
+        return Some((project_name, task_id));
+    }
+
+    if !is_interactive() {
+        println!("{}", tr!("No task given."));
+        return None;
+    }
+
+    let data = load_data();
+    let Some(project) = data.iter().find(|p| p.name == project_name) else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+        return None;
+    };
+
+    let tasks: Vec<&Task> = project.tasks.iter().filter(|t| filter(t)).collect();
+    if tasks.is_empty() {
+        println!(
+            "{}",
+            tr!("No matching tasks in project '{}'.", project_name)
+        );
+        return None;
+    }
+
+    let items: Vec<String> = tasks
+        .iter()
+        .map(|t| format!("{}: {}", t.id, privacy::description(t)))
+        .collect();
+    let index = fuzzy_select(&tr!("Task"), &items)?;
+
+    Some((project_name, tasks[index].id))
+}
+
+/// Let the user tick any number of the tasks of `project_name` matching
+/// `filter`. Returns their IDs, or `None` if nothing was picked.
+pub fn pick_tasks(project_name: &str, filter: impl Fn(&Task) -> bool) -> Option<Vec<u32>> {
+    if !is_interactive() {
+        println!("{}", tr!("Picking tasks needs an interactive terminal."));
+        return None;
+    }
+
+    let data = load_data();
+    let Some(project) = data.iter().find(|p| p.name == project_name) else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+        return None;
+    };
+
+    let tasks: Vec<&Task> = project.tasks.iter().filter(|t| filter(t)).collect();
+    if tasks.is_empty() {
+        println!(
+            "{}",
+            tr!("No matching tasks in project '{}'.", project_name)
+        );
+        return None;
+    }
+
+    let items: Vec<String> = tasks
+        .iter()
+        .map(|t| format!("{}: {}", project.display_id(t.id), privacy::description(t)))
+        .collect();
+    let picked = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt(tr!("Tasks (space to select, enter to confirm)"))
+        .items(&items)
+        .interact_opt()
+        .ok()
+        .flatten()
+        .filter(|picked| !picked.is_empty())?;
+
+    Some(picked.into_iter().map(|i| tasks[i].id).collect())
+}
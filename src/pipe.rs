@@ -0,0 +1,45 @@
+//! Pipelines between commands: listings print tasks as `project:id` with
+//! `--ids`, and commands changing tasks read them back with `--stdin`, as in
+//! `search login --ids | complete-task --stdin`.
+
+use std::io;
+
+use crate::{i18n::tr, template::TaskTemplate};
+
+/// The template `--ids` prints tasks through.
+pub fn ids_template() -> TaskTemplate {
+    crate::template::parse_task_template("{project}:{id}").expect("Valid template")
+}
+
+/// Parse a `project:id` pair.
+fn parse_id(line: &str) -> Option<(&str, u32)> {
+    let (project, id) = line.rsplit_once(':')?;
+    Some((project, id.trim().parse().ok()?))
+}
+
+/// Read `project:id` pairs from stdin, one per line, grouped by project in
+/// the order they come. Prints why and returns `None` on a line that isn't
+/// one, before anything is changed.
+pub fn read_ids() -> Option<Vec<(String, Vec<u32>)>> {
+    let mut groups: Vec<(String, Vec<u32>)> = Vec::new();
+    for line in io::stdin().lines() {
+        let line = line.expect("Unable to read from stdin.");
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some((project, id)) = parse_id(line) else {
+            println!("{}", tr!("'{}' is not a task, expected project:id.", line));
+            return None;
+        };
+        match groups.iter_mut().find(|(name, _)| name == project) {
+            Some((_, ids)) => ids.push(id),
+            None => groups.push((project.to_string(), vec![id])),
+        }
+    }
+    if groups.is_empty() {
+        println!("{}", tr!("No tasks given on stdin."));
+        return None;
+    }
+    Some(groups)
+}
@@ -0,0 +1,105 @@
+//! Project prefixes: a project with the prefix `WEB` shows its tasks as
+//! `WEB-12`, which is unique across the whole tracker and can be used
+//! wherever a project and a task ID are expected, as in
+//! `complete-task WEB-12` (see [`crate::args`]).
+
+use crate::{i18n::tr, load_data, save_data, Project};
+
+impl Project {
+    /// How a task of this project is shown inside the project itself.
+    pub fn display_id(&self, task_id: u32) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{}-{}", prefix, task_id),
+            None => task_id.to_string(),
+        }
+    }
+
+    /// How a task of this project is referred to among other projects.
+    pub fn task_ref(&self, task_id: u32) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{}-{}", prefix, task_id),
+            None => format!("{}#{}", self.name, task_id),
+        }
+    }
+}
+
+/// Whether `prefix` is usable: a letter followed by letters or digits.
+fn is_valid(prefix: &str) -> bool {
+    prefix
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic())
+        && prefix.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Split `WEB-12` into `("WEB", 12)`.
+pub fn parse_ref(word: &str) -> Option<(&str, u32)> {
+    let (prefix, id) = word.rsplit_once('-')?;
+    let id = id.parse().ok()?;
+    is_valid(prefix).then_some((prefix, id))
+}
+
+/// The project `prefix` belongs to.
+pub fn find_project<'a>(data: &'a [Project], prefix: &str) -> Option<&'a Project> {
+    data.iter().find(|p| {
+        p.prefix
+            .as_deref()
+            .is_some_and(|p| p.eq_ignore_ascii_case(prefix))
+    })
+}
+
+pub fn set_prefix(project_name: &str, prefix: &str) {
+    let prefix = if prefix.eq_ignore_ascii_case("none") {
+        None
+    } else if is_valid(prefix) {
+        Some(prefix.to_uppercase())
+    } else {
+        println!(
+            "{}",
+            tr!(
+                "Invalid prefix '{}': use a letter followed by letters or digits.",
+                prefix
+            )
+        );
+        return;
+    };
+
+    let mut data = load_data();
+    if let Some(prefix) = &prefix {
+        if let Some(other) = data
+            .iter()
+            .find(|p| p.name != project_name && p.prefix.as_ref() == Some(prefix))
+        {
+            println!(
+                "{}",
+                tr!(
+                    "Prefix '{}' is already used by project '{}'.",
+                    prefix,
+                    other.name
+                )
+            );
+            return;
+        }
+    }
+
+    if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
+        project.prefix = prefix.clone();
+        save_data(&data);
+        match prefix {
+            Some(prefix) => println!(
+                "{}",
+                tr!(
+                    "Tasks of project '{}' are now numbered {}-<id>.",
+                    project_name,
+                    prefix
+                )
+            ),
+            None => println!(
+                "{}",
+                tr!("Project '{}' no longer has a prefix.", project_name)
+            ),
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
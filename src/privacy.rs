@@ -0,0 +1,178 @@
+//! Private tasks, for entries not meant for everyone's eyes: their
+//! descriptions show as "•••" in exports, webhooks and what `serve` hands
+//! out, along with anything else telling what they're about, in changelogs,
+//! commit messages and emailed digests, and in listings with `--redacted`,
+//! as when screen-sharing, while plain listings show them as usual.
+
+use std::sync::OnceLock;
+
+use crate::{history::TaskEvent, i18n::tr, load_data, save_data, Project, Task};
+
+/// What private descriptions are replaced with.
+pub const MASK: &str = "•••";
+
+static REDACTED: OnceLock<bool> = OnceLock::new();
+
+/// Record whether `--redacted` was given.
+pub fn set_redacted(redacted: bool) {
+    let _ = REDACTED.set(redacted);
+}
+
+pub fn is_redacted() -> bool {
+    REDACTED.get().copied().unwrap_or(false)
+}
+
+/// The description of `task` as listings show it, masked if it's private
+/// and they're redacted.
+pub fn description(task: &Task) -> &str {
+    if task.private && is_redacted() {
+        MASK
+    } else {
+        &task.description
+    }
+}
+
+/// The description of `task` as it's published, in changelogs, commit
+/// messages and emails: masked if it's private, redacted or not.
+pub fn public_description(task: &Task) -> &str {
+    if task.private {
+        MASK
+    } else {
+        &task.description
+    }
+}
+
+/// A copy of `task` fit to leave the machine: if it's private, with its
+/// description and everything else that could tell what it's about masked,
+/// its checklist, fields, attachments, notes and history details, and
+/// without its reminders.
+pub fn masked_task(task: &Task) -> Task {
+    let mut task = task.clone();
+    if !task.private {
+        return task;
+    }
+    task.description = MASK.to_string();
+    for item in &mut task.checklist {
+        item.text = MASK.to_string();
+    }
+    for value in task.fields.values_mut() {
+        *value = MASK.to_string();
+    }
+    for attachment in &mut task.attachments {
+        *attachment = MASK.to_string();
+    }
+    if let Some(waiting) = &mut task.waiting {
+        waiting.what = MASK.to_string();
+    }
+    for entry in &mut task.time_entries {
+        if entry.note.is_some() {
+            entry.note = Some(MASK.to_string());
+        }
+    }
+    for entry in &mut task.history {
+        if entry.detail.is_some() {
+            entry.detail = Some(MASK.to_string());
+        }
+    }
+    task.reminders.clear();
+    task
+}
+
+/// Whether `task` is a private task as it left the machine, masked.
+pub fn is_masked(task: &Task) -> bool {
+    task.private && task.description == MASK
+}
+
+/// A copy of `projects` fit to leave the machine, with private tasks
+/// masked.
+pub fn masked(projects: &[&Project]) -> Vec<Project> {
+    projects
+        .iter()
+        .map(|project| Project {
+            tasks: project.tasks.iter().map(masked_task).collect(),
+            ..(*project).clone()
+        })
+        .collect()
+}
+
+/// Mark a task as private, or as not private anymore.
+pub fn set_private(project_name: &str, task_id: u32, private: bool) {
+    let mut data = load_data();
+    let Some(project) = data.iter_mut().find(|p| p.name == project_name) else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+        return;
+    };
+    let Some(task) = project.tasks.iter_mut().find(|t| t.id == task_id) else {
+        println!(
+            "{}",
+            tr!("Task {} not found in project '{}'.", task_id, project_name)
+        );
+        return;
+    };
+    if task.private == private {
+        let message = if private {
+            tr!("Task {} is already private.", task_id)
+        } else {
+            tr!("Task {} isn't private.", task_id)
+        };
+        println!("{}", message);
+        return;
+    }
+    task.private = private;
+    let detail = if private {
+        "made private"
+    } else {
+        "no longer private"
+    };
+    task.record(TaskEvent::Edited, Some(detail.to_string()));
+    save_data(&data);
+    let message = if private {
+        tr!("Task {} is now private.", task_id)
+    } else {
+        tr!("Task {} is no longer private.", task_id)
+    };
+    println!("{}", message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    use crate::checklist::ChecklistItem;
+
+    fn task(private: bool) -> Task {
+        Task {
+            id: 1,
+            description: "see the doctor".to_string(),
+            private,
+            checklist: vec![ChecklistItem {
+                text: "book it".to_string(),
+                done: false,
+            }],
+            fields: BTreeMap::from([("clinic".to_string(), "downtown".to_string())]),
+            attachments: vec!["referral.pdf".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn private_tasks_are_never_published() {
+        assert_eq!(public_description(&task(true)), MASK);
+        assert_eq!(public_description(&task(false)), "see the doctor");
+    }
+
+    #[test]
+    fn masked_tasks_tell_nothing_of_what_they_are_about() {
+        let masked = masked_task(&task(true));
+        assert!(is_masked(&masked));
+        assert_eq!(masked.checklist[0].text, MASK);
+        assert_eq!(masked.fields["clinic"], MASK);
+        assert_eq!(masked.attachments, [MASK]);
+
+        let shown = masked_task(&task(false));
+        assert!(!is_masked(&shown));
+        assert_eq!(shown.description, "see the doctor");
+        assert_eq!(shown.checklist[0].text, "book it");
+    }
+}
@@ -0,0 +1,252 @@
+//! How far along projects are, the same in every view: by the number of
+//! tasks completed or, with the `progress-weighting` setting at `estimate`,
+//! by their estimates, so that finishing an 8-hour task counts for more
+//! than three 5-minute ones.
+//!
+//! Also a single line of project progress for shell prompts and status
+//! bars, e.g. `status --project web --format "{completed}/{total} {percent}%"`.
+//!
+//! The format is a template as described in the `template` module.
+//!
+//! `prompt-segment` is terser still, `3!/12` for 3 overdue tasks out of 12
+//! pending, and cached, so even a large tracker doesn't slow the prompt.
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::PathBuf,
+    sync::OnceLock,
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    config::load_config,
+    dates, get_config_dir,
+    i18n::tr,
+    load_data, remote, storage,
+    template::{self, Piece},
+    workspace, Project, Task,
+};
+
+/// What tasks count for in a project's progress.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Weighting {
+    /// All tasks the same.
+    Count,
+    /// Tasks as much as their estimate, those without one as much as the
+    /// average estimate of the others.
+    Estimate,
+}
+
+/// The `progress-weighting` setting, read once since every project shown
+/// needs it.
+pub fn weighting() -> Weighting {
+    static WEIGHTING: OnceLock<Weighting> = OnceLock::new();
+    *WEIGHTING.get_or_init(|| {
+        load_config()
+            .effective()
+            .progress_weighting
+            .unwrap_or(Weighting::Count)
+    })
+}
+
+/// Share of `tasks` completed, from 0 to 1, weighted as `weighting` says.
+/// Without any estimate, tasks are counted whatever the weighting.
+pub fn completion_by<'a>(tasks: impl IntoIterator<Item = &'a Task>, weighting: Weighting) -> f64 {
+    let tasks: Vec<&Task> = tasks.into_iter().collect();
+    let estimates: Vec<f64> = tasks
+        .iter()
+        .filter_map(|t| t.estimate)
+        .map(f64::from)
+        .collect();
+    let average = match weighting {
+        Weighting::Estimate if !estimates.is_empty() => {
+            Some(estimates.iter().sum::<f64>() / estimates.len() as f64)
+        }
+        _ => None,
+    };
+    let weight = |task: &&Task| match average {
+        Some(average) => task.estimate.map_or(average, f64::from),
+        None => 1.0,
+    };
+
+    let total: f64 = tasks.iter().map(weight).sum();
+    if total == 0.0 {
+        return 0.0;
+    }
+    tasks
+        .iter()
+        .filter(|t| t.completed)
+        .map(weight)
+        .sum::<f64>()
+        / total
+}
+
+/// Share of `tasks` completed, from 0 to 1, weighted as the
+/// `progress-weighting` setting says.
+pub fn completion<'a>(tasks: impl IntoIterator<Item = &'a Task>) -> f64 {
+    completion_by(tasks, weighting())
+}
+
+/// `completion` as a whole percentage, rounded down.
+pub fn percent(completion: f64) -> usize {
+    // Rounding errors aside: 0.29 * 100.0 is just short of 29.
+    (completion * 100.0 + 1e-9) as usize
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Placeholder {
+    Project,
+    Completed,
+    Total,
+    Remaining,
+    InProgress,
+    Overdue,
+    Percent,
+}
+
+const PLACEHOLDERS: &[(&str, Placeholder)] = &[
+    ("project", Placeholder::Project),
+    ("completed", Placeholder::Completed),
+    ("total", Placeholder::Total),
+    ("remaining", Placeholder::Remaining),
+    ("in_progress", Placeholder::InProgress),
+    ("overdue", Placeholder::Overdue),
+    ("percent", Placeholder::Percent),
+];
+
+/// A parsed `--format` template.
+#[derive(Debug, Clone)]
+pub struct Template(Vec<Piece<Placeholder>>);
+
+pub const DEFAULT_FORMAT: &str = "{completed}/{total} ({percent}%)";
+
+/// Parse a `--format` template.
+///
+/// Meant to be used as a clap `value_parser`, hence the `String` error.
+pub fn parse_template(input: &str) -> Result<Template, String> {
+    template::parse(input, PLACEHOLDERS).map(Template)
+}
+
+impl Template {
+    fn render(&self, name: &str, tasks: &[&Task]) -> String {
+        let total = tasks.len();
+        let completed = tasks.iter().filter(|t| t.completed).count();
+        let today = dates::today();
+
+        self.0
+            .iter()
+            .map(|piece| match piece {
+                Piece::Text(text) => text.clone(),
+                Piece::Placeholder(placeholder) => match placeholder {
+                    Placeholder::Project => name.to_string(),
+                    Placeholder::Completed => completed.to_string(),
+                    Placeholder::Total => total.to_string(),
+                    Placeholder::Remaining => (total - completed).to_string(),
+                    Placeholder::InProgress => tasks
+                        .iter()
+                        .filter(|t| !t.completed && t.in_progress)
+                        .count()
+                        .to_string(),
+                    Placeholder::Overdue => tasks
+                        .iter()
+                        .filter(|t| !t.completed && t.due.is_some_and(|due| due < today))
+                        .count()
+                        .to_string(),
+                    Placeholder::Percent => percent(completion(tasks.iter().copied())).to_string(),
+                },
+            })
+            .collect()
+    }
+}
+
+/// Print the progress of `project`, or of all projects together.
+pub fn show_status(project_name: Option<&str>, template: &Template) {
+    let data = load_data();
+
+    let projects: Vec<&Project> = match project_name {
+        Some(name) => match data.iter().find(|p| p.name == name) {
+            Some(project) => vec![project],
+            None => {
+                println!("{}", tr!("Project '{}' not found.", name));
+                return;
+            }
+        },
+        None => data.iter().collect(),
+    };
+
+    let tasks: Vec<&Task> = projects.iter().flat_map(|p| &p.tasks).collect();
+    println!("{}", template.render(project_name.unwrap_or(""), &tasks));
+}
+
+/// Where the segment of `project`, or of all projects, is cached.
+fn segment_cache(project: Option<&str>) -> PathBuf {
+    let mut name = format!("prompt-{}", workspace::active());
+    if let Some(project) = project {
+        name.push('-');
+        name.extend(
+            project
+                .chars()
+                .map(|c| if c.is_alphanumeric() { c } else { '_' }),
+        );
+    }
+    get_config_dir().join("cache").join(name)
+}
+
+/// The cached segment, unless it's older than `max_age` or the data
+/// changed since.
+fn cached_segment(cache: &PathBuf, max_age: Duration) -> Option<String> {
+    let cached_at = fs::metadata(cache).and_then(|m| m.modified()).ok()?;
+    if SystemTime::now().duration_since(cached_at).ok()? > max_age {
+        return None;
+    }
+    if !remote::is_active() {
+        let changed_at = storage::last_modified(workspace::active())?;
+        if changed_at > cached_at {
+            return None;
+        }
+    }
+    fs::read_to_string(cache).ok()
+}
+
+/// `3!/12` for 3 overdue tasks out of 12 pending, `12` with none overdue,
+/// and nothing with nothing pending.
+fn segment(tasks: &[&Task]) -> String {
+    let today = dates::today();
+    let pending: Vec<&&Task> = tasks.iter().filter(|t| !t.completed).collect();
+    let overdue = pending
+        .iter()
+        .filter(|t| t.due.is_some_and(|due| due < today))
+        .count();
+    match (overdue, pending.len()) {
+        (_, 0) => String::new(),
+        (0, pending) => pending.to_string(),
+        (overdue, pending) => format!("{}!/{}", overdue, pending),
+    }
+}
+
+/// Print the segment of `project`, or of all projects together, from the
+/// cache if it's no older than `max_age` seconds. Prints nothing for a
+/// project that doesn't exist, rather than spoil the prompt.
+pub fn show_segment(project_name: Option<&str>, max_age: u64) {
+    let cache = segment_cache(project_name);
+    if let Some(segment) = cached_segment(&cache, Duration::from_secs(max_age)) {
+        println!("{}", segment);
+        return;
+    }
+
+    // A prompt shows this all the time, only reading the project it needs
+    // helps with large workspaces.
+    let data = match project_name {
+        Some(name) => storage::load_project(name).into_iter().collect(),
+        None => load_data(),
+    };
+    let tasks: Vec<&Task> = data.iter().flat_map(|p| &p.tasks).collect();
+    let segment = segment(&tasks);
+    println!("{}", segment);
+    // A prompt can do without the cache.
+    let _ = fs::create_dir_all(cache.parent().expect("The cache is in a directory"))
+        .and_then(|_| fs::write(&cache, &segment));
+}
@@ -0,0 +1,243 @@
+//! Property tests of the changes commands make to the data, running the
+//! commands themselves against the in-memory storage: whatever tasks are
+//! added, completed, reopened, linked, deleted or merged, and in whichever
+//! order, the data stays consistent.
+
+use proptest::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    add_tasks, complete_task, delete_task, links, load_data,
+    merge::{self, Resolution},
+    reopen_task, storage, Project, Task,
+};
+
+const PROJECTS: [&str; 3] = ["web", "api", "docs"];
+
+#[derive(Debug, Clone)]
+enum Change {
+    Add {
+        project: usize,
+        description: String,
+    },
+    Complete {
+        project: usize,
+        task: usize,
+    },
+    Reopen {
+        project: usize,
+        task: usize,
+    },
+    Delete {
+        project: usize,
+        task: usize,
+    },
+    Link {
+        from: (usize, usize),
+        to: (usize, usize),
+    },
+}
+
+fn change() -> impl Strategy<Value = Change> {
+    let project = 0..PROJECTS.len();
+    let task = (project.clone(), any::<usize>());
+    prop_oneof![
+        3 => (project.clone(), "[a-z ]{1,12}")
+            .prop_map(|(project, description)| Change::Add { project, description }),
+        2 => (project.clone(), any::<usize>())
+            .prop_map(|(project, task)| Change::Complete { project, task }),
+        1 => (project.clone(), any::<usize>())
+            .prop_map(|(project, task)| Change::Reopen { project, task }),
+        1 => (project, any::<usize>())
+            .prop_map(|(project, task)| Change::Delete { project, task }),
+        1 => (task.clone(), task).prop_map(|(from, to)| Change::Link { from, to }),
+    ]
+}
+
+fn empty_data() -> Vec<Project> {
+    PROJECTS
+        .iter()
+        .map(|name| Project {
+            name: name.to_string(),
+            next_id: 1,
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// The ID of the task at `index`, wrapping around, of a project that has
+/// any.
+fn task_id(data: &[Project], project: usize, index: usize) -> Option<u32> {
+    let tasks = &data[project].tasks;
+    tasks.get(index.checked_rem(tasks.len())?).map(|t| t.id)
+}
+
+/// Make `change` with the command making it, on the data in storage.
+fn run(change: &Change) {
+    let data = load_data();
+    match change {
+        Change::Add {
+            project,
+            description,
+        } => add_tasks(
+            PROJECTS[*project],
+            std::slice::from_ref(description),
+            &Task::default(),
+            false,
+        ),
+        Change::Complete { project, task } => {
+            if let Some(id) = task_id(&data, *project, *task) {
+                complete_task(PROJECTS[*project], id);
+            }
+        }
+        Change::Reopen { project, task } => {
+            if let Some(id) = task_id(&data, *project, *task) {
+                reopen_task(PROJECTS[*project], id);
+            }
+        }
+        Change::Delete { project, task } => {
+            if let Some(id) = task_id(&data, *project, *task) {
+                delete_task(PROJECTS[*project], id);
+            }
+        }
+        Change::Link { from, to } => {
+            let refer = |(project, task): (usize, usize)| {
+                task_id(&data, project, task).map(|id| format!("{}:{}", PROJECTS[project], id))
+            };
+            if let (Some(from), Some(to)) = (refer(*from), refer(*to)) {
+                links::link_tasks(&from, &to, false);
+            }
+        }
+    }
+}
+
+/// The data after making `changes` to `data`.
+fn run_all(data: Vec<Project>, changes: &[Change]) -> Vec<Project> {
+    storage::use_memory(data);
+    changes.iter().for_each(run);
+    load_data()
+}
+
+fn check_invariants(data: &[Project]) -> Result<(), TestCaseError> {
+    for project in data {
+        let ids: BTreeSet<u32> = project.tasks.iter().map(|t| t.id).collect();
+        prop_assert_eq!(
+            ids.len(),
+            project.tasks.len(),
+            "duplicate IDs in {}",
+            project.name
+        );
+        for task in &project.tasks {
+            prop_assert!(task.id >= 1);
+            prop_assert!(
+                task.id < project.next_id,
+                "ID {} not below next ID",
+                task.id
+            );
+            prop_assert_eq!(task.completed, task.completed_at.is_some());
+            prop_assert!(!(task.completed && task.in_progress));
+        }
+        let percent = project.progress() * 100.0;
+        prop_assert!((0.0..=100.0).contains(&percent), "progress {}%", percent);
+    }
+    Ok(())
+}
+
+/// Links only point to tasks still there, which link back.
+fn check_links(data: &[Project]) -> Result<(), TestCaseError> {
+    let find = |project: &str, id: u32| {
+        data.iter()
+            .find(|p| p.name == project)
+            .and_then(|p| p.tasks.iter().find(|t| t.id == id))
+    };
+    for project in data {
+        for task in &project.tasks {
+            for link in &task.links {
+                let Some(linked) = find(&link.project, link.id) else {
+                    return Err(TestCaseError::fail(format!(
+                        "{}#{} links to {}, which is gone",
+                        project.name, task.id, link
+                    )));
+                };
+                prop_assert!(
+                    linked
+                        .links
+                        .iter()
+                        .any(|back| back.project == project.name && back.id == task.id),
+                    "{} doesn't link back to {}#{}",
+                    link,
+                    project.name,
+                    task.id
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn ids(data: &[Project]) -> BTreeMap<String, BTreeSet<u32>> {
+    data.iter()
+        .map(|p| (p.name.clone(), p.tasks.iter().map(|t| t.id).collect()))
+        .collect()
+}
+
+proptest! {
+    #[test]
+    fn changes_keep_the_data_consistent(changes in prop::collection::vec(change(), 0..60)) {
+        storage::use_memory(empty_data());
+        for change in &changes {
+            run(change);
+            let data = load_data();
+            check_invariants(&data)?;
+            check_links(&data)?;
+        }
+    }
+
+    #[test]
+    fn ids_of_deleted_tasks_are_never_reused(changes in prop::collection::vec(change(), 0..60)) {
+        storage::use_memory(empty_data());
+        let mut used: BTreeMap<String, BTreeSet<u32>> = BTreeMap::new();
+        let mut before = ids(&load_data());
+        for change in &changes {
+            run(change);
+            let now = ids(&load_data());
+            for (name, ids) in &now {
+                let used = used.entry(name.clone()).or_default();
+                for &added in ids.difference(&before[name]) {
+                    prop_assert!(used.insert(added), "ID {} reused in {}", added, name);
+                }
+            }
+            before = now;
+        }
+    }
+
+    #[test]
+    fn merging_keeps_every_task_id(
+        base in prop::collection::vec(change(), 0..30),
+        mine in prop::collection::vec(change(), 0..30),
+        theirs in prop::collection::vec(change(), 0..30),
+        resolutions in prop::collection::vec(0..3u8, 30),
+    ) {
+        let base = run_all(empty_data(), &base);
+        let mut data = run_all(base.clone(), &mine);
+        let other = run_all(base, &theirs);
+
+        let mut expected = ids(&data);
+        for (name, theirs) in ids(&other) {
+            expected.entry(name).or_default().extend(theirs);
+        }
+
+        let mut resolutions = resolutions.into_iter().cycle();
+        merge::merge_data(&mut data, other, |_, _, _| match resolutions.next() {
+            Some(0) => Resolution::Mine,
+            Some(1) => Resolution::Theirs,
+            _ => Resolution::Both,
+        });
+
+        check_invariants(&data)?;
+        for (name, expected) in expected {
+            let merged = &ids(&data)[&name];
+            prop_assert!(merged.is_superset(&expected), "tasks of {} lost", name);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Protected projects: with the `protected` setting on for a project,
+//! deleting it, deleting its tasks or completing several of them at once
+//! only goes ahead once its name is typed, as deleting a GitHub repository
+//! does. Without a terminal to ask in, it doesn't go ahead.
+
+use dialoguer::Input;
+use std::{collections::BTreeSet, sync::Mutex};
+
+use crate::{config::load_config, i18n::tr, picker, theme::Themed};
+
+/// Projects already confirmed during this run, so a range of task IDs
+/// only asks once. The shell forgets them after each line.
+static CONFIRMED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+pub fn is_protected(project: &str) -> bool {
+    load_config().effective_for(Some(project)).protected == Some(true)
+}
+
+/// Forget the projects confirmed so far, so they're asked about again.
+pub fn forget_confirmations() {
+    CONFIRMED
+        .lock()
+        .expect("Confirmations are never poisoned")
+        .clear();
+}
+
+/// Whether to go ahead with `action` on `project`: right away unless it's
+/// protected, otherwise once its name is typed.
+pub fn confirm(project: &str, action: &str) -> bool {
+    if !is_protected(project) {
+        return true;
+    }
+    let mut confirmed = CONFIRMED.lock().expect("Confirmations are never poisoned");
+    if confirmed.contains(project) {
+        return true;
+    }
+
+    println!(
+        "{}",
+        tr!("Project '{}' is protected: {}.", project, action).warn()
+    );
+    if !picker::is_interactive() {
+        println!(
+            "{}",
+            tr!("Nothing done, confirming needs an interactive terminal.")
+        );
+        return false;
+    }
+    let typed: String = Input::new()
+        .with_prompt(tr!("Type the project's name to confirm"))
+        .allow_empty(true)
+        .interact_text()
+        .unwrap_or_default();
+    if typed.trim() != project {
+        println!("{}", tr!("The name doesn't match, nothing done."));
+        return false;
+    }
+    confirmed.insert(project.to_string());
+    true
+}
@@ -0,0 +1,145 @@
+//! Quick capture: a task and its details typed as one line, as in
+//! `work: fix the login bug !high due:friday #auth`.
+//!
+//! - `project:` first, naming the project (or a project alias), unless
+//!   it's the project focused on or the `default-project`;
+//! - `!high`, `!medium` or `!low` for the priority;
+//! - `due:<date>`, where the date is one word, or two after `next`/`this`
+//!   (`due:next friday`), or three after `in` (`due:in 3 days`);
+//! - `~2h` for the estimate;
+//! - `#tag` and `@context`, as many as needed.
+//!
+//! Every other word makes up the description, as does any word starting
+//! with a backslash (`\!important`), the backslash left out.
+
+use clap::ValueEnum;
+
+use crate::{
+    add_tasks, config::load_config, contexts, dates, durations, focus, i18n::tr, tags, Priority,
+    Task,
+};
+
+/// A parsed quick-add line.
+struct QuickTask {
+    project: String,
+    description: String,
+    details: Task,
+}
+
+/// Whether `word` would be read as a detail rather than part of the
+/// description.
+fn is_detail(word: &str) -> bool {
+    word.starts_with('\\')
+        || (word.len() > 1 && ['!', '~', '#', '@'].iter().any(|&c| word.starts_with(c)))
+        || (word.len() > 4 && word.starts_with("due:"))
+}
+
+/// Split `text` into the description and the details written among it.
+/// A word starting with a backslash is always part of the description, the
+/// backslash left out, as in `\#1`.
+pub fn parse_details(text: &str) -> Result<(String, Task), String> {
+    let mut words = text.split_whitespace();
+    let mut description = Vec::new();
+    let mut details = Task::default();
+    while let Some(word) = words.next() {
+        if let Some(word) = word.strip_prefix('\\') {
+            description.push(word);
+        } else if let Some(priority) = word.strip_prefix('!').filter(|p| !p.is_empty()) {
+            details.priority =
+                Some(Priority::from_str(priority, true).map_err(|_| {
+                    tr!("Invalid priority '{}': use low, medium or high.", priority)
+                })?);
+        } else if let Some(first) = word.strip_prefix("due:").filter(|d| !d.is_empty()) {
+            let extra = match first.to_lowercase().as_str() {
+                "next" | "this" => 1,
+                "in" => 2,
+                _ => 0,
+            };
+            let mut date = first.to_string();
+            for _ in 0..extra {
+                if let Some(word) = words.next() {
+                    date.push(' ');
+                    date.push_str(word);
+                }
+            }
+            details.due = Some(dates::parse_date(&date)?);
+        } else if let Some(estimate) = word.strip_prefix('~').filter(|e| !e.is_empty()) {
+            details.estimate = Some(durations::parse_duration(estimate)?);
+        } else if word.len() > 1 && word.starts_with('#') {
+            details.tags.push(tags::normalize(word));
+        } else if word.len() > 1 && word.starts_with('@') {
+            details.contexts.push(contexts::normalize(word));
+        } else {
+            description.push(word);
+        }
+    }
+    Ok((description.join(" "), details))
+}
+
+/// `description` followed by the details of `task`, as `parse_details`
+/// reads them back.
+pub fn format_details(description: &str, task: &Task) -> String {
+    let mut words: Vec<String> = description
+        .split_whitespace()
+        .map(|word| {
+            if is_detail(word) {
+                format!("\\{}", word)
+            } else {
+                word.to_string()
+            }
+        })
+        .collect();
+    if let Some(priority) = task.priority {
+        words.push(format!("!{}", priority));
+    }
+    if let Some(due) = task.due {
+        words.push(format!("due:{}", due));
+    }
+    if let Some(estimate) = task.estimate {
+        words.push(format!("~{}", durations::format_duration(estimate)));
+    }
+    words.extend(task.tags.iter().map(|tag| format!("#{}", tag)));
+    words.extend(task.contexts.iter().cloned());
+    words.join(" ")
+}
+
+fn parse(input: &str) -> Result<QuickTask, String> {
+    let input = input.trim_start();
+    let (first, rest) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+
+    let (project, rest) = match first.strip_suffix(':') {
+        Some(project) if !project.is_empty() => (project.to_string(), rest),
+        _ => match focus::default_project() {
+            Some(project) => (project, input),
+            None => {
+                return Err(tr!(
+                    "Start with the project, as in 'work: fix the login bug'."
+                ))
+            }
+        },
+    };
+    let project = load_config()
+        .aliases
+        .projects
+        .get(&project)
+        .cloned()
+        .unwrap_or(project);
+
+    let (description, details) = parse_details(rest)?;
+    if description.is_empty() {
+        return Err(tr!("The task needs a description."));
+    }
+
+    Ok(QuickTask {
+        project,
+        description,
+        details,
+    })
+}
+
+pub fn quick_add(input: &str) {
+    match parse(input) {
+        Ok(task) => add_tasks(&task.project, &[task.description], &task.details, false),
+        Err(e) => println!("{}", e),
+    }
+}
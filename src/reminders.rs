@@ -0,0 +1,154 @@
+//! Reminders at specific times, as in `remind-at web 12 "tomorrow 09:00"`
+//! or `remind-at web 12 90m`, fired by `remind`, from cron or left running
+//! with `--watch`.
+//!
+//! Each reminder fires once: it's marked as fired and saved before the
+//! notification goes out, and a lock file next to the config keeps two
+//! `remind` runs from firing the same reminders at once.
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::{thread, time::Duration};
+
+use crate::{
+    dates, durations, get_config_dir, i18n::tr, load_data, lock::Lock, notify, save_data, TaskEvent,
+};
+
+/// How often `remind --watch` looks for reminders to fire.
+const WATCH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Age after which a lock file is taken to be left over from a crash.
+const STALE_LOCK: Duration = Duration::from_secs(300);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Reminder {
+    pub at: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fired: bool,
+}
+
+/// The time `when` is, a date and time or a span of time from now such as
+/// `90m`.
+fn parse_when(when: &str) -> Result<DateTime<Local>, String> {
+    if durations::looks_like_duration(when) {
+        let minutes = durations::parse_span(when)?;
+        Ok(Local::now() + chrono::Duration::minutes(i64::from(minutes)))
+    } else {
+        dates::parse_datetime(when)
+    }
+}
+
+/// Add a reminder to a task, or with `none` drop the ones not fired yet.
+pub fn remind_at(project_name: &str, task_id: u32, when: &str) {
+    let at = if when.eq_ignore_ascii_case("none") {
+        None
+    } else {
+        match parse_when(when) {
+            Ok(at) => Some(at),
+            Err(e) => {
+                println!("{}", tr!("Invalid time: {}", e));
+                return;
+            }
+        }
+    };
+
+    let mut data = load_data();
+    if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
+        if let Some(task) = project.tasks.iter_mut().find(|t| t.id == task_id) {
+            match at {
+                Some(at) => {
+                    task.reminders.push(Reminder { at, fired: false });
+                    task.reminders.sort_by_key(|r| r.at);
+                    let time = at.format("%Y-%m-%d %H:%M");
+                    task.record(
+                        TaskEvent::Edited,
+                        Some(format!("reminder set for {}", time)),
+                    );
+                    save_data(&data);
+                    println!(
+                        "{}",
+                        tr!("You'll be reminded of task {} on {}.", task_id, time)
+                    );
+                    if at <= Local::now() {
+                        println!(
+                            "{}",
+                            tr!("That time has passed, the next `remind` fires it right away.")
+                        );
+                    }
+                }
+                None => {
+                    task.reminders.retain(|r| r.fired);
+                    task.record(TaskEvent::Edited, Some("reminders cleared".to_string()));
+                    save_data(&data);
+                    println!("{}", tr!("Task {} no longer has reminders.", task_id));
+                }
+            }
+        } else {
+            println!(
+                "{}",
+                tr!("Task {} not found in project '{}'.", task_id, project_name)
+            );
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
+
+/// Fire the reminders whose time has come, of tasks not completed yet.
+/// Returns how many fired.
+fn fire_due() -> usize {
+    let lock = Lock::acquire(get_config_dir().join("remind.lock"), STALE_LOCK);
+    let Some(_lock) = lock else {
+        return 0;
+    };
+
+    let mut data = load_data();
+    let now = Local::now();
+    let mut due = Vec::new();
+    for project in data.iter_mut() {
+        for task in project.tasks.iter_mut().filter(|t| !t.completed) {
+            let mut fired = false;
+            for reminder in task.reminders.iter_mut() {
+                if !reminder.fired && reminder.at <= now {
+                    reminder.fired = true;
+                    fired = true;
+                }
+            }
+            if fired {
+                due.push((project.name.clone(), task.clone()));
+            }
+        }
+    }
+    if due.is_empty() {
+        return 0;
+    }
+
+    // Saved first, so a reminder never fires twice.
+    save_data(&data);
+    for (project_name, task) in &due {
+        println!(
+            "{}",
+            tr!(
+                "Reminder: {} (task {} in '{}')",
+                task.description,
+                task.id,
+                project_name
+            )
+        );
+        notify::reminder(project_name, task);
+    }
+    due.len()
+}
+
+pub fn remind(watch: bool) {
+    if !watch {
+        if fire_due() == 0 {
+            println!("{}", tr!("No reminders due."));
+        }
+        return;
+    }
+    loop {
+        fire_due();
+        thread::sleep(WATCH_INTERVAL);
+    }
+}
@@ -0,0 +1,399 @@
+//! Working against the data of a tracker running `serve` elsewhere, with
+//! `--remote https://pt.example.com --token ...`, so that a machine keeps
+//! nothing locally.
+//!
+//! `serve` answers `GET /data` with the projects as JSON, tagged with an
+//! `ETag`, and `PUT /data` replaces them, provided they weren't changed
+//! since by someone else (`If-Match`). Both need the token given to the
+//! server in `PT_API_TOKEN`, as `Authorization: Bearer <token>`.
+//!
+//! Settings, hooks and notifications stay on the client. Archiving, the
+//! search index and the progress history are left to the server.
+
+use sha2::{Digest, Sha256};
+use std::{
+    io::Cursor,
+    sync::{Mutex, OnceLock},
+    time::Duration,
+};
+use tiny_http::{Header, Method, Request, Response};
+use tracing::debug;
+
+use crate::{
+    i18n::tr,
+    load_data,
+    storage::{self, Storage},
+    Project,
+};
+
+/// Largest data the client accepts from a server.
+const MAX_DATA: u64 = 100 * 1024 * 1024;
+
+struct Remote {
+    url: String,
+    token: Option<String>,
+}
+
+static REMOTE: OnceLock<Option<Remote>> = OnceLock::new();
+
+/// Tag of the data last read from the server, to save over.
+static ETAG: Mutex<Option<String>> = Mutex::new(None);
+
+/// Record the `--remote` and `--token` given, if any.
+pub fn set_remote(url: Option<String>, token: Option<String>) {
+    let remote = url.map(|url| Remote {
+        url: format!("{}/data", url.trim_end_matches('/')),
+        token,
+    });
+    let _ = REMOTE.set(remote);
+}
+
+/// Whether the data is on a remote tracker rather than on this machine.
+pub fn is_active() -> bool {
+    REMOTE.get().is_some_and(Option::is_some)
+}
+
+fn remote() -> &'static Remote {
+    REMOTE
+        .get()
+        .and_then(Option::as_ref)
+        .expect("Remote data is only used with --remote")
+}
+
+fn agent() -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(30)))
+        .http_status_as_error(false)
+        .build()
+        .into()
+}
+
+fn fail(message: String) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+fn etag(content: &[u8]) -> String {
+    let digest = Sha256::digest(content);
+    format!(
+        "\"{}\"",
+        digest
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    )
+}
+
+/// The data of the tracker given with `--remote`.
+pub struct RemoteStorage;
+
+impl Storage for RemoteStorage {
+    fn load(&self) -> Vec<Project> {
+        load()
+    }
+
+    fn save(&self, data: &[Project]) {
+        save(data)
+    }
+}
+
+/// The remote tracker's projects. Exits when they can't be had, as nothing
+/// can be done without them.
+fn load() -> Vec<Project> {
+    let remote = remote();
+    let mut request = agent().get(&remote.url);
+    if let Some(token) = &remote.token {
+        request = request.header("Authorization", &format!("Bearer {}", token));
+    }
+    let mut response = request
+        .call()
+        .unwrap_or_else(|e| fail(tr!("Unable to reach {}: {}", remote.url, e)));
+    check_status(response.status().as_u16());
+
+    let content = response
+        .body_mut()
+        .with_config()
+        .limit(MAX_DATA)
+        .read_to_vec()
+        .unwrap_or_else(|e| fail(tr!("Unable to reach {}: {}", remote.url, e)));
+    let data = serde_json::from_slice(&content)
+        .unwrap_or_else(|e| fail(tr!("Unexpected answer from {}: {}", remote.url, e)));
+    let tag = response
+        .headers()
+        .get("ETag")
+        .and_then(|tag| tag.to_str().ok())
+        .map(str::to_string);
+    debug!("loaded data from {}, tagged {:?}", remote.url, tag);
+    *ETAG.lock().unwrap() = tag;
+    data
+}
+
+/// Replace the remote tracker's projects with `data`. Exits when they
+/// can't be saved, rather than carrying on as if they were.
+fn save(data: &[Project]) {
+    let remote = remote();
+    let content = serde_json::to_vec(data).expect("Unable to serialize data.");
+    let mut request = agent()
+        .put(&remote.url)
+        .header("Content-Type", "application/json");
+    if let Some(token) = &remote.token {
+        request = request.header("Authorization", &format!("Bearer {}", token));
+    }
+    if let Some(tag) = ETAG.lock().unwrap().as_deref() {
+        request = request.header("If-Match", tag);
+    }
+    let response = request
+        .send(&content[..])
+        .unwrap_or_else(|e| fail(tr!("Unable to reach {}: {}", remote.url, e)));
+    check_status(response.status().as_u16());
+
+    let tag = response
+        .headers()
+        .get("ETag")
+        .and_then(|tag| tag.to_str().ok())
+        .map(str::to_string);
+    debug!("saved data to {}, now tagged {:?}", remote.url, tag);
+    *ETAG.lock().unwrap() = tag;
+}
+
+fn check_status(status: u16) {
+    let url = &remote().url;
+    match status {
+        200..=299 => {}
+        401 => fail(tr!("{} refused the token, check --token.", url)),
+        403 => fail(tr!("{} is read-only, nothing was changed.", url)),
+        404 | 503 => fail(tr!("{} doesn't serve tracker data.", url)),
+        409 => fail(tr!(
+            "{} couldn't read its data file entirely, nothing was saved.",
+            url
+        )),
+        412 => fail(tr!(
+            "The data on {} was changed by someone else meanwhile, nothing was saved. Run the command again.",
+            url
+        )),
+        status => fail(tr!("{} answered with status {}.", url, status)),
+    }
+}
+
+/// The token clients must present, from the `PT_API_TOKEN` environment
+/// variable.
+pub fn api_token() -> Option<String> {
+    std::env::var("PT_API_TOKEN").ok().filter(|s| !s.is_empty())
+}
+
+/// Whether `request` carries the `Bearer` token, compared in constant time.
+pub fn is_authorized(request: &Request, token: &str) -> bool {
+    let expected = format!("Bearer {}", token);
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .is_some_and(|h| {
+            let given = h.value.as_str();
+            given.len() == expected.len()
+                && given
+                    .bytes()
+                    .zip(expected.bytes())
+                    .fold(0, |diff, (a, b)| diff | (a ^ b))
+                    == 0
+        })
+}
+
+fn data_response(content: Vec<u8>) -> Response<Cursor<Vec<u8>>> {
+    let tag = etag(&content);
+    Response::from_data(content)
+        .with_header(
+            "Content-Type: application/json"
+                .parse::<Header>()
+                .expect("Valid header"),
+        )
+        .with_header(
+            format!("ETag: {}", tag)
+                .parse::<Header>()
+                .expect("Valid header"),
+        )
+}
+
+/// Answer `GET /data` and `PUT /data` for a client using `--remote`.
+pub fn handle(request: &mut Request) -> Response<Cursor<Vec<u8>>> {
+    answer(request, api_token())
+}
+
+/// Answer `request` for the clients with `token`, if any are allowed.
+fn answer(request: &mut Request, token: Option<String>) -> Response<Cursor<Vec<u8>>> {
+    let Some(token) = token else {
+        return Response::from_string("Remote access is not enabled").with_status_code(503);
+    };
+    if !is_authorized(request, &token) {
+        return Response::from_string("Invalid token").with_status_code(401);
+    }
+
+    let current = serde_json::to_vec(&load_data()).expect("Unable to serialize data.");
+    if *request.method() == Method::Get {
+        return data_response(current);
+    }
+
+    if storage::is_read_only() {
+        return Response::from_string("Read-only").with_status_code(403);
+    }
+    if storage::is_corrupt() {
+        return Response::from_string("Data file is corrupt").with_status_code(409);
+    }
+    let if_match = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("If-Match"))
+        .map(|h| h.value.as_str().to_string());
+    if if_match.is_some_and(|tag| tag != "*" && tag != etag(&current)) {
+        return Response::from_string("Changed meanwhile").with_status_code(412);
+    }
+
+    let mut body = Vec::new();
+    if request.as_reader().read_to_end(&mut body).is_err() {
+        return Response::from_string("Bad request").with_status_code(400);
+    }
+    let data: Vec<Project> = match serde_json::from_slice(&body) {
+        Ok(data) => data,
+        Err(e) => {
+            return Response::from_string(format!("Invalid data: {}", e)).with_status_code(400)
+        }
+    };
+    storage::save_data(&data);
+    data_response(serde_json::to_vec(&data).expect("Unable to serialize data."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::serve;
+
+    const TOKEN: &str = "s3cret";
+
+    fn data(description: &str) -> Vec<Project> {
+        vec![Project {
+            name: "web".to_string(),
+            tasks: vec![crate::Task {
+                id: 1,
+                description: description.to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }]
+    }
+
+    /// Status, `ETag` and body of a request to `/data`.
+    fn send(
+        url: &str,
+        method: &str,
+        headers: &[(&str, &str)],
+        body: &[u8],
+    ) -> (u16, Option<String>, String) {
+        let request = ureq::http::Request::builder()
+            .method(method)
+            .uri(format!("{}/data", url));
+        let request = headers
+            .iter()
+            .fold(request, |request, (name, value)| {
+                request.header(*name, *value)
+            })
+            .body(body.to_vec())
+            .unwrap();
+        let mut response = agent().run(request).expect("The server answers");
+        let tag = response
+            .headers()
+            .get("ETag")
+            .map(|tag| tag.to_str().unwrap().to_string());
+        let body = response.body_mut().read_to_string().unwrap_or_default();
+        (response.status().as_u16(), tag, body)
+    }
+
+    const AUTHORIZED: (&str, &str) = ("Authorization", "Bearer s3cret");
+
+    #[test]
+    fn only_clients_with_the_token_get_the_data() {
+        storage::use_memory(data("secret plans"));
+        let answers = serve::exchange(
+            |request| answer(request, Some(TOKEN.to_string())),
+            |url| {
+                [
+                    send(&url, "GET", &[], b""),
+                    send(&url, "GET", &[("Authorization", "Bearer s3cre")], b""),
+                    send(&url, "GET", &[("Authorization", "Bearer s3cret!")], b""),
+                    send(&url, "GET", &[("Authorization", "s3cret")], b""),
+                    send(&url, "PUT", &[], b"[]"),
+                    send(&url, "GET", &[AUTHORIZED], b""),
+                ]
+            },
+        );
+        for (status, _, body) in &answers[..5] {
+            assert_eq!(*status, 401);
+            assert!(!body.contains("secret plans"));
+        }
+        let (status, tag, body) = &answers[5];
+        assert_eq!(*status, 200);
+        assert!(body.contains("secret plans"));
+        assert_eq!(tag.as_deref(), Some(etag(body.as_bytes()).as_str()));
+        assert_eq!(load_data()[0].tasks[0].description, "secret plans");
+    }
+
+    #[test]
+    fn nothing_is_served_without_a_token() {
+        storage::use_memory(data("one"));
+        let (status, _, _) = serve::exchange(
+            |request| answer(request, None),
+            |url| send(&url, "GET", &[AUTHORIZED], b""),
+        );
+        assert_eq!(status, 503);
+    }
+
+    #[test]
+    fn saves_over_changes_made_meanwhile_are_refused() {
+        storage::use_memory(data("one"));
+        let stale = etag(&serde_json::to_vec(&data("zero")).unwrap());
+        let theirs = serde_json::to_vec(&data("theirs")).unwrap();
+        let (refused, _, _) = serve::exchange(
+            |request| answer(request, Some(TOKEN.to_string())),
+            move |url| send(&url, "PUT", &[AUTHORIZED, ("If-Match", &stale)], &theirs),
+        );
+        assert_eq!(refused, 412);
+        assert_eq!(load_data()[0].tasks[0].description, "one");
+    }
+
+    #[test]
+    fn saves_over_the_data_loaded_go_through() {
+        storage::use_memory(data("one"));
+        let mine = serde_json::to_vec(&data("two")).unwrap();
+        let (loaded, saved, tags) = serve::exchange(
+            |request| answer(request, Some(TOKEN.to_string())),
+            move |url| {
+                let (_, tag, _) = send(&url, "GET", &[AUTHORIZED], b"");
+                let tag = tag.expect("The data is tagged");
+                let (saved, new_tag, _) =
+                    send(&url, "PUT", &[AUTHORIZED, ("If-Match", &tag)], &mine);
+                let (forced, _, _) = send(
+                    &url,
+                    "PUT",
+                    &[AUTHORIZED, ("If-Match", "*")],
+                    &serde_json::to_vec(&data("three")).unwrap(),
+                );
+                (saved, forced, (tag, new_tag))
+            },
+        );
+        assert_eq!((loaded, saved), (200, 200));
+        let (before, after) = tags;
+        assert_ne!(Some(before), after, "the tag follows the data");
+        assert_eq!(load_data()[0].tasks[0].description, "three");
+    }
+
+    #[test]
+    fn invalid_data_is_refused() {
+        storage::use_memory(data("one"));
+        let (status, _, _) = serve::exchange(
+            |request| answer(request, Some(TOKEN.to_string())),
+            |url| send(&url, "PUT", &[AUTHORIZED], b"{\"not\": \"projects\"}"),
+        );
+        assert_eq!(status, 400);
+        assert_eq!(load_data()[0].tasks[0].description, "one");
+    }
+}
@@ -0,0 +1,81 @@
+//! Manual ordering of the tasks in a project. The order is simply the order
+//! tasks are stored in, which listings follow unless asked to sort.
+
+use crate::{i18n::tr, load_data, save_data, Project};
+
+/// Position of task `task_id` in `project`, after telling the user if it
+/// doesn't exist.
+fn position(project: &Project, task_id: u32) -> Option<usize> {
+    let position = project.tasks.iter().position(|t| t.id == task_id);
+    if position.is_none() {
+        println!(
+            "{}",
+            tr!("Task {} not found in project '{}'.", task_id, project.name)
+        );
+    }
+    position
+}
+
+/// Move a task one place up (towards the top) or down.
+pub fn move_task(project_name: &str, task_id: u32, up: bool) {
+    let mut data = load_data();
+    if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
+        let Some(from) = position(project, task_id) else {
+            return;
+        };
+        let to = if up {
+            from.checked_sub(1)
+        } else {
+            Some(from + 1).filter(|&to| to < project.tasks.len())
+        };
+        let Some(to) = to else {
+            if up {
+                println!("{}", tr!("Task {} is already at the top.", task_id));
+            } else {
+                println!("{}", tr!("Task {} is already at the bottom.", task_id));
+            }
+            return;
+        };
+        project.tasks.swap(from, to);
+        save_data(&data);
+        if up {
+            println!("{}", tr!("Task {} moved up.", task_id));
+        } else {
+            println!("{}", tr!("Task {} moved down.", task_id));
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
+
+/// Move a task right before, or right after, another one.
+pub fn reorder(project_name: &str, task_id: u32, other_id: u32, after: bool) {
+    if task_id == other_id {
+        println!("{}", tr!("A task can't be moved next to itself."));
+        return;
+    }
+
+    let mut data = load_data();
+    if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
+        let Some(from) = position(project, task_id) else {
+            return;
+        };
+        if position(project, other_id).is_none() {
+            return;
+        }
+        let task = project.tasks.remove(from);
+        let to = project.tasks.iter().position(|t| t.id == other_id).unwrap() + usize::from(after);
+        project.tasks.insert(to, task);
+        save_data(&data);
+        if after {
+            println!("{}", tr!("Task {} moved after task {}.", task_id, other_id));
+        } else {
+            println!(
+                "{}",
+                tr!("Task {} moved before task {}.", task_id, other_id)
+            );
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
@@ -0,0 +1,317 @@
+//! Reports summing up a stretch of work for someone else, starting with
+//! `report monthly`: the tasks completed and created and the time tracked
+//! over a month, by project and by tag, as text, Markdown or CSV.
+
+use chrono::{Datelike, Local, Months, NaiveDate};
+use clap::{Subcommand, ValueEnum};
+use colored::Colorize;
+use std::collections::BTreeMap;
+
+use crate::{archive, durations::format_duration, i18n::tr, load_data, privacy, Project, Task};
+
+#[derive(Subcommand)]
+pub enum ReportCommand {
+    /// Sum up a month: tasks completed and created, and time tracked, by
+    /// project and by tag.
+    Monthly {
+        /// Month to report on, e.g. 2025-06 (default: the current month).
+        #[arg(long, value_parser = parse_month)]
+        month: Option<NaiveDate>,
+        #[arg(long, value_enum, default_value_t = MonthlyFormat::Text)]
+        format: MonthlyFormat,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum MonthlyFormat {
+    Text,
+    Markdown,
+    Csv,
+}
+
+pub fn run(command: &ReportCommand) {
+    match command {
+        ReportCommand::Monthly { month, format } => {
+            let today = Local::now().date_naive();
+            let month = month.unwrap_or_else(|| today.with_day(1).expect("Every month has a 1st"));
+            monthly(month, *format)
+        }
+    }
+}
+
+/// Parse a month such as `2025-06` into its first day, as a clap
+/// `value_parser`.
+fn parse_month(input: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(&format!("{}-01", input.trim()), "%Y-%m-%d")
+        .map_err(|_| tr!("expected a month such as 2025-06, got '{}'", input))
+}
+
+#[derive(Default, Clone, Copy)]
+struct Counts {
+    completed: usize,
+    created: usize,
+    minutes: u32,
+}
+
+impl Counts {
+    fn add(&mut self, other: Counts) {
+        self.completed += other.completed;
+        self.created += other.created;
+        self.minutes += other.minutes;
+    }
+}
+
+struct Summary {
+    total: Counts,
+    projects: BTreeMap<String, Counts>,
+    /// Untagged tasks under the empty tag; tasks with several tags count
+    /// towards each of them.
+    tags: BTreeMap<String, Counts>,
+    /// Tasks completed during the month, as `(project, task ref,
+    /// description)`.
+    completed: Vec<(String, String, String)>,
+}
+
+fn summarize(projects: &[Project], first: NaiveDate, next: NaiveDate) -> Summary {
+    let in_month = |date: NaiveDate| date >= first && date < next;
+    let mut summary = Summary {
+        total: Counts::default(),
+        projects: BTreeMap::new(),
+        tags: BTreeMap::new(),
+        completed: Vec::new(),
+    };
+
+    for project in projects {
+        for task in &project.tasks {
+            let completed = task
+                .completed_at
+                .is_some_and(|at| in_month(at.date_naive()));
+            let counts = Counts {
+                completed: usize::from(completed),
+                created: usize::from(
+                    task.created_at()
+                        .is_some_and(|at| in_month(at.date_naive())),
+                ),
+                minutes: task
+                    .time_entries
+                    .iter()
+                    .filter(|entry| in_month(entry.date))
+                    .map(|entry| entry.minutes)
+                    .sum(),
+            };
+            if counts.completed == 0 && counts.created == 0 && counts.minutes == 0 {
+                continue;
+            }
+
+            summary.total.add(counts);
+            summary
+                .projects
+                .entry(project.name.clone())
+                .or_default()
+                .add(counts);
+            for tag in tags(task) {
+                summary.tags.entry(tag).or_default().add(counts);
+            }
+            if completed {
+                summary.completed.push((
+                    project.name.clone(),
+                    project.task_ref(task.id),
+                    privacy::description(task).to_string(),
+                ));
+            }
+        }
+    }
+    summary
+}
+
+fn tags(task: &Task) -> Vec<String> {
+    if task.tags.is_empty() {
+        vec![String::new()]
+    } else {
+        task.tags.clone()
+    }
+}
+
+fn tag_name(tag: &str) -> String {
+    if tag.is_empty() {
+        tr!("(untagged)")
+    } else {
+        format!("#{}", tag)
+    }
+}
+
+fn monthly(first: NaiveDate, format: MonthlyFormat) {
+    let next = first + Months::new(1);
+    // Tasks completed long enough ago may have been archived since.
+    let mut projects = load_data();
+    projects.extend(archive::load_archive());
+    let summary = summarize(&projects, first, next);
+    let title = first.format("%B %Y").to_string();
+
+    match format {
+        MonthlyFormat::Text => print_text(&title, &summary),
+        MonthlyFormat::Markdown => print_markdown(&title, &summary),
+        MonthlyFormat::Csv => print_csv(&summary),
+    }
+}
+
+fn totals(counts: &Counts) -> String {
+    tr!(
+        "{} tasks completed, {} created, {} tracked.",
+        counts.completed,
+        counts.created,
+        format_duration(counts.minutes)
+    )
+}
+
+fn print_text(title: &str, summary: &Summary) {
+    println!("{}", tr!("Report for {}", title).bold());
+    println!("{}", totals(&summary.total));
+    if summary.projects.is_empty() {
+        return;
+    }
+
+    let width = summary
+        .projects
+        .keys()
+        .chain(summary.tags.keys())
+        .map(|name| tag_name(name).chars().count())
+        .max()
+        .unwrap_or(0);
+    let line = |name: &str, counts: &Counts| {
+        println!(
+            "    {:<width$}  {:>4} {:>4} {:>8}",
+            name,
+            counts.completed,
+            counts.created,
+            format_duration(counts.minutes),
+            width = width
+        )
+    };
+    let header = || {
+        println!(
+            "    {:<width$}  {:>4} {:>4} {:>8}",
+            "",
+            tr!("done"),
+            tr!("new"),
+            tr!("time"),
+            width = width
+        )
+    };
+
+    println!();
+    println!("{}", tr!("By project:"));
+    header();
+    for (project, counts) in &summary.projects {
+        line(project, counts);
+    }
+    println!();
+    println!("{}", tr!("By tag:"));
+    header();
+    for (tag, counts) in &summary.tags {
+        line(&tag_name(tag), counts);
+    }
+
+    if !summary.completed.is_empty() {
+        println!();
+        println!("{}", tr!("Completed:"));
+        for (_, task_ref, description) in &summary.completed {
+            println!("    {}: {}", task_ref, description);
+        }
+    }
+}
+
+fn markdown_table(heading: &str, column: &str, rows: Vec<(String, &Counts)>) {
+    println!();
+    println!("## {}", heading);
+    println!();
+    println!(
+        "| {} | {} | {} | {} |",
+        column,
+        tr!("Completed"),
+        tr!("Created"),
+        tr!("Time")
+    );
+    println!("|---|---:|---:|---:|");
+    for (name, counts) in rows {
+        println!(
+            "| {} | {} | {} | {} |",
+            name.replace('|', "\\|"),
+            counts.completed,
+            counts.created,
+            format_duration(counts.minutes)
+        );
+    }
+}
+
+fn print_markdown(title: &str, summary: &Summary) {
+    println!("# {}", tr!("Report for {}", title));
+    println!();
+    println!("{}", totals(&summary.total));
+    if summary.projects.is_empty() {
+        return;
+    }
+
+    markdown_table(
+        &tr!("By project"),
+        &tr!("Project"),
+        summary
+            .projects
+            .iter()
+            .map(|(name, counts)| (name.clone(), counts))
+            .collect(),
+    );
+    markdown_table(
+        &tr!("By tag"),
+        &tr!("Tag"),
+        summary
+            .tags
+            .iter()
+            .map(|(tag, counts)| (tag_name(tag), counts))
+            .collect(),
+    );
+
+    if !summary.completed.is_empty() {
+        println!();
+        println!("## {}", tr!("Completed"));
+        let mut current = None;
+        for (project, task_ref, description) in &summary.completed {
+            if current != Some(project) {
+                println!();
+                println!("### {}", project);
+                println!();
+                current = Some(project);
+            }
+            println!("- {}: {}", task_ref, description);
+        }
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn print_csv(summary: &Summary) {
+    println!("{}", tr!("group,name,completed,created,hours"));
+    let row = |group: &str, name: &str, counts: &Counts| {
+        println!(
+            "{},{},{},{},{:.2}",
+            group,
+            csv_field(name),
+            counts.completed,
+            counts.created,
+            counts.minutes as f64 / 60.0
+        )
+    };
+    for (project, counts) in &summary.projects {
+        row("project", project, counts);
+    }
+    for (tag, counts) in &summary.tags {
+        row("tag", tag, counts);
+    }
+    row("total", "", &summary.total);
+}
@@ -0,0 +1,216 @@
+//! `review`: the weekly review, project by project. Overdue tasks and tasks
+//! untouched for a while come up one at a time, to keep, complete, delete
+//! or snooze, and projects with nothing left to do, to keep or delete.
+//! Keeping a task records that it was reviewed, so it isn't stale again
+//! until it's left untouched for as long once more.
+
+use colored::Colorize;
+use dialoguer::{theme::ColorfulTheme, Input, Select};
+
+use crate::{
+    complete_task, dates, delete_project, delete_task, display, history::TaskEvent, i18n::tr,
+    load_data, picker, save_data, snooze, stale, task_details, theme::Themed, Project, Task,
+};
+
+/// Why a task comes up in the review.
+fn reasons(task: &Task, days: u32) -> Vec<String> {
+    let mut reasons = Vec::new();
+    if task.completed {
+        return reasons;
+    }
+    if let Some(due) = task.due.filter(|due| *due < dates::today()) {
+        reasons.push(tr!("overdue since {}", due));
+    }
+    if let Some(age) = stale::untouched_days(task).filter(|age| *age > i64::from(days)) {
+        reasons.push(tr!("untouched for {} days", age));
+    }
+    reasons
+}
+
+#[derive(Default)]
+struct Tally {
+    kept: usize,
+    completed: usize,
+    deleted: usize,
+    snoozed: usize,
+}
+
+/// Ask what to do with `task` of `project`, and do it. Returns
+/// `false` to stop the review.
+fn review_task(project: &Project, task: &Task, reasons: &[String], tally: &mut Tally) -> bool {
+    println!(
+        "  {}: {}{} {}",
+        project.display_id(task.id),
+        display::description(task),
+        task_details(task),
+        format!("({})", reasons.join(", ")).warn()
+    );
+    let theme = ColorfulTheme::default();
+    let choices = [
+        tr!("Keep"),
+        tr!("Complete"),
+        tr!("Delete"),
+        tr!("Snooze"),
+        tr!("Stop the review"),
+    ];
+    let choice = Select::with_theme(&theme)
+        .items(&choices)
+        .default(0)
+        .interact_opt()
+        .ok()
+        .flatten();
+
+    match choice {
+        Some(0) => {
+            keep(&project.name, task.id);
+            tally.kept += 1;
+        }
+        // Completing or deleting may be refused, by an unticked checklist
+        // or a protected project: only what was done is counted.
+        Some(1) => {
+            complete_task(&project.name, task.id);
+            if saved_task(&project.name, task.id).is_some_and(|t| t.completed) {
+                tally.completed += 1;
+            }
+        }
+        Some(2) => {
+            delete_task(&project.name, task.id);
+            if saved_task(&project.name, task.id).is_none() {
+                tally.deleted += 1;
+            }
+        }
+        Some(3) => {
+            let until: Option<String> = Input::with_theme(&theme)
+                .with_prompt(tr!("Snooze until"))
+                .default("next monday".to_string())
+                .interact_text()
+                .ok();
+            match until {
+                Some(until) => {
+                    snooze::snooze(&project.name, task.id, &until);
+                    if saved_task(&project.name, task.id)
+                        .is_some_and(|t| t.snoozed_until != task.snoozed_until)
+                    {
+                        tally.snoozed += 1;
+                    }
+                }
+                None => return false,
+            }
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Task `task_id` of `project_name` as saved, if it's still there.
+fn saved_task(project_name: &str, task_id: u32) -> Option<Task> {
+    load_data()
+        .into_iter()
+        .find(|p| p.name == project_name)?
+        .tasks
+        .into_iter()
+        .find(|t| t.id == task_id)
+}
+
+/// Record that the task was reviewed and is still wanted.
+fn keep(project_name: &str, task_id: u32) {
+    let mut data = load_data();
+    let task = data
+        .iter_mut()
+        .find(|p| p.name == project_name)
+        .and_then(|p| p.tasks.iter_mut().find(|t| t.id == task_id));
+    if let Some(task) = task {
+        task.record(TaskEvent::Edited, Some("reviewed".to_string()));
+        save_data(&data);
+    }
+}
+
+/// Ask whether to keep `project`, which has nothing left to do. Returns
+/// `false` to stop the review.
+fn review_empty_project(project: &Project) -> bool {
+    let message = if project.tasks.is_empty() {
+        tr!("{} has no tasks.", project.name)
+    } else {
+        tr!("{} has no pending tasks.", project.name)
+    };
+    println!("{}", message.warn());
+    let choices = [
+        tr!("Keep the project"),
+        tr!("Delete the project"),
+        tr!("Stop the review"),
+    ];
+    let choice = Select::with_theme(&ColorfulTheme::default())
+        .items(&choices)
+        .default(0)
+        .interact_opt()
+        .ok()
+        .flatten();
+    match choice {
+        Some(0) => true,
+        Some(1) => {
+            delete_project(&project.name);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Walk through the projects, asking about the tasks overdue or untouched
+/// for more than `days` days, and about projects with nothing left to do.
+pub fn review(days: u32) {
+    if !picker::is_interactive() {
+        println!("{}", tr!("The review needs an interactive terminal."));
+        return;
+    }
+
+    let data = load_data();
+    let mut tally = Tally::default();
+    let mut asked = false;
+    'projects: for project in &data {
+        let tasks: Vec<(&Task, Vec<String>)> = project
+            .tasks
+            .iter()
+            .filter(|task| snooze::shown(task))
+            .map(|task| (task, reasons(task, days)))
+            .filter(|(_, reasons)| !reasons.is_empty())
+            .collect();
+        let empty = project.tasks.iter().all(|t| t.completed);
+        if tasks.is_empty() && !empty {
+            continue;
+        }
+
+        asked = true;
+        println!("{}", project.name.bold());
+        if empty {
+            if !review_empty_project(project) {
+                break;
+            }
+            continue;
+        }
+        for (task, reasons) in &tasks {
+            if !review_task(project, task, reasons, &mut tally) {
+                break 'projects;
+            }
+        }
+    }
+
+    if !asked {
+        println!(
+            "{}",
+            tr!("Nothing to review: no overdue or stale tasks, and no empty projects.")
+        );
+        return;
+    }
+    let reviewed = tally.kept + tally.completed + tally.deleted + tally.snoozed;
+    println!(
+        "{}",
+        tr!(
+            "Reviewed {} tasks: {} kept, {} completed, {} deleted, {} snoozed.",
+            reviewed,
+            tally.kept,
+            tally.completed,
+            tally.deleted,
+            tally.snoozed
+        )
+    );
+}
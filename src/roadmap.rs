@@ -0,0 +1,181 @@
+//! The order projects are meant to be worked on: `set-after` records that a
+//! project follows others, and `roadmap` lists projects so that each comes
+//! after the ones it follows.
+
+use colored::Colorize;
+
+use crate::{bars, i18n::tr, load_data, save_data, theme::Themed, Project};
+
+/// ` → `, or ` -> ` with ASCII only.
+fn arrow() -> &'static str {
+    if bars::is_ascii() {
+        " -> "
+    } else {
+        " → "
+    }
+}
+
+/// Make `project_name` follow `after`, or with `none` follow nothing.
+pub fn set_after(project_name: &str, after: &str) {
+    let mut data = load_data();
+    let Some(index) = data.iter().position(|p| p.name == project_name) else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+        return;
+    };
+
+    if after.eq_ignore_ascii_case("none") {
+        data[index].after.clear();
+        save_data(&data);
+        println!(
+            "{}",
+            tr!(
+                "Project '{}' no longer follows other projects.",
+                project_name
+            )
+        );
+        return;
+    }
+    if after == project_name {
+        println!("{}", tr!("A project can't follow itself."));
+        return;
+    }
+    if !data.iter().any(|p| p.name == after) {
+        println!("{}", tr!("Project '{}' not found.", after));
+        return;
+    }
+    if data[index].after.iter().any(|a| a == after) {
+        println!(
+            "{}",
+            tr!("Project '{}' already follows '{}'.", project_name, after)
+        );
+        return;
+    }
+    if let Some(path) = path(&data, after, project_name) {
+        println!(
+            "{}",
+            tr!(
+                "'{}' already follows '{}' ({}), that would make a cycle.",
+                after,
+                project_name,
+                path.join(arrow())
+            )
+        );
+        return;
+    }
+
+    data[index].after.push(after.to_string());
+    save_data(&data);
+    println!(
+        "{}",
+        tr!("Project '{}' now follows '{}'.", project_name, after)
+    );
+}
+
+/// The projects leading from `from` back to `to` through what they follow,
+/// if `from` follows `to` at all.
+fn path(data: &[Project], from: &str, to: &str) -> Option<Vec<String>> {
+    let mut stack = vec![vec![from.to_string()]];
+    let mut seen = vec![from.to_string()];
+    while let Some(path) = stack.pop() {
+        let last = path.last().expect("Paths are never empty");
+        let Some(project) = data.iter().find(|p| &p.name == last) else {
+            continue;
+        };
+        for after in &project.after {
+            let mut next = path.clone();
+            next.push(after.clone());
+            if after == to {
+                return Some(next);
+            }
+            if !seen.contains(after) {
+                seen.push(after.clone());
+                stack.push(next);
+            }
+        }
+    }
+    None
+}
+
+/// Projects in roadmap order: each after the projects it follows, and
+/// otherwise in the order they're kept in. Projects caught in a cycle can't
+/// be put in order and are returned apart.
+fn order(data: &[Project]) -> (Vec<&Project>, Vec<&Project>) {
+    let mut ordered: Vec<&Project> = Vec::new();
+    let mut left: Vec<&Project> = data.iter().collect();
+    loop {
+        // Projects following one that's gone are treated as following nothing.
+        let ready = left.iter().position(|project| {
+            project.after.iter().all(|after| {
+                ordered.iter().any(|p| &p.name == after) || !data.iter().any(|p| &p.name == after)
+            })
+        });
+        match ready {
+            Some(index) => ordered.push(left.remove(index)),
+            None => return (ordered, left),
+        }
+    }
+}
+
+fn is_done(project: &Project) -> bool {
+    !project.tasks.is_empty() && project.tasks.iter().all(|t| t.completed)
+}
+
+pub fn show_roadmap() {
+    let data = load_data();
+    if data.is_empty() {
+        println!("{}", tr!("No projects found."));
+        return;
+    }
+
+    let (ordered, cyclic) = order(&data);
+    println!("{}", tr!("Roadmap:"));
+    for (number, project) in ordered.iter().enumerate() {
+        print_project(&data, number + 1, project);
+    }
+
+    if !cyclic.is_empty() {
+        println!();
+        println!(
+            "{}",
+            tr!("These projects are in a cycle, or follow one:").bad()
+        );
+        for project in &cyclic {
+            println!(
+                "    {} {} {}",
+                project.name,
+                arrow().trim().bad(),
+                project.after.join(", ")
+            );
+        }
+        println!("{}", tr!("Break it with `set-after <project> none`."));
+    }
+}
+
+fn print_project(data: &[Project], number: usize, project: &Project) {
+    println!(
+        "{:>3}. {} {}",
+        number,
+        project.name.bold(),
+        bars::progress_bar(project.progress())
+    );
+
+    // What it's still waiting for, if anything.
+    let waiting: Vec<&str> = project
+        .after
+        .iter()
+        .filter(|after| {
+            data.iter()
+                .find(|p| &&p.name == after)
+                .is_some_and(|p| !is_done(p))
+        })
+        .map(String::as_str)
+        .collect();
+    if !waiting.is_empty() {
+        println!("     {}", tr!("waiting for {}", waiting.join(", ")).warn());
+    } else if !project.after.is_empty() {
+        println!(
+            "     {}",
+            tr!("after {}", project.after.join(", ")).dimmed()
+        );
+    }
+}
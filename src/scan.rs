@@ -0,0 +1,295 @@
+//! Tasks read from the `TODO.md` checklists (and, optionally, `TODO:`
+//! comments) of a repository.
+//!
+//! Every task found this way records the file it came from in its `source`
+//! field, which is how a later scan finds it again: matching tasks are
+//! updated rather than duplicated, and the ones gone from their file are
+//! completed.
+
+use chrono::Local;
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    diff, diff::Change, history::TaskEvent, hooks, i18n::tr, load_data, save_data, Event, Project,
+    Task,
+};
+
+/// Field recording the file a task was scanned from.
+const SOURCE_FIELD: &str = "source";
+
+/// Directories never worth descending into.
+const IGNORED_DIRS: &[&str] = &["target", "node_modules", "vendor", "build", "dist"];
+
+/// Extensions of the files searched for `TODO:` comments.
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "rs", "c", "h", "cc", "cpp", "hpp", "go", "java", "kt", "swift", "js", "jsx", "ts", "tsx",
+    "py", "rb", "php", "sh", "lua", "hs", "ex", "exs", "sql", "toml", "yaml", "yml",
+];
+
+/// Markers a `TODO:` has to follow on its line to count as a comment.
+const COMMENT_MARKERS: &[&str] = &["//", "/*", "#", "--", ";"];
+
+/// A task as found in a file.
+struct Item {
+    source: String,
+    description: String,
+    done: bool,
+}
+
+/// `- [ ] text` and `- [x] text` items of a Markdown checklist.
+fn parse_markdown(content: &str) -> Vec<(String, bool)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start();
+            let line = line
+                .strip_prefix("- ")
+                .or_else(|| line.strip_prefix("* "))
+                .or_else(|| line.strip_prefix("+ "))?;
+            let (done, text) = if let Some(text) = line.strip_prefix("[ ] ") {
+                (false, text)
+            } else if let Some(text) = line
+                .strip_prefix("[x] ")
+                .or_else(|| line.strip_prefix("[X] "))
+            {
+                (true, text)
+            } else {
+                return None;
+            };
+            let text = text.trim();
+            (!text.is_empty()).then(|| (text.to_string(), done))
+        })
+        .collect()
+}
+
+/// Text of the `TODO:` comments in source code.
+fn parse_code(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let start = line.find("TODO:")?;
+            if !COMMENT_MARKERS.iter().any(|m| line[..start].contains(m)) {
+                return None;
+            }
+            let text = line[start + "TODO:".len()..]
+                .trim()
+                .trim_end_matches("*/")
+                .trim();
+            (!text.is_empty()).then(|| text.to_string())
+        })
+        .collect()
+}
+
+fn is_todo_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.eq_ignore_ascii_case("todo.md"))
+}
+
+fn is_source_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| SOURCE_EXTENSIONS.contains(&ext))
+}
+
+/// The files to scan under `dir`, skipping hidden and build directories.
+fn find_files(dir: &Path, code: bool, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    paths.sort();
+
+    for path in paths {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        if path.is_dir() {
+            if !name.starts_with('.') && !IGNORED_DIRS.contains(&name) {
+                find_files(&path, code, files);
+            }
+        } else if is_todo_file(&path) || (code && is_source_file(&path)) {
+            files.push(path);
+        }
+    }
+}
+
+/// Everything found in `files`. A `TODO.md`, or any Markdown file given
+/// directly, is read as a checklist and anything else as source code.
+fn collect(files: &[PathBuf]) -> Vec<Item> {
+    let mut items = Vec::new();
+    for file in files {
+        let Ok(content) = fs::read_to_string(file) else {
+            continue;
+        };
+        let source = file.display().to_string();
+        if file.extension().is_some_and(|ext| ext == "md") {
+            items.extend(
+                parse_markdown(&content)
+                    .into_iter()
+                    .map(|(description, done)| Item {
+                        source: source.clone(),
+                        description,
+                        done,
+                    }),
+            );
+        } else {
+            items.extend(parse_code(&content).into_iter().map(|description| Item {
+                source: source.clone(),
+                description,
+                done: false,
+            }));
+        }
+    }
+    items
+}
+
+fn set_completed(task: &mut Task, done: bool, detail: Option<String>) {
+    task.completed = done;
+    if done {
+        task.in_progress = false;
+        task.completed_at = Some(Local::now());
+        task.record(TaskEvent::Completed, detail);
+    } else {
+        task.completed_at = None;
+        task.record(TaskEvent::Reopened, detail);
+    }
+}
+
+/// Bring the tasks scanned from `files` in `project` up to date with
+/// `items`.
+fn apply(project: &mut Project, root: &Path, files: &[PathBuf], items: Vec<Item>) {
+    let mut seen = HashSet::new();
+
+    for item in items {
+        let existing = project.tasks.iter_mut().find(|t| {
+            !seen.contains(&t.id)
+                && t.fields.get(SOURCE_FIELD) == Some(&item.source)
+                && t.description == item.description
+        });
+
+        match existing {
+            Some(task) => {
+                seen.insert(task.id);
+                if task.completed != item.done {
+                    set_completed(task, item.done, None);
+                }
+            }
+            None => {
+                let mut task = Task {
+                    id: project.allocate_id(),
+                    description: item.description,
+                    ..Default::default()
+                };
+                task.fields.insert(SOURCE_FIELD.to_string(), item.source);
+                task.record(TaskEvent::Created, None);
+                if item.done {
+                    set_completed(&mut task, true, None);
+                }
+                seen.insert(task.id);
+                project.tasks.push(task);
+            }
+        }
+    }
+
+    // Gone from a file that was just scanned, or whose file is gone: done.
+    let scanned: HashSet<String> = files.iter().map(|f| f.display().to_string()).collect();
+    for task in &mut project.tasks {
+        let Some(source) = task.fields.get(SOURCE_FIELD) else {
+            continue;
+        };
+        let vanished = scanned.contains(source)
+            || (Path::new(source).starts_with(root) && !Path::new(source).exists());
+        if vanished && !seen.contains(&task.id) && !task.completed {
+            let detail = tr!("no longer in {}", source);
+            set_completed(task, true, Some(detail));
+        }
+    }
+}
+
+/// Scan `path`, a file or a directory, into `project_name`, by default
+/// named after it. With `dry_run`, only show what would change.
+pub fn scan(path: &Path, project_name: Option<&str>, code: bool, dry_run: bool) {
+    let root = match fs::canonicalize(path) {
+        Ok(root) => root,
+        Err(e) => {
+            println!("{}", tr!("Unable to read '{}': {}", path.display(), e));
+            return;
+        }
+    };
+    let project_name = match project_name {
+        Some(name) => name.to_string(),
+        None => {
+            // A TODO.md is named after the directory it's in.
+            let named = if root.is_dir() {
+                Some(root.as_path())
+            } else {
+                root.parent()
+            };
+            named
+                .and_then(|path| path.file_name())
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        }
+    };
+
+    let mut files = Vec::new();
+    if root.is_dir() {
+        find_files(&root, code, &mut files);
+    } else {
+        files.push(root.clone());
+    }
+    let items = collect(&files);
+    let found = items.len();
+
+    let before = load_data();
+    let mut data = before.clone();
+    if !data.iter().any(|p| p.name == project_name) {
+        data.push(Project {
+            name: project_name.clone(),
+            ..Default::default()
+        });
+    }
+    let project = data
+        .iter_mut()
+        .find(|p| p.name == project_name)
+        .expect("Project was just added");
+    apply(project, &root, &files, items);
+
+    let changes = diff::diff(&before, &data);
+    diff::print(&changes);
+    if dry_run {
+        println!("{}", tr!("Dry run, nothing was changed."));
+        return;
+    }
+    save_data(&data);
+
+    let (added, _, modified) = diff::count(&changes);
+    println!(
+        "{}",
+        tr!(
+            "Found {} tasks in {} files: {} new, {} updated.",
+            found,
+            files.len(),
+            added,
+            modified
+        )
+    );
+    for change in &changes {
+        match change {
+            Change::ProjectAdded(_) => {
+                if let Some(project) = data.iter().find(|p| p.name == project_name) {
+                    hooks::run(Event::ProjectAdd(project));
+                }
+            }
+            Change::TaskAdded(project, task) => hooks::run(Event::TaskAdd(project, task)),
+            Change::TaskCompleted(project, task) => hooks::run(Event::TaskComplete(project, task)),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Searching tasks across all projects.
+
+use crate::{
+    filter::Filter, i18n::tr, index, load_data, print_matching_tasks, template::TaskTemplate,
+    Project, Task,
+};
+
+/// Whether a search for `text`, lowercased, and `filter` finds `task` of
+/// `project`.
+fn finds(text: &str, filter: Option<&Filter>, project: &Project, task: &Task) -> bool {
+    task.description.to_lowercase().contains(text)
+        && filter.is_none_or(|f| f.matches(&project.name, task))
+}
+
+/// Print every task whose description contains `text` (case-insensitively)
+/// and that matches `filter`, if given, through `template` if given.
+pub fn search(text: &str, filter: Option<&Filter>, template: Option<&TaskTemplate>) {
+    let data = load_data();
+    let candidates = index::candidates(&data, text);
+    let text = text.to_lowercase();
+
+    let found = print_matching_tasks(&data, template, |project, task| {
+        candidates.as_ref().is_none_or(|candidates| {
+            candidates
+                .get(&project.name)
+                .is_some_and(|ids| ids.contains(&task.id))
+        }) && finds(&text, filter, project, task)
+    });
+
+    if found == 0 && template.is_none() {
+        println!("{}", tr!("No matching tasks."));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    use crate::{filter, Cli, Commands};
+
+    #[test]
+    fn filters_narrow_searches_down() {
+        let project = Project {
+            name: "web".to_string(),
+            ..Default::default()
+        };
+        let task = Task {
+            description: "Fix the Login form".to_string(),
+            tags: vec!["bug".to_string()],
+            ..Default::default()
+        };
+        let bugs = filter::parse("tag:bug").unwrap();
+        let elsewhere = filter::parse("project:api").unwrap();
+
+        assert!(finds("login", None, &project, &task));
+        assert!(finds("login", Some(&bugs), &project, &task));
+        assert!(!finds("login", Some(&elsewhere), &project, &task));
+        assert!(!finds("signup", Some(&bugs), &project, &task));
+    }
+
+    #[test]
+    fn bad_filters_are_refused_on_the_command_line() {
+        for command in ["search", "list-tasks"] {
+            let parse =
+                |filter: &str| Cli::try_parse_from(["pt", command, "web", "--filter", filter]);
+            assert!(parse("status:todo AND (tag:infra OR priority:high)").is_ok());
+            let error = parse("status:todo AND (tag:infra")
+                .err()
+                .expect("The filter is refused");
+            assert!(
+                error.to_string().contains("missing closing parenthesis"),
+                "{}",
+                error
+            );
+        }
+        let cli = Cli::try_parse_from(["pt", "search", "login", "--filter", "tag:bug"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Search {
+                filter: Some(_),
+                ..
+            })
+        ));
+    }
+}
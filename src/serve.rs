@@ -0,0 +1,102 @@
+//! `serve`: a small HTTP server making the tracker reachable from elsewhere.
+//!
+//! Routes:
+//!
+//! - `POST /slack`: Slack slash commands, see the `slack` module.
+//! - `GET /data` and `PUT /data`: the data itself, for clients using
+//!   `--remote`, see the `remote` module.
+//! - `/` and `/ui/...`: a web UI to check tasks off from a phone, see the
+//!   `webui` module.
+
+use std::{io, process::Command};
+use tiny_http::{Method, Response, Server};
+
+use crate::{i18n::tr, remote, slack, storage, webui, workspace};
+
+/// Run a command on the served workspace, in a process of its own so that
+/// what it prints can be sent back, and return that output.
+///
+/// `args` come from whoever sent the request, so none may be an option:
+/// the global ones, like `--workspace` or `--force`, would override the
+/// served workspace and its safeguards.
+pub fn run_command(args: &[String]) -> io::Result<String> {
+    if let Some(option) = args.iter().find(|arg| arg.starts_with('-')) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            tr!("options such as '{}' can't be given here", option),
+        ));
+    }
+    let mut process = Command::new(std::env::current_exe().expect("Failed to find executable"));
+    process.args(["--workspace", workspace::active()]);
+    if storage::is_read_only() {
+        process.arg("--read-only");
+    }
+    // Whatever is sent back may be seen by others.
+    process.arg("--redacted");
+    let output = process.args(args).env("NO_COLOR", "1").output()?;
+    Ok(format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    )
+    .trim_end()
+    .to_string())
+}
+
+/// Answer the requests to a server on a free local port with `answer`, on
+/// this thread, while `client` sends them from another, given the server's
+/// URL. Returns what `client` does.
+#[cfg(test)]
+pub fn exchange<T: Send + 'static>(
+    mut answer: impl FnMut(&mut tiny_http::Request) -> Response<io::Cursor<Vec<u8>>>,
+    client: impl FnOnce(String) -> T + Send + 'static,
+) -> T {
+    let server = Server::http("127.0.0.1:0").expect("A local port is free");
+    let addr = server.server_addr().to_ip().expect("The server is on IP");
+    let client = std::thread::spawn(move || client(format!("http://{}", addr)));
+    while !client.is_finished() {
+        if let Ok(Some(mut request)) = server.recv_timeout(std::time::Duration::from_millis(10)) {
+            let response = answer(&mut request);
+            let _ = request.respond(response);
+        }
+    }
+    client
+        .join()
+        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
+
+pub fn serve(addr: &str) {
+    let server = match Server::http(addr) {
+        Ok(server) => server,
+        Err(e) => {
+            println!("{}", tr!("Unable to listen on {}: {}", addr, e));
+            return;
+        }
+    };
+
+    println!("{}", tr!("Listening on http://{}", addr));
+    if slack::signing_secret().is_none() {
+        println!(
+            "{}",
+            tr!("PT_SLACK_SIGNING_SECRET is not set, Slack commands will be refused.")
+        );
+    }
+    if remote::api_token().is_none() {
+        println!(
+            "{}",
+            tr!("PT_API_TOKEN is not set, remote clients will be refused.")
+        );
+    }
+
+    for mut request in server.incoming_requests() {
+        let response = match (request.method(), request.url()) {
+            (Method::Post, "/slack") => slack::handle(&mut request),
+            (Method::Get | Method::Put, "/data") => remote::handle(&mut request),
+            (Method::Get, "/") => webui::redirect(),
+            (_, url) if url.starts_with("/ui/") => webui::handle(&mut request),
+            _ => Response::from_string("Not found").with_status_code(404),
+        };
+        // The client going away before the response is sent is its problem.
+        let _ = request.respond(response);
+    }
+}
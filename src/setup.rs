@@ -0,0 +1,185 @@
+//! The setup wizard: where to keep the data, the theme, the workspace to
+//! use by default and tasks to bring over from elsewhere, written to the
+//! config file. It runs the first time the tracker is used from a
+//! terminal, with no data or config yet, and again with `setup`.
+
+use clap::ValueEnum;
+use dialoguer::{theme::ColorfulTheme, Input, Select};
+use std::{
+    io::{stdin, stdout, IsTerminal},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{
+    config::{self, load_config, save_config},
+    i18n::tr,
+    import::ImportFormat,
+    theme::Theme,
+    workspace::{self, DEFAULT_WORKSPACE},
+};
+
+/// Whether the tracker was never used here, and someone is at the terminal
+/// to set it up.
+pub fn is_first_run() -> bool {
+    stdin().is_terminal()
+        && stdout().is_terminal()
+        && !config::is_saved()
+        && workspace::existing_data_file(DEFAULT_WORKSPACE).is_none()
+}
+
+fn select(prompt: &str, choices: &[String], default: usize) -> usize {
+    Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .items(choices)
+        .default(default)
+        .interact_opt()
+        .ok()
+        .flatten()
+        .unwrap_or(default)
+}
+
+fn input(prompt: &str, default: Option<&str>) -> Option<String> {
+    let theme = ColorfulTheme::default();
+    let mut input = Input::<String>::with_theme(&theme).with_prompt(prompt);
+    if let Some(default) = default {
+        input = input.default(default.to_string());
+    }
+    input
+        .interact_text()
+        .ok()
+        .map(|answer| answer.trim().to_string())
+}
+
+/// A directory for the data, or `None` to keep it where it is.
+fn ask_data_dir() -> Option<PathBuf> {
+    let choices = [
+        tr!(
+            "In the config directory, {}",
+            workspace::data_dir().display()
+        ),
+        tr!("Somewhere else, e.g. a synced folder"),
+    ];
+    if select(&tr!("Where should the data be kept?"), &choices, 0) == 0 {
+        return None;
+    }
+    loop {
+        let dir = input(&tr!("Directory"), None)?;
+        match config::parse_dir(&dir) {
+            Ok(dir) => return Some(dir),
+            Err(e) => println!("{}", e),
+        }
+    }
+}
+
+fn ask_theme() -> Theme {
+    let current = load_config().settings.theme.unwrap_or_default();
+    let themes = Theme::value_variants();
+    let names: Vec<String> = themes
+        .iter()
+        .filter_map(|theme| theme.to_possible_value())
+        .map(|value| value.get_name().to_string())
+        .collect();
+    let default = themes.iter().position(|t| *t == current).unwrap_or(0);
+    themes[select(&tr!("Theme"), &names, default)]
+}
+
+fn ask_workspace() -> String {
+    let current = load_config()
+        .settings
+        .default_workspace
+        .unwrap_or_else(|| DEFAULT_WORKSPACE.to_string());
+    loop {
+        let Some(name) = input(&tr!("Workspace to use by default"), Some(&current)) else {
+            return current;
+        };
+        if workspace::is_valid_name(&name) {
+            return name;
+        }
+        println!("{}", workspace::invalid_name(&name));
+    }
+}
+
+/// A file to import tasks from, and its format, if any.
+fn ask_import() -> Option<(PathBuf, ImportFormat)> {
+    let formats = [
+        (tr!("Nothing, start afresh"), None),
+        (
+            tr!("A Markdown file with task lists"),
+            Some(ImportFormat::Markdown),
+        ),
+        (
+            tr!("A Todoist project exported as CSV"),
+            Some(ImportFormat::Todoist),
+        ),
+        (
+            tr!("TaskWarrior, from `task export > tasks.json`"),
+            Some(ImportFormat::Taskwarrior),
+        ),
+    ];
+    let choices: Vec<String> = formats.iter().map(|(name, _)| name.clone()).collect();
+    let format = formats[select(&tr!("Import tasks from"), &choices, 0)].1?;
+    loop {
+        let file = PathBuf::from(input(&tr!("File"), None)?);
+        if file.is_file() {
+            return Some((file, format));
+        }
+        println!("{}", tr!("No file at '{}'.", file.display()));
+    }
+}
+
+/// Import `file` into `workspace_name`, as `import` would.
+fn import(workspace_name: &str, file: &Path, format: ImportFormat) {
+    let format = format
+        .to_possible_value()
+        .expect("Import formats are all shown");
+    let status = Command::new(std::env::current_exe().expect("Failed to find executable"))
+        .args(["--workspace", workspace_name, "import"])
+        .arg(file)
+        .args(["--format", format.get_name()])
+        .status();
+    if let Err(e) = status {
+        println!("{}", tr!("Unable to import '{}': {}", file.display(), e));
+    }
+}
+
+/// Ask how to set the tracker up, and do it.
+pub fn wizard() {
+    if !stdin().is_terminal() {
+        println!("{}", tr!("The setup asks questions, and needs a terminal."));
+        return;
+    }
+    println!(
+        "{}",
+        tr!("Welcome! A few questions to set the tracker up; the defaults are fine too.")
+    );
+
+    let data_dir = ask_data_dir();
+    let theme = ask_theme();
+    let workspace_name = ask_workspace();
+    let to_import = ask_import();
+
+    let previous_data_dir = workspace::data_dir();
+    let mut config = load_config();
+    if data_dir.is_some() {
+        config.settings.data_dir = data_dir;
+    }
+    config.settings.theme = Some(theme);
+    config.settings.default_workspace =
+        (workspace_name != DEFAULT_WORKSPACE).then(|| workspace_name.clone());
+    save_config(&config);
+    if workspace::data_dir() != previous_data_dir {
+        workspace::move_data(&previous_data_dir);
+    }
+    if !workspace::exists(&workspace_name) {
+        workspace::create_workspace(&workspace_name);
+    }
+    if let Some((file, format)) = to_import {
+        import(&workspace_name, &file, format);
+    }
+
+    println!(
+        "{}",
+        tr!("All set! Change any of it with `config set`, or run `setup` again.")
+    );
+}
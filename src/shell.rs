@@ -0,0 +1,254 @@
+//! Interactive shell: run subcommands without the binary name, with line
+//! editing, persistent history and tab-completion of commands and projects.
+
+use clap::{CommandFactory, Parser};
+use rustyline::{
+    completion::Completer, error::ReadlineError, Context, Editor, Helper, Highlighter, Hinter,
+    Validator,
+};
+use std::sync::OnceLock;
+
+use crate::{
+    aliases, args, confirm_range, get_config_dir, i18n::tr, load_data, protect, run, storage,
+    workspace, Cli, Commands, GlobalArgs,
+};
+
+static GLOBAL_OPTIONS: OnceLock<GlobalArgs> = OnceLock::new();
+
+#[derive(Helper, Highlighter, Hinter, Validator)]
+struct ShellHelper {
+    commands: Vec<String>,
+    projects: Vec<String>,
+}
+
+impl ShellHelper {
+    fn refresh_projects(&mut self) {
+        self.projects = load_data().into_iter().map(|p| p.name).collect();
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos].rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let word = &line[start..pos];
+
+        // The first word is a command, anything after it is most likely a
+        // project name.
+        let candidates = if line[..start].trim().is_empty() {
+            self.commands
+                .iter()
+                .filter(|c| c.starts_with(word))
+                .cloned()
+                .collect()
+        } else {
+            self.projects
+                .iter()
+                .filter(|p| p.starts_with(word))
+                .map(|p| {
+                    if p.contains(char::is_whitespace) {
+                        format!("\"{}\"", p)
+                    } else {
+                        p.clone()
+                    }
+                })
+                .collect()
+        };
+
+        Ok((start, candidates))
+    }
+}
+
+/// Record the global options the shell is started with.
+pub fn set_global_options(options: GlobalArgs) {
+    let _ = GLOBAL_OPTIONS.set(options);
+}
+
+/// Why the global options of a line can't be used, if they can't: they're
+/// set once, when the shell starts, and would otherwise be silently ignored.
+fn refusal(options: &GlobalArgs) -> Option<String> {
+    if options
+        .workspace
+        .as_deref()
+        .is_some_and(|w| w != workspace::active())
+    {
+        return Some(tr!("The workspace can't be changed inside the shell."));
+    }
+    if options.read_only && !storage::is_read_only() {
+        return Some(tr!(
+            "Start the shell with --read-only to make it read-only."
+        ));
+    }
+
+    let startup = GLOBAL_OPTIONS.get().cloned().unwrap_or_default();
+    let changed = [
+        ("--dry-run", options.dry_run && !startup.dry_run),
+        ("--force", options.force && !startup.force),
+        ("--redacted", options.redacted && !startup.redacted),
+        ("--plain", options.plain && !startup.plain),
+        ("--ascii", options.ascii && !startup.ascii),
+        (
+            "--include-snoozed",
+            options.include_snoozed && !startup.include_snoozed,
+        ),
+        ("--verbose", options.verbose > startup.verbose),
+        (
+            "--remote",
+            options.remote.is_some() && options.remote != startup.remote,
+        ),
+        (
+            "--token",
+            options.token.is_some() && options.token != startup.token,
+        ),
+    ];
+    let (option, _) = changed.into_iter().find(|(_, changed)| *changed)?;
+    Some(tr!(
+        "{} can't be changed inside the shell, start the shell with it instead.",
+        option
+    ))
+}
+
+/// The command lines to run for the words of a line, the aliases already
+/// expanded. Prints why and returns `None` when it can't be run.
+fn parse_words(words: Vec<String>) -> Option<Vec<Cli>> {
+    if let Some(refusal) = refusal(&args::global_options(&words)) {
+        println!("{}", refusal);
+        return None;
+    }
+    let lines = args::expand(words)?;
+    match lines
+        .into_iter()
+        .map(Cli::try_parse_from)
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(clis) => Some(clis),
+        Err(e) => {
+            let _ = e.print();
+            None
+        }
+    }
+}
+
+pub fn run_shell() {
+    let mut editor: Editor<ShellHelper, _> = match Editor::new() {
+        Ok(editor) => editor,
+        Err(e) => {
+            println!("{}", tr!("Unable to start the shell: {}", e));
+            return;
+        }
+    };
+
+    let mut helper = ShellHelper {
+        commands: Cli::command()
+            .get_subcommands()
+            .map(|c| c.get_name().to_string())
+            .chain(["help", "exit"].map(String::from))
+            .collect(),
+        projects: Vec::new(),
+    };
+    helper.refresh_projects();
+    editor.set_helper(Some(helper));
+
+    let history_file = get_config_dir().join("shell_history");
+    let _ = editor.load_history(&history_file);
+
+    println!(
+        "{}",
+        tr!("Type `help` for a list of commands, `exit` or Ctrl-D to leave.")
+    );
+
+    loop {
+        let line = match editor.readline("pt> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                println!("{}", tr!("Unable to read input: {}", e));
+                break;
+            }
+        };
+
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+
+        if line == "exit" || line == "quit" {
+            break;
+        }
+
+        let Some(words) = shlex::split(line) else {
+            println!("{}", tr!("Unbalanced quotes."));
+            continue;
+        };
+        let words = aliases::expand(std::iter::once("pt".to_string()).chain(words).collect());
+        let Some(clis) = parse_words(words) else {
+            continue;
+        };
+        // Each line is confirmed on its own, as each run of the binary is.
+        protect::forget_confirmations();
+        if !confirm_range(&clis) {
+            continue;
+        }
+
+        for cli in &clis {
+            match &cli.command {
+                Some(Commands::Shell) => println!("{}", tr!("Already in the shell.")),
+                command => run(command.as_ref()),
+            }
+        }
+
+        if let Some(helper) = editor.helper_mut() {
+            helper.refresh_projects();
+        }
+    }
+
+    let _ = editor.save_history(&history_file);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(line: &str) -> Vec<String> {
+        std::iter::once("pt".to_string())
+            .chain(shlex::split(line).expect("Balanced quotes"))
+            .collect()
+    }
+
+    #[test]
+    fn lines_changing_global_options_are_not_run() {
+        for line in [
+            "--dry-run delete-project scratch",
+            "delete-project scratch --force",
+            "--redacted list-projects",
+            "--plain list-projects",
+            "--ascii list-projects",
+            "--include-snoozed list-projects",
+            "-v list-projects",
+            "--remote http://localhost:8080 list-projects",
+            "--workspace=elsewhere list-projects",
+        ] {
+            assert!(parse_words(words(line)).is_none(), "'{}' was run", line);
+        }
+    }
+
+    #[test]
+    fn lines_without_global_options_are_run() {
+        let clis = parse_words(words("delete-project scratch")).expect("The line is run");
+        assert!(matches!(
+            clis.as_slice(),
+            [Cli {
+                command: Some(Commands::DeleteProject { .. }),
+                ..
+            }]
+        ));
+    }
+}
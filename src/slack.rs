@@ -0,0 +1,321 @@
+//! Slack slash commands, answered by `serve` on `/slack`.
+//!
+//! Requests are verified with the app's signing secret, taken from the
+//! `PT_SLACK_SIGNING_SECRET` environment variable. Each command is run as a
+//! separate `project-tracker` process, its output sent back as blocks:
+//!
+//! ```text
+//! /pt add myproject "fix bug"
+//! /pt done myproject 3
+//! /pt list [project]
+//! /pt today
+//! ```
+
+use chrono::Utc;
+use hmac::{Hmac, KeyInit, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::HashMap;
+use tiny_http::{Header, Request, Response};
+
+use crate::{i18n::tr, serve};
+
+/// Requests signed longer ago than this many seconds are refused, so that
+/// captured ones can't be replayed.
+const MAX_AGE: i64 = 5 * 60;
+
+/// Slack refuses section texts longer than this.
+const MAX_TEXT: usize = 3000;
+
+pub fn signing_secret() -> Option<String> {
+    std::env::var("PT_SLACK_SIGNING_SECRET")
+        .ok()
+        .filter(|s| !s.is_empty())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Slack's `v0=` signature of `body` sent at `timestamp`.
+fn sign(secret: &str, timestamp: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("v0:{}:{}", timestamp, body).as_bytes());
+    format!("v0={}", hex(&mac.finalize().into_bytes()))
+}
+
+/// Whether `signature` is Slack's `v0=` signature of `body` sent at
+/// `timestamp`.
+fn verify(secret: &str, timestamp: &str, body: &str, signature: &str) -> bool {
+    let Ok(sent_at) = timestamp.parse::<i64>() else {
+        return false;
+    };
+    if (Utc::now().timestamp() - sent_at).abs() > MAX_AGE {
+        return false;
+    }
+
+    let expected = sign(secret, timestamp, body);
+    // Compared in constant time, not to leak how much of it matched.
+    expected.len() == signature.len()
+        && expected
+            .bytes()
+            .zip(signature.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn decode_component(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let digits = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+                match u8::from_str_radix(digits, 16) {
+                    Ok(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    Err(_) => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Fields of an `application/x-www-form-urlencoded` body.
+fn parse_form(body: &str) -> HashMap<String, String> {
+    body.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (decode_component(key), decode_component(value)))
+        .collect()
+}
+
+fn header<'a>(request: &'a Request, name: &str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.as_str().as_str().eq_ignore_ascii_case(name))
+        .map(|h| h.value.as_str())
+}
+
+fn json_response(body: &Value) -> Response<std::io::Cursor<Vec<u8>>> {
+    Response::from_string(body.to_string()).with_header(
+        "Content-Type: application/json"
+            .parse::<Header>()
+            .expect("Valid header"),
+    )
+}
+
+/// Answer the slash command in `request`.
+pub fn handle(request: &mut Request) -> Response<std::io::Cursor<Vec<u8>>> {
+    let mut body = String::new();
+    if request.as_reader().read_to_string(&mut body).is_err() {
+        return Response::from_string("Bad request").with_status_code(400);
+    }
+
+    let Some(secret) = signing_secret() else {
+        return Response::from_string("Slack commands are not enabled").with_status_code(503);
+    };
+    let timestamp = header(request, "X-Slack-Request-Timestamp").unwrap_or_default();
+    let signature = header(request, "X-Slack-Signature").unwrap_or_default();
+    if !verify(&secret, timestamp, &body, signature) {
+        return Response::from_string("Invalid signature").with_status_code(401);
+    }
+
+    let form = parse_form(&body);
+    let command = form.get("command").map_or("/pt", String::as_str);
+    let text = form.get("text").map_or("", String::as_str);
+    let user = form.get("user_id").map_or("", String::as_str);
+    json_response(&reply(command, text, user))
+}
+
+/// Arguments of the `project-tracker` command for the slash command's
+/// `text`, and whether the result is worth showing to the whole channel.
+fn to_args(text: &str) -> Result<(Vec<String>, bool), String> {
+    let Some(words) = shlex::split(text) else {
+        return Err(tr!("Unbalanced quotes in '{}'.", text));
+    };
+    let Some((name, rest)) = words.split_first() else {
+        return Err(usage());
+    };
+
+    let args = match (name.as_str(), rest) {
+        ("add", [project, description @ ..]) if !description.is_empty() => (
+            vec![
+                "add-task".to_string(),
+                project.clone(),
+                description.join(" "),
+            ],
+            true,
+        ),
+        ("done", task) if !task.is_empty() => (
+            ["complete-task".to_string()]
+                .into_iter()
+                .chain(task.iter().cloned())
+                .collect(),
+            true,
+        ),
+        ("list", []) => (vec!["list-projects".to_string()], false),
+        ("list", [project]) => (vec!["list-tasks".to_string(), project.clone()], false),
+        ("today", []) => (vec!["today".to_string()], false),
+        _ => return Err(usage()),
+    };
+    Ok(args)
+}
+
+fn usage() -> String {
+    tr!("Usage: add <project> <description>, done <project> <task id>, list [project], today")
+}
+
+/// The response to `/pt <text>`, as Slack blocks.
+fn reply(command: &str, text: &str, user: &str) -> Value {
+    let (args, in_channel) = match to_args(text) {
+        Ok(args) => args,
+        Err(message) => {
+            return json!({
+                "response_type": "ephemeral",
+                "text": message,
+            })
+        }
+    };
+
+    let mut result = match serve::run_command(&args) {
+        Ok(output) => output,
+        Err(e) => {
+            return json!({
+                "response_type": "ephemeral",
+                "text": tr!("Failed to run the command: {}", e),
+            })
+        }
+    };
+    if result.len() > MAX_TEXT - 6 {
+        let mut end = MAX_TEXT - 7;
+        while !result.is_char_boundary(end) {
+            end -= 1;
+        }
+        result.truncate(end);
+        result.push('…');
+    }
+
+    json!({
+        "response_type": if in_channel { "in_channel" } else { "ephemeral" },
+        "text": result,
+        "blocks": [
+            {
+                "type": "context",
+                "elements": [{
+                    "type": "mrkdwn",
+                    "text": format!("<@{}> `{} {}`", user, command, text),
+                }],
+            },
+            {
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": format!("```{}```", result) },
+            },
+        ],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "8f742231b10e8888abcd99yyyzzz85a5";
+
+    #[test]
+    fn signatures_match_slacks() {
+        // The example of https://api.slack.com/authentication/verifying-requests-from-slack.
+        let body = "token=xyzz0WbapA4vBCDEFasx0q6G&team_id=T1DC2JH3J&team_domain=testteamnow&channel_id=G8PSS9T3V&channel_name=foobar&user_id=U2CERLKJA&user_name=roadrunner&command=%2Fwebhook-collect&text=&response_url=https%3A%2F%2Fhooks.slack.com%2Fcommands%2FT1DC2JH3J%2F397700885554%2F96rGlfmibIGlgcZRskXaIFfN&trigger_id=398738663015.47445629121.803a0bc887a14d10d2c447fce8b6703c";
+        assert_eq!(
+            sign(SECRET, "1531420618", body),
+            "v0=a2114d57b48eac39b9ad189dd8316235a7b4a8d21a10bd27519666489c69b503"
+        );
+    }
+
+    #[test]
+    fn only_fresh_requests_signed_with_the_secret_pass() {
+        let body = "command=%2Fpt&text=today";
+        let now = Utc::now().timestamp().to_string();
+        let signature = sign(SECRET, &now, body);
+        assert!(verify(SECRET, &now, body, &signature));
+
+        assert!(!verify(SECRET, &now, "command=%2Fpt&text=list", &signature));
+        assert!(!verify("another secret", &now, body, &signature));
+        assert!(!verify(
+            SECRET,
+            &now,
+            body,
+            &signature[..signature.len() - 1]
+        ));
+        assert!(!verify(SECRET, &now, body, ""));
+        assert!(!verify(SECRET, "now", body, &signature));
+
+        let old = (Utc::now().timestamp() - MAX_AGE - 1).to_string();
+        assert!(!verify(SECRET, &old, body, &sign(SECRET, &old, body)));
+        let ahead = (Utc::now().timestamp() + MAX_AGE + 1).to_string();
+        assert!(!verify(SECRET, &ahead, body, &sign(SECRET, &ahead, body)));
+    }
+
+    #[test]
+    fn forms_are_decoded() {
+        let form =
+            parse_form("command=%2Fpt&text=add+web+%22fix+bug%22&user_id=U1&broken=%zz&empty=");
+        assert_eq!(form["command"], "/pt");
+        assert_eq!(form["text"], "add web \"fix bug\"");
+        assert_eq!(form["broken"], "%zz");
+        assert_eq!(form["empty"], "");
+    }
+
+    #[test]
+    fn slash_commands_become_tracker_commands() {
+        let args = |text| to_args(text).map(|(args, in_channel)| (args.join("|"), in_channel));
+        assert_eq!(
+            args("add web \"fix the bug\" now"),
+            Ok(("add-task|web|fix the bug now".to_string(), true))
+        );
+        assert_eq!(
+            args("done web 3"),
+            Ok(("complete-task|web|3".to_string(), true))
+        );
+        assert_eq!(args("list"), Ok(("list-projects".to_string(), false)));
+        assert_eq!(args("list web"), Ok(("list-tasks|web".to_string(), false)));
+        assert_eq!(args("today"), Ok(("today".to_string(), false)));
+        for text in [
+            "",
+            "add web",
+            "done",
+            "list web api",
+            "delete-project web",
+            "add \"web",
+        ] {
+            assert!(to_args(text).is_err(), "'{}' is run", text);
+        }
+    }
+
+    #[test]
+    fn options_are_never_run() {
+        for text in [
+            "done web 3 --force",
+            "list --workspace=other",
+            "add web --force",
+        ] {
+            let reply = reply("/pt", text, "U1");
+            assert_eq!(reply["response_type"], "ephemeral", "'{}' was run", text);
+            assert!(
+                reply["text"]
+                    .as_str()
+                    .unwrap()
+                    .contains("can't be given here"),
+                "{}",
+                reply
+            );
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Snapshots of the data at a point in time, gzip-compressed under
+//! `snapshots/<workspace>/` in the config directory, and what changed
+//! between two of them, e.g. over a sprint.
+
+use chrono::Local;
+use clap::Subcommand;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use std::{
+    fs::{self, File},
+    io::{Read, Write},
+    path::PathBuf,
+};
+
+use crate::{diff, diff::Change, get_config_dir, i18n::tr, load_data, workspace, Project};
+
+const EXTENSION: &str = ".json.gz";
+
+#[derive(Subcommand)]
+pub enum SnapshotCommand {
+    /// Save a snapshot of the current data.
+    Save {
+        /// Name to refer to it by, e.g. sprint-12.
+        label: Option<String>,
+    },
+    /// List saved snapshots, oldest first.
+    List,
+    /// Show what changed between two snapshots, or since one.
+    Diff {
+        from: String,
+        /// Defaults to the current data.
+        to: Option<String>,
+    },
+    /// Delete a snapshot.
+    Delete { name: String },
+}
+
+fn get_snapshots_dir() -> PathBuf {
+    get_config_dir().join("snapshots").join(workspace::active())
+}
+
+/// Names of the saved snapshots, oldest first. Names start with the time
+/// they were taken, followed by their label if any.
+fn all() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(get_snapshots_dir()) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            e.file_name()
+                .to_string_lossy()
+                .strip_suffix(EXTENSION)
+                .map(str::to_string)
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// The snapshot `reference` refers to: its full name, its label, or the
+/// start of its name such as a date.
+fn find(reference: &str) -> Result<String, String> {
+    let names = all();
+    if let Some(name) = names.iter().find(|name| {
+        *name == reference
+            || name
+                .split_once('-')
+                .is_some_and(|(_, label)| label == reference)
+    }) {
+        return Ok(name.clone());
+    }
+
+    let matching: Vec<&String> = names
+        .iter()
+        .filter(|name| name.starts_with(reference))
+        .collect();
+    match matching.as_slice() {
+        [name] => Ok((*name).clone()),
+        [] => Err(tr!("Snapshot '{}' not found.", reference)),
+        _ => Err(tr!(
+            "Several snapshots match '{}': {}",
+            reference,
+            matching
+                .iter()
+                .map(|s| s.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+fn read(name: &str) -> Result<Vec<Project>, String> {
+    let file = File::open(get_snapshots_dir().join(format!("{}{}", name, EXTENSION)))
+        .map_err(|e| e.to_string())?;
+    let mut content = Vec::new();
+    GzDecoder::new(file)
+        .read_to_end(&mut content)
+        .map_err(|e| e.to_string())?;
+    serde_json::from_slice(&content).map_err(|e| e.to_string())
+}
+
+pub fn run(command: &SnapshotCommand) {
+    match command {
+        SnapshotCommand::Save { label } => save(label.as_deref()),
+        SnapshotCommand::List => list(),
+        SnapshotCommand::Diff { from, to } => show_diff(from, to.as_deref()),
+        SnapshotCommand::Delete { name } => delete(name),
+    }
+}
+
+fn save(label: Option<&str>) {
+    let label = label.map(str::trim).filter(|label| !label.is_empty());
+    if let Some(label) = label {
+        let valid = label
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            println!(
+                "{}",
+                tr!(
+                    "Invalid label '{}': use only letters, digits, '-' and '_'.",
+                    label
+                )
+            );
+            return;
+        }
+        if all()
+            .iter()
+            .any(|name| name.split_once('-').is_some_and(|(_, l)| l == label))
+        {
+            println!("{}", tr!("A snapshot labelled '{}' already exists.", label));
+            return;
+        }
+    }
+
+    let mut name = Local::now().format("%Y%m%dT%H%M%S").to_string();
+    if let Some(label) = label {
+        name = format!("{}-{}", name, label);
+    }
+
+    fs::create_dir_all(get_snapshots_dir()).expect("Failed to create snapshots directory");
+    let file = File::create(get_snapshots_dir().join(format!("{}{}", name, EXTENSION)))
+        .expect("Unable to create snapshot.");
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    let content = serde_json::to_vec(&load_data()).expect("Unable to serialize data.");
+    encoder
+        .write_all(&content)
+        .and_then(|_| encoder.finish().map(drop))
+        .expect("Unable to write snapshot.");
+
+    println!("{}", tr!("Snapshot '{}' saved.", name));
+}
+
+fn list() {
+    let names = all();
+    if names.is_empty() {
+        println!("{}", tr!("No snapshots saved."));
+        return;
+    }
+    println!("{}", tr!("Snapshots:"));
+    for name in names {
+        println!(" - {}", name);
+    }
+}
+
+fn show_diff(from: &str, to: Option<&str>) {
+    let load = |reference: &str| find(reference).and_then(|name| read(&name));
+    let before = match load(from) {
+        Ok(data) => data,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    let after = match to {
+        Some(to) => match load(to) {
+            Ok(data) => data,
+            Err(e) => {
+                println!("{}", e);
+                return;
+            }
+        },
+        None => load_data(),
+    };
+
+    let changes = diff::diff(&before, &after);
+    diff::print(&changes);
+    if changes.is_empty() {
+        return;
+    }
+
+    let count = |matches: fn(&Change) -> bool| changes.iter().filter(|c| matches(c)).count();
+    println!();
+    println!(
+        "{}",
+        tr!(
+            "{} added, {} completed, {} deleted, {} otherwise changed.",
+            count(|c| matches!(c, Change::TaskAdded(..))),
+            count(|c| matches!(c, Change::TaskCompleted(..))),
+            count(|c| matches!(c, Change::TaskRemoved(..))),
+            count(|c| matches!(c, Change::TaskModified(..)))
+        )
+    );
+}
+
+fn delete(reference: &str) {
+    match find(reference) {
+        Ok(name) => {
+            fs::remove_file(get_snapshots_dir().join(format!("{}{}", name, EXTENSION)))
+                .expect("Unable to delete snapshot.");
+            println!("{}", tr!("Snapshot '{}' has been deleted.", name));
+        }
+        Err(e) => println!("{}", e),
+    }
+}
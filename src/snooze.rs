@@ -0,0 +1,88 @@
+//! Snoozing tasks: `snooze web 12 --until monday` keeps a task out of
+//! listings until that day comes, for ideas to come back to later, as does
+//! `--until 2w` for two weeks.
+//! `--include-snoozed` shows them anyway.
+
+use chrono::{Duration, NaiveDate};
+use std::sync::OnceLock;
+
+use crate::{dates, durations, i18n::tr, load_data, save_data, Task, TaskEvent};
+
+static INCLUDE_FLAG: OnceLock<bool> = OnceLock::new();
+
+/// Record whether `--include-snoozed` was given.
+pub fn set_include_snoozed(include: bool) {
+    let _ = INCLUDE_FLAG.set(include);
+}
+
+impl Task {
+    /// Whether the task is snoozed until a day still to come.
+    pub fn is_snoozed(&self) -> bool {
+        !self.completed
+            && self
+                .snoozed_until
+                .is_some_and(|until| until > dates::today())
+    }
+}
+
+/// Whether `task` should be listed, given `--include-snoozed`.
+pub fn shown(task: &Task) -> bool {
+    !task.is_snoozed() || INCLUDE_FLAG.get().copied().unwrap_or(false)
+}
+
+/// The day `until` is, a date or a span of time from today such as `3d`.
+fn parse_until(until: &str) -> Result<NaiveDate, String> {
+    if !durations::looks_like_duration(until) {
+        return dates::parse_date(until);
+    }
+    // Tasks are snoozed for whole days, a part of one counting as one.
+    let minutes = durations::parse_span(until)?;
+    Ok(dates::today() + Duration::days(i64::from(minutes.div_ceil(24 * 60))))
+}
+
+/// Snooze a task until `until`, or wake it up with `none`.
+pub fn snooze(project_name: &str, task_id: u32, until: &str) {
+    let until = if until.eq_ignore_ascii_case("none") {
+        None
+    } else {
+        match parse_until(until) {
+            Ok(date) if date <= dates::today() => {
+                println!("{}", tr!("Tasks can only be snoozed until a later day."));
+                return;
+            }
+            Ok(date) => Some(date),
+            Err(e) => {
+                println!("{}", tr!("Invalid date: {}", e));
+                return;
+            }
+        }
+    };
+
+    let mut data = load_data();
+    if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
+        if let Some(task) = project.tasks.iter_mut().find(|t| t.id == task_id) {
+            if task.completed && until.is_some() {
+                println!("{}", tr!("Task {} is already completed!", task_id));
+                return;
+            }
+            task.snoozed_until = until;
+            let detail = match until {
+                Some(date) => format!("snoozed until {}", date),
+                None => "woken up".to_string(),
+            };
+            task.record(TaskEvent::Edited, Some(detail));
+            save_data(&data);
+            match until {
+                Some(date) => println!("{}", tr!("Task {} is snoozed until {}.", task_id, date)),
+                None => println!("{}", tr!("Task {} is no longer snoozed.", task_id)),
+            }
+        } else {
+            println!(
+                "{}",
+                tr!("Task {} not found in project '{}'.", task_id, project_name)
+            );
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
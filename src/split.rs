@@ -0,0 +1,84 @@
+//! `split-task`: break a task too big to get done in one go into several,
+//! each with the original's tags, contexts, priority and due date. The
+//! original is closed as split, or deleted with `--remove-original`, and
+//! the history of both sides says where the tasks came from.
+
+use chrono::Local;
+
+use crate::{
+    history::TaskEvent,
+    hooks::{self, Event},
+    i18n::tr,
+    links, load_data, protect, save_data, sync, Task,
+};
+
+pub fn split_task(project_name: &str, task_id: u32, parts: &[String], remove_original: bool) {
+    if remove_original && !protect::confirm(project_name, &tr!("deleting task {}", task_id)) {
+        return;
+    }
+    let mut data = load_data();
+    let Some(project) = data.iter_mut().find(|p| p.name == project_name) else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+        return;
+    };
+    let Some(original) = project.tasks.iter().find(|t| t.id == task_id) else {
+        println!(
+            "{}",
+            tr!("Task {} not found in project '{}'.", task_id, project_name)
+        );
+        return;
+    };
+    let template = Task {
+        tags: original.tags.clone(),
+        contexts: original.contexts.clone(),
+        priority: original.priority,
+        due: original.due,
+        ..Task::default()
+    };
+    let original_ref = project.display_id(task_id);
+
+    let mut ids = Vec::new();
+    for part in parts {
+        let task = project.add_task(part, &template);
+        if let Some(created) = task.history.last_mut() {
+            created.detail = Some(format!("split from {}", original_ref));
+        }
+        ids.push(task.id);
+    }
+    let new_refs: Vec<String> = ids.iter().map(|id| project.display_id(*id)).collect();
+
+    if remove_original {
+        project.remove_task(task_id);
+        links::unlink_deleted(&mut data, project_name, Some(task_id));
+    } else {
+        let original = project
+            .tasks
+            .iter_mut()
+            .find(|t| t.id == task_id)
+            .expect("The task was found above");
+        original.completed = true;
+        original.in_progress = false;
+        original.completed_at = Some(Local::now());
+        original.record(
+            TaskEvent::Split,
+            Some(format!("into {}", new_refs.join(", "))),
+        );
+    }
+    save_data(&data);
+
+    println!(
+        "{}",
+        tr!("Split task {} into {}.", original_ref, new_refs.join(", "))
+    );
+    let project = data
+        .iter()
+        .find(|p| p.name == project_name)
+        .expect("The project was found above");
+    for task in project.tasks.iter().filter(|t| ids.contains(&t.id)) {
+        hooks::run(Event::TaskAdd(project_name, task));
+    }
+    if remove_original {
+        sync::record_deletion(project_name, Some(task_id));
+        hooks::run(Event::TaskDelete(project_name, task_id));
+    }
+}
@@ -0,0 +1,76 @@
+//! Stale tasks: pending tasks nobody has touched in a while.
+
+use chrono::Local;
+
+use crate::{
+    contexts, display, i18n::tr, load_data, save_data, tags, task_details, theme::Themed, Project,
+    Task,
+};
+
+/// Days since `task` was last changed, if that was recorded.
+pub fn untouched_days(task: &Task) -> Option<i64> {
+    Some((Local::now() - task.updated_at()?).num_days())
+}
+
+/// Pending tasks untouched for more than `days` days, oldest first, with
+/// their age in days.
+fn stale_tasks(data: &[Project], days: u32) -> Vec<(&Project, &Task, i64)> {
+    let mut stale: Vec<(&Project, &Task, i64)> = data
+        .iter()
+        .flat_map(|project| project.tasks.iter().map(move |task| (project, task)))
+        .filter(|(_, task)| !task.completed && contexts::shown(task))
+        .filter_map(|(project, task)| {
+            let age = untouched_days(task)?;
+            (age > i64::from(days)).then_some((project, task, age))
+        })
+        .collect();
+    stale.sort_by_key(|(_, _, age)| -age);
+    stale
+}
+
+pub fn show_stale(days: u32, auto_tag: Option<&str>) {
+    let mut data = load_data();
+
+    let stale: Vec<(String, u32)> = {
+        let stale = stale_tasks(&data, days);
+        if stale.is_empty() {
+            println!("{}", tr!("No tasks untouched for more than {} days.", days));
+            return;
+        }
+
+        println!("{}", tr!("Tasks untouched for more than {} days:", days));
+        for (project, task, age) in &stale {
+            println!(
+                "    {}: {}{} {}",
+                project.task_ref(task.id),
+                display::description(task),
+                task_details(task),
+                tr!("({} days)", age).bad()
+            );
+        }
+        stale
+            .iter()
+            .map(|(project, task, _)| (project.name.clone(), task.id))
+            .collect()
+    };
+
+    let Some(tag) = auto_tag.map(tags::normalize).filter(|t| !t.is_empty()) else {
+        return;
+    };
+
+    let mut tagged = 0;
+    for (project_name, task_id) in stale {
+        let task = data
+            .iter_mut()
+            .find(|p| p.name == project_name)
+            .and_then(|p| p.tasks.iter_mut().find(|t| t.id == task_id));
+        // Not recorded in the history on purpose: that would count as
+        // touching the task, and it would no longer show up as stale.
+        if let Some(task) = task.filter(|t| !t.tags.contains(&tag)) {
+            task.tags.push(tag.clone());
+            tagged += 1;
+        }
+    }
+    save_data(&data);
+    println!("{}", tr!("Tagged {} tasks #{}.", tagged, tag));
+}
@@ -0,0 +1,1005 @@
+//! Loading and saving of the active workspace's data file.
+//!
+//! Loading never touches the file (a missing file is just an empty
+//! tracker), and saving is skipped entirely when nothing changed, so
+//! read-only commands never rewrite the data.
+//!
+//! The file can be stored as JSON, TOML or YAML. Loading picks up whichever
+//! one exists, saving uses the `format` setting and converts the file when
+//! it changed.
+//!
+//! With the `split-projects` setting, each project is kept in a file of its
+//! own instead, and the data file only lists them in order. Saving then only
+//! writes the files of the projects that changed, loading only parses those
+//! that changed since last loaded, and `load_project` reads a single one.
+//! Turning the setting on or off converts the data like `format` does.
+//!
+//! A workspace can be made read-only, with `--read-only` or the `read-only`
+//! setting, for files that are only meant to be looked at.
+//!
+//! With `--dry-run`, saving only shows what would have changed.
+//!
+//! A data file that can't be parsed is copied aside as
+//! `<data file>.corrupt-<time>`, and whatever projects can still be read
+//! from it are loaded. Saving over it is then refused unless `--force` is
+//! given, so what couldn't be recovered is never lost by accident.
+//!
+//! With `--remote`, the data is loaded from and saved to another tracker
+//! instead, see the `remote` module. Both are behind the `Storage` trait,
+//! as is the in-memory storage tests switch to with `use_memory`, to run
+//! commands without touching the disk.
+//!
+//! The data last loaded or saved is kept in memory along with the size and
+//! modification time of its files, so that loading it again, as most
+//! commands do a few times, only parses the files that changed.
+
+use chrono::Local;
+use clap::ValueEnum;
+use serde::{de, de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    fs::{self, File, Metadata},
+    io::{BufWriter, ErrorKind, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    time::{Instant, SystemTime},
+};
+use tracing::{debug, info, Level};
+
+use crate::{
+    backup, config::load_config, diff, graph, i18n::tr, index, remote, workspace, Project,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum DataFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl DataFormat {
+    pub const ALL: [DataFormat; 3] = [DataFormat::Json, DataFormat::Toml, DataFormat::Yaml];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            DataFormat::Json => "json",
+            DataFormat::Toml => "toml",
+            DataFormat::Yaml => "yaml",
+        }
+    }
+}
+
+/// TOML documents must be tables, so the project list goes under a key.
+#[derive(Serialize, Deserialize)]
+struct TomlDocument<T> {
+    projects: T,
+}
+
+/// `value` in `format`, where it has to be a table for TOML.
+fn encode<T: Serialize + ?Sized>(value: &T, format: DataFormat) -> Vec<u8> {
+    let content = match format {
+        DataFormat::Json => serde_json::to_vec_pretty(value).map_err(|e| e.to_string()),
+        DataFormat::Toml => toml::to_string_pretty(value)
+            .map(String::into_bytes)
+            .map_err(|e| e.to_string()),
+        DataFormat::Yaml => serde_yaml::to_string(value)
+            .map(String::into_bytes)
+            .map_err(|e| e.to_string()),
+    };
+    content.expect("Unable to serialize data.")
+}
+
+fn decode<T: DeserializeOwned>(content: &[u8], format: DataFormat) -> Result<T, String> {
+    match format {
+        DataFormat::Json => serde_json::from_slice(content).map_err(|e| e.to_string()),
+        DataFormat::Toml => std::str::from_utf8(content)
+            .map_err(|e| e.to_string())
+            .and_then(|s| toml::from_str(s).map_err(|e| e.to_string())),
+        DataFormat::Yaml => serde_yaml::from_slice(content).map_err(|e| e.to_string()),
+    }
+}
+
+/// `items` in `format`, under a `projects` key for TOML.
+fn encode_list<T: Serialize>(items: &[T], format: DataFormat) -> Vec<u8> {
+    match format {
+        DataFormat::Toml => encode(&TomlDocument { projects: items }, format),
+        _ => encode(items, format),
+    }
+}
+
+fn decode_list<T: DeserializeOwned>(content: &[u8], format: DataFormat) -> Result<Vec<T>, String> {
+    match format {
+        DataFormat::Toml => {
+            decode(content, format).map(|document: TomlDocument<Vec<T>>| document.projects)
+        }
+        _ => decode(content, format),
+    }
+}
+
+pub fn serialize(data: &[Project], format: DataFormat) -> Vec<u8> {
+    encode_list(data, format)
+}
+
+fn deserialize(content: &[u8], format: DataFormat) -> Result<Vec<Project>, String> {
+    decode_list(content, format)
+}
+
+/// The file `project` is kept in with `split-projects`. Anything in its
+/// name but lowercase letters, digits, '-' and '_' is escaped, for the name
+/// to be a valid one everywhere, on case-insensitive file systems too.
+fn project_file(dir: &Path, project: &str, format: DataFormat) -> PathBuf {
+    let mut name = String::new();
+    for byte in project.bytes() {
+        match byte {
+            b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' => name.push(byte as char),
+            _ => name.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    dir.join(format!("{}.{}", name, format.extension()))
+}
+
+/// Whether `path` is a project file, in any format, and not a copy of one
+/// kept aside.
+fn is_project_file(path: &Path) -> bool {
+    path.is_file()
+        && path.extension().is_some_and(|ext| {
+            DataFormat::ALL
+                .iter()
+                .any(|format| ext == format.extension())
+        })
+}
+
+/// Collects the elements of a JSON array up to the first one that can't be
+/// parsed, for getting what's left of a truncated file.
+struct PartialSeq<'a>(&'a mut Vec<Value>);
+
+impl<'de> de::Visitor<'de> for PartialSeq<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list of projects")
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(value) = seq.next_element()? {
+            self.0.push(value);
+        }
+        Ok(())
+    }
+}
+
+/// The projects that can still be read from a data file that can't be
+/// parsed as a whole.
+fn recover(content: &[u8], format: DataFormat) -> Vec<Project> {
+    match format {
+        DataFormat::Json => {
+            let mut values = Vec::new();
+            let _ = serde_json::Deserializer::from_slice(content)
+                .deserialize_seq(PartialSeq(&mut values));
+            values
+                .into_iter()
+                .filter_map(|value| serde_json::from_value(value).ok())
+                .collect()
+        }
+        DataFormat::Toml => std::str::from_utf8(content)
+            .ok()
+            .and_then(|s| toml::from_str::<toml::Table>(s).ok())
+            .and_then(|mut table| table.remove("projects"))
+            .and_then(|projects| match projects {
+                toml::Value::Array(projects) => Some(projects),
+                _ => None,
+            })
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|project| project.try_into().ok())
+            .collect(),
+        DataFormat::Yaml => serde_yaml::from_slice::<serde_yaml::Value>(content)
+            .ok()
+            .and_then(|value| match value {
+                serde_yaml::Value::Sequence(projects) => Some(projects),
+                _ => None,
+            })
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|project| serde_yaml::from_value(project).ok())
+            .collect(),
+    }
+}
+
+/// Copy a corrupt data file aside, unless an identical copy is already
+/// there from an earlier run. Returns where the copy is.
+fn keep_corrupt(data_file: &Path, content: &[u8]) -> PathBuf {
+    let name = data_file
+        .file_name()
+        .map(|name| format!("{}.corrupt-", name.to_string_lossy()))
+        .unwrap_or_default();
+    let dir = data_file.parent().unwrap_or(Path::new("."));
+    let existing = fs::read_dir(dir).ok().and_then(|entries| {
+        entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .is_some_and(|n| n.to_string_lossy().starts_with(&name))
+            })
+            .find(|path| fs::read(path).is_ok_and(|copy| copy == content))
+    });
+    existing.unwrap_or_else(|| {
+        let stamp = format!("{}{}", name, Local::now().format("%Y%m%d-%H%M%S"));
+        // Another copy may have been kept within the same second.
+        let copy = (1..)
+            .map(|n| match n {
+                1 => dir.join(&stamp),
+                n => dir.join(format!("{}-{}", stamp, n)),
+            })
+            .find(|copy| !copy.exists())
+            .expect("A free name is found");
+        fs::write(&copy, content).expect("Unable to copy corrupt data file.");
+        copy
+    })
+}
+
+static READ_ONLY_FLAG: OnceLock<bool> = OnceLock::new();
+static DRY_RUN_FLAG: OnceLock<bool> = OnceLock::new();
+static FORCE_FLAG: OnceLock<bool> = OnceLock::new();
+
+/// Workspaces with a data file that couldn't be read entirely, and the
+/// file.
+static CORRUPT: Mutex<BTreeSet<(String, PathBuf)>> = Mutex::new(BTreeSet::new());
+
+/// The size and modification time of a file, to tell whether it changed.
+struct FileState {
+    path: PathBuf,
+    modified: SystemTime,
+    len: u64,
+}
+
+impl FileState {
+    fn of(path: &Path) -> Option<FileState> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(FileState {
+            path: path.to_path_buf(),
+            modified: metadata.modified().ok()?,
+            len: metadata.len(),
+        })
+    }
+
+    fn is_current(&self) -> bool {
+        fs::metadata(&self.path).is_ok_and(|metadata| self.matches(&metadata))
+    }
+
+    fn matches(&self, metadata: &Metadata) -> bool {
+        self.len == metadata.len()
+            && metadata
+                .modified()
+                .is_ok_and(|modified| modified == self.modified)
+    }
+}
+
+/// A workspace's data as last loaded or saved.
+struct Cached {
+    /// The data file first, then the project files with `split-projects`.
+    files: Vec<FileState>,
+    data: Vec<Project>,
+    /// The content of the project files, by path.
+    contents: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+impl Cached {
+    fn is_current(&self, data_file: &Path) -> bool {
+        self.files
+            .first()
+            .is_some_and(|file| file.path == data_file)
+            && self.files.iter().all(FileState::is_current)
+    }
+
+    /// The content of `path`, unless it changed since.
+    fn content(&self, path: &Path) -> Option<&Vec<u8>> {
+        let file = self.files.iter().find(|file| file.path == path)?;
+        file.is_current().then(|| self.contents.get(path)).flatten()
+    }
+}
+
+static CACHE: Mutex<BTreeMap<String, Cached>> = Mutex::new(BTreeMap::new());
+
+/// Keep `data` as the content of `files`, as they are now.
+fn cache(name: &str, files: &[PathBuf], data: &[Project], contents: BTreeMap<PathBuf, Vec<u8>>) {
+    let Some(files) = files.iter().map(|file| FileState::of(file)).collect() else {
+        return;
+    };
+    CACHE.lock().unwrap().insert(
+        name.to_string(),
+        Cached {
+            files,
+            data: data.to_vec(),
+            contents,
+        },
+    );
+}
+
+/// Record whether `--read-only` was given.
+pub fn set_read_only(read_only: bool) {
+    let _ = READ_ONLY_FLAG.set(read_only);
+}
+
+/// Whether the active workspace must not be written to.
+pub fn is_read_only() -> bool {
+    READ_ONLY_FLAG.get().copied().unwrap_or(false)
+        || load_config().effective().read_only.unwrap_or(false)
+}
+
+/// Record whether `--dry-run` was given.
+pub fn set_dry_run(dry_run: bool) {
+    let _ = DRY_RUN_FLAG.set(dry_run);
+}
+
+/// Whether changes are only to be shown, not written.
+pub fn is_dry_run() -> bool {
+    DRY_RUN_FLAG.get().copied().unwrap_or(false)
+}
+
+/// Record whether `--force` was given.
+pub fn set_force(force: bool) {
+    let _ = FORCE_FLAG.set(force);
+}
+
+/// Whether `--force` was given.
+pub fn is_forced() -> bool {
+    FORCE_FLAG.get().copied().unwrap_or(false)
+}
+
+/// Whether the active workspace's data file couldn't be read entirely.
+pub fn is_corrupt() -> bool {
+    CORRUPT
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|(name, _)| name == workspace::active())
+}
+
+/// Keep a data file that couldn't be parsed aside, and say so, once.
+fn report_corrupt(name: &str, file: &Path, content: &[u8], error: &str, recovered: usize) {
+    // Only once, the data may be loaded several times.
+    if CORRUPT
+        .lock()
+        .unwrap()
+        .insert((name.to_string(), file.to_path_buf()))
+    {
+        let copy = keep_corrupt(file, content);
+        info!("unable to parse {}: {}", file.display(), error);
+        eprintln!(
+            "{}",
+            tr!(
+                "Unable to parse data file: {}. A copy was kept as '{}', and {} projects could be recovered. Nothing will be saved over it unless --force is given.",
+                error,
+                copy.display(),
+                recovered
+            )
+        );
+    }
+}
+
+/// Read a data file that isn't the active workspace's, in whichever format
+/// its extension says.
+pub fn read_data_file(path: &Path) -> Result<Vec<Project>, String> {
+    let format = path
+        .extension()
+        .and_then(|ext| {
+            DataFormat::ALL
+                .into_iter()
+                .find(|format| ext == format.extension())
+        })
+        .unwrap_or(DataFormat::Json);
+    let content = fs::read(path).map_err(|e| e.to_string())?;
+    deserialize(&content, format)
+}
+
+/// Where projects are kept, to be loaded from and saved to.
+pub trait Storage {
+    fn load(&self) -> Vec<Project>;
+    fn save(&self, data: &[Project]);
+}
+
+/// Where the active workspace's data is kept: on another tracker with
+/// `--remote`, in its data file otherwise.
+fn backend() -> &'static dyn Storage {
+    #[cfg(test)]
+    if MEMORY.with_borrow(Option::is_some) {
+        return &MemoryStorage;
+    }
+    if remote::is_active() {
+        &remote::RemoteStorage
+    } else {
+        &FileStorage
+    }
+}
+
+pub fn load_data() -> Vec<Project> {
+    backend().load()
+}
+
+/// Load the data of a workspace other than the active one.
+pub fn load_workspace_data(name: &str) -> Vec<Project> {
+    let Some((data_file, format)) = workspace::existing_data_file(name) else {
+        debug!("no data file for workspace '{}'", name);
+        return Vec::new();
+    };
+    let started = Instant::now();
+
+    if let Some(cached) = CACHE.lock().unwrap().get(name) {
+        if cached.is_current(&data_file) {
+            debug!("{} unchanged since loaded", data_file.display());
+            return cached.data.clone();
+        }
+    }
+
+    let content = match fs::read(&data_file) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Vec::new(),
+        Err(e) => panic!("Unable to read data file: {}", e),
+    };
+
+    let projects_dir = workspace::projects_dir(name);
+    if projects_dir.is_dir() {
+        return load_split(name, &data_file, &content, format, &projects_dir);
+    }
+
+    if content.iter().all(u8::is_ascii_whitespace) {
+        return Vec::new();
+    }
+    let mut data = deserialize(&content, format).unwrap_or_else(|e| {
+        let data = recover(&content, format);
+        report_corrupt(name, &data_file, &content, &e, data.len());
+        data
+    });
+    migrate(&mut data);
+    cache(
+        name,
+        std::slice::from_ref(&data_file),
+        &data,
+        BTreeMap::new(),
+    );
+    debug!(
+        "loaded {} projects from {} in {:?}",
+        data.len(),
+        data_file.display(),
+        started.elapsed()
+    );
+    data
+}
+
+/// Load the projects of a workspace kept in a file each, in `dir`, in the
+/// order its data file lists them. The ones whose file didn't change since
+/// last loaded aren't parsed again.
+fn load_split(
+    name: &str,
+    data_file: &Path,
+    content: &[u8],
+    format: DataFormat,
+    dir: &Path,
+) -> Vec<Project> {
+    let started = Instant::now();
+    let names: Option<Vec<String>> = if content.iter().all(u8::is_ascii_whitespace) {
+        Some(Vec::new())
+    } else {
+        decode_list(content, format).ok()
+    };
+    // Without the list, every project file there is recovered.
+    let files: Vec<PathBuf> = match &names {
+        Some(names) => names
+            .iter()
+            .map(|project| project_file(dir, project, format))
+            .collect(),
+        None => {
+            let mut files: Vec<PathBuf> = fs::read_dir(dir)
+                .expect("Unable to read projects directory.")
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.extension()
+                        .is_some_and(|ext| ext == format.extension())
+                })
+                .collect();
+            files.sort();
+            files
+        }
+    };
+
+    let cache_guard = CACHE.lock().unwrap();
+    let cached = cache_guard.get(name);
+    let (mut data, mut loaded, mut contents, mut parsed) = (
+        Vec::new(),
+        vec![data_file.to_path_buf()],
+        BTreeMap::new(),
+        0,
+    );
+    for file in &files {
+        let content = match cached.and_then(|cached| cached.content(file)) {
+            Some(content) => content.clone(),
+            None => match fs::read(file) {
+                Ok(content) => content,
+                Err(e) if e.kind() == ErrorKind::NotFound => {
+                    CORRUPT
+                        .lock()
+                        .unwrap()
+                        .insert((name.to_string(), file.clone()));
+                    eprintln!(
+                        "{}",
+                        tr!(
+                            "'{}' is missing, its project wasn't loaded. Nothing will be saved unless --force is given.",
+                            file.display()
+                        )
+                    );
+                    continue;
+                }
+                Err(e) => panic!("Unable to read data file: {}", e),
+            },
+        };
+        let project = cached
+            .filter(|cached| cached.content(file) == Some(&content))
+            .and_then(|cached| {
+                let index = cached.files.iter().position(|f| f.path == *file)?;
+                // The data file comes first.
+                cached.data.get(index - 1).cloned()
+            });
+        let project = match project {
+            Some(project) => project,
+            None => {
+                parsed += 1;
+                match decode::<Project>(&content, format) {
+                    Ok(project) => project,
+                    Err(e) => {
+                        report_corrupt(name, file, &content, &e, 0);
+                        continue;
+                    }
+                }
+            }
+        };
+        data.push(project);
+        loaded.push(file.clone());
+        contents.insert(file.clone(), content);
+    }
+    drop(cache_guard);
+
+    if names.is_none() {
+        report_corrupt(
+            name,
+            data_file,
+            content,
+            &tr!("unable to read the project list"),
+            data.len(),
+        );
+    }
+    migrate(&mut data);
+    cache(name, &loaded, &data, contents);
+    debug!(
+        "loaded {} projects from {}, {} of them parsed, in {:?}",
+        data.len(),
+        dir.display(),
+        parsed,
+        started.elapsed()
+    );
+    data
+}
+
+/// Load a project of the active workspace, which with `split-projects`
+/// only reads its own file.
+pub fn load_project(project: &str) -> Option<Project> {
+    let dir = workspace::projects_dir(workspace::active());
+    if remote::is_active() || !dir.is_dir() {
+        return load_data().into_iter().find(|p| p.name == project);
+    }
+    let (_, format) = workspace::existing_data_file(workspace::active())?;
+    let content = fs::read(project_file(&dir, project, format)).ok()?;
+    match decode::<Project>(&content, format) {
+        Ok(project) => {
+            let mut data = vec![project];
+            migrate(&mut data);
+            data.pop()
+        }
+        // Loading everything says what's wrong with it.
+        Err(_) => load_data().into_iter().find(|p| p.name == project),
+    }
+}
+
+/// The files a workspace's data is kept in: its data file, and the file of
+/// each project with `split-projects`.
+pub fn data_files(name: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = workspace::existing_data_file(name)
+        .map(|(data_file, _)| data_file)
+        .into_iter()
+        .collect();
+    if let Ok(entries) = fs::read_dir(workspace::projects_dir(name)) {
+        let mut projects: Vec<PathBuf> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| is_project_file(path))
+            .collect();
+        projects.sort();
+        files.extend(projects);
+    }
+    files
+}
+
+/// When a workspace's data last changed, if it has any.
+pub fn last_modified(name: &str) -> Option<SystemTime> {
+    data_files(name)
+        .iter()
+        .filter_map(|file| fs::metadata(file).and_then(|m| m.modified()).ok())
+        .max()
+}
+
+/// Give projects saved before they kept a task ID counter one, starting
+/// after the highest ID in use.
+fn migrate(data: &mut [Project]) {
+    for project in data.iter_mut().filter(|p| p.next_id == 0) {
+        project.next_id = project.tasks.iter().map(|t| t.id + 1).max().unwrap_or(1);
+    }
+}
+
+pub fn save_data(data: &[Project]) {
+    if is_dry_run() {
+        diff::print(&diff::diff(&load_data(), data));
+        println!("{}", tr!("Dry run, nothing was saved."));
+        return;
+    }
+
+    // Commands changing the data are refused long before getting here.
+    assert!(!is_read_only(), "Refusing to write a read-only workspace.");
+
+    if is_corrupt() && !is_forced() {
+        eprintln!(
+            "{}",
+            tr!("The data file couldn't be read entirely, nothing was saved. Use --force to save the recovered projects over it.")
+        );
+        std::process::exit(1);
+    }
+
+    backend().save(data);
+}
+
+/// The active workspace's data file.
+pub struct FileStorage;
+
+impl Storage for FileStorage {
+    fn load(&self) -> Vec<Project> {
+        load_workspace_data(workspace::active())
+    }
+
+    fn save(&self, data: &[Project]) {
+        let name = workspace::active();
+        let existing = workspace::existing_data_file(name);
+        let settings = load_config().effective();
+        let format = settings
+            .format
+            .or(existing.as_ref().map(|(_, format)| *format))
+            .unwrap_or(DataFormat::Json);
+        let data_file = workspace::data_file_path(name, format);
+        let projects_dir = workspace::projects_dir(name);
+        let split = settings
+            .split_projects
+            .unwrap_or_else(|| projects_dir.is_dir());
+
+        // What's about to change, for anyone wondering later where a task went.
+        if tracing::enabled!(Level::INFO) {
+            for change in diff::diff(&load_data(), data) {
+                info!("{}", diff::describe(&change));
+            }
+        }
+
+        let started = Instant::now();
+        // The search index only needs updating when descriptions changed, which
+        // most of the time they didn't.
+        let descriptions_changed = CACHE
+            .lock()
+            .unwrap()
+            .get(name)
+            .is_none_or(|cached| !same_descriptions(&cached.data, data));
+
+        let written = if split {
+            save_split(name, data, &data_file, format, &projects_dir)
+        } else {
+            save_whole(name, data, &data_file, format, &projects_dir)
+        };
+        if !written {
+            debug!("{} unchanged, not saved", data_file.display());
+            return;
+        }
+
+        // The format changed, the old file has been replaced by the new one.
+        if let Some((old_file, _)) = existing.filter(|(old_file, _)| *old_file != data_file) {
+            info!(
+                "replacing {} with {}",
+                old_file.display(),
+                data_file.display()
+            );
+            fs::remove_file(old_file).expect("Unable to remove old data file.");
+        }
+        info!(
+            "saved {} projects to {} in {:?}",
+            data.len(),
+            data_file.display(),
+            started.elapsed()
+        );
+
+        if descriptions_changed {
+            index::update(data);
+        }
+        graph::record(data);
+        backup::schedule();
+    }
+}
+
+/// Write `content` to a sibling file first and move it into place, so an
+/// interrupted save can never leave a truncated file behind.
+fn write_atomically(path: &Path, content: &[u8]) {
+    let extension = path
+        .extension()
+        .map(|ext| format!("{}.tmp", ext.to_string_lossy()))
+        .unwrap_or_else(|| "tmp".to_string());
+    let tmp_file = path.with_extension(extension);
+    let file = File::create(&tmp_file).expect("Unable to open data file.");
+    let mut writer = BufWriter::new(file);
+    writer
+        .write_all(content)
+        .and_then(|_| writer.flush())
+        .expect("Unable to write data file.");
+    drop(writer);
+
+    fs::rename(&tmp_file, path).expect("Unable to write data file.");
+}
+
+/// Save `data` to `data_file`, all of it, turning `split-projects` off if
+/// it was on. Returns whether anything was written.
+fn save_whole(
+    name: &str,
+    data: &[Project],
+    data_file: &Path,
+    format: DataFormat,
+    projects_dir: &Path,
+) -> bool {
+    let content = serialize(data, format);
+    let was_split = projects_dir.is_dir();
+    if !was_split && fs::read(data_file).is_ok_and(|current| current == content) {
+        return false;
+    }
+    write_atomically(data_file, &content);
+    if was_split {
+        info!(
+            "merging {} into {}",
+            projects_dir.display(),
+            data_file.display()
+        );
+        fs::remove_dir_all(projects_dir).expect("Unable to remove project files.");
+    }
+    cache(name, &[data_file.to_path_buf()], data, BTreeMap::new());
+    true
+}
+
+/// Save each project of `data` to its own file in `dir`, only writing those
+/// that changed, and their names to `data_file`. Returns whether anything
+/// was written.
+fn save_split(
+    name: &str,
+    data: &[Project],
+    data_file: &Path,
+    format: DataFormat,
+    dir: &Path,
+) -> bool {
+    fs::create_dir_all(dir).expect("Unable to create projects directory.");
+    let mut written = false;
+
+    let cache_guard = CACHE.lock().unwrap();
+    let cached = cache_guard.get(name);
+    let (mut files, mut contents) = (vec![data_file.to_path_buf()], BTreeMap::new());
+    for project in data {
+        let file = project_file(dir, &project.name, format);
+        let content = encode(project, format);
+        let unchanged = match cached.and_then(|cached| cached.content(&file)) {
+            Some(current) => *current == content,
+            None => fs::read(&file).is_ok_and(|current| current == content),
+        };
+        if !unchanged {
+            debug!("writing {}", file.display());
+            write_atomically(&file, &content);
+            written = true;
+        }
+        files.push(file.clone());
+        contents.insert(file, content);
+    }
+    drop(cache_guard);
+
+    // Those of projects deleted or renamed since, or kept in another format.
+    for entry in fs::read_dir(dir).expect("Unable to read projects directory.") {
+        let path = entry.expect("Unable to read projects directory.").path();
+        if is_project_file(&path) && !contents.contains_key(&path) {
+            debug!("removing {}", path.display());
+            fs::remove_file(&path).expect("Unable to remove project file.");
+            written = true;
+        }
+    }
+
+    let names: Vec<&str> = data.iter().map(|project| project.name.as_str()).collect();
+    let content = encode_list(&names, format);
+    if !fs::read(data_file).is_ok_and(|current| current == content) {
+        write_atomically(data_file, &content);
+        written = true;
+    }
+    cache(name, &files, data, contents);
+    written
+}
+
+#[cfg(test)]
+thread_local! {
+    /// The data of the in-memory storage, once a test switched to it.
+    static MEMORY: std::cell::RefCell<Option<Vec<Project>>> = const { std::cell::RefCell::new(None) };
+}
+
+/// Projects kept in memory only, for tests: commands run on the thread of
+/// a test calling `use_memory` load and save them instead of the data file.
+#[cfg(test)]
+pub struct MemoryStorage;
+
+#[cfg(test)]
+impl Storage for MemoryStorage {
+    fn load(&self) -> Vec<Project> {
+        MEMORY.with_borrow(|data| data.clone().unwrap_or_default())
+    }
+
+    fn save(&self, data: &[Project]) {
+        MEMORY.set(Some(data.to_vec()));
+    }
+}
+
+/// Switch the current thread to the in-memory storage, starting from `data`.
+#[cfg(test)]
+pub fn use_memory(data: Vec<Project>) {
+    MEMORY.set(Some(data));
+}
+
+/// Whether `a` and `b` have the same tasks with the same descriptions.
+fn same_descriptions(a: &[Project], b: &[Project]) -> bool {
+    fn descriptions(data: &[Project]) -> impl Iterator<Item = (&str, u32, &str)> {
+        data.iter().flat_map(|p| {
+            p.tasks
+                .iter()
+                .map(move |t| (p.name.as_str(), t.id, t.description.as_str()))
+        })
+    }
+    descriptions(a).eq(descriptions(b))
+}
+
+/// Rewrite the active workspace's data in the configured format.
+pub fn convert() {
+    if is_read_only() {
+        println!(
+            "{}",
+            tr!("The workspace is read-only, its data file keeps its current format.")
+        );
+        return;
+    }
+    if workspace::existing_data_file(workspace::active()).is_some() {
+        save_data(&load_data());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_files_have_safe_distinct_names() {
+        let dir = Path::new("projects");
+        let file = |name| project_file(dir, name, DataFormat::Json);
+        assert_eq!(file("web-2_a"), dir.join("web-2_a.json"));
+        assert_eq!(file("Big Stuff/2"), dir.join("%42ig%20%53tuff%2F2.json"));
+        assert_ne!(file("Web"), file("web"));
+        assert_ne!(file("a/b"), file("a%2Fb"));
+    }
+
+    #[test]
+    fn project_lists_round_trip_in_every_format() {
+        let names = vec!["web".to_string(), "Big Stuff".to_string()];
+        for format in DataFormat::ALL {
+            let content = encode_list(&names, format);
+            assert_eq!(decode_list::<String>(&content, format).unwrap(), names);
+            // A list of names is never taken for the projects themselves.
+            assert!(deserialize(&content, format).is_err());
+        }
+    }
+
+    fn projects(names: &[&str]) -> Vec<Project> {
+        names
+            .iter()
+            .map(|name| Project {
+                name: name.to_string(),
+                tasks: vec![crate::Task {
+                    id: 1,
+                    description: format!("A task of {}", name),
+                    ..Default::default()
+                }],
+                next_id: 2,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn names(data: &[Project]) -> Vec<&str> {
+        data.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn projects_that_can_be_read_are_recovered_in_every_format() {
+        let data = projects(&["web", "api", "docs"]);
+        for format in DataFormat::ALL {
+            // The tasks of "api" made a number.
+            let content = match format {
+                DataFormat::Json => {
+                    let mut value = serde_json::to_value(&data).unwrap();
+                    value[1]["tasks"] = 5.into();
+                    serde_json::to_vec(&value).unwrap()
+                }
+                DataFormat::Toml => {
+                    let content = serialize(&data, format);
+                    let mut table: toml::Table =
+                        toml::from_str(std::str::from_utf8(&content).unwrap()).unwrap();
+                    table["projects"][1]
+                        .as_table_mut()
+                        .unwrap()
+                        .insert("tasks".to_string(), 5.into());
+                    toml::to_string(&table).unwrap().into_bytes()
+                }
+                DataFormat::Yaml => {
+                    let mut value = serde_yaml::to_value(&data).unwrap();
+                    value[1]["tasks"] = 5.into();
+                    serde_yaml::to_string(&value).unwrap().into_bytes()
+                }
+            };
+            assert!(deserialize(&content, format).is_err(), "{:?}", format);
+            let recovered = recover(&content, format);
+            assert_eq!(names(&recovered), ["web", "docs"], "{:?}", format);
+            assert_eq!(recovered[1].tasks[0].description, "A task of docs");
+        }
+    }
+
+    #[test]
+    fn projects_before_where_a_json_file_was_cut_are_recovered() {
+        let content = serialize(&projects(&["web", "api", "docs"]), DataFormat::Json);
+        let docs = content.windows(6).position(|w| w == b"\"docs\"").unwrap();
+        let truncated = &content[..docs + 10];
+        assert!(deserialize(truncated, DataFormat::Json).is_err());
+        assert_eq!(names(&recover(truncated, DataFormat::Json)), ["web", "api"]);
+    }
+
+    #[test]
+    fn nothing_is_recovered_from_garbage() {
+        for format in DataFormat::ALL {
+            assert!(recover(b"\0\x01 not projects {[", format).is_empty());
+        }
+    }
+
+    #[test]
+    fn corrupt_files_are_kept_once_each() {
+        let dir = crate::get_config_dir().join("corrupt");
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("data.json");
+
+        let first = keep_corrupt(&file, b"[{\"name\": ");
+        assert_eq!(fs::read(&first).unwrap(), b"[{\"name\": ");
+        assert!(first
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("data.json.corrupt-"));
+        assert_eq!(keep_corrupt(&file, b"[{\"name\": "), first);
+
+        let second = keep_corrupt(&file, b"[{\"name\": \"web");
+        assert_ne!(second, first);
+        assert_eq!(fs::read(&first).unwrap(), b"[{\"name\": ");
+        assert_eq!(fs::read(&second).unwrap(), b"[{\"name\": \"web");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+    }
+}
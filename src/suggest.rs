@@ -0,0 +1,157 @@
+//! `suggest-commit`: a draft commit message from the tasks completed since
+//! the last commit of the repository in the current directory or, outside
+//! of one, since the last time it was asked for, ready for
+//! `suggest-commit | git commit -F - -e`.
+
+use chrono::{DateTime, Duration, Local};
+use std::{fs, path::PathBuf, process::Command};
+
+use crate::{get_config_dir, i18n::tr, load_data, privacy, workspace, Project, Task};
+
+/// When `revision`, a commit or tag of the repository in the current
+/// directory, was committed, if in one and it's there.
+pub fn commit_time(revision: &str) -> Option<DateTime<Local>> {
+    let output = Command::new("git")
+        .args(["log", "-1", "--format=%cI", revision, "--"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let date = String::from_utf8(output.stdout).ok()?;
+    DateTime::parse_from_rfc3339(date.trim())
+        .ok()
+        .map(|date| date.with_timezone(&Local))
+}
+
+/// Where the time of the last suggestion is kept.
+fn last_run_file() -> PathBuf {
+    get_config_dir()
+        .join("cache")
+        .join(format!("suggest-commit-{}", workspace::active()))
+}
+
+fn last_run() -> Option<DateTime<Local>> {
+    let content = fs::read_to_string(last_run_file()).ok()?;
+    DateTime::parse_from_rfc3339(content.trim())
+        .ok()
+        .map(|date| date.with_timezone(&Local))
+}
+
+/// `Fix the login form`, out of `fix the login form.`.
+fn sentence(description: &str) -> String {
+    let description = description.trim().trim_end_matches('.');
+    let mut chars = description.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn message(completed: &[(&Project, &Task)], project_name: Option<&str>) -> String {
+    let subject = match completed {
+        [(_, task)] => sentence(privacy::public_description(task)),
+        _ => match project_name {
+            Some(project) => tr!("Complete {} tasks in {}", completed.len(), project),
+            None => tr!("Complete {} tasks", completed.len()),
+        },
+    };
+    let body: Vec<String> = completed
+        .iter()
+        .map(|(project, task)| {
+            format!(
+                "- {} ({})",
+                sentence(privacy::public_description(task)),
+                project.task_ref(task.id)
+            )
+        })
+        .collect();
+    format!("{}\n\n{}", subject, body.join("\n"))
+}
+
+/// Print a commit message for the tasks of `project`, or of all projects,
+/// completed since the last commit or suggestion.
+pub fn suggest_commit(project_name: Option<&str>) {
+    // The first time outside of a repository, the last day will do.
+    let since = commit_time("HEAD")
+        .or_else(last_run)
+        .unwrap_or_else(|| Local::now() - Duration::days(1));
+
+    let data = load_data();
+    if let Some(name) = project_name.filter(|name| !data.iter().any(|p| p.name == *name)) {
+        println!("{}", tr!("Project '{}' not found.", name));
+        return;
+    }
+    let mut completed: Vec<(&Project, &Task)> = data
+        .iter()
+        .filter(|p| project_name.is_none_or(|name| p.name == name))
+        .flat_map(|p| p.tasks.iter().map(move |t| (p, t)))
+        .filter(|(_, t)| t.completed_at.is_some_and(|at| at > since))
+        .collect();
+    completed.sort_by_key(|(_, t)| t.completed_at);
+
+    // Remembered even when nothing was found, so the next suggestion
+    // starts from here.
+    let _ = fs::create_dir_all(get_config_dir().join("cache"))
+        .and_then(|_| fs::write(last_run_file(), Local::now().to_rfc3339()));
+
+    if completed.is_empty() {
+        println!(
+            "{}",
+            tr!(
+                "No tasks completed since {}.",
+                since.format("%Y-%m-%d %H:%M")
+            )
+        );
+        return;
+    }
+    println!("{}", message(&completed, project_name));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> Project {
+        let task = |id, description: &str, private| Task {
+            id,
+            description: description.to_string(),
+            private,
+            completed: true,
+            ..Default::default()
+        };
+        Project {
+            name: "web".to_string(),
+            tasks: vec![
+                task(1, "fix the login form.", false),
+                task(2, "call the bank", true),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn one_task_makes_the_subject() {
+        let project = project();
+        let message = message(&[(&project, &project.tasks[0])], None);
+        assert_eq!(
+            message,
+            "Fix the login form\n\n- Fix the login form (web#1)"
+        );
+    }
+
+    #[test]
+    fn private_tasks_are_masked() {
+        let project = project();
+        let one = message(&[(&project, &project.tasks[1])], Some("web"));
+        assert!(!one.contains("bank"), "{}", one);
+        assert!(one.contains(privacy::MASK), "{}", one);
+
+        let both: Vec<_> = project.tasks.iter().map(|t| (&project, t)).collect();
+        let message = message(&both, Some("web"));
+        assert!(!message.contains("bank"), "{}", message);
+        assert!(
+            message.contains("Fix the login form (web#1)"),
+            "{}",
+            message
+        );
+    }
+}
@@ -0,0 +1,797 @@
+//! Syncing a workspace between devices through a shared directory (a
+//! Dropbox or Syncthing folder, a network share...), without conflicts.
+//!
+//! Instead of the whole data file, each device writes the tasks it changed
+//! since its last sync to a change set file of its own, and applies the
+//! change sets of the other devices. Applying them is a CRDT merge, so
+//! every device ends up with the same data whatever the order:
+//!
+//! - each field of a task is a last-writer-wins register: every change is
+//!   stamped with the time it was made, and the latest one wins, whichever
+//!   device it comes from. Histories are joined.
+//! - so is each field of a project, stamped when the change is synced, as
+//!   projects don't record when they change.
+//! - deletions are kept as tombstones, which win over older changes: a
+//!   project deleted then created again under the same name stays.
+//! - two devices creating a task with the same ID keep both tasks: the one
+//!   of the device with the greater device ID keeps the ID everywhere, and
+//!   the other one is given a new ID by the device that created it only,
+//!   which syncs it like any new task. Other devices drop it meanwhile.
+
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet},
+    fs,
+    hash::{Hash, Hasher},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use tracing::info;
+
+use crate::{
+    diff, get_config_dir, i18n::tr, load_data, save_data, storage, workspace, Project, Task,
+};
+
+/// When each field of a task was last changed, by field name.
+type Clocks = BTreeMap<String, DateTime<Local>>;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Tombstone {
+    project: String,
+    /// The deleted task, or `None` when the whole project was deleted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    task: Option<u32>,
+    at: DateTime<Local>,
+}
+
+/// What this device knows about syncing the active workspace.
+#[derive(Serialize, Deserialize, Default)]
+struct SyncState {
+    device: String,
+    last_sync: Option<DateTime<Local>>,
+    /// Change set files already applied, by file name.
+    applied: BTreeSet<String>,
+    tombstones: Vec<Tombstone>,
+    /// The data as of the last sync, to tell what changed since.
+    base: Vec<Project>,
+    /// Field clocks of every task, by `project#id`.
+    clocks: BTreeMap<String, Clocks>,
+    /// Field clocks of every project, by name.
+    #[serde(default)]
+    project_clocks: BTreeMap<String, Clocks>,
+    /// The device each task was created on, by `project#id`, for the ones
+    /// created elsewhere.
+    #[serde(default)]
+    origins: BTreeMap<String, String>,
+    /// Tasks of this device given a new ID while applying changes, to send
+    /// as new ones next time.
+    #[serde(skip)]
+    renumbered: Vec<(String, u32)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TaskChange {
+    project: String,
+    task: Task,
+    clocks: Clocks,
+    /// The device the task was created on, if not the one sending it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    origin: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ChangeSet {
+    device: String,
+    /// Every project, without its tasks.
+    projects: Vec<Project>,
+    /// Field clocks of every project, by name.
+    #[serde(default)]
+    project_clocks: BTreeMap<String, Clocks>,
+    tasks: Vec<TaskChange>,
+    tombstones: Vec<Tombstone>,
+}
+
+fn get_state_path() -> PathBuf {
+    get_config_dir()
+        .join("sync")
+        .join(format!("{}.json", workspace::active()))
+}
+
+fn load_state() -> Option<SyncState> {
+    match fs::read(get_state_path()) {
+        Ok(content) => Some(serde_json::from_slice(&content).expect("Unable to parse sync state.")),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => panic!("Unable to read sync state: {}", e),
+    }
+}
+
+fn save_state(state: &SyncState) {
+    let path = get_state_path();
+    fs::create_dir_all(path.parent().unwrap()).expect("Failed to create sync directory");
+    let content = serde_json::to_vec(state).expect("Unable to serialize sync state.");
+    fs::write(path, content).expect("Unable to write sync state.");
+}
+
+/// A name for this device that no other device will pick.
+fn new_device_id() -> String {
+    let host = std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .unwrap_or_else(|_| "device".to_string());
+    let mut hasher = DefaultHasher::new();
+    (&host, Local::now(), std::process::id()).hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Remember that a task, or a whole project, was deleted, so the deletion
+/// can be synced. Does nothing when the workspace was never synced.
+pub fn record_deletion(project: &str, task: Option<u32>) {
+    if storage::is_dry_run() {
+        return;
+    }
+    if let Some(mut state) = load_state() {
+        state.tombstones.push(Tombstone {
+            project: project.to_string(),
+            task,
+            at: Local::now(),
+        });
+        // A project created again under the same name is a new one.
+        if task.is_none() {
+            state.base.retain(|p| p.name != project);
+            state.project_clocks.remove(project);
+        }
+        save_state(&state);
+    }
+}
+
+fn task_key(project: &str, task_id: u32) -> String {
+    format!("{}#{}", project, task_id)
+}
+
+/// The fields of a task, except its history which is merged on its own.
+fn fields(task: &Task) -> Map<String, Value> {
+    match serde_json::to_value(task) {
+        Ok(Value::Object(mut map)) => {
+            map.remove("history");
+            map
+        }
+        _ => Map::new(),
+    }
+}
+
+/// The fields of a project, except its tasks, left empty as they're synced
+/// on their own, and the next task ID, which only ever grows.
+fn project_fields(project: &Project) -> Map<String, Value> {
+    let project = Project {
+        tasks: Vec::new(),
+        ..project.clone()
+    };
+    match serde_json::to_value(project) {
+        Ok(Value::Object(mut map)) => {
+            map.remove("next_id");
+            map
+        }
+        _ => Map::new(),
+    }
+}
+
+/// Stamp the fields that differ between `new` and `old` with `at`, in
+/// `clocks`.
+fn stamp(
+    clocks: &mut Clocks,
+    new: &Map<String, Value>,
+    old: &Map<String, Value>,
+    at: DateTime<Local>,
+) {
+    let keys: BTreeSet<&String> = new.keys().chain(old.keys()).collect();
+    for key in keys {
+        if new.get(key) != old.get(key) {
+            // A change always wins over the value it replaced, even if that
+            // came from a device with a clock running ahead.
+            let stamp = match clocks.get(key) {
+                Some(previous) if *previous >= at => *previous + Duration::milliseconds(1),
+                _ => at,
+            };
+            clocks.insert(key.clone(), stamp);
+        }
+    }
+}
+
+fn created_at(task: &Task) -> Option<DateTime<Local>> {
+    task.history.first().map(|entry| entry.at)
+}
+
+fn is_deleted(tombstones: &[Tombstone], project: &str, task: &Task) -> bool {
+    tombstones.iter().any(|tombstone| {
+        tombstone.project == project
+            && tombstone.task.is_none_or(|id| id == task.id)
+            && task.updated_at().is_none_or(|at| at <= tombstone.at)
+    })
+}
+
+/// Whether `project` was deleted after it last changed, as its field
+/// `clocks` tell.
+fn is_project_deleted(tombstones: &[Tombstone], project: &str, clocks: Option<&Clocks>) -> bool {
+    let changed_at = clocks.and_then(|clocks| clocks.values().max());
+    tombstones.iter().any(|t| {
+        t.project == project && t.task.is_none() && changed_at.is_none_or(|at| *at <= t.at)
+    })
+}
+
+/// Stamp the fields of the tasks changed since the last sync and collect
+/// them, with the deletions made since, into a change set.
+fn local_changes(data: &[Project], state: &mut SyncState) -> ChangeSet {
+    let mut tasks = Vec::new();
+
+    for project in data {
+        for task in &project.tasks {
+            let base = state
+                .base
+                .iter()
+                .find(|p| p.name == project.name)
+                .and_then(|p| p.tasks.iter().find(|t| t.id == task.id));
+            let new_fields = fields(task);
+            let old_fields = base.map(fields).unwrap_or_default();
+            let history_changed = base.is_none_or(|b| b.history.len() != task.history.len());
+            if new_fields == old_fields && !history_changed {
+                continue;
+            }
+
+            let at = task.updated_at().unwrap_or_else(Local::now);
+            let key = task_key(&project.name, task.id);
+            let clocks = state.clocks.entry(key.clone()).or_default();
+            stamp(clocks, &new_fields, &old_fields, at);
+
+            tasks.push(TaskChange {
+                project: project.name.clone(),
+                task: task.clone(),
+                clocks: clocks.clone(),
+                origin: state.origins.get(&key).cloned(),
+            });
+        }
+
+        let base = state.base.iter().find(|p| p.name == project.name);
+        let new_fields = project_fields(project);
+        // A new project has all its fields, its name included, changed.
+        let old_fields = base.map(project_fields).unwrap_or_default();
+        let clocks = state
+            .project_clocks
+            .entry(project.name.clone())
+            .or_default();
+        stamp(clocks, &new_fields, &old_fields, Local::now());
+    }
+
+    ChangeSet {
+        device: state.device.clone(),
+        projects: data
+            .iter()
+            .map(|project| Project {
+                tasks: Vec::new(),
+                ..project.clone()
+            })
+            .collect(),
+        project_clocks: state.project_clocks.clone(),
+        tasks,
+        tombstones: state
+            .tombstones
+            .iter()
+            .filter(|t| state.last_sync.is_none_or(|last_sync| t.at > last_sync))
+            .cloned()
+            .collect(),
+    }
+}
+
+/// Merge `theirs` into `mine`, field by field, keeping the latest change
+/// of each.
+fn merge_fields(
+    mine_fields: &mut Map<String, Value>,
+    mine_clocks: &mut Clocks,
+    theirs_fields: &Map<String, Value>,
+    their_clocks: &Clocks,
+) {
+    let keys: Vec<String> = mine_fields
+        .keys()
+        .chain(theirs_fields.keys())
+        .cloned()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    for key in keys {
+        let (ours, other) = (mine_fields.get(&key), theirs_fields.get(&key));
+        if ours == other {
+            // The value was set again, which a deletion may be older than.
+            if let Some(clock) = their_clocks.get(&key) {
+                if mine_clocks.get(&key).is_none_or(|mine| mine < clock) {
+                    mine_clocks.insert(key, *clock);
+                }
+            }
+            continue;
+        }
+        // Equal clocks are settled by comparing the values, so every
+        // device makes the same choice.
+        let take_theirs = match (their_clocks.get(&key), mine_clocks.get(&key)) {
+            (Some(t), Some(m)) if t != m => t > m,
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            _ => other.map(Value::to_string) > ours.map(Value::to_string),
+        };
+        if take_theirs {
+            match other {
+                Some(value) => mine_fields.insert(key.clone(), value.clone()),
+                None => mine_fields.remove(&key),
+            };
+            if let Some(clock) = their_clocks.get(&key) {
+                mine_clocks.insert(key, *clock);
+            }
+        }
+    }
+}
+
+/// Merge `theirs` into `mine`, field by field, keeping the latest change
+/// of each, and join their histories.
+fn merge_task(mine: &mut Task, mine_clocks: &mut Clocks, theirs: &Task, their_clocks: &Clocks) {
+    let mut mine_fields = fields(mine);
+    merge_fields(&mut mine_fields, mine_clocks, &fields(theirs), their_clocks);
+
+    let mut history = mine.history.clone();
+    for entry in &theirs.history {
+        let known = history
+            .iter()
+            .any(|e| e.at == entry.at && e.event == entry.event && e.detail == entry.detail);
+        if !known {
+            history.push(entry.clone());
+        }
+    }
+    history.sort_by_key(|entry| entry.at);
+
+    if let Ok(merged) = serde_json::from_value::<Task>(Value::Object(mine_fields)) {
+        *mine = merged;
+    }
+    mine.history = history;
+}
+
+/// Apply a change sent by `device`.
+fn apply_task(data: &mut Vec<Project>, state: &mut SyncState, change: TaskChange, device: &str) {
+    let TaskChange {
+        project: project_name,
+        task,
+        clocks,
+        origin,
+    } = change;
+    let origin = origin.unwrap_or_else(|| device.to_string());
+    let key = task_key(&project_name, task.id);
+
+    let Some(project) = data.iter_mut().find(|p| p.name == project_name) else {
+        state.clocks.insert(key.clone(), clocks);
+        state.origins.insert(key, origin);
+        data.push(Project {
+            name: project_name,
+            tasks: vec![task],
+            ..Default::default()
+        });
+        return;
+    };
+
+    let Some(current) = project.tasks.iter_mut().find(|t| t.id == task.id) else {
+        state.clocks.insert(key.clone(), clocks);
+        state.origins.insert(key, origin);
+        // New tasks go last, leaving the manual order alone.
+        project.tasks.push(task);
+        return;
+    };
+
+    if created_at(current) == created_at(&task) {
+        merge_task(
+            current,
+            state.clocks.entry(key).or_default(),
+            &task,
+            &clocks,
+        );
+        return;
+    }
+
+    // Two different tasks got the same ID: the one of the greater device ID
+    // keeps it, whichever device this is. The other one is dropped, unless
+    // it was created here: it then takes a new ID, synced from here only.
+    let current_origin = state
+        .origins
+        .get(&key)
+        .cloned()
+        .unwrap_or_else(|| state.device.clone());
+    if origin <= current_origin {
+        return;
+    }
+    let moved = std::mem::replace(current, task);
+    let moved_clocks = state.clocks.insert(key.clone(), clocks).unwrap_or_default();
+    state.origins.insert(key, origin);
+    if current_origin != state.device {
+        return;
+    }
+    let id = project.allocate_id();
+    state
+        .clocks
+        .insert(task_key(&project_name, id), moved_clocks);
+    state.origins.remove(&task_key(&project_name, id));
+    state.renumbered.push((project_name, id));
+    project.tasks.push(Task { id, ..moved });
+}
+
+fn apply(data: &mut Vec<Project>, state: &mut SyncState, changes: ChangeSet) {
+    state.tombstones.extend(changes.tombstones);
+
+    for project in changes.projects {
+        let their_clocks = changes
+            .project_clocks
+            .get(&project.name)
+            .cloned()
+            .unwrap_or_default();
+        match data.iter_mut().find(|p| p.name == project.name) {
+            Some(mine) => {
+                let mut mine_fields = project_fields(mine);
+                let mine_clocks = state.project_clocks.entry(mine.name.clone()).or_default();
+                merge_fields(
+                    &mut mine_fields,
+                    mine_clocks,
+                    &project_fields(&project),
+                    &their_clocks,
+                );
+                if let Ok(merged) = serde_json::from_value::<Project>(Value::Object(mine_fields)) {
+                    mine.prefix = merged.prefix;
+                    mine.deadline = merged.deadline;
+                    mine.weekly_goal = merged.weekly_goal;
+                    mine.after = merged.after;
+                }
+                mine.next_id = mine.next_id.max(project.next_id);
+            }
+            None if !is_project_deleted(&state.tombstones, &project.name, Some(&their_clocks)) => {
+                state
+                    .project_clocks
+                    .insert(project.name.clone(), their_clocks);
+                data.push(project);
+            }
+            None => {}
+        }
+    }
+
+    // Before applying the tasks, as a project created again reuses the IDs
+    // of the deleted one's tasks.
+    for project in data.iter_mut() {
+        let name = project.name.clone();
+        project
+            .tasks
+            .retain(|task| !is_deleted(&state.tombstones, &name, task));
+    }
+
+    for change in changes.tasks {
+        if !is_deleted(&state.tombstones, &change.project, &change.task) {
+            apply_task(data, state, change, &changes.device);
+        }
+    }
+    data.retain(|project| {
+        !project.tasks.is_empty()
+            || !is_project_deleted(
+                &state.tombstones,
+                &project.name,
+                state.project_clocks.get(&project.name),
+            )
+    });
+}
+
+fn read_change_set(path: &Path) -> Result<ChangeSet, String> {
+    let content = fs::read(path).map_err(|e| e.to_string())?;
+    serde_json::from_slice(&content).map_err(|e| e.to_string())
+}
+
+pub fn sync(dir: &Path) {
+    if let Err(e) = fs::create_dir_all(dir) {
+        println!(
+            "{}",
+            tr!("Unable to use '{}' for syncing: {}", dir.display(), e)
+        );
+        return;
+    }
+
+    let mut state = load_state().unwrap_or_else(|| SyncState {
+        device: new_device_id(),
+        ..Default::default()
+    });
+    let started = Local::now();
+
+    let before = load_data();
+    let mut data = before.clone();
+
+    let outgoing = local_changes(&data, &mut state);
+    let sent = outgoing.tasks.len();
+    let file_name = format!(
+        "{}-{}.json",
+        state.device,
+        started.format("%Y%m%dT%H%M%S%.f")
+    );
+    let dry_run = storage::is_dry_run();
+    if !dry_run {
+        let content = serde_json::to_vec(&outgoing).expect("Unable to serialize changes.");
+        info!("writing change set {}", dir.join(&file_name).display());
+        fs::write(dir.join(&file_name), content).expect("Unable to write change set.");
+    }
+    state.applied.insert(file_name);
+
+    let mut incoming: Vec<PathBuf> = fs::read_dir(dir)
+        .expect("Unable to read sync directory.")
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| !state.applied.contains(&*name.to_string_lossy()))
+        })
+        .collect();
+    incoming.sort();
+
+    let mut received = 0;
+    for path in incoming {
+        match read_change_set(&path) {
+            Ok(changes) => {
+                info!("applying change set {}", path.display());
+                apply(&mut data, &mut state, changes);
+                received += 1;
+                state
+                    .applied
+                    .insert(path.file_name().unwrap().to_string_lossy().into_owned());
+            }
+            Err(e) => println!("{}", tr!("Skipping '{}': {}", path.display(), e)),
+        }
+    }
+
+    diff::print(&diff::diff(&before, &data));
+    if dry_run {
+        println!(
+            "{}",
+            tr!(
+                "Dry run, {} changed tasks and {} change sets were left alone.",
+                sent,
+                received
+            )
+        );
+        return;
+    }
+    save_data(&data);
+    state.last_sync = Some(started);
+    state.base = data;
+    // Leaving them out of the base sends them next time.
+    for (name, id) in std::mem::take(&mut state.renumbered) {
+        if let Some(project) = state.base.iter_mut().find(|p| p.name == name) {
+            project.tasks.retain(|t| t.id != id);
+        }
+    }
+    save_state(&state);
+
+    println!(
+        "{}",
+        tr!(
+            "Sent {} changed tasks, applied {} change sets from other devices.",
+            sent,
+            received
+        )
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    use crate::history::{HistoryEntry, TaskEvent};
+
+    fn at(hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 7, 1, hour, 0, 0).unwrap()
+    }
+
+    fn entry(hour: u32, event: TaskEvent) -> HistoryEntry {
+        HistoryEntry {
+            at: at(hour),
+            event,
+            detail: None,
+            by: None,
+        }
+    }
+
+    fn task(id: u32, description: &str, created: u32) -> Task {
+        Task {
+            id,
+            description: description.to_string(),
+            history: vec![entry(created, TaskEvent::Created)],
+            ..Default::default()
+        }
+    }
+
+    struct Device {
+        data: Vec<Project>,
+        state: SyncState,
+    }
+
+    fn device(id: &str, tasks: Vec<Task>) -> Device {
+        Device {
+            data: vec![Project {
+                name: "web".to_string(),
+                next_id: tasks.iter().map(|t| t.id + 1).max().unwrap_or(1),
+                tasks,
+                ..Default::default()
+            }],
+            state: SyncState {
+                device: id.to_string(),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Sync `a` and `b` with each other, the way `sync` does through a
+    /// directory.
+    fn sync_both(a: &mut Device, b: &mut Device) {
+        let to_b = local_changes(&a.data, &mut a.state);
+        let to_a = local_changes(&b.data, &mut b.state);
+        apply(&mut a.data, &mut a.state, to_a);
+        apply(&mut b.data, &mut b.state, to_b);
+        for device in [a, b] {
+            device.state.base = device.data.clone();
+            for (name, id) in std::mem::take(&mut device.state.renumbered) {
+                if let Some(project) = device.state.base.iter_mut().find(|p| p.name == name) {
+                    project.tasks.retain(|t| t.id != id);
+                }
+            }
+        }
+    }
+
+    /// Two devices with the same task, synced.
+    fn synced(task: Task) -> (Device, Device) {
+        let mut a = device("aaa", vec![task]);
+        let mut b = device("bbb", Vec::new());
+        sync_both(&mut a, &mut b);
+        (a, b)
+    }
+
+    fn tasks(device: &Device) -> Vec<(u32, &str)> {
+        device.data[0]
+            .tasks
+            .iter()
+            .map(|t| (t.id, t.description.as_str()))
+            .collect()
+    }
+
+    fn assert_converged(a: &Device, b: &Device) {
+        assert_eq!(
+            serde_json::to_value(&a.data).unwrap(),
+            serde_json::to_value(&b.data).unwrap()
+        );
+    }
+
+    fn edit(device: &mut Device, description: &str, hour: u32) {
+        let task = &mut device.data[0].tasks[0];
+        task.description = description.to_string();
+        task.history.push(entry(hour, TaskEvent::Edited));
+    }
+
+    #[test]
+    fn new_tasks_reach_the_other_device() {
+        let (a, b) = synced(task(1, "Fix the login form", 9));
+        assert_eq!(tasks(&b), [(1, "Fix the login form")]);
+        assert_converged(&a, &b);
+    }
+
+    #[test]
+    fn changes_to_different_fields_are_both_kept() {
+        let (mut a, mut b) = synced(task(1, "Fix the login form", 9));
+        edit(&mut a, "Fix the signup form", 10);
+        let done = &mut b.data[0].tasks[0];
+        done.completed = true;
+        done.completed_at = Some(at(11));
+        done.history.push(entry(11, TaskEvent::Completed));
+
+        sync_both(&mut a, &mut b);
+        assert_converged(&a, &b);
+        let task = &a.data[0].tasks[0];
+        assert_eq!(task.description, "Fix the signup form");
+        assert!(task.completed);
+        let events: Vec<TaskEvent> = task.history.iter().map(|e| e.event).collect();
+        assert_eq!(
+            events,
+            [TaskEvent::Created, TaskEvent::Edited, TaskEvent::Completed]
+        );
+    }
+
+    #[test]
+    fn the_latest_change_to_a_field_wins_everywhere() {
+        let (mut a, mut b) = synced(task(1, "Fix the login form", 9));
+        edit(&mut a, "Later", 11);
+        edit(&mut b, "Earlier", 10);
+
+        sync_both(&mut a, &mut b);
+        assert_converged(&a, &b);
+        assert_eq!(tasks(&a), [(1, "Later")]);
+    }
+
+    #[test]
+    fn changes_made_at_the_same_time_are_settled_the_same_way_everywhere() {
+        let clocks: Clocks = [("description".to_string(), at(10))].into();
+        let fields_of = |description: &str| fields(&task(1, description, 9));
+
+        let mut mine = fields_of("apple");
+        merge_fields(&mut mine, &mut clocks.clone(), &fields_of("pear"), &clocks);
+        let mut theirs = fields_of("pear");
+        merge_fields(
+            &mut theirs,
+            &mut clocks.clone(),
+            &fields_of("apple"),
+            &clocks,
+        );
+        assert_eq!(mine, theirs);
+    }
+
+    #[test]
+    fn a_change_wins_over_the_value_it_replaced_from_a_clock_ahead() {
+        let mut clocks: Clocks = [("description".to_string(), at(12))].into();
+        let (new, old) = (fields(&task(1, "new", 9)), fields(&task(1, "old", 9)));
+        stamp(&mut clocks, &new, &old, at(10));
+        assert!(clocks["description"] > at(12));
+        // Fields that didn't change keep their clocks.
+        assert!(!clocks.contains_key("priority"));
+    }
+
+    #[test]
+    fn tasks_created_with_the_same_id_are_both_kept() {
+        let mut a = device("aaa", vec![task(1, "Mine", 9)]);
+        let mut b = device("bbb", vec![task(1, "Theirs", 10)]);
+
+        sync_both(&mut a, &mut b);
+        // The greater device ID keeps it; the other task is renumbered
+        // where it was created, and dropped elsewhere meanwhile.
+        assert_eq!(tasks(&a), [(1, "Theirs"), (2, "Mine")]);
+        assert_eq!(tasks(&b), [(1, "Theirs")]);
+
+        sync_both(&mut a, &mut b);
+        assert_eq!(tasks(&b), [(1, "Theirs"), (2, "Mine")]);
+        assert_converged(&a, &b);
+    }
+
+    #[test]
+    fn deletions_win_over_older_changes_only() {
+        let delete = |device: &mut Device, hour| {
+            device.data[0].tasks.clear();
+            device.state.tombstones.push(Tombstone {
+                project: "web".to_string(),
+                task: Some(1),
+                at: at(hour),
+            });
+        };
+
+        let (mut a, mut b) = synced(task(1, "Fix the login form", 9));
+        delete(&mut a, 12);
+        edit(&mut b, "Edited before", 11);
+        sync_both(&mut a, &mut b);
+        assert!(tasks(&a).is_empty());
+        assert!(tasks(&b).is_empty());
+
+        let (mut a, mut b) = synced(task(1, "Fix the login form", 9));
+        delete(&mut a, 12);
+        edit(&mut b, "Edited after", 13);
+        sync_both(&mut a, &mut b);
+        assert_eq!(tasks(&a), [(1, "Edited after")]);
+        assert_converged(&a, &b);
+    }
+
+    #[test]
+    fn deleted_projects_are_not_brought_back() {
+        let (mut a, mut b) = synced(task(1, "Fix the login form", 9));
+        a.data.clear();
+        a.state.tombstones.push(Tombstone {
+            project: "web".to_string(),
+            task: None,
+            at: Local::now() + Duration::seconds(1),
+        });
+
+        sync_both(&mut a, &mut b);
+        assert!(a.data.is_empty());
+        assert!(b.data.is_empty());
+    }
+}
@@ -0,0 +1,217 @@
+//! Task listings as an aligned table, with a choice of columns, and the
+//! orders tasks can be sorted in.
+
+use clap::ValueEnum;
+use colored::{ColoredString, Colorize};
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+
+use crate::{
+    bars, dates, display, durations, i18n::tr, privacy, theme::Themed, Priority, Project, Status,
+    Task,
+};
+
+#[derive(Clone, Copy, PartialEq, Debug, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Column {
+    Id,
+    #[value(alias = "description")]
+    #[serde(alias = "description")]
+    Desc,
+    Status,
+    Priority,
+    Due,
+    Scheduled,
+    Estimate,
+    Spent,
+    Tags,
+    Contexts,
+    Completed,
+}
+
+/// Columns shown when `--columns` isn't given.
+pub const DEFAULT_COLUMNS: &[Column] = &[
+    Column::Id,
+    Column::Desc,
+    Column::Priority,
+    Column::Due,
+    Column::Status,
+];
+
+/// Missing values sort last, whichever way the rest goes.
+fn none_last<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Tasks in progress come first, then those to do, then completed ones.
+fn status_rank(task: &Task) -> u8 {
+    match task.status() {
+        Status::InProgress => 0,
+        Status::Todo => 1,
+        Status::Done => 2,
+    }
+}
+
+impl Column {
+    fn header(self) -> String {
+        match self {
+            Column::Id => tr!("ID"),
+            Column::Desc => tr!("Description"),
+            Column::Status => tr!("Status"),
+            Column::Priority => tr!("Priority"),
+            Column::Due => tr!("Due"),
+            Column::Scheduled => tr!("Scheduled"),
+            Column::Estimate => tr!("Estimate"),
+            Column::Spent => tr!("Spent"),
+            Column::Tags => tr!("Tags"),
+            Column::Contexts => tr!("Contexts"),
+            Column::Completed => tr!("Completed"),
+        }
+    }
+
+    /// The plain text of `task`'s cell.
+    pub fn cell(self, project: &Project, task: &Task) -> String {
+        match self {
+            Column::Id => project.display_id(task.id),
+            Column::Desc => privacy::description(task).to_string(),
+            Column::Status => task.status().to_string(),
+            Column::Priority => task.priority.map(|p| p.to_string()).unwrap_or_default(),
+            Column::Due => task.due.map(|d| d.to_string()).unwrap_or_default(),
+            Column::Scheduled => task.scheduled.map(|d| d.to_string()).unwrap_or_default(),
+            Column::Estimate => task
+                .estimate
+                .map(durations::format_duration)
+                .unwrap_or_default(),
+            Column::Spent => match task.time_spent() {
+                0 => String::new(),
+                spent => durations::format_duration(spent),
+            },
+            Column::Tags => task
+                .tags
+                .iter()
+                .map(|t| format!("#{}", t))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Column::Contexts => task.contexts.join(" "),
+            Column::Completed => task
+                .completed_at
+                .map(|at| at.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// `text`, the padded cell of `task`, colored like the other listings.
+    fn style(self, task: &Task, text: String) -> ColoredString {
+        match self {
+            Column::Status => match task.status() {
+                Status::Done => text.good(),
+                Status::InProgress => text.magenta(),
+                Status::Todo => text.normal(),
+            },
+            Column::Priority => match task.priority {
+                Some(Priority::High) => text.bad(),
+                Some(Priority::Medium) => text.warn(),
+                Some(Priority::Low) => text.minor(),
+                None => text.normal(),
+            },
+            Column::Due => match task.due {
+                Some(due) if !task.completed && due < dates::today() => text.bad(),
+                Some(due) if !task.completed && due == dates::today() => text.warn(),
+                _ => text.normal(),
+            },
+            Column::Desc if display::is_escalated(task) => text.bad().bold(),
+            Column::Tags => text.cyan(),
+            Column::Contexts => text.magenta(),
+            _ => text.normal(),
+        }
+    }
+
+    /// Order of `a` and `b` when sorting on this column: most pressing first
+    /// (highest priority, earliest dates), with missing values last.
+    pub fn compare(self, a: &Task, b: &Task) -> Ordering {
+        match self {
+            Column::Id => a.id.cmp(&b.id),
+            Column::Desc => a
+                .description
+                .to_lowercase()
+                .cmp(&b.description.to_lowercase()),
+            Column::Status => status_rank(a).cmp(&status_rank(b)),
+            Column::Priority => none_last(a.priority.map(Reverse), b.priority.map(Reverse)),
+            Column::Due => none_last(a.due, b.due),
+            Column::Scheduled => none_last(a.scheduled, b.scheduled),
+            Column::Estimate => none_last(a.estimate, b.estimate),
+            Column::Spent => a.time_spent().cmp(&b.time_spent()),
+            Column::Tags => none_last(a.tags.first(), b.tags.first()),
+            Column::Contexts => none_last(a.contexts.first(), b.contexts.first()),
+            Column::Completed => none_last(a.completed_at, b.completed_at),
+        }
+    }
+}
+
+/// Sort `tasks` on each of `keys` in turn. With no keys, they're left in
+/// their manual order.
+pub fn sort(tasks: &mut [&Task], keys: &[Column]) {
+    tasks.sort_by(|a, b| {
+        keys.iter()
+            .map(|key| key.compare(a, b))
+            .find(|order| order.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+}
+
+/// Parse a comma-separated list of columns, as in `status,priority,due`.
+pub fn parse_columns(input: &str) -> Result<Vec<Column>, String> {
+    input
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| Column::from_str(name, true).map_err(|_| tr!("unknown column '{}'", name)))
+        .collect()
+}
+
+/// Print `tasks` of `project` as a table of `columns`.
+pub fn print(project: &Project, tasks: &[&Task], columns: &[Column]) {
+    let rows: Vec<Vec<String>> = tasks
+        .iter()
+        .map(|task| columns.iter().map(|c| c.cell(project, task)).collect())
+        .collect();
+    let headers: Vec<String> = columns.iter().map(|c| c.header()).collect();
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, header)| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain([header.chars().count()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let pad =
+        |text: &str, width: usize| format!("{}{}", text, " ".repeat(width - text.chars().count()));
+
+    let header: Vec<String> = headers
+        .iter()
+        .zip(&widths)
+        .map(|(header, &width)| pad(header, width).bold().to_string())
+        .collect();
+    println!("    {}", header.join("  ").trim_end());
+    let line = if bars::is_ascii() { "-" } else { "─" };
+    let rule: Vec<String> = widths.iter().map(|&width| line.repeat(width)).collect();
+    println!("    {}", rule.join("  ").dimmed());
+
+    for (task, row) in tasks.iter().zip(rows) {
+        let cells: Vec<String> = columns
+            .iter()
+            .zip(row)
+            .zip(&widths)
+            .map(|((column, cell), &width)| column.style(task, pad(&cell, width)).to_string())
+            .collect();
+        println!("    {}", cells.join("  ").trim_end());
+    }
+}
@@ -0,0 +1,54 @@
+//! Free-form tags on tasks.
+
+use crate::{history::TaskEvent, i18n::tr, load_data, save_data};
+
+/// Tags are stored without a leading `#`, whether or not one was typed.
+pub fn normalize(tag: &str) -> String {
+    tag.trim().trim_start_matches('#').to_string()
+}
+
+/// Add (or, with `remove`, remove) `tags` to a task.
+pub fn tag_task(project_name: &str, task_id: u32, tags: &[String], remove: bool) {
+    let mut data = load_data();
+    if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
+        if let Some(task) = project.tasks.iter_mut().find(|t| t.id == task_id) {
+            let mut changed = Vec::new();
+            for tag in tags.iter().map(|t| normalize(t)) {
+                let present = task.tags.contains(&tag);
+                if remove && present {
+                    task.tags.retain(|t| *t != tag);
+                    changed.push(tag);
+                } else if !remove && !present && !tag.is_empty() {
+                    task.tags.push(tag.clone());
+                    changed.push(tag);
+                }
+            }
+
+            if changed.is_empty() {
+                println!("{}", tr!("Task {} left unchanged.", task_id));
+                return;
+            }
+
+            let action = if remove { "untagged" } else { "tagged" };
+            let list = changed
+                .iter()
+                .map(|t| format!("#{}", t))
+                .collect::<Vec<_>>()
+                .join(" ");
+            task.record(TaskEvent::Edited, Some(format!("{} {}", action, list)));
+            save_data(&data);
+            if remove {
+                println!("{}", tr!("Task {} untagged {}.", task_id, list));
+            } else {
+                println!("{}", tr!("Task {} tagged {}.", task_id, list));
+            }
+        } else {
+            println!(
+                "{}",
+                tr!("Task {} not found in project '{}'.", task_id, project_name)
+            );
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
@@ -0,0 +1,125 @@
+//! `--format` templates: plain text with placeholders in braces, like
+//! `"{id}\t{desc}"`, with `{{` and `}}` for literal braces and `\t` and `\n`
+//! for tabs and newlines. Nothing they print is ever colored, so the output
+//! can be fed to other tools.
+
+use crate::{i18n::tr, table::Column, Project, Task};
+
+#[derive(Debug, Clone)]
+pub enum Piece<P> {
+    Text(String),
+    Placeholder(P),
+}
+
+/// Split `input` into text and the placeholders named in `known`.
+///
+/// Meant to back clap `value_parser`s, hence the `String` error.
+pub fn parse<P: Copy>(input: &str, known: &[(&str, P)]) -> Result<Vec<Piece<P>>, String> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '\\' => match chars.peek() {
+                Some('t') => {
+                    chars.next();
+                    text.push('\t');
+                }
+                Some('n') => {
+                    chars.next();
+                    text.push('\n');
+                }
+                Some('\\') => {
+                    chars.next();
+                    text.push('\\');
+                }
+                _ => text.push('\\'),
+            },
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(tr!("unterminated '{{'")),
+                    }
+                }
+                let Some((_, placeholder)) = known.iter().find(|(n, _)| *n == name.trim()) else {
+                    let names: Vec<&str> = known.iter().map(|(n, _)| *n).collect();
+                    return Err(tr!(
+                        "unknown placeholder '{{{}}}' (known: {})",
+                        name,
+                        names.join(", ")
+                    ));
+                };
+                if !text.is_empty() {
+                    pieces.push(Piece::Text(std::mem::take(&mut text)));
+                }
+                pieces.push(Piece::Placeholder(*placeholder));
+            }
+            '}' => return Err(tr!("unmatched '}}', write '}}}}' for a literal one")),
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        pieces.push(Piece::Text(text));
+    }
+
+    Ok(pieces)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum TaskPlaceholder {
+    Project,
+    /// The task's ID qualified with its project, as in `web#12`.
+    Ref,
+    Column(Column),
+}
+
+const TASK_PLACEHOLDERS: &[(&str, TaskPlaceholder)] = &[
+    ("project", TaskPlaceholder::Project),
+    ("ref", TaskPlaceholder::Ref),
+    ("id", TaskPlaceholder::Column(Column::Id)),
+    ("desc", TaskPlaceholder::Column(Column::Desc)),
+    ("status", TaskPlaceholder::Column(Column::Status)),
+    ("priority", TaskPlaceholder::Column(Column::Priority)),
+    ("due", TaskPlaceholder::Column(Column::Due)),
+    ("scheduled", TaskPlaceholder::Column(Column::Scheduled)),
+    ("estimate", TaskPlaceholder::Column(Column::Estimate)),
+    ("spent", TaskPlaceholder::Column(Column::Spent)),
+    ("tags", TaskPlaceholder::Column(Column::Tags)),
+    ("contexts", TaskPlaceholder::Column(Column::Contexts)),
+    ("completed", TaskPlaceholder::Column(Column::Completed)),
+];
+
+/// A `--format` template for task listings, printing one line per task.
+#[derive(Debug, Clone)]
+pub struct TaskTemplate(Vec<Piece<TaskPlaceholder>>);
+
+/// Parse a task `--format` template, as a clap `value_parser`.
+pub fn parse_task_template(input: &str) -> Result<TaskTemplate, String> {
+    parse(input, TASK_PLACEHOLDERS).map(TaskTemplate)
+}
+
+impl TaskTemplate {
+    pub fn render(&self, project: &Project, task: &Task) -> String {
+        self.0
+            .iter()
+            .map(|piece| match piece {
+                Piece::Text(text) => text.clone(),
+                Piece::Placeholder(TaskPlaceholder::Project) => project.name.clone(),
+                Piece::Placeholder(TaskPlaceholder::Ref) => project.task_ref(task.id),
+                Piece::Placeholder(TaskPlaceholder::Column(column)) => column.cell(project, task),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,207 @@
+//! Task templates, for the kinds of work that keep coming back: a
+//! description with variables in braces, like `"Fix {ticket}: {summary}"`,
+//! and the tags, contexts and priority such tasks get.
+//! `add-task web --template bugfix --var ticket=ABC-123 --var summary=...`
+//! fills them in.
+
+use clap::Subcommand;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{
+    config::{load_config, save_config},
+    contexts,
+    i18n::tr,
+    tags, Priority,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Template {
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub contexts: Vec<String>,
+}
+
+#[derive(Subcommand)]
+pub enum TemplateCommand {
+    /// Save a task template, e.g. `template save bugfix "Fix {ticket}" -t bug`.
+    Save {
+        name: String,
+        /// Description of the tasks, with variables in braces; `{{` and `}}`
+        /// for literal braces.
+        description: String,
+        #[arg(short, long, value_enum)]
+        priority: Option<Priority>,
+        /// Tag the tasks, can be repeated.
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+        /// Context the tasks can be done in, can be repeated.
+        #[arg(short, long = "context")]
+        contexts: Vec<String>,
+    },
+    /// List the task templates.
+    List,
+    /// Delete a task template.
+    Delete { name: String },
+}
+
+pub fn run(command: &TemplateCommand) {
+    match command {
+        TemplateCommand::Save {
+            name,
+            description,
+            priority,
+            tags,
+            contexts,
+        } => {
+            let template = Template {
+                description: description.clone(),
+                priority: *priority,
+                tags: tags.iter().map(|t| tags::normalize(t)).collect(),
+                contexts: contexts.iter().map(|c| contexts::normalize(c)).collect(),
+            };
+            save_template(name, template)
+        }
+        TemplateCommand::List => list_templates(),
+        TemplateCommand::Delete { name } => delete_template(name),
+    }
+}
+
+fn save_template(name: &str, template: Template) {
+    if let Err(e) = variables(&template.description) {
+        println!("{}", tr!("Invalid template: {}", e));
+        return;
+    }
+
+    let mut config = load_config();
+    let replaced = config
+        .templates
+        .insert(name.to_string(), template)
+        .is_some();
+    save_config(&config);
+
+    if replaced {
+        println!("{}", tr!("Template '{}' updated.", name));
+    } else {
+        println!("{}", tr!("Template '{}' saved.", name));
+    }
+}
+
+fn list_templates() {
+    let config = load_config();
+    if config.templates.is_empty() {
+        println!("{}", tr!("No templates saved."));
+        return;
+    }
+
+    println!("{}", tr!("Templates:"));
+    for (name, template) in &config.templates {
+        let mut details = Vec::new();
+        if let Some(priority) = template.priority {
+            details.push(priority.to_string());
+        }
+        details.extend(template.tags.iter().map(|t| format!("#{}", t)));
+        details.extend(template.contexts.iter().cloned());
+        if details.is_empty() {
+            println!(" - {}: {}", name.bold(), template.description);
+        } else {
+            println!(
+                " - {}: {} {}",
+                name.bold(),
+                template.description,
+                format!("({})", details.join(", ")).dimmed()
+            );
+        }
+    }
+}
+
+fn delete_template(name: &str) {
+    let mut config = load_config();
+    if config.templates.remove(name).is_some() {
+        save_config(&config);
+        println!("{}", tr!("Template '{}' has been deleted.", name));
+    } else {
+        println!("{}", tr!("Template '{}' not found.", name));
+    }
+}
+
+/// The template saved as `name`.
+pub fn find(name: &str) -> Result<Template, String> {
+    load_config()
+        .templates
+        .remove(name)
+        .ok_or_else(|| tr!("Template '{}' not found.", name))
+}
+
+/// Split `text` into literal text and variable names, as `(is_variable,
+/// text)`.
+fn variables(text: &str) -> Result<Vec<(bool, String)>, String> {
+    let mut pieces = Vec::new();
+    let mut literal = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' if chars.peek() == Some(&c) => {
+                chars.next();
+                literal.push(c);
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(tr!("unterminated '{{'")),
+                    }
+                }
+                if name.trim().is_empty() {
+                    return Err(tr!("empty variable name '{{}}'"));
+                }
+                pieces.push((false, std::mem::take(&mut literal)));
+                pieces.push((true, name.trim().to_string()));
+            }
+            '}' => return Err(tr!("unmatched '}}', write '}}}}' for a literal one")),
+            c => literal.push(c),
+        }
+    }
+    pieces.push((false, literal));
+
+    Ok(pieces)
+}
+
+impl Template {
+    /// The description with the variables replaced by `vars`, or the
+    /// variables missing from them.
+    pub fn expand(&self, vars: &[(String, String)]) -> Result<String, String> {
+        let vars: BTreeMap<&str, &str> = vars
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        let mut description = String::new();
+        let mut missing = Vec::new();
+        for (is_variable, text) in variables(&self.description)? {
+            match (is_variable, vars.get(text.as_str())) {
+                (false, _) => description.push_str(&text),
+                (true, Some(value)) => description.push_str(value),
+                (true, None) if !missing.contains(&text) => missing.push(text),
+                (true, None) => {}
+            }
+        }
+
+        if missing.is_empty() {
+            Ok(description)
+        } else {
+            Err(tr!(
+                "No value for {}, give one with --var name=value.",
+                missing.join(", ")
+            ))
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Color themes, picked with the `theme` setting, for listings that stay
+//! readable whatever one's eyes or terminal:
+//!
+//! - `default`: green for what's done, red for what's late, yellow for what
+//!   needs attention soon.
+//! - `colorblind`: blue and orange instead of green and red, which look
+//!   alike with the most common color blindness, and `[✓]` for what's
+//!   done.
+//! - `high-contrast`: bright, bold colors, and `[✓]`.
+//! - `mono`: no colors at all, and `[✓]`.
+//!
+//! Listings color what they show as `good`, `bad`, `warn` or `minor`,
+//! through the `Themed` trait, rather than naming colors, so that every
+//! view follows the theme.
+//!
+//! Independently of the theme, the `icons` setting swaps the checkboxes and
+//! priority markers for Nerd Font icons, for terminals using such a font.
+
+use clap::ValueEnum;
+use colored::{ColoredString, Colorize};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::{bars, config::load_config, output, Priority};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    #[default]
+    Default,
+    Colorblind,
+    HighContrast,
+    Mono,
+}
+
+/// Blue, orange, yellow and reddish purple of the Okabe-Ito palette, told
+/// apart with any kind of color blindness.
+const BLUE: (u8, u8, u8) = (0, 114, 178);
+const ORANGE: (u8, u8, u8) = (230, 159, 0);
+const YELLOW: (u8, u8, u8) = (240, 228, 66);
+const PURPLE: (u8, u8, u8) = (204, 121, 167);
+
+/// The `theme` setting, read once since every colored word needs it.
+fn current() -> Theme {
+    static THEME: OnceLock<Theme> = OnceLock::new();
+    *THEME.get_or_init(|| load_config().effective().theme.unwrap_or_default())
+}
+
+/// Whether to show Nerd Font icons: the `icons` setting, unless drawing
+/// with ASCII only.
+fn icons() -> bool {
+    static ICONS: OnceLock<bool> = OnceLock::new();
+    *ICONS.get_or_init(|| load_config().effective().icons == Some(true) && !bars::is_ascii())
+}
+
+/// Turn colors off altogether for the `mono` theme or plain output,
+/// before anything is printed.
+pub fn apply() {
+    if current() == Theme::Mono || output::is_plain() {
+        colored::control::set_override(false);
+    }
+}
+
+/// Colors with a meaning, following the theme, for anything `colored` can
+/// color.
+pub trait Themed: Colorize + Sized {
+    /// Something done or going well.
+    fn good(self) -> ColoredString {
+        match current() {
+            // Colors are off anyway with `mono`.
+            Theme::Default | Theme::Mono => self.green(),
+            Theme::Colorblind => self.truecolor(BLUE.0, BLUE.1, BLUE.2),
+            Theme::HighContrast => self.bright_green().bold(),
+        }
+    }
+
+    /// Something late, failed or left undone.
+    fn bad(self) -> ColoredString {
+        match current() {
+            Theme::Default | Theme::Mono => self.red(),
+            Theme::Colorblind => self.truecolor(ORANGE.0, ORANGE.1, ORANGE.2),
+            Theme::HighContrast => self.bright_red().bold(),
+        }
+    }
+
+    /// Something needing attention soon.
+    fn warn(self) -> ColoredString {
+        match current() {
+            Theme::Default | Theme::Mono => self.yellow(),
+            Theme::Colorblind => self.truecolor(YELLOW.0, YELLOW.1, YELLOW.2),
+            Theme::HighContrast => self.bright_yellow().bold(),
+        }
+    }
+
+    /// Something that can wait, apart from what's done.
+    fn minor(self) -> ColoredString {
+        match current() {
+            Theme::Default | Theme::Mono => self.blue(),
+            Theme::Colorblind => self.truecolor(PURPLE.0, PURPLE.1, PURPLE.2),
+            Theme::HighContrast => self.bright_cyan().bold(),
+        }
+    }
+}
+
+impl<T: Colorize> Themed for T {}
+
+/// `[x]`, or `[✓]` with themes other than the default, for something done,
+/// `[ ]` for something left to do; their icons with `icons` on.
+pub fn mark(done: bool) -> &'static str {
+    if icons() {
+        // nf-fa-check_square_o and nf-fa-square_o.
+        return if done { "\u{f046}" } else { "\u{f096}" };
+    }
+    if !done {
+        "[ ]"
+    } else if current() == Theme::Default || bars::is_ascii() {
+        "[x]"
+    } else {
+        "[✓]"
+    }
+}
+
+/// The mark of a task, colored.
+pub fn checkbox(completed: bool) -> ColoredString {
+    if completed {
+        mark(true).good()
+    } else {
+        mark(false).bad()
+    }
+}
+
+/// `!high`, `!medium` or `!low`, or with icons an arrow and the priority,
+/// colored by urgency.
+pub fn priority(priority: Priority) -> ColoredString {
+    let text = if icons() {
+        // nf-fa-arrow_up, nf-fa-arrow_right and nf-fa-arrow_down.
+        let icon = match priority {
+            Priority::High => '\u{f062}',
+            Priority::Medium => '\u{f061}',
+            Priority::Low => '\u{f063}',
+        };
+        format!("{} {}", icon, priority)
+    } else {
+        format!("!{}", priority)
+    };
+    match priority {
+        Priority::High => text.bad(),
+        Priority::Medium => text.warn(),
+        Priority::Low => text.minor(),
+    }
+}
@@ -0,0 +1,236 @@
+//! Time tracking: time spent on tasks, and reports of it for invoicing.
+
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+use crate::{
+    dates, durations::format_duration, history::TaskEvent, i18n::tr, load_data, privacy, save_data,
+    Task,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TimeEntry {
+    pub date: NaiveDate,
+    pub minutes: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum GroupBy {
+    Project,
+    /// Tasks with several tags count towards each of them.
+    Tag,
+}
+
+impl Task {
+    /// Minutes logged on the task, in total.
+    pub fn time_spent(&self) -> u32 {
+        self.time_entries.iter().map(|entry| entry.minutes).sum()
+    }
+}
+
+pub fn log_time(
+    project_name: &str,
+    task_id: u32,
+    minutes: u32,
+    date: Option<NaiveDate>,
+    note: Option<&str>,
+) {
+    let date = date.unwrap_or_else(dates::today);
+
+    let mut data = load_data();
+    if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
+        if let Some(task) = project.tasks.iter_mut().find(|t| t.id == task_id) {
+            task.time_entries.push(TimeEntry {
+                date,
+                minutes,
+                note: note.map(str::to_string),
+            });
+            task.record(
+                TaskEvent::Edited,
+                Some(format!("logged {} on {}", format_duration(minutes), date)),
+            );
+            let total = task.time_spent();
+            save_data(&data);
+            println!(
+                "{}",
+                tr!(
+                    "Logged {} on task {} ({} in total).",
+                    format_duration(minutes),
+                    task_id,
+                    format_duration(total)
+                )
+            );
+        } else {
+            println!(
+                "{}",
+                tr!("Task {} not found in project '{}'.", task_id, project_name)
+            );
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
+
+/// One line of the report: the time logged on a task within a group.
+struct Row {
+    group: String,
+    project: String,
+    task_id: u32,
+    description: String,
+    tags: Vec<String>,
+    minutes: u32,
+}
+
+fn hours(minutes: u32) -> f64 {
+    minutes as f64 / 60.0
+}
+
+/// Round to cents, so amounts add up the way they're printed.
+fn amount(minutes: u32, rate: f64) -> f64 {
+    (hours(minutes) * rate * 100.0).round() / 100.0
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn time_report(
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    rate: Option<f64>,
+    format: ReportFormat,
+    group_by: GroupBy,
+) {
+    let in_range =
+        |date: NaiveDate| from.is_none_or(|from| date >= from) && to.is_none_or(|to| date <= to);
+
+    let data = load_data();
+    let mut rows = Vec::new();
+    for project in &data {
+        for task in &project.tasks {
+            let minutes: u32 = task
+                .time_entries
+                .iter()
+                .filter(|entry| in_range(entry.date))
+                .map(|entry| entry.minutes)
+                .sum();
+            if minutes == 0 {
+                continue;
+            }
+
+            let groups = match group_by {
+                GroupBy::Project => vec![project.name.clone()],
+                GroupBy::Tag if task.tags.is_empty() => vec![String::new()],
+                GroupBy::Tag => task.tags.clone(),
+            };
+            for group in groups {
+                rows.push(Row {
+                    group,
+                    project: project.name.clone(),
+                    task_id: task.id,
+                    description: privacy::description(task).to_string(),
+                    tags: task.tags.clone(),
+                    minutes,
+                });
+            }
+        }
+    }
+    rows.sort_by(|a, b| (&a.group, &a.project, a.task_id).cmp(&(&b.group, &b.project, b.task_id)));
+
+    let group_name = match group_by {
+        GroupBy::Project => "project",
+        GroupBy::Tag => "tag",
+    };
+
+    match format {
+        ReportFormat::Csv => {
+            // Grouping by project needs no extra column, the rows already
+            // have one.
+            let group_column = |row: &Row| match group_by {
+                GroupBy::Project => String::new(),
+                GroupBy::Tag => format!("{},", csv_field(&row.group)),
+            };
+            let header = match group_by {
+                GroupBy::Project => "",
+                GroupBy::Tag => "tag,",
+            };
+            println!("{}project,task,description,tags,hours,rate,amount", header);
+            for row in &rows {
+                println!(
+                    "{}{},{},{},{},{:.2},{},{}",
+                    group_column(row),
+                    csv_field(&row.project),
+                    row.task_id,
+                    csv_field(&row.description),
+                    csv_field(&row.tags.join(" ")),
+                    hours(row.minutes),
+                    rate.map_or(String::new(), |rate| rate.to_string()),
+                    rate.map_or(String::new(), |rate| format!(
+                        "{:.2}",
+                        amount(row.minutes, rate)
+                    )),
+                );
+            }
+        }
+        ReportFormat::Json => {
+            let mut groups: BTreeMap<&str, Vec<&Row>> = BTreeMap::new();
+            for row in &rows {
+                groups.entry(&row.group).or_default().push(row);
+            }
+
+            let groups: Vec<_> = groups
+                .into_iter()
+                .map(|(group, rows)| {
+                    let minutes: u32 = rows.iter().map(|row| row.minutes).sum();
+                    let tasks: Vec<_> = rows
+                        .iter()
+                        .map(|row| {
+                            json!({
+                                "project": row.project,
+                                "task": row.task_id,
+                                "description": row.description,
+                                "tags": row.tags,
+                                "hours": hours(row.minutes),
+                                "amount": rate.map(|rate| amount(row.minutes, rate)),
+                            })
+                        })
+                        .collect();
+                    json!({
+                        group_name: group,
+                        "hours": hours(minutes),
+                        "amount": rate.map(|rate| {
+                            rows.iter().map(|row| amount(row.minutes, rate)).sum::<f64>()
+                        }),
+                        "tasks": tasks,
+                    })
+                })
+                .collect();
+
+            let report = json!({
+                "from": from,
+                "to": to,
+                "rate": rate,
+                "groups": groups,
+            });
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&report).expect("Unable to serialize report.")
+            );
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Daily planning: scheduling tasks onto days and the `today` overview.
+
+use chrono::NaiveDate;
+use colored::Colorize;
+
+use crate::{
+    contexts, dates, display, history::TaskEvent, i18n::tr, load_data, save_data, snooze,
+    task_details, Status, Task,
+};
+
+/// Schedule a task for `date`, or unschedule it when `date` is `none`.
+pub fn plan(project_name: &str, task_id: u32, date: &str) {
+    let scheduled = if date.eq_ignore_ascii_case("none") {
+        None
+    } else {
+        match dates::parse_date(date) {
+            Ok(date) => Some(date),
+            Err(e) => {
+                println!("{}", tr!("Invalid date: {}", e));
+                return;
+            }
+        }
+    };
+
+    let mut data = load_data();
+    if let Some(project) = data.iter_mut().find(|p| p.name == project_name) {
+        if let Some(task) = project.tasks.iter_mut().find(|t| t.id == task_id) {
+            task.scheduled = scheduled;
+            let detail = match scheduled {
+                Some(date) => format!("scheduled for {}", date),
+                None => "unscheduled".to_string(),
+            };
+            task.record(TaskEvent::Edited, Some(detail));
+            save_data(&data);
+            match scheduled {
+                Some(date) => println!("{}", tr!("Task {} is scheduled for {}.", task_id, date)),
+                None => println!("{}", tr!("Task {} is no longer scheduled.", task_id)),
+            }
+        } else {
+            println!(
+                "{}",
+                tr!("Task {} not found in project '{}'.", task_id, project_name)
+            );
+        }
+    } else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+    }
+}
+
+fn is_on_or_before(date: Option<NaiveDate>, day: NaiveDate) -> bool {
+    date.is_some_and(|date| date <= day)
+}
+
+/// Show what's on the plate today: tasks in progress, scheduled for today
+/// (or earlier and not done yet) and due today or overdue.
+pub fn show_today() {
+    let data = load_data();
+    let today = dates::today();
+
+    let mut in_progress = Vec::new();
+    let mut scheduled = Vec::new();
+    let mut due = Vec::new();
+
+    for project in &data {
+        for task in project
+            .tasks
+            .iter()
+            .filter(|t| contexts::shown(t) && snooze::shown(t))
+        {
+            let entry = (project.name.as_str(), task);
+            match task.status() {
+                Status::Done => {}
+                Status::InProgress => in_progress.push(entry),
+                Status::Todo if is_on_or_before(task.scheduled, today) => scheduled.push(entry),
+                Status::Todo if is_on_or_before(task.due, today) => due.push(entry),
+                Status::Todo => {}
+            }
+        }
+    }
+
+    println!("{}", today.format("Today, %A %Y-%m-%d").to_string().bold());
+
+    if in_progress.is_empty() && scheduled.is_empty() && due.is_empty() {
+        println!("{}", tr!("Nothing planned for today."));
+        return;
+    }
+
+    print_section(&tr!("In progress"), &in_progress);
+    print_section(&tr!("Scheduled"), &scheduled);
+    print_section(&tr!("Due"), &due);
+}
+
+fn print_section(title: &str, tasks: &[(&str, &Task)]) {
+    if tasks.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("{}:", title);
+    for (project, task) in tasks {
+        println!(
+            "    {}#{}: {}{}",
+            project,
+            task.id,
+            display::description(task),
+            task_details(task)
+        );
+    }
+}
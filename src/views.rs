@@ -0,0 +1,100 @@
+//! Saved filters ("smart views") executed across all projects.
+
+use clap::Subcommand;
+use colored::Colorize;
+
+use crate::{
+    config::{load_config, save_config},
+    filter,
+    i18n::tr,
+    load_data, print_matching_tasks,
+};
+
+#[derive(Subcommand)]
+pub enum ViewCommand {
+    /// Save a filter expression under a name.
+    Save { name: String, query: String },
+    /// List saved views.
+    List,
+    /// Delete a saved view.
+    Delete { name: String },
+}
+
+pub fn run(command: &ViewCommand) {
+    match command {
+        ViewCommand::Save { name, query } => save_view(name, query),
+        ViewCommand::List => list_views(),
+        ViewCommand::Delete { name } => delete_view(name),
+    }
+}
+
+fn save_view(name: &str, query: &str) {
+    if let Err(e) = filter::parse(query) {
+        println!("{}", tr!("Invalid filter: {}", e));
+        return;
+    }
+
+    let mut config = load_config();
+    let replaced = config
+        .views
+        .insert(name.to_string(), query.to_string())
+        .is_some();
+    save_config(&config);
+
+    if replaced {
+        println!("{}", tr!("View '{}' updated.", name));
+    } else {
+        println!("{}", tr!("View '{}' saved.", name));
+    }
+}
+
+fn list_views() {
+    let config = load_config();
+    if config.views.is_empty() {
+        println!("{}", tr!("No views saved."));
+        return;
+    }
+
+    println!("{}", tr!("Views:"));
+    for (name, query) in &config.views {
+        println!(" - {}: {}", name.bold(), query);
+    }
+}
+
+fn delete_view(name: &str) {
+    let mut config = load_config();
+    if config.views.remove(name).is_some() {
+        save_config(&config);
+        println!("{}", tr!("View '{}' has been deleted.", name));
+    } else {
+        println!("{}", tr!("View '{}' not found.", name));
+    }
+}
+
+/// Print every task, across all projects, matching the view `name`.
+pub fn show_view(name: &str) {
+    let config = load_config();
+    let Some(query) = config.views.get(name) else {
+        println!("{}", tr!("View '{}' not found.", name));
+        return;
+    };
+
+    let filter = match filter::parse(query) {
+        Ok(filter) => filter,
+        Err(e) => {
+            println!("{}", tr!("View '{}' has an invalid filter: {}", name, e));
+            return;
+        }
+    };
+
+    println!("{}", tr!("View '{}': {}", name, query));
+
+    let data = load_data();
+    let found = print_matching_tasks(&data, None, |project, task| {
+        filter.matches(&project.name, task)
+    });
+
+    if found == 0 {
+        println!("    {}", tr!("No matching tasks."));
+    }
+}
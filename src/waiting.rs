@@ -0,0 +1,126 @@
+//! Waiting for someone else: `waiting web 3 --for "reply from vendor"`
+//! marks a task as blocked on an outside party, since today or the day
+//! given with `--since`, and `waiting list` shows what's waiting, longest
+//! first, to know whom to chase.
+
+use chrono::NaiveDate;
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+
+use crate::{dates, display, history::TaskEvent, i18n::tr, load_data, save_data, task_details};
+
+/// What a task is waiting for, and since when.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Waiting {
+    #[serde(rename = "for")]
+    pub what: String,
+    pub since: NaiveDate,
+}
+
+#[derive(Subcommand)]
+pub enum WaitingCommand {
+    /// List the tasks waiting on someone, longest waiting first.
+    List,
+}
+
+/// `(waiting for reply from vendor, 12 days)`, for listings.
+pub fn marker(waiting: &Waiting) -> String {
+    let days = (dates::today() - waiting.since).num_days();
+    tr!("(waiting for {}, {} days)", waiting.what, days)
+}
+
+/// Mark a task as waiting for `what` since `since`, today if not given.
+pub fn wait(project_name: &str, task_id: u32, what: &str, since: Option<NaiveDate>) {
+    let since = since.unwrap_or_else(dates::today);
+    if since > dates::today() {
+        println!("{}", tr!("Tasks can't be waiting since a day to come."));
+        return;
+    }
+
+    let mut data = load_data();
+    let Some(project) = data.iter_mut().find(|p| p.name == project_name) else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+        return;
+    };
+    let Some(task) = project.tasks.iter_mut().find(|t| t.id == task_id) else {
+        println!(
+            "{}",
+            tr!("Task {} not found in project '{}'.", task_id, project_name)
+        );
+        return;
+    };
+    if task.completed {
+        println!("{}", tr!("Task {} is already completed!", task_id));
+        return;
+    }
+    task.waiting = Some(Waiting {
+        what: what.to_string(),
+        since,
+    });
+    task.record(TaskEvent::Edited, Some(format!("waiting for {}", what)));
+    save_data(&data);
+    println!(
+        "{}",
+        tr!("Task {} is waiting for {} since {}.", task_id, what, since)
+    );
+}
+
+/// Stop waiting on a task, the wait being over.
+pub fn stop_waiting(project_name: &str, task_id: u32) {
+    let mut data = load_data();
+    let Some(project) = data.iter_mut().find(|p| p.name == project_name) else {
+        println!("{}", tr!("Project '{}' not found.", project_name));
+        return;
+    };
+    let Some(task) = project.tasks.iter_mut().find(|t| t.id == task_id) else {
+        println!(
+            "{}",
+            tr!("Task {} not found in project '{}'.", task_id, project_name)
+        );
+        return;
+    };
+    let Some(waiting) = task.waiting.take() else {
+        println!("{}", tr!("Task {} isn't waiting for anything.", task_id));
+        return;
+    };
+    let days = (dates::today() - waiting.since).num_days();
+    task.record(
+        TaskEvent::Edited,
+        Some(format!("done waiting for {}", waiting.what)),
+    );
+    save_data(&data);
+    println!(
+        "{}",
+        tr!(
+            "Task {} is no longer waiting, after {} days.",
+            task_id,
+            days
+        )
+    );
+}
+
+pub fn list() {
+    let data = load_data();
+    let mut waiting: Vec<_> = data
+        .iter()
+        .flat_map(|p| p.tasks.iter().map(move |t| (p, t)))
+        .filter(|(_, t)| !t.completed)
+        .filter_map(|(p, t)| Some((p, t, t.waiting.as_ref()?)))
+        .collect();
+    if waiting.is_empty() {
+        println!("{}", tr!("No tasks waiting on anyone."));
+        return;
+    }
+    waiting.sort_by_key(|(_, _, waiting)| waiting.since);
+
+    // The details say what each task is waiting for, and for how long.
+    println!("{}", tr!("Tasks waiting, longest first:"));
+    for (project, task, _) in waiting {
+        println!(
+            "    {}: {}{}",
+            project.task_ref(task.id),
+            display::description(task),
+            task_details(task)
+        );
+    }
+}
@@ -0,0 +1,366 @@
+//! Webhooks: URLs a JSON payload is POSTed to whenever a task event happens,
+//! so events can flow into chat or home-automation systems.
+//!
+//! Commands never wait on the network: deliveries are appended to a queue
+//! file and sent by a detached `webhook flush` process. Failed deliveries
+//! stay queued and are retried by later flushes, waiting longer after every
+//! failure, up to `MAX_ATTEMPTS` times.
+
+use chrono::{DateTime, Duration, Local};
+use clap::{Subcommand, ValueEnum};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+use tracing::{debug, info};
+
+use crate::{
+    config::{load_config, save_config},
+    get_config_dir,
+    i18n::tr,
+    workspace,
+};
+
+/// Events a webhook can be registered for, as named by the hooks.
+const EVENTS: &[&str] = &[
+    "project-add",
+    "project-delete",
+    "task-add",
+    "task-complete",
+    "task-reopen",
+    "task-delete",
+];
+
+/// Deliveries failing this many times are dropped.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// How the payload is shaped for the receiving end.
+#[derive(Serialize, Deserialize, ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    /// The event as passed to hooks.
+    #[default]
+    Json,
+    /// A Slack incoming webhook message.
+    Slack,
+    /// A Discord webhook message.
+    Discord,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Webhook {
+    pub url: String,
+    pub events: Vec<String>,
+    #[serde(default)]
+    pub format: WebhookFormat,
+}
+
+/// A payload waiting to be POSTed.
+#[derive(Serialize, Deserialize)]
+struct Delivery {
+    url: String,
+    body: Value,
+    #[serde(default)]
+    attempts: u32,
+    /// Not retried before this, after a failed attempt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retry_at: Option<DateTime<Local>>,
+}
+
+#[derive(Subcommand)]
+pub enum WebhookCommand {
+    /// Register a URL to POST events to.
+    Add {
+        url: String,
+        /// Event to send, may be repeated.
+        #[arg(long = "event", value_parser = parse_event,
+              default_values_t = ["task-add".to_string(), "task-complete".to_string()])]
+        events: Vec<String>,
+        #[arg(long, value_enum, default_value_t = WebhookFormat::Json)]
+        format: WebhookFormat,
+    },
+    /// List registered webhooks and how many deliveries are queued.
+    List,
+    /// Unregister a webhook.
+    Remove { url: String },
+    /// Send queued deliveries now.
+    Flush {
+        /// Run quietly, leaving deliveries whose retry isn't due yet, as done
+        /// in the background after every event.
+        #[arg(long, hide = true)]
+        background: bool,
+    },
+}
+
+fn parse_event(event: &str) -> Result<String, String> {
+    if EVENTS.contains(&event) {
+        Ok(event.to_string())
+    } else {
+        Err(tr!("expected one of: {}", EVENTS.join(", ")))
+    }
+}
+
+pub fn run(command: &WebhookCommand) {
+    match command {
+        WebhookCommand::Add {
+            url,
+            events,
+            format,
+        } => add_webhook(url, events, *format),
+        WebhookCommand::List => list_webhooks(),
+        WebhookCommand::Remove { url } => remove_webhook(url),
+        WebhookCommand::Flush { background } => flush(*background),
+    }
+}
+
+fn add_webhook(url: &str, events: &[String], format: WebhookFormat) {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        println!("{}", tr!("Invalid webhook URL '{}'.", url));
+        return;
+    }
+
+    let mut events = events.to_vec();
+    events.sort();
+    events.dedup();
+
+    let mut config = load_config();
+    let webhook = Webhook {
+        url: url.to_string(),
+        events,
+        format,
+    };
+    if let Some(existing) = config.webhooks.iter_mut().find(|w| w.url == url) {
+        *existing = webhook;
+        save_config(&config);
+        println!("{}", tr!("Webhook '{}' updated.", url));
+    } else {
+        config.webhooks.push(webhook);
+        save_config(&config);
+        println!("{}", tr!("Webhook '{}' added.", url));
+    }
+}
+
+fn list_webhooks() {
+    let config = load_config();
+    if config.webhooks.is_empty() {
+        println!("{}", tr!("No webhooks registered."));
+    } else {
+        println!("{}", tr!("Webhooks:"));
+        for webhook in &config.webhooks {
+            let format = match webhook.format {
+                WebhookFormat::Json => String::new(),
+                other => format!(" [{:?}]", other).to_lowercase(),
+            };
+            println!(
+                " - {}{}: {}",
+                webhook.url.bold(),
+                format,
+                webhook.events.join(", ")
+            );
+        }
+    }
+
+    let queued = read_queue(&get_queue_file()).len();
+    if queued > 0 {
+        println!("{}", tr!("{} deliveries queued.", queued));
+    }
+}
+
+fn remove_webhook(url: &str) {
+    let mut config = load_config();
+    let before = config.webhooks.len();
+    config.webhooks.retain(|w| w.url != url);
+    if config.webhooks.len() == before {
+        println!("{}", tr!("Webhook '{}' not found.", url));
+        return;
+    }
+    save_config(&config);
+    println!("{}", tr!("Webhook '{}' removed.", url));
+}
+
+fn get_queue_file() -> PathBuf {
+    get_config_dir().join("webhook-queue.jsonl")
+}
+
+fn read_queue(path: &PathBuf) -> Vec<Delivery> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+fn append_to_queue(deliveries: &[Delivery]) {
+    if deliveries.is_empty() {
+        return;
+    }
+    let mut lines = String::new();
+    for delivery in deliveries {
+        lines.push_str(&serde_json::to_string(delivery).expect("Failed to serialize delivery"));
+        lines.push('\n');
+    }
+
+    fs::create_dir_all(get_config_dir()).expect("Failed to create config directory");
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(get_queue_file())
+        .expect("Failed to open webhook queue");
+    file.write_all(lines.as_bytes())
+        .expect("Failed to write webhook queue");
+}
+
+/// One line describing the event, for chat messages.
+fn summary(payload: &Value) -> String {
+    let event = payload["event"].as_str().unwrap_or_default();
+    let project = match &payload["project"] {
+        Value::String(name) => name.clone(),
+        project => project["name"].as_str().unwrap_or_default().to_string(),
+    };
+    let task = &payload["task"];
+    let task_id = task["id"].as_u64().or(payload["task_id"].as_u64());
+    let description = task["description"].as_str().unwrap_or_default();
+
+    match (event, task_id) {
+        ("project-add", _) => tr!("Project '{}' added.", project),
+        ("project-delete", _) => tr!("Project '{}' deleted.", project),
+        ("task-add", Some(id)) => tr!("Task {} added to '{}': {}", id, project, description),
+        ("task-complete", Some(id)) => {
+            tr!("Task {} completed in '{}': {}", id, project, description)
+        }
+        ("task-reopen", Some(id)) => tr!("Task {} reopened in '{}': {}", id, project, description),
+        ("task-delete", Some(id)) => tr!("Task {} deleted from '{}'.", id, project),
+        _ => event.to_string(),
+    }
+}
+
+fn body(format: WebhookFormat, payload: &Value) -> Value {
+    match format {
+        WebhookFormat::Json => payload.clone(),
+        WebhookFormat::Slack => json!({ "text": summary(payload) }),
+        WebhookFormat::Discord => json!({ "content": summary(payload) }),
+    }
+}
+
+/// Queue `payload` for every webhook registered for `event`, and start
+/// sending it in the background.
+pub fn enqueue(event: &str, payload: &Value) {
+    let config = load_config();
+    let deliveries: Vec<Delivery> = config
+        .webhooks
+        .iter()
+        .filter(|w| w.events.iter().any(|e| e == event))
+        .map(|w| Delivery {
+            url: w.url.clone(),
+            body: body(w.format, payload),
+            attempts: 0,
+            retry_at: None,
+        })
+        .collect();
+    if deliveries.is_empty() {
+        return;
+    }
+
+    debug!("queueing {} deliveries of {}", deliveries.len(), event);
+    append_to_queue(&deliveries);
+    spawn_flush();
+}
+
+/// Start a detached `webhook flush`, which outlives this process.
+fn spawn_flush() {
+    let Ok(exe) = std::env::current_exe() else {
+        return;
+    };
+    let spawned = Command::new(exe)
+        .args(["--workspace", workspace::active()])
+        .args(["webhook", "flush", "--background"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    if let Err(e) = spawned {
+        eprintln!("{}", tr!("Failed to start sending webhooks: {}", e));
+    }
+}
+
+fn post(url: &str, body: &Value) -> Result<(), String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(std::time::Duration::from_secs(10)))
+        .build()
+        .into();
+    agent
+        .post(url)
+        .header("Content-Type", "application/json")
+        .send(body.to_string().as_bytes())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Send the queued deliveries, putting failed ones back in the queue.
+///
+/// The queue is moved aside first, so that events happening meanwhile (or
+/// another flush) don't get mixed up with the ones being sent.
+fn flush(background: bool) {
+    let queue = get_queue_file();
+    let sending = queue.with_extension(format!("{}.jsonl", std::process::id()));
+    if fs::rename(&queue, &sending).is_err() {
+        if !background {
+            println!("{}", tr!("No deliveries queued."));
+        }
+        return;
+    }
+    let deliveries = read_queue(&sending);
+
+    let now = Local::now();
+    let (mut sent, mut failed) = (0, 0);
+    let mut remaining = Vec::new();
+    for mut delivery in deliveries {
+        if background && delivery.retry_at.is_some_and(|at| at > now) {
+            remaining.push(delivery);
+            continue;
+        }
+
+        match post(&delivery.url, &delivery.body) {
+            Ok(()) => {
+                info!("sent delivery to {}", delivery.url);
+                sent += 1;
+            }
+            Err(e) => {
+                info!("delivery to {} failed: {}", delivery.url, e);
+                failed += 1;
+                delivery.attempts += 1;
+                if delivery.attempts >= MAX_ATTEMPTS {
+                    if !background {
+                        println!(
+                            "{}",
+                            tr!(
+                                "Giving up on '{}' after {} attempts: {}",
+                                delivery.url,
+                                delivery.attempts,
+                                e
+                            )
+                        );
+                    }
+                    continue;
+                }
+                if !background {
+                    println!("{}", tr!("Failed to send to '{}': {}", delivery.url, e));
+                }
+                delivery.retry_at = Some(now + Duration::minutes(1 << delivery.attempts));
+                remaining.push(delivery);
+            }
+        }
+    }
+
+    append_to_queue(&remaining);
+    let _ = fs::remove_file(&sending);
+
+    if !background {
+        println!("{}", tr!("{} deliveries sent, {} failed.", sent, failed));
+    }
+}
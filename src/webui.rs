@@ -0,0 +1,378 @@
+//! A web UI served by `serve` under `/ui/`, to see the pending tasks and
+//! check them off from a phone browser on the same network. The page and
+//! its assets are built into the binary, from `src/webui/`.
+//!
+//! The page keeps the tasks it last loaded and the checks made while the
+//! server can't be reached, and sends those once it can, so it keeps
+//! working offline. It needs the token given to the server in
+//! `PT_API_TOKEN`, asked for once and kept by the browser.
+//!
+//! Routes, besides the assets:
+//!
+//! - `GET /ui/tasks`: the tasks to show, by project, as JSON.
+//! - `POST /ui/complete` and `POST /ui/reopen`: run `complete-task` or
+//!   `reopen-task` on `{"project": ..., "id": ...}`.
+
+use chrono::Local;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{self, Cursor};
+use tiny_http::{Header, Method, Request, Response};
+
+use crate::{display, load_data, privacy, remote, serve, snooze, Project, Task};
+
+/// The assets, as `(file name, content type, content)`.
+const ASSETS: &[(&str, &str, &[u8])] = &[
+    (
+        "index.html",
+        "text/html; charset=utf-8",
+        include_bytes!("webui/index.html"),
+    ),
+    (
+        "app.js",
+        "text/javascript; charset=utf-8",
+        include_bytes!("webui/app.js"),
+    ),
+    (
+        "app.css",
+        "text/css; charset=utf-8",
+        include_bytes!("webui/app.css"),
+    ),
+    (
+        "sw.js",
+        "text/javascript; charset=utf-8",
+        include_bytes!("webui/sw.js"),
+    ),
+    (
+        "manifest.webmanifest",
+        "application/manifest+json",
+        include_bytes!("webui/manifest.webmanifest"),
+    ),
+];
+
+fn header(header: &str) -> Header {
+    header.parse().expect("Valid header")
+}
+
+fn json_response(body: &Value) -> Response<Cursor<Vec<u8>>> {
+    Response::from_string(body.to_string()).with_header(header("Content-Type: application/json"))
+}
+
+/// Send `/` to the UI.
+pub fn redirect() -> Response<Cursor<Vec<u8>>> {
+    Response::from_string("")
+        .with_status_code(303)
+        .with_header(header("Location: /ui/"))
+}
+
+/// Answer a request for anything under `/ui/`.
+pub fn handle(request: &mut Request) -> Response<Cursor<Vec<u8>>> {
+    answer(request, remote::api_token())
+}
+
+/// Answer `request` for the browsers with `token`, if any are allowed.
+fn answer(request: &mut Request, token: Option<String>) -> Response<Cursor<Vec<u8>>> {
+    let url = request.url();
+    let path = url[..url.find('?').unwrap_or(url.len())]
+        .trim_start_matches("/ui/")
+        .to_string();
+
+    match (request.method(), path.as_str()) {
+        (Method::Get, "tasks") | (Method::Post, "complete" | "reopen") => {}
+        (Method::Get, path) => return asset(if path.is_empty() { "index.html" } else { path }),
+        _ => return Response::from_string("Not found").with_status_code(404),
+    }
+
+    let Some(token) = token else {
+        return Response::from_string("The web UI is not enabled").with_status_code(503);
+    };
+    if !remote::is_authorized(request, &token) {
+        return Response::from_string("Invalid token").with_status_code(401);
+    }
+    match path.as_str() {
+        "tasks" => json_response(&tasks(&load_data())),
+        command => check(request, command),
+    }
+}
+
+fn asset(name: &str) -> Response<Cursor<Vec<u8>>> {
+    match ASSETS.iter().find(|(file, _, _)| *file == name) {
+        Some((_, content_type, content)) => Response::from_data(content.to_vec())
+            .with_header(header(&format!("Content-Type: {}", content_type)))
+            // The page checks for a newer version whenever it's online.
+            .with_header(header("Cache-Control: no-cache")),
+        None => Response::from_string("Not found").with_status_code(404),
+    }
+}
+
+/// Whether `task` is worth showing: pending and not snoozed, or completed
+/// today, to be unchecked if that was a mistake.
+fn shown(task: &Task) -> bool {
+    let today = Local::now().date_naive();
+    match task.completed_at {
+        Some(at) => at.date_naive() == today,
+        None => snooze::shown(task),
+    }
+}
+
+fn tasks(data: &[Project]) -> Value {
+    let projects: Vec<Value> = data
+        .iter()
+        .map(|project| {
+            let tasks: Vec<Value> = project
+                .tasks
+                .iter()
+                .filter(|task| shown(task))
+                .map(|task| {
+                    json!({
+                        "id": task.id,
+                        "ref": project.task_ref(task.id),
+                        "description": privacy::public_description(task),
+                        "completed": task.completed,
+                        "priority": task.priority,
+                        "due": task.due,
+                        "escalated": display::is_escalated(task),
+                        "tags": task.tags,
+                    })
+                })
+                .collect();
+            json!({ "name": project.name, "tasks": tasks })
+        })
+        .collect();
+    json!({ "projects": projects })
+}
+
+#[derive(Deserialize)]
+struct Check {
+    project: String,
+    id: u32,
+}
+
+/// Complete or reopen the task in `request` the way the command line does,
+/// hooks and notifications included.
+fn check(request: &mut Request, command: &str) -> Response<Cursor<Vec<u8>>> {
+    let check: Check = match serde_json::from_reader(request.as_reader()) {
+        Ok(check) => check,
+        Err(e) => {
+            return Response::from_string(format!("Invalid request: {}", e)).with_status_code(400)
+        }
+    };
+    let args = [
+        format!("{}-task", command),
+        check.project,
+        check.id.to_string(),
+    ];
+    match serve::run_command(&args) {
+        Ok(output) => json_response(&json!({ "message": output })),
+        Err(e) if e.kind() == io::ErrorKind::InvalidInput => {
+            Response::from_string(format!("Invalid request: {}", e)).with_status_code(400)
+        }
+        Err(e) => {
+            Response::from_string(format!("Failed to run the command: {}", e)).with_status_code(500)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::Duration;
+
+    use crate::storage;
+
+    const TOKEN: &str = "s3cret";
+
+    fn task(id: u32, description: &str) -> Task {
+        Task {
+            id,
+            description: description.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn data() -> Vec<Project> {
+        let today = Local::now();
+        vec![Project {
+            name: "web".to_string(),
+            tasks: vec![
+                task(1, "Fix the login form"),
+                Task {
+                    private: true,
+                    ..task(2, "Talk to HR about the raise")
+                },
+                Task {
+                    completed: true,
+                    completed_at: Some(today),
+                    ..task(3, "Done today")
+                },
+                Task {
+                    completed: true,
+                    completed_at: Some(today - Duration::days(2)),
+                    ..task(4, "Done before")
+                },
+                Task {
+                    snoozed_until: Some(today.date_naive() + Duration::days(3)),
+                    ..task(5, "Snoozed")
+                },
+            ],
+            ..Default::default()
+        }]
+    }
+
+    /// Status, content type and body of a request to `path`.
+    fn send(
+        url: &str,
+        method: &str,
+        path: &str,
+        token: Option<&str>,
+        body: &str,
+    ) -> (u16, String, String) {
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .build()
+            .into();
+        let request = ureq::http::Request::builder()
+            .method(method)
+            .uri(format!("{}{}", url, path));
+        let request = match token {
+            Some(token) => request.header("Authorization", format!("Bearer {}", token)),
+            None => request,
+        };
+        let mut response = agent
+            .run(request.body(body.to_string()).unwrap())
+            .expect("The server answers");
+        let content_type = response
+            .headers()
+            .get("Content-Type")
+            .map(|value| value.to_str().unwrap().to_string())
+            .unwrap_or_default();
+        let body = response.body_mut().read_to_string().unwrap_or_default();
+        (response.status().as_u16(), content_type, body)
+    }
+
+    #[test]
+    fn only_pending_and_just_completed_tasks_are_shown() {
+        let tasks = tasks(&data());
+        let shown: Vec<(u64, &str)> = tasks["projects"][0]["tasks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| {
+                (
+                    t["id"].as_u64().unwrap(),
+                    t["description"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            shown,
+            [
+                (1, "Fix the login form"),
+                (2, privacy::MASK),
+                (3, "Done today")
+            ]
+        );
+        assert_eq!(tasks["projects"][0]["tasks"][2]["completed"], true);
+        assert!(!tasks.to_string().contains("raise"));
+    }
+
+    #[test]
+    fn the_page_is_served_to_anyone() {
+        let answers = serve::exchange(
+            |request| answer(request, Some(TOKEN.to_string())),
+            |url| {
+                [
+                    send(&url, "GET", "/ui/", None, ""),
+                    send(&url, "GET", "/ui/app.js?v=2", None, ""),
+                    send(&url, "GET", "/ui/secrets.txt", None, ""),
+                    send(&url, "GET", "/ui/../tasks.json", None, ""),
+                    send(&url, "DELETE", "/ui/tasks", Some(TOKEN), ""),
+                ]
+            },
+        );
+        let (status, content_type, body) = &answers[0];
+        assert_eq!(*status, 200);
+        assert!(content_type.starts_with("text/html"));
+        assert_eq!(body.as_bytes(), ASSETS[0].2);
+        let (status, content_type, _) = &answers[1];
+        assert_eq!(*status, 200);
+        assert!(content_type.starts_with("text/javascript"));
+        for (status, _, _) in &answers[2..] {
+            assert_eq!(*status, 404);
+        }
+    }
+
+    #[test]
+    fn tasks_are_only_listed_with_the_token() {
+        storage::use_memory(data());
+        let answers = serve::exchange(
+            |request| answer(request, Some(TOKEN.to_string())),
+            |url| {
+                [
+                    send(&url, "GET", "/ui/tasks", None, ""),
+                    send(&url, "GET", "/ui/tasks", Some("s3cre"), ""),
+                    send(
+                        &url,
+                        "POST",
+                        "/ui/complete",
+                        None,
+                        r#"{"project":"web","id":1}"#,
+                    ),
+                    send(&url, "GET", "/ui/tasks", Some(TOKEN), ""),
+                ]
+            },
+        );
+        for (status, _, body) in &answers[..3] {
+            assert_eq!(*status, 401);
+            assert!(!body.contains("login"));
+        }
+        let (status, content_type, body) = &answers[3];
+        assert_eq!(*status, 200);
+        assert_eq!(content_type, "application/json");
+        let listed: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(listed, tasks(&data()));
+        assert!(!load_data()[0].tasks[0].completed);
+    }
+
+    #[test]
+    fn nothing_but_the_page_is_served_without_a_token() {
+        let (status, _, _) = serve::exchange(
+            |request| answer(request, None),
+            |url| send(&url, "GET", "/ui/tasks", Some(TOKEN), ""),
+        );
+        assert_eq!(status, 503);
+    }
+
+    #[test]
+    fn invalid_checks_are_refused() {
+        storage::use_memory(data());
+        let answers = serve::exchange(
+            |request| answer(request, Some(TOKEN.to_string())),
+            |url| {
+                [
+                    send(&url, "POST", "/ui/complete", Some(TOKEN), "web 1"),
+                    send(
+                        &url,
+                        "POST",
+                        "/ui/reopen",
+                        Some(TOKEN),
+                        r#"{"project":"web"}"#,
+                    ),
+                    send(
+                        &url,
+                        "POST",
+                        "/ui/complete",
+                        Some(TOKEN),
+                        r#"{"project":"--force","id":1}"#,
+                    ),
+                ]
+            },
+        );
+        for (status, _, body) in &answers {
+            assert_eq!(*status, 400, "{}", body);
+            assert!(body.starts_with("Invalid request"), "{}", body);
+        }
+        assert!(!load_data()[0].tasks[0].completed);
+    }
+}
@@ -0,0 +1,210 @@
+// The tracker's web UI: pending tasks by project, to check off from a phone.
+//
+// The tasks last loaded and the checks not sent yet are kept in
+// localStorage: the page shows the former when the server can't be reached,
+// and sends the latter, in order, once it can.
+"use strict";
+
+const TOKEN = "project-tracker-token";
+const TASKS = "project-tracker-tasks";
+const QUEUE = "project-tracker-queue";
+
+const $ = id => document.getElementById(id);
+
+function read(key, fallback) {
+  return JSON.parse(localStorage.getItem(key) || "null") ?? fallback;
+}
+
+function write(key, value) {
+  localStorage.setItem(key, JSON.stringify(value));
+}
+
+function say(message) {
+  $("message").textContent = message;
+  clearTimeout(say.timer);
+  say.timer = setTimeout(() => ($("message").textContent = ""), 4000);
+}
+
+// Thrown when the server answered, but not with what was asked for, as
+// opposed to not being reachable.
+class Refused extends Error {
+  constructor(status, message) {
+    super(message);
+    this.status = status;
+  }
+}
+
+async function api(path, body) {
+  const response = await fetch(path, {
+    method: body ? "POST" : "GET",
+    headers: {
+      Authorization: "Bearer " + (localStorage.getItem(TOKEN) || ""),
+      "Content-Type": "application/json",
+    },
+    body: body && JSON.stringify(body),
+  });
+  if (response.status === 401) {
+    askToken();
+    throw new Refused(401, "The server refused the token.");
+  }
+  if (!response.ok) {
+    throw new Refused(response.status, await response.text());
+  }
+  return response.json();
+}
+
+function askToken() {
+  localStorage.removeItem(TOKEN);
+  $("login").hidden = false;
+  $("token").focus();
+}
+
+function render() {
+  const queued = read(QUEUE, []);
+  const isQueued = (project, id) => queued.some(c => c.project === project && c.id === id);
+  const main = $("projects");
+  main.replaceChildren();
+
+  for (const project of read(TASKS, { projects: [] }).projects) {
+    if (project.tasks.length === 0) {
+      continue;
+    }
+    const section = document.createElement("section");
+    const title = document.createElement("h2");
+    title.textContent = project.name;
+    const list = document.createElement("ul");
+
+    for (const task of project.tasks) {
+      const item = document.createElement("li");
+      item.classList.toggle("completed", task.completed);
+      item.classList.toggle("escalated", task.escalated);
+      const label = document.createElement("label");
+      const box = document.createElement("input");
+      box.type = "checkbox";
+      box.checked = task.completed;
+      box.addEventListener("change", () => toggle(project.name, task, box.checked));
+
+      const description = document.createElement("span");
+      description.className = "description";
+      description.textContent = task.description;
+      description.classList.toggle("high", task.priority === "high");
+      description.classList.toggle("pending", isQueued(project.name, task.id));
+      const ref = document.createElement("span");
+      ref.className = "ref";
+      ref.textContent = task.ref;
+      label.append(box, description);
+      if (task.due && !task.completed) {
+        const due = document.createElement("span");
+        due.className = "due";
+        due.classList.toggle("late", task.due < new Date().toISOString().slice(0, 10));
+        due.textContent = task.due;
+        label.append(due);
+      }
+      label.append(ref);
+      item.append(label);
+      list.append(item);
+    }
+    section.append(title, list);
+    main.append(section);
+  }
+  if (!main.hasChildNodes()) {
+    main.textContent = "Nothing to do.";
+  }
+}
+
+// Check `task` off, or back on, right away, and queue the change for the
+// server.
+function toggle(project, task, completed) {
+  task.completed = completed;
+  const data = read(TASKS, { projects: [] });
+  const stored = data.projects
+    .find(p => p.name === project)
+    ?.tasks.find(t => t.id === task.id);
+  if (stored) {
+    stored.completed = completed;
+  }
+  write(TASKS, data);
+
+  // Checking and unchecking before anything was sent cancel out.
+  const queue = read(QUEUE, []);
+  const index = queue.findIndex(c => c.project === project && c.id === task.id);
+  if (index >= 0) {
+    queue.splice(index, 1);
+  } else {
+    queue.push({ command: completed ? "complete" : "reopen", project, id: task.id });
+  }
+  write(QUEUE, queue);
+  render();
+  sync();
+}
+
+// Send the queued checks, then load the tasks again.
+async function sync() {
+  if (sync.running || !localStorage.getItem(TOKEN)) {
+    return;
+  }
+  sync.running = true;
+  try {
+    let queue = read(QUEUE, []);
+    while (queue.length > 0) {
+      const { command, project, id } = queue[0];
+      try {
+        const answer = await api(command, { project, id });
+        say(answer.message);
+      } catch (error) {
+        // Without a valid token, the check is kept to be sent with one.
+        if (!(error instanceof Refused) || error.status === 401) {
+          throw error;
+        }
+        say(error.message);
+      }
+      // The queue may have changed meanwhile.
+      queue = read(QUEUE, []);
+      const sent = queue.findIndex(
+        c => c.command === command && c.project === project && c.id === id
+      );
+      if (sent >= 0) {
+        queue.splice(sent, 1);
+      }
+      write(QUEUE, queue);
+    }
+    write(TASKS, await api("tasks"));
+    $("status").textContent = "";
+  } catch (error) {
+    if (error instanceof Refused) {
+      say(error.message);
+    } else {
+      $("status").textContent = navigator.onLine ? "Server unreachable" : "Offline";
+    }
+  } finally {
+    sync.running = false;
+    render();
+  }
+}
+
+$("login").addEventListener("submit", event => {
+  event.preventDefault();
+  localStorage.setItem(TOKEN, $("token").value);
+  $("token").value = "";
+  $("login").hidden = true;
+  sync();
+});
+
+window.addEventListener("online", sync);
+document.addEventListener("visibilitychange", () => {
+  if (document.visibilityState === "visible") {
+    sync();
+  }
+});
+
+// Browsers only allow service workers on HTTPS or localhost.
+if ("serviceWorker" in navigator) {
+  navigator.serviceWorker.register("sw.js").catch(() => {});
+}
+
+render();
+if (localStorage.getItem(TOKEN)) {
+  sync();
+} else {
+  askToken();
+}
@@ -0,0 +1,27 @@
+// Keeps the page itself available without a connection: its files are
+// fetched from the server when it's reachable, and from the cache otherwise.
+"use strict";
+
+const CACHE = "project-tracker";
+const FILES = ["./", "app.js", "app.css", "manifest.webmanifest"];
+
+self.addEventListener("install", event => {
+  event.waitUntil(caches.open(CACHE).then(cache => cache.addAll(FILES)));
+});
+
+self.addEventListener("fetch", event => {
+  const url = event.request.url;
+  const isFile = FILES.some(file => new URL(file, self.registration.scope).href === url);
+  if (event.request.method !== "GET" || !isFile) {
+    return;
+  }
+  event.respondWith(
+    fetch(event.request)
+      .then(response => {
+        const copy = response.clone();
+        caches.open(CACHE).then(cache => cache.put(event.request, copy));
+        return response;
+      })
+      .catch(() => caches.match(event.request))
+  );
+});
@@ -0,0 +1,48 @@
+//! Work-in-progress limits: how many tasks of a project may be in progress
+//! at the same time, set with `config set wip-limit <n> --project <name>`.
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::load_config, i18n::tr, theme::Themed};
+
+/// What happens when a task would go over the limit.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum WipPolicy {
+    /// Move the task anyway, with a warning.
+    Warn,
+    /// Don't move the task.
+    Refuse,
+}
+
+/// Check whether another task may be moved in progress in a project that
+/// already has `in_progress` of them, warning about going over the limit.
+/// Returns `false` if the move should not happen.
+pub fn allows_another(project_name: &str, in_progress: usize) -> bool {
+    let settings = load_config().effective_for(Some(project_name));
+    let Some(limit) = settings.wip_limit else {
+        return true;
+    };
+
+    if in_progress < limit as usize {
+        return true;
+    }
+
+    let message = tr!(
+        "Project '{}' already has {} tasks in progress (limit {}).",
+        project_name,
+        in_progress,
+        limit
+    );
+    match settings.wip_policy.unwrap_or(WipPolicy::Warn) {
+        WipPolicy::Warn => {
+            println!("{}", message.warn());
+            true
+        }
+        WipPolicy::Refuse => {
+            println!("{}", tr!("{} Finish something first.", message.bad()));
+            false
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! Workspaces: fully independent data files living side by side.
+//!
+//! The `default` workspace keeps using `data.json` so existing setups keep
+//! working, every other workspace is stored in `workspaces/<name>.json`
+//! (or `.toml`/`.yaml`, depending on the data format), in the config
+//! directory or the one set with `data-dir`. With `split-projects`, the
+//! projects are in `data.projects/` or `workspaces/<name>.projects/` instead,
+//! and the data file only lists them.
+
+use clap::Subcommand;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use crate::{
+    archive,
+    config::load_config,
+    get_config_dir, graph,
+    i18n::tr,
+    index,
+    storage::{serialize, DataFormat},
+};
+
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+static ACTIVE: OnceLock<String> = OnceLock::new();
+
+#[derive(Subcommand)]
+pub enum WorkspaceCommand {
+    /// List all workspaces.
+    List,
+    /// Create a new, empty workspace.
+    Create { name: String },
+    /// Delete a workspace and all of its data.
+    Delete { name: String },
+}
+
+/// Select the workspace every subsequent load/save operates on, unless its
+/// name isn't a valid one, which could point outside the workspaces
+/// directory.
+pub fn set_active(name: Option<String>) -> bool {
+    let name = name.unwrap_or_else(|| DEFAULT_WORKSPACE.to_string());
+    if !is_valid_name(&name) {
+        println!("{}", invalid_name(&name));
+        return false;
+    }
+    let _ = ACTIVE.set(name);
+    true
+}
+
+pub fn active() -> &'static str {
+    ACTIVE.get().map_or(DEFAULT_WORKSPACE, String::as_str)
+}
+
+/// Where the data files are kept: the `data-dir` setting, or the config
+/// directory.
+pub fn data_dir() -> PathBuf {
+    match load_config().global().data_dir {
+        Some(dir) => {
+            fs::create_dir_all(&dir).expect("Failed to create data directory");
+            dir
+        }
+        None => get_config_dir(),
+    }
+}
+
+/// Move the data files from `from` to the data directory, just changed.
+pub fn move_data(from: &Path) {
+    let to = data_dir();
+    if from == to {
+        return;
+    }
+    // The archive, index and progress files are named after the data file.
+    let entries: Vec<PathBuf> = match fs::read_dir(from) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.file_name().is_some_and(|name| {
+                    let name = name.to_string_lossy();
+                    name.starts_with("data.") || name == "workspaces"
+                })
+            })
+            .collect(),
+        Err(_) => return,
+    };
+    for entry in entries {
+        let target = to.join(entry.file_name().expect("Entries have a name"));
+        if target.exists() {
+            println!(
+                "{}",
+                tr!(
+                    "'{}' is already there, left '{}' where it was.",
+                    target.display(),
+                    entry.display()
+                )
+            );
+            continue;
+        }
+        if let Err(e) = fs::rename(&entry, &target) {
+            println!(
+                "{}",
+                tr!(
+                    "Unable to move '{}' to '{}': {}",
+                    entry.display(),
+                    to.display(),
+                    e
+                )
+            );
+        }
+    }
+    println!("{}", tr!("The data is now kept in '{}'.", to.display()));
+}
+
+fn get_workspaces_dir() -> PathBuf {
+    data_dir().join("workspaces")
+}
+
+pub fn data_file_path(name: &str, format: DataFormat) -> PathBuf {
+    if name == DEFAULT_WORKSPACE {
+        data_dir().join(format!("data.{}", format.extension()))
+    } else {
+        get_workspaces_dir().join(format!("{}.{}", name, format.extension()))
+    }
+}
+
+/// The directory a workspace's projects are kept in, one file each, with
+/// `split-projects`.
+pub fn projects_dir(name: &str) -> PathBuf {
+    data_file_path(name, DataFormat::Json).with_extension("projects")
+}
+
+/// The data file of a workspace and its format, if it has one yet.
+pub fn existing_data_file(name: &str) -> Option<(PathBuf, DataFormat)> {
+    DataFormat::ALL
+        .into_iter()
+        .map(|format| (data_file_path(name, format), format))
+        .find(|(path, _)| path.is_file())
+}
+
+pub fn exists(name: &str) -> bool {
+    name == DEFAULT_WORKSPACE || (is_valid_name(name) && existing_data_file(name).is_some())
+}
+
+fn all() -> Vec<String> {
+    let mut names = vec![DEFAULT_WORKSPACE.to_string()];
+
+    if let Ok(entries) = fs::read_dir(get_workspaces_dir()) {
+        let mut others: Vec<String> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.extension().is_some_and(|ext| {
+                    DataFormat::ALL
+                        .iter()
+                        .any(|format| ext == format.extension())
+                })
+            })
+            .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))
+            // Skips the search index and archive files living alongside.
+            .filter(|name| is_valid_name(name))
+            .collect();
+        others.sort();
+        others.dedup();
+        names.extend(others);
+    }
+
+    names
+}
+
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+/// The error for a workspace name that isn't valid.
+pub fn invalid_name(name: &str) -> String {
+    tr!(
+        "Invalid workspace name '{}': use only letters, digits, '-' and '_'.",
+        name
+    )
+}
+
+pub fn run(command: &WorkspaceCommand) {
+    match command {
+        WorkspaceCommand::List => list_workspaces(),
+        WorkspaceCommand::Create { name } => create_workspace(name),
+        WorkspaceCommand::Delete { name } => delete_workspace(name),
+    }
+}
+
+fn list_workspaces() {
+    println!("{}", tr!("Workspaces:"));
+    for name in all() {
+        let marker = if name == active() { "*" } else { " " };
+        println!(" {} {}", marker, name);
+    }
+}
+
+pub fn create_workspace(name: &str) {
+    if !is_valid_name(name) {
+        println!("{}", invalid_name(name));
+        return;
+    }
+
+    if exists(name) {
+        println!("{}", tr!("Workspace '{}' already exists.", name));
+        return;
+    }
+
+    fs::create_dir_all(get_workspaces_dir()).expect("Failed to create workspaces directory");
+    let format = load_config().settings.format.unwrap_or(DataFormat::Json);
+    fs::write(data_file_path(name, format), serialize(&[], format))
+        .expect("Unable to create workspace data file.");
+
+    println!("{}", tr!("Workspace '{}' created.", name));
+}
+
+fn delete_workspace(name: &str) {
+    if name == DEFAULT_WORKSPACE {
+        println!("{}", tr!("The default workspace cannot be deleted."));
+        return;
+    }
+
+    if !exists(name) {
+        println!("{}", tr!("Workspace '{}' not found.", name));
+        return;
+    }
+
+    if let Some((data_file, _)) = existing_data_file(name) {
+        fs::remove_file(data_file).expect("Unable to delete workspace data file.");
+    }
+    let projects_dir = projects_dir(name);
+    if projects_dir.is_dir() {
+        fs::remove_dir_all(projects_dir).expect("Unable to delete workspace data file.");
+    }
+    index::remove(name);
+    archive::remove(name);
+    graph::remove(name);
+    println!("{}", tr!("Workspace '{}' has been deleted.", name));
+}